@@ -0,0 +1,119 @@
+//! Render pipeline benchmark: `cargo bench --bench render`.
+//!
+//! Reports wall time and heap allocations per iteration for `build_segments` and the
+//! renderers, using a counting global allocator so allocation regressions show up as numbers.
+
+use codexline::config::{Config, StyleMode};
+use codexline::context::{GitStatus, RateLimitSnapshot, SessionMetaSnapshot, TokenUsageSnapshot};
+use codexline::{build_segments, render, render_line, StatusContext};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+const ITERATIONS: usize = 20_000;
+
+fn context() -> StatusContext {
+    let mut ctx = StatusContext::new(chrono::Utc::now(), "/work/codexline".into());
+    ctx.project_root = Some("/work/codexline".into());
+    ctx.model = Some("gpt-5-codex".to_string());
+    ctx.git = Some(GitStatus {
+        branch: "feature/render".to_string(),
+        dirty: true,
+        staged: 2,
+        unstaged: 3,
+        untracked: 1,
+        conflicted: 0,
+        ahead: Some(1),
+        behind: Some(4),
+        diffstat: None,
+    });
+    ctx.usage = Some(TokenUsageSnapshot {
+        input_tokens: 120_000,
+        output_tokens: 8_000,
+        cached_input_tokens: 0,
+        reasoning_output_tokens: 0,
+        total_tokens: 128_000,
+        model_context_window: Some(272_000),
+        used_percent: Some(47),
+        remaining_percent: Some(53),
+    });
+    ctx.limits = Some(RateLimitSnapshot {
+        primary_used_percent: Some(31.5),
+        secondary_used_percent: Some(12.0),
+    });
+    ctx.session = Some(SessionMetaSnapshot {
+        thread_id: Some("0199a1b2-c3d4-7e5f-8a9b-0c1d2e3f4a5b".to_string()),
+        cli_version: Some("0.46.0".to_string()),
+        model_provider: Some("openai".to_string()),
+        title: None,
+    });
+    ctx
+}
+
+fn bench(name: &str, mut run: impl FnMut() -> usize) {
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let started = Instant::now();
+    let mut bytes = 0;
+    for _ in 0..ITERATIONS {
+        bytes += black_box(run());
+    }
+    let elapsed = started.elapsed();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+    println!(
+        "{name:<24} {:>8.2} us/iter {:>6.1} allocs/iter ({} bytes)",
+        elapsed.as_secs_f64() * 1e6 / ITERATIONS as f64,
+        allocations as f64 / ITERATIONS as f64,
+        bytes / ITERATIONS,
+    );
+}
+
+fn main() {
+    let ctx = context();
+    for mode in [StyleMode::NerdFont, StyleMode::Powerline] {
+        let mut cfg = Config::default();
+        cfg.style.mode = mode;
+        cfg.segments
+            .iter_mut()
+            .for_each(|segment| segment.enabled = true);
+        let segments = build_segments(&cfg, &ctx);
+        println!("{mode:?}, {} segments", segments.len());
+
+        bench("build_segments", || build_segments(&cfg, &ctx).len());
+        bench("render_line (ansi)", || {
+            render_line(&cfg, &segments, false).len()
+        });
+        bench("render_line (plain)", || {
+            render_line(&cfg, &segments, true).len()
+        });
+        bench("render_porcelain", || {
+            render::render_porcelain(&segments).len()
+        });
+        bench("render_tmux", || render::render_tmux(&cfg, &segments).len());
+        bench("render_zsh", || render::render_zsh(&cfg, &segments).len());
+        bench("render_fish", || render::render_fish(&cfg, &segments).len());
+    }
+}
@@ -0,0 +1,51 @@
+use std::path::Path;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn main() {
+    let commit = Command::new("git")
+        .args(["rev-parse", "--short=12", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|commit| !commit.is_empty())
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=CODEXLINE_GIT_COMMIT={commit}");
+
+    // Honour SOURCE_DATE_EPOCH so reproducible builds get a stable date.
+    let timestamp = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|value| value.parse::<u64>().ok())
+        .unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0)
+        });
+    println!("cargo:rustc-env=CODEXLINE_BUILD_TIMESTAMP={timestamp}");
+
+    let mut features: Vec<String> = std::env::vars()
+        .filter_map(|(key, _)| key.strip_prefix("CARGO_FEATURE_").map(str::to_string))
+        .map(|name| name.to_lowercase().replace('_', "-"))
+        .filter(|name| name != "default")
+        .collect();
+    features.sort();
+    println!("cargo:rustc-env=CODEXLINE_FEATURES={}", features.join(","));
+
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+    // Any rebuild of the crate re-runs this script, so the build date stays current; the git
+    // paths catch new commits that change no source.
+    for path in [
+        "src",
+        "Cargo.toml",
+        "build.rs",
+        ".git/HEAD",
+        ".git/refs",
+        ".git/packed-refs",
+    ] {
+        if Path::new(path).exists() {
+            println!("cargo:rerun-if-changed={path}");
+        }
+    }
+}
@@ -0,0 +1,244 @@
+use crate::config::{AlertsConfig, Config};
+use crate::context::StatusContext;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::thread;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alert {
+    pub metric: &'static str,
+    pub used_percent: f64,
+    pub threshold: f64,
+}
+
+/// Posts newly crossed thresholds to `alerts.webhook_url`.
+///
+/// Thresholds that already fired are remembered per session in the cache dir so every render
+/// does not re-send them. The POST runs `curl` in the background, with the URL passed on
+/// stdin so it stays out of the process list; failures are ignored.
+pub fn notify(cfg: &Config, ctx: &StatusContext) {
+    let Some(url) = cfg.alerts.webhook_url.as_deref() else {
+        return;
+    };
+    let readings = readings(&cfg.alerts, ctx);
+    if readings.is_empty() {
+        return;
+    }
+    let path = cfg.cache_dir().join("alerts.json");
+    let mut state = load_state(&path);
+    let session = ctx
+        .session
+        .as_ref()
+        .and_then(|session| session.thread_id.clone())
+        .unwrap_or_default();
+    let active = state.entry(session.clone()).or_default();
+    let fresh = update_active(active, &readings);
+    if active.is_empty() {
+        state.remove(&session);
+    }
+    let _ = save_state(&path, &state);
+    if fresh.is_empty() {
+        return;
+    }
+
+    let Ok(mut child) = Command::new("curl")
+        .args(["-fsS", "--max-time", "5", "-X", "POST", "-K", "-"])
+        .args(["-H", "Content-Type: application/json"])
+        .args(["--data-binary", &payload(&fresh, ctx).to_string()])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+    else {
+        return;
+    };
+    // Written before returning: a one-shot render exits right after, and the config is far
+    // smaller than a pipe buffer.
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(curl_config(url).as_bytes());
+    }
+    // Reaped in the background so `--watch` does not collect zombies.
+    thread::spawn(move || {
+        let _ = child.wait();
+    });
+}
+
+/// `url` as a curl config file line (`-K`), quoted with curl's escapes.
+fn curl_config(url: &str) -> String {
+    let mut line = String::from("url = \"");
+    for c in url.chars() {
+        match c {
+            '"' | '\\' => {
+                line.push('\\');
+                line.push(c);
+            }
+            '\n' | '\r' => {}
+            c => line.push(c),
+        }
+    }
+    line.push_str("\"\n");
+    line
+}
+
+/// Configured metrics that have data in `ctx`, whether or not they crossed their threshold.
+/// A metric without data (rollout missing or timed out) is left out, so its state is kept.
+fn readings(alerts: &AlertsConfig, ctx: &StatusContext) -> Vec<Alert> {
+    let mut out = Vec::new();
+    if let (Some(threshold), Some(used)) = (
+        alerts.context_percent,
+        ctx.usage.as_ref().and_then(|usage| usage.used_percent),
+    ) {
+        out.push(Alert {
+            metric: "context",
+            used_percent: used as f64,
+            threshold,
+        });
+    }
+    if let (Some(threshold), Some(limits)) = (alerts.rate_limit_percent, ctx.limits.as_ref()) {
+        for (metric, used) in [
+            ("rate_limit.primary", limits.primary_used_percent),
+            ("rate_limit.secondary", limits.secondary_used_percent),
+        ] {
+            if let Some(used_percent) = used {
+                out.push(Alert {
+                    metric,
+                    used_percent,
+                    threshold,
+                });
+            }
+        }
+    }
+    out
+}
+
+/// Marks the readings at or above their threshold active and re-arms the ones below it;
+/// returns the alerts that were not active before. Metrics without a reading keep their state.
+fn update_active(active: &mut BTreeSet<String>, readings: &[Alert]) -> Vec<Alert> {
+    let mut fresh = Vec::new();
+    for reading in readings {
+        if reading.used_percent < reading.threshold {
+            active.remove(reading.metric);
+        } else if active.insert(reading.metric.to_string()) {
+            fresh.push(reading.clone());
+        }
+    }
+    fresh
+}
+
+fn payload(alerts: &[Alert], ctx: &StatusContext) -> Value {
+    let summary = alerts
+        .iter()
+        .map(|alert| {
+            format!(
+                "{} at {:.0}% (threshold {:.0}%)",
+                alert.metric, alert.used_percent, alert.threshold
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    let text = match &ctx.model {
+        Some(model) => format!("codexline: {summary} [{model}]"),
+        None => format!("codexline: {summary}"),
+    };
+    json!({
+        "text": text,
+        "content": text,
+        "alerts": alerts
+            .iter()
+            .map(|alert| json!({
+                "metric": alert.metric,
+                "used_percent": alert.used_percent,
+                "threshold": alert.threshold,
+            }))
+            .collect::<Vec<_>>(),
+        "model": ctx.model,
+        "session": ctx.session.as_ref().and_then(|session| session.thread_id.clone()),
+        "timestamp": ctx.now.to_rfc3339(),
+    })
+}
+
+/// Active metrics by session id (empty for a rollout without one); sessions with nothing
+/// active are dropped.
+type AlertState = BTreeMap<String, BTreeSet<String>>;
+
+fn load_state(path: &Path) -> AlertState {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+fn save_state(path: &Path, state: &AlertState) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_string(state)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::{RateLimitSnapshot, TokenUsageSnapshot};
+
+    #[test]
+    fn thresholds_fire_once_until_rearmed() {
+        let alerts = AlertsConfig {
+            webhook_url: Some("http://localhost/hook".to_string()),
+            context_percent: Some(80.0),
+            rate_limit_percent: Some(90.0),
+        };
+        let mut ctx = StatusContext {
+            model: Some("gpt-5".to_string()),
+            usage: Some(TokenUsageSnapshot {
+                input_tokens: 0,
+                output_tokens: 0,
+                cached_input_tokens: 0,
+                reasoning_output_tokens: 0,
+                total_tokens: 0,
+                model_context_window: None,
+                used_percent: Some(85),
+                remaining_percent: Some(15),
+            }),
+            limits: Some(RateLimitSnapshot {
+                primary_used_percent: Some(95.0),
+                secondary_used_percent: Some(40.0),
+            }),
+            ..StatusContext::new(chrono::Utc::now(), std::path::PathBuf::from("/work"))
+        };
+        let mut active = BTreeSet::new();
+
+        let first = update_active(&mut active, &readings(&alerts, &ctx));
+        let metrics: Vec<_> = first.iter().map(|alert| alert.metric).collect();
+        assert_eq!(metrics, vec!["context", "rate_limit.primary"]);
+        assert!(update_active(&mut active, &readings(&alerts, &ctx)).is_empty());
+
+        // A render without data does not re-arm anything.
+        let usage = ctx.usage.take();
+        let limits = ctx.limits.take();
+        assert!(readings(&alerts, &ctx).is_empty());
+        ctx.usage = usage;
+        ctx.limits = limits;
+        assert!(update_active(&mut active, &readings(&alerts, &ctx)).is_empty());
+
+        ctx.usage.as_mut().expect("usage").used_percent = Some(10);
+        assert!(update_active(&mut active, &readings(&alerts, &ctx)).is_empty());
+        assert_eq!(active, BTreeSet::from(["rate_limit.primary".to_string()]));
+        ctx.usage.as_mut().expect("usage").used_percent = Some(90);
+        let rearmed = update_active(&mut active, &readings(&alerts, &ctx));
+        assert_eq!(rearmed.len(), 1);
+        assert_eq!(rearmed[0].metric, "context");
+        assert!(payload(&rearmed, &ctx)["text"]
+            .as_str()
+            .expect("text")
+            .contains("context at 90%"));
+
+        assert_eq!(
+            curl_config("https://hooks.example/a\"b\\c"),
+            "url = \"https://hooks.example/a\\\"b\\\\c\"\n"
+        );
+    }
+}
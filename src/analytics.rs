@@ -0,0 +1,106 @@
+//! Estimates derived from the usage history, such as when a rate-limit window fills up.
+
+use crate::context::LimitForecast;
+use crate::history::{History, Snapshot};
+use chrono::{DateTime, Duration, Utc};
+
+/// How far back the burn rate is measured.
+const LOOKBACK_MINUTES: i64 = 60;
+
+/// Shorter spans give rates too noisy to extrapolate from.
+const MIN_SPAN_MINUTES: f64 = 5.0;
+
+/// A drop larger than this between snapshots means the window reset.
+const RESET_DROP_PERCENT: f64 = 1.0;
+
+const PRIMARY_WINDOW_MINUTES: f64 = 5.0 * 60.0;
+const SECONDARY_WINDOW_MINUTES: f64 = 7.0 * 24.0 * 60.0;
+
+/// Minutes until the 5h and weekly windows reach 100% at the rate they rose over the last
+/// hour. A window gets no estimate when it did not rise, or when it would only fill after a
+/// full window length, by which time it has reset.
+pub fn limit_forecast(history: &History, now: DateTime<Utc>) -> LimitForecast {
+    let recent: Vec<&Snapshot> = history
+        .snapshots()
+        .filter(|snapshot| snapshot.at >= now - Duration::minutes(LOOKBACK_MINUTES))
+        .collect();
+    let series = |percent: fn(&Snapshot) -> Option<f64>| -> Vec<(DateTime<Utc>, f64)> {
+        recent
+            .iter()
+            .filter_map(|snapshot| Some((snapshot.at, percent(snapshot)?)))
+            .collect()
+    };
+    LimitForecast {
+        primary_full_minutes: minutes_to_full(
+            &series(|snapshot| snapshot.primary_used_percent),
+            PRIMARY_WINDOW_MINUTES,
+        ),
+        secondary_full_minutes: minutes_to_full(
+            &series(|snapshot| snapshot.secondary_used_percent),
+            SECONDARY_WINDOW_MINUTES,
+        ),
+    }
+}
+
+/// Linear extrapolation from the first point after the last reset to the newest one.
+fn minutes_to_full(points: &[(DateTime<Utc>, f64)], window_minutes: f64) -> Option<u32> {
+    let start = points
+        .windows(2)
+        .rposition(|pair| pair[1].1 < pair[0].1 - RESET_DROP_PERCENT)
+        .map_or(0, |reset| reset + 1);
+    let (first_at, first) = *points.get(start)?;
+    let (last_at, last) = *points.last()?;
+    let span = (last_at - first_at).num_seconds() as f64 / 60.0;
+    if span < MIN_SPAN_MINUTES {
+        return None;
+    }
+    if last >= 100.0 {
+        return Some(0);
+    }
+    let rate = (last - first) / span;
+    if rate <= 0.0 {
+        return None;
+    }
+    let minutes = ((100.0 - last) / rate).ceil();
+    (minutes < window_minutes).then_some(minutes as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points(percents: &[(i64, f64)]) -> Vec<(DateTime<Utc>, f64)> {
+        let start = DateTime::from_timestamp(1_760_000_000, 0).expect("timestamp");
+        percents
+            .iter()
+            .map(|(minute, percent)| (start + Duration::minutes(*minute), *percent))
+            .collect()
+    }
+
+    #[test]
+    fn forecast_extrapolates_since_the_last_reset() {
+        // 2% per 10 minutes, now at 62%: the 38% left fills in 190 minutes.
+        let rising = points(&[(0, 60.0), (10, 62.0)]);
+        assert_eq!(minutes_to_full(&rising, PRIMARY_WINDOW_MINUTES), Some(190));
+        // Before the reset the window rose fast; only the points after it count.
+        let reset = points(&[(0, 90.0), (10, 99.0), (20, 50.0), (30, 60.0)]);
+        assert_eq!(minutes_to_full(&reset, PRIMARY_WINDOW_MINUTES), Some(40));
+
+        assert_eq!(
+            minutes_to_full(&points(&[(0, 60.0), (2, 70.0)]), 300.0),
+            None
+        );
+        assert_eq!(
+            minutes_to_full(&points(&[(0, 60.0), (30, 60.0)]), 300.0),
+            None
+        );
+        assert_eq!(
+            minutes_to_full(&points(&[(0, 10.0), (10, 11.0)]), 300.0),
+            None
+        );
+        assert_eq!(
+            minutes_to_full(&points(&[(0, 99.0), (10, 100.0)]), 300.0),
+            Some(0)
+        );
+    }
+}
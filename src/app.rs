@@ -0,0 +1,1368 @@
+//! The `codexline` command line: argument dispatch and the interactive / output modes built on
+//! the library API.
+
+use crate::cli::{self, Cli, EnhancementKind, InspectSource, SchemaTarget};
+use crate::config::{ColorMode, Config};
+use crate::exit::{ConfigInvalid, ExitStatus};
+use crate::profiles::{self, Enhancement};
+#[cfg(feature = "sqlite")]
+use crate::store;
+#[cfg(feature = "otel")]
+use crate::telemetry;
+use crate::trace::Level;
+use crate::{
+    alerts, clean, codex_setup, collect, compat, config, context, fix, fixtures, hooks, install,
+    patch_diagnose, pipe, prime, render, screenshot, segments, serve, terminal, themes, trace,
+    usage_log, version, wsl,
+};
+#[cfg(feature = "tui")]
+use crate::{ui, wizard};
+use anyhow::{Context, Result};
+use clap::Parser;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::io::{BufRead, IsTerminal, Read, Write};
+use std::path::Path;
+
+/// Bumped whenever a field is removed or changes meaning in `--json`/`--inspect` output.
+const JSON_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Serialize, JsonSchema)]
+struct StatuslineOutput {
+    schema_version: u32,
+    line: String,
+    segments: Vec<segments::SegmentPiece>,
+    context: context::StatusContext,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct InspectOutput {
+    schema_version: u32,
+    source: String,
+    codex_home: String,
+    sessions_dir: String,
+    latest_rollout: Option<String>,
+    model: Option<String>,
+    git: Option<context::GitStatus>,
+    usage: Option<context::TokenUsageSnapshot>,
+    limits: Option<context::RateLimitSnapshot>,
+    session: Option<context::SessionMetaSnapshot>,
+}
+
+pub(crate) fn run() -> Result<ExitStatus> {
+    let cli = Cli::parse();
+    trace::init(cli.log_file.as_deref())?;
+
+    if cli.version {
+        if !cli.verbose {
+            println!("{}", version::short());
+        } else if cli.json {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&version::collect_info())?
+            );
+        } else {
+            println!("{}", version::render_text(&version::collect_info()));
+        }
+        return Ok(ExitStatus::Success);
+    }
+
+    if cli.init {
+        let result = config::init()?;
+        let path = config::config_path();
+        match result {
+            config::InitResult::Created => println!("created config: {}", path.display()),
+            config::InitResult::AlreadyExists => {
+                println!("config already exists: {}", path.display())
+            }
+        }
+        return Ok(ExitStatus::Success);
+    }
+
+    if let Some(dir) = cli.generate_fixtures.as_deref() {
+        let spec = fixtures::FixtureSpec {
+            sessions: cli.fixture_sessions,
+            events: cli.fixture_events,
+            max_age_days: cli.fixture_max_age_days,
+            ..fixtures::FixtureSpec::default()
+        };
+        let paths = fixtures::generate(dir, &spec)?;
+        println!(
+            "wrote {} rollout files; use CODEX_HOME={}",
+            paths.len(),
+            dir.display()
+        );
+        return Ok(ExitStatus::Success);
+    }
+
+    if let Some(target) = cli.json_schema {
+        let schema = match target {
+            SchemaTarget::Statusline => schemars::schema_for!(StatuslineOutput),
+            SchemaTarget::Inspect => schemars::schema_for!(InspectOutput),
+        };
+        println!("{}", serde_json::to_string_pretty(&schema)?);
+        return Ok(ExitStatus::Success);
+    }
+
+    if let Some(shell) = cli.install_shell {
+        return run_install_shell(shell, cli.append, cli.yes);
+    }
+
+    if cli.clean {
+        return run_clean(cli.all, cli.yes);
+    }
+
+    #[cfg(feature = "tui")]
+    if should_open_menu(&cli) && !config::config_path().exists() {
+        run_first_run_wizard()?;
+    }
+
+    let mut cfg = config::load().context(ConfigInvalid)?;
+
+    #[cfg(feature = "sqlite")]
+    if let Some(days) = cli.stats {
+        return run_stats(&cfg, days, cli.json);
+    }
+
+    #[cfg(all(feature = "sqlite", not(feature = "tui")))]
+    if cli.dashboard.is_some() {
+        anyhow::bail!("--dashboard needs the `tui` feature; this build was made without it");
+    }
+
+    #[cfg(all(feature = "sqlite", feature = "tui"))]
+    if let Some(days) = cli.dashboard {
+        let Some(path) = &cfg.usage.sqlite else {
+            anyhow::bail!("usage.sqlite is not set in the config");
+        };
+        ui::run_dashboard(path, days)?;
+        return Ok(ExitStatus::Success);
+    }
+
+    if cli.quick_config || !cli.enhance.is_empty() {
+        if cli.quick_config {
+            profiles::apply_quick_config(&mut cfg);
+        }
+
+        let mut seen = HashSet::new();
+        let mut applied = Vec::new();
+        for capability in &cli.enhance {
+            if !seen.insert(*capability) {
+                continue;
+            }
+            let enhancement = match capability {
+                EnhancementKind::Git => Enhancement::Git,
+                EnhancementKind::Observability => Enhancement::Observability,
+            };
+            profiles::apply_enhancement(&mut cfg, enhancement);
+            applied.push(*capability);
+        }
+
+        config::save(&cfg)?;
+        println!("saved config: {}", config::config_path().display());
+        if cli.quick_config {
+            println!("- quick profile applied");
+        }
+        for capability in applied {
+            match capability {
+                EnhancementKind::Git => {
+                    println!("- enhancement applied: git (detailed git metrics)")
+                }
+                EnhancementKind::Observability => {
+                    println!("- enhancement applied: observability (usage/limits/session/version)")
+                }
+            }
+        }
+        return Ok(ExitStatus::Success);
+    }
+
+    if let Some(dir) = cli.theme_dir.clone() {
+        cfg.theme_dir = Some(dir);
+    }
+
+    if let Some(sides) = cli.compare.as_deref() {
+        return run_compare(&cfg, sides, &cli);
+    }
+
+    let themes_dir = cfg.themes_dir();
+    let theme_span = trace::span("theme", "apply");
+    if let Some(theme) = cli.theme.as_deref() {
+        cfg = themes::apply_theme(&cfg, theme, &themes_dir)?;
+    } else if let Some(ssh_theme) = cfg
+        .style
+        .ssh_theme
+        .clone()
+        .filter(|_| collect::ssh_session())
+    {
+        // Keep the configured theme name so saving from a remote shell does not persist it.
+        let theme = cfg.theme.clone();
+        cfg = themes::apply_theme(&cfg, &ssh_theme, &themes_dir).unwrap_or(cfg);
+        cfg.theme = theme;
+    } else {
+        cfg = match themes::apply_theme(&cfg, &cfg.theme, &themes_dir) {
+            Ok(themed) => themed,
+            Err(err) => {
+                trace::event(
+                    Level::Warn,
+                    "theme",
+                    format_args!("theme {:?} not applied: {err:#}", cfg.theme),
+                );
+                cfg
+            }
+        };
+    }
+    drop(theme_span);
+
+    if cli.print {
+        println!("{}", toml::to_string_pretty(&cfg)?);
+        return Ok(ExitStatus::Success);
+    }
+
+    if cli.check {
+        cfg.validate().context(ConfigInvalid)?;
+        println!("configuration valid");
+        return Ok(ExitStatus::Success);
+    }
+
+    let collect_options = cli.collect_options();
+
+    #[cfg(not(feature = "tui"))]
+    if cli.config || cli.tweak || cli.menu {
+        anyhow::bail!(
+            "--config, --tweak and --menu need the `tui` feature; this build was made without it"
+        );
+    }
+
+    #[cfg(feature = "tui")]
+    if cli.config {
+        let result = ui::run_configurator(&cfg, collect_options)?;
+        if result.is_some() {
+            println!("configuration saved");
+        } else {
+            println!("configuration not changed");
+        }
+        return Ok(ExitStatus::Success);
+    }
+
+    #[cfg(feature = "tui")]
+    if cli.tweak {
+        let result = ui::run_tweak(&cfg, collect_options)?;
+        if result.is_some() {
+            println!("configuration saved");
+        } else {
+            println!("configuration not changed");
+        }
+        return Ok(ExitStatus::Success);
+    }
+
+    if cli.prime {
+        return Ok(prime::run(&cfg, collect_options));
+    }
+
+    if let Some(path) = cli.screenshot.as_deref() {
+        return run_screenshot(&cfg, collect_options, path);
+    }
+
+    if cli.doctor {
+        return run_doctor(&cfg, collect_options, cli.json, cli.fix, cli.yes);
+    }
+
+    if let Some(source) = cli.inspect {
+        return run_inspect(&cfg, collect_options, source);
+    }
+
+    if cli.patch {
+        if cli.apply {
+            return run_patch_apply(&cfg, collect_options, cli.yes);
+        }
+        return run_patch_diagnose(&cfg, collect_options, cli.json);
+    }
+
+    #[cfg(feature = "tui")]
+    if cli.menu || should_open_menu(&cli) {
+        let action = ui::run_main_menu(&cfg)?;
+        match action {
+            ui::MainMenuAction::Render => {}
+            ui::MainMenuAction::Configure => {
+                let result = ui::run_configurator(&cfg, collect_options)?;
+                if result.is_some() {
+                    println!("configuration saved");
+                } else {
+                    println!("configuration not changed");
+                }
+                return Ok(ExitStatus::Success);
+            }
+            ui::MainMenuAction::Sessions => {
+                ui::run_session_browser(&cfg)?;
+                return Ok(ExitStatus::Success);
+            }
+            ui::MainMenuAction::Profiles => {
+                ui::run_profile_manager()?;
+                return Ok(ExitStatus::Success);
+            }
+            ui::MainMenuAction::Diagnostics => {
+                return run_diagnostics_view(&cfg, collect_options);
+            }
+            ui::MainMenuAction::Init => {
+                let result = config::init()?;
+                let path = config::config_path();
+                match result {
+                    config::InitResult::Created => println!("created config: {}", path.display()),
+                    config::InitResult::AlreadyExists => {
+                        println!("config already exists: {}", path.display())
+                    }
+                }
+                return Ok(ExitStatus::Success);
+            }
+            ui::MainMenuAction::Check => {
+                cfg.validate().context(ConfigInvalid)?;
+                println!("configuration valid");
+                return Ok(ExitStatus::Success);
+            }
+            ui::MainMenuAction::Patch => {
+                return run_patch_diagnose(&cfg, collect_options, false);
+            }
+            ui::MainMenuAction::Exit => return Ok(ExitStatus::Success),
+        }
+    }
+
+    if cli.watch || cli.stream || cli.serve.is_some() {
+        return run_watch(&cfg, collect_options, &cli);
+    }
+
+    run_statusline(&cfg, collect_options, &cli)
+}
+
+fn run_install_shell(shell: cli::ShellKind, append: bool, assume_yes: bool) -> Result<ExitStatus> {
+    let snippet = install::snippet(shell);
+    if !append {
+        print!("{}", snippet);
+        return Ok(ExitStatus::Success);
+    }
+
+    let path = install::rc_path(shell);
+    println!("{}", snippet);
+    if !assume_yes && !confirm(&format!("Append the snippet above to {}?", path.display()))? {
+        println!("not modified: {}", path.display());
+        return Ok(ExitStatus::Success);
+    }
+
+    match install::append_snippet(shell, &path)? {
+        install::AppendResult::Appended => println!("appended to: {}", path.display()),
+        install::AppendResult::AlreadyInstalled => {
+            println!("already installed: {}", path.display())
+        }
+    }
+    Ok(ExitStatus::Success)
+}
+
+fn run_clean(all: bool, assume_yes: bool) -> Result<ExitStatus> {
+    let targets = if all {
+        vec![config::config_dir(), config::bridge_cache_dir()]
+    } else {
+        vec![
+            config::cache_dir(),
+            config::backups_dir(),
+            config::bridge_cache_dir(),
+        ]
+    };
+
+    if all && !assume_yes {
+        let question = format!(
+            "Remove {} including config and themes?",
+            config::config_dir().display()
+        );
+        if !confirm(&question)? {
+            println!("nothing removed");
+            return Ok(ExitStatus::Success);
+        }
+    }
+
+    let report = clean::remove_targets(&targets)?;
+    for path in &report.removed {
+        println!("removed: {}", path.display());
+    }
+    for path in &report.missing {
+        println!("not found: {}", path.display());
+    }
+    Ok(ExitStatus::Success)
+}
+
+#[cfg(feature = "tui")]
+fn run_first_run_wizard() -> Result<()> {
+    let stdin = std::io::stdin();
+    let mut stdout = std::io::stdout();
+    let Some(answers) = wizard::ask(&mut stdin.lock(), &mut stdout)? else {
+        // Write defaults so the wizard is not offered again on every launch.
+        config::init()?;
+        println!("created config: {}", config::config_path().display());
+        return Ok(());
+    };
+    config::save(&wizard::build_config(&answers))?;
+    config::ensure_themes_exist();
+    println!("saved config: {}", config::config_path().display());
+    Ok(())
+}
+
+fn confirm(question: &str) -> Result<bool> {
+    if !std::io::stdin().is_terminal() {
+        anyhow::bail!("confirmation required but stdin is not a terminal, pass --yes");
+    }
+    print!("{} [y/N] ", question);
+    std::io::stdout().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes" | "Yes"))
+}
+
+#[cfg(feature = "tui")]
+fn should_open_menu(cli: &Cli) -> bool {
+    !cli.has_explicit_action() && std::io::stdin().is_terminal() && std::io::stdout().is_terminal()
+}
+
+#[derive(Serialize)]
+struct DoctorReport {
+    config_path: String,
+    config_exists: bool,
+    theme: String,
+    style_mode: String,
+    separator: String,
+    codex_home: String,
+    sessions_dir: String,
+    sessions_exists: bool,
+    latest_rollout: Option<String>,
+    git: Option<context::GitStatus>,
+    rollout_issues: Vec<collect::RolloutIssue>,
+    terminal: Vec<terminal::Capability>,
+    warnings: Vec<String>,
+}
+
+fn run_doctor(
+    cfg: &config::Config,
+    collect_options: collect::CollectOptions,
+    as_json: bool,
+    apply_fixes: bool,
+    assume_yes: bool,
+) -> Result<ExitStatus> {
+    let collection = collect::collect_with(cfg, collect_options)?;
+
+    let config_path = config::config_path();
+    let config_exists = config_path.exists();
+    let sessions_exists = collection.sessions_dir.exists();
+    let latest_rollout = collection
+        .latest_rollout
+        .as_ref()
+        .map(|path| path.display().to_string());
+
+    let rollout_issues = doctor_rollout_issues(cfg, &collection, collect_options);
+    let capabilities = terminal_capabilities(cfg, &collection);
+    let checks = doctor_checks(
+        cfg,
+        &collection,
+        collect_options,
+        config_exists,
+        &rollout_issues,
+        &capabilities,
+    );
+    let fixes = if apply_fixes {
+        fix::plan(&checks, &collection.sessions_dir)
+    } else {
+        Vec::new()
+    };
+    let warnings = checks
+        .iter()
+        .filter(|check| !matches!(check.status, patch_diagnose::CheckStatus::Ok))
+        .map(|check| check.detail.clone())
+        .collect();
+
+    let report = DoctorReport {
+        config_path: config_path.display().to_string(),
+        config_exists,
+        theme: cfg.theme.clone(),
+        style_mode: format!("{:?}", cfg.style.mode),
+        separator: cfg.style.separator.clone(),
+        codex_home: collection.codex_home.display().to_string(),
+        sessions_dir: collection.sessions_dir.display().to_string(),
+        sessions_exists,
+        latest_rollout,
+        git: collection.context.git,
+        rollout_issues,
+        terminal: capabilities,
+        warnings,
+    };
+
+    let status = checks_status(&checks);
+
+    if as_json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(status);
+    }
+
+    println!("config: {}", report.config_path);
+    println!("config_exists: {}", report.config_exists);
+    println!("theme: {}", report.theme);
+    println!("style_mode: {}", report.style_mode);
+    println!("separator: {}", report.separator);
+    println!("codex_home: {}", report.codex_home);
+    println!("sessions_dir: {}", report.sessions_dir);
+    println!("sessions_exists: {}", report.sessions_exists);
+
+    if let Some(path) = &report.latest_rollout {
+        println!("latest_rollout: {}", path);
+    } else {
+        println!("latest_rollout: <none>");
+    }
+
+    if let Some(git) = &report.git {
+        println!(
+            "git: branch={} dirty={} staged={} unstaged={} untracked={} conflicted={}",
+            git.branch, git.dirty, git.staged, git.unstaged, git.untracked, git.conflicted
+        );
+    } else {
+        println!("git: <not-a-repo>");
+    }
+
+    println!("terminal:");
+    for capability in &report.terminal {
+        let status = match capability.status {
+            patch_diagnose::CheckStatus::Ok => "ok",
+            patch_diagnose::CheckStatus::Warn => "warn",
+            patch_diagnose::CheckStatus::Fail => "fail",
+        };
+        println!("- {}: {} ({})", capability.name, status, capability.detail);
+    }
+
+    if !report.warnings.is_empty() {
+        println!("warnings:");
+        for warning in report.warnings {
+            println!("- {}", warning);
+        }
+    }
+
+    if apply_fixes {
+        return run_doctor_fix(cfg, collect_options, &fixes, assume_yes, status);
+    }
+    Ok(status)
+}
+
+fn run_doctor_fix(
+    cfg: &config::Config,
+    collect_options: collect::CollectOptions,
+    fixes: &[fix::Fix],
+    assume_yes: bool,
+    status: ExitStatus,
+) -> Result<ExitStatus> {
+    if fixes.is_empty() {
+        println!("nothing to fix");
+        return Ok(status);
+    }
+    println!("fixes:");
+    for planned in fixes {
+        println!("- {}", planned.describe());
+    }
+    if !assume_yes && !confirm("Apply these fixes?")? {
+        println!("nothing changed");
+        return Ok(status);
+    }
+    let mut failed = false;
+    for planned in fixes {
+        match planned.apply(cfg) {
+            Ok(()) => println!("fixed: {}", planned.describe()),
+            Err(err) => {
+                failed = true;
+                eprintln!("failed: {}: {err:#}", planned.describe());
+            }
+        }
+    }
+    if failed {
+        return Ok(ExitStatus::ChecksFailed);
+    }
+    // The exit code reflects what is left after fixing, not what was found before.
+    Ok(checks_status(&current_doctor_checks(cfg, collect_options)?))
+}
+
+/// Newest rollouts `--doctor` reads looking for files the pipeline skips.
+const DOCTOR_ROLLOUT_SAMPLE: usize = 5;
+
+fn doctor_rollout_issues(
+    cfg: &config::Config,
+    collection: &collect::Collection,
+    collect_options: collect::CollectOptions,
+) -> Vec<collect::RolloutIssue> {
+    if collect_options.skip_rollout {
+        return Vec::new();
+    }
+    collect::rollout_issues(cfg, &collection.sessions_dir, DOCTOR_ROLLOUT_SAMPLE)
+}
+
+/// Terminal capabilities measured against the line this config renders.
+fn terminal_capabilities(
+    cfg: &config::Config,
+    collection: &collect::Collection,
+) -> Vec<terminal::Capability> {
+    let segment_list = segments::build_segments(cfg, &collection.context);
+    let width = render::render_line(cfg, &segment_list, true)
+        .chars()
+        .count();
+    terminal::capabilities(cfg.style.mode, width)
+}
+
+/// Collects afresh and runs the doctor checks on the result.
+fn current_doctor_checks(
+    cfg: &config::Config,
+    collect_options: collect::CollectOptions,
+) -> Result<Vec<patch_diagnose::PatchCheck>> {
+    let collection = collect::collect_with(cfg, collect_options)?;
+    Ok(doctor_checks_for(cfg, &collection, collect_options))
+}
+
+fn doctor_checks_for(
+    cfg: &config::Config,
+    collection: &collect::Collection,
+    collect_options: collect::CollectOptions,
+) -> Vec<patch_diagnose::PatchCheck> {
+    doctor_checks(
+        cfg,
+        collection,
+        collect_options,
+        config::config_path().exists(),
+        &doctor_rollout_issues(cfg, collection, collect_options),
+        &terminal_capabilities(cfg, collection),
+    )
+}
+
+/// `ChecksFailed` only when a check failed; warnings (skipped collectors, font and terminal
+/// guesses) are reported but keep the exit code at 0.
+fn checks_status(checks: &[patch_diagnose::PatchCheck]) -> ExitStatus {
+    if checks
+        .iter()
+        .any(|check| matches!(check.status, patch_diagnose::CheckStatus::Fail))
+    {
+        ExitStatus::ChecksFailed
+    } else {
+        ExitStatus::Success
+    }
+}
+
+fn doctor_checks(
+    cfg: &config::Config,
+    collection: &collect::Collection,
+    collect_options: collect::CollectOptions,
+    config_exists: bool,
+    rollout_issues: &[collect::RolloutIssue],
+    capabilities: &[terminal::Capability],
+) -> Vec<patch_diagnose::PatchCheck> {
+    use patch_diagnose::{CheckStatus, PatchCheck};
+
+    let check = |name: &str, status: CheckStatus, detail: String| PatchCheck {
+        name: name.to_string(),
+        status,
+        detail,
+    };
+    let mut checks = Vec::new();
+
+    checks.push(if config_exists {
+        check(
+            "config",
+            CheckStatus::Ok,
+            config::config_path().display().to_string(),
+        )
+    } else {
+        check(
+            "config",
+            CheckStatus::Warn,
+            "config file missing, run codexline --init".to_string(),
+        )
+    });
+    checks.push(if collection.sessions_dir.exists() {
+        check(
+            "sessions_dir",
+            CheckStatus::Ok,
+            collection.sessions_dir.display().to_string(),
+        )
+    } else {
+        check(
+            "sessions_dir",
+            CheckStatus::Warn,
+            "sessions directory missing, run Codex once to initialize".to_string(),
+        )
+    });
+    let (status, detail) = codex_setup::auth_check(&collection.codex_home);
+    checks.push(check("codex_auth", status, detail));
+    let (status, detail) = codex_setup::config_check(&collection.codex_home);
+    checks.push(check("codex_config", status, detail));
+    checks.push(match &collection.latest_rollout {
+        _ if collect_options.skip_rollout => check(
+            "rollout",
+            CheckStatus::Warn,
+            "rollout collection skipped (--no-rollout)".to_string(),
+        ),
+        Some(path) => check("rollout", CheckStatus::Ok, path.display().to_string()),
+        None => check(
+            "rollout",
+            CheckStatus::Warn,
+            "no rollout data found in sessions directory".to_string(),
+        ),
+    });
+    checks.extend(rollout_issues.iter().map(|issue| {
+        check(
+            "rollout_file",
+            CheckStatus::Warn,
+            format!("{}: {}", issue.path.display(), issue.problem),
+        )
+    }));
+    checks.push(match &collection.context.git {
+        _ if collect_options.skip_git => check(
+            "git",
+            CheckStatus::Warn,
+            "git collection skipped (--no-git)".to_string(),
+        ),
+        Some(git) => check(
+            "git",
+            CheckStatus::Ok,
+            format!("branch={} dirty={}", git.branch, git.dirty),
+        ),
+        None => check(
+            "git",
+            CheckStatus::Warn,
+            "current directory is not a git repository".to_string(),
+        ),
+    });
+    if cfg.style.mode != config::StyleMode::Plain {
+        checks.push(match terminal::nerd_font_support() {
+            terminal::Support::Likely(reason) => check("nerd_font", CheckStatus::Ok, reason),
+            terminal::Support::Unlikely(reason) => check(
+                "nerd_font",
+                CheckStatus::Warn,
+                format!(
+                    "{reason}; icons may show as boxes, install a Nerd Font or set \
+                     style.mode = \"plain\""
+                ),
+            ),
+            terminal::Support::Unknown => check(
+                "nerd_font",
+                CheckStatus::Ok,
+                "could not detect the terminal font; if icons show as boxes, set \
+                 style.mode = \"plain\""
+                    .to_string(),
+            ),
+        });
+    }
+    checks.extend(capabilities.iter().map(|capability| {
+        let detail = match capability.hint {
+            Some(hint) => format!("{}; {hint}", capability.detail),
+            None => capability.detail.clone(),
+        };
+        check(capability.name, capability.status, detail)
+    }));
+    if wsl::is_wsl() {
+        checks.push(match wsl::windows_path(&collection.sessions_dir) {
+            Some(windows) => check(
+                "wsl",
+                CheckStatus::Warn,
+                format!(
+                    "sessions dir is on the Windows side ({windows}); scans through /mnt are slow \
+                     and only see Windows Codex sessions, set CODEX_HOME to a Linux path"
+                ),
+            ),
+            None => check(
+                "wsl",
+                CheckStatus::Ok,
+                "running under WSL, sessions dir is on the Linux filesystem".to_string(),
+            ),
+        });
+    }
+    checks
+}
+
+#[cfg(feature = "tui")]
+fn run_diagnostics_view(
+    cfg: &config::Config,
+    collect_options: collect::CollectOptions,
+) -> Result<ExitStatus> {
+    let collection = collect::collect_with(cfg, collect_options)?;
+    let doctor = doctor_checks_for(cfg, &collection, collect_options);
+    let patch = patch_diagnose::run_patch_diagnostics(cfg, &collection);
+    let status = if patch.has_failures() {
+        ExitStatus::ChecksFailed
+    } else {
+        checks_status(&doctor)
+    };
+
+    ui::run_diagnostics_view(&[
+        ui::DiagnosticsSection {
+            title: "doctor".to_string(),
+            checks: doctor,
+            notes: Vec::new(),
+        },
+        ui::DiagnosticsSection {
+            title: "patch".to_string(),
+            checks: patch.checks,
+            notes: patch.suggestions,
+        },
+    ])?;
+    Ok(status)
+}
+
+fn run_inspect(
+    cfg: &config::Config,
+    collect_options: collect::CollectOptions,
+    source: InspectSource,
+) -> Result<ExitStatus> {
+    let collection = collect::collect_with(cfg, collect_options)?;
+
+    let (model, git, usage, limits, session, source_name) = match source {
+        InspectSource::Rollout => (
+            collection.context.model,
+            None,
+            collection.context.usage,
+            collection.context.limits,
+            collection.context.session,
+            "rollout",
+        ),
+        InspectSource::Git => (None, collection.context.git, None, None, None, "git"),
+        InspectSource::All => (
+            collection.context.model,
+            collection.context.git,
+            collection.context.usage,
+            collection.context.limits,
+            collection.context.session,
+            "all",
+        ),
+    };
+
+    let has_data = model.is_some()
+        || git.is_some()
+        || usage.is_some()
+        || limits.is_some()
+        || session.is_some();
+
+    let payload = InspectOutput {
+        schema_version: JSON_SCHEMA_VERSION,
+        source: source_name.to_string(),
+        codex_home: collection.codex_home.display().to_string(),
+        sessions_dir: collection.sessions_dir.display().to_string(),
+        latest_rollout: collection
+            .latest_rollout
+            .as_ref()
+            .map(|path| path.display().to_string()),
+        model,
+        git,
+        usage,
+        limits,
+        session,
+    };
+
+    println!("{}", serde_json::to_string_pretty(&payload)?);
+    Ok(if has_data {
+        ExitStatus::Success
+    } else {
+        ExitStatus::NoData
+    })
+}
+
+fn run_patch_diagnose(
+    cfg: &config::Config,
+    collect_options: collect::CollectOptions,
+    as_json: bool,
+) -> Result<ExitStatus> {
+    let collection = collect::collect_with(cfg, collect_options)?;
+    let report = patch_diagnose::run_patch_diagnostics(cfg, &collection);
+    if as_json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!("{}", patch_diagnose::render_text(&report));
+    }
+    Ok(if report.has_failures() {
+        ExitStatus::ChecksFailed
+    } else {
+        ExitStatus::Success
+    })
+}
+
+fn run_patch_apply(
+    cfg: &config::Config,
+    collect_options: collect::CollectOptions,
+    assume_yes: bool,
+) -> Result<ExitStatus> {
+    let collection = collect::collect_with(cfg, collect_options)?;
+    let program = patch_diagnose::notify_program();
+    let actions = patch_diagnose::plan_apply(&collection, &program);
+    if actions.is_empty() {
+        println!("nothing to apply");
+        return Ok(ExitStatus::Success);
+    }
+    println!("changes:");
+    for action in &actions {
+        println!("- {}", action.describe());
+    }
+    if !assume_yes && !confirm("Apply these changes?")? {
+        println!("nothing changed");
+        return Ok(ExitStatus::Success);
+    }
+    let report = patch_diagnose::apply(&actions, &config::backups_dir(), chrono::Utc::now())?;
+    for applied in &report.applied {
+        println!("applied: {}", applied);
+    }
+    for backup in &report.backups {
+        println!("backup: {}", backup.display());
+    }
+    Ok(ExitStatus::Success)
+}
+
+fn run_statusline(
+    cfg: &config::Config,
+    collect_options: collect::CollectOptions,
+    cli: &Cli,
+) -> Result<ExitStatus> {
+    let collection = match cli.compat {
+        Some(cli::CompatMode::Claude) if !collect_options.demo => {
+            collect_claude_compat(cfg, collect_options)?
+        }
+        _ => collect::collect_with(cfg, collect_options)?,
+    };
+    let mut segment_list = segments::build_segments(cfg, &collection.context);
+    // Demo data is for looking at: it is not exported, logged, alerted on or fed to hooks.
+    if !collect_options.demo {
+        #[cfg(feature = "otel")]
+        telemetry::export(cfg, &collection.context);
+        alerts::notify(cfg, &collection.context);
+        log_usage(cfg, &collection.context);
+        update_terminal(cfg, cli, &collection.context);
+        segment_list = hooks::apply(&cfg.hooks, segment_list, collection.deadline);
+    }
+    let status = if segment_list.is_empty() {
+        ExitStatus::NoData
+    } else {
+        ExitStatus::Success
+    };
+
+    println!(
+        "{}",
+        render_output(cfg, segment_list, collection.context, cli, false)?
+    );
+    Ok(status)
+}
+
+/// `--screenshot`: renders like [`run_statusline`] but always in color, into an image file
+/// instead of stdout.
+fn run_screenshot(
+    cfg: &config::Config,
+    collect_options: collect::CollectOptions,
+    path: &Path,
+) -> Result<ExitStatus> {
+    let collection = collect::collect_with(cfg, collect_options)?;
+    let segment_list = segments::build_segments(cfg, &collection.context);
+    screenshot::write(path, &screenshot::svg(cfg, &segment_list))?;
+    println!("wrote {}", path.display());
+    Ok(if segment_list.is_empty() {
+        ExitStatus::NoData
+    } else {
+        ExitStatus::Success
+    })
+}
+
+/// `--compare A B`: each side is a config file (an existing path or a `.toml` name) with its
+/// own theme applied, or a theme applied to the current config. Both render from one
+/// collection, colored and plain, followed by the settings that differ.
+fn run_compare(cfg: &config::Config, sides: &[String], cli: &Cli) -> Result<ExitStatus> {
+    let resolve = |side: &str| -> Result<config::Config> {
+        let path = Path::new(side);
+        if path.is_file() || side.ends_with(".toml") {
+            let loaded = config::load_from_path(path).context(ConfigInvalid)?;
+            return themes::apply_theme(&loaded, &loaded.theme, &loaded.themes_dir());
+        }
+        themes::apply_theme(cfg, side, &cfg.themes_dir())
+    };
+    let configs = sides
+        .iter()
+        .map(|side| resolve(side))
+        .collect::<Result<Vec<_>>>()?;
+    for (side, side_cfg) in sides.iter().zip(&configs) {
+        // Each side with its own config, so its rollout or sessions dir overrides apply.
+        let context = collect::collect_with(side_cfg, cli.collect_options())?.context;
+        let segment_list = segments::build_segments(side_cfg, &context);
+        println!("{side}");
+        if use_color(side_cfg, cli) {
+            println!("  {}", render::render_line(side_cfg, &segment_list, false));
+        }
+        println!("  {}", render::render_line(side_cfg, &segment_list, true));
+    }
+    println!();
+    let differences = config_differences(&configs[0], &configs[1]);
+    if differences.is_empty() {
+        println!("no config differences");
+    } else {
+        println!("config differences ({} -> {}):", sides[0], sides[1]);
+        for difference in differences {
+            println!("  {difference}");
+        }
+    }
+    Ok(ExitStatus::Success)
+}
+
+/// `key: a -> b` for every setting that differs, with dotted keys; list entries that have an
+/// `id` (segments) are keyed by it instead of their position.
+fn config_differences(a: &config::Config, b: &config::Config) -> Vec<String> {
+    fn flatten(prefix: &str, value: &serde_json::Value, out: &mut BTreeMap<String, String>) {
+        let key = |name: &str| {
+            if prefix.is_empty() {
+                name.to_string()
+            } else {
+                format!("{prefix}.{name}")
+            }
+        };
+        match value {
+            serde_json::Value::Object(map) => {
+                for (name, value) in map {
+                    flatten(&key(name), value, out);
+                }
+            }
+            serde_json::Value::Array(items)
+                if items.iter().all(|item| item["id"].is_string()) && !items.is_empty() =>
+            {
+                for item in items {
+                    let id = item["id"].as_str().unwrap_or_default();
+                    flatten(&key(id), item, out);
+                }
+            }
+            value => {
+                out.insert(prefix.to_string(), value.to_string());
+            }
+        }
+    }
+    let flat = |cfg: &config::Config| {
+        let mut out = BTreeMap::new();
+        if let Ok(value) = serde_json::to_value(cfg) {
+            flatten("", &value, &mut out);
+        }
+        out
+    };
+    let (a, b) = (flat(a), flat(b));
+    let keys: BTreeSet<&String> = a.keys().chain(b.keys()).collect();
+    keys.into_iter()
+        .filter(|key| a.get(*key) != b.get(*key))
+        .map(|key| {
+            let side = |map: &BTreeMap<String, String>| {
+                map.get(key)
+                    .cloned()
+                    .unwrap_or_else(|| "(unset)".to_string())
+            };
+            format!("{key}: {} -> {}", side(&a), side(&b))
+        })
+        .collect()
+}
+
+/// `--compat claude`: the session comes from Claude Code's stdin JSON instead of Codex
+/// rollouts; git is still collected, from the workspace directory Claude reports.
+fn collect_claude_compat(
+    cfg: &config::Config,
+    collect_options: collect::CollectOptions,
+) -> Result<collect::Collection> {
+    let mut text = String::new();
+    std::io::stdin()
+        .read_to_string(&mut text)
+        .context("failed to read statusline JSON from stdin")?;
+    let input = compat::parse_claude_input(&text)?;
+    if let Some(dir) = input.current_dir() {
+        std::env::set_current_dir(dir)
+            .with_context(|| format!("failed to enter workspace dir: {}", dir.display()))?;
+    }
+
+    let options = collect::CollectOptions {
+        skip_rollout: true,
+        ..collect_options
+    };
+    let mut collection = collect::collect_with(cfg, options)?;
+    input.apply(&mut collection.context);
+    Ok(collection)
+}
+
+/// Re-collects every `--interval` seconds and prints a new line (or, with `--stream`, one
+/// compact JSON object) whenever the rendered segments change. Runs until interrupted or
+/// stdout is closed.
+fn run_watch(
+    cfg: &config::Config,
+    collect_options: collect::CollectOptions,
+    cli: &Cli,
+) -> Result<ExitStatus> {
+    let interval = std::time::Duration::from_secs(cli.interval());
+    let mut stdout = std::io::stdout();
+    let mut last_segments: Option<String> = None;
+    let endpoint = cli.serve.map(serve::StatusEndpoint::bind).transpose()?;
+    let mut pipe = cli
+        .output_pipe
+        .as_deref()
+        .map(pipe::OutputPipe::open)
+        .transpose()?;
+    if let Some(endpoint) = &endpoint {
+        eprintln!("codexline: serving http://{}/status", endpoint.addr());
+    }
+
+    loop {
+        // A failed update (a rollout being rotated, a cache dir briefly unwritable) is
+        // reported and retried; the last output and endpoint body stay as they were.
+        let collection = match collect::collect_with(cfg, collect_options) {
+            Ok(collection) => collection,
+            Err(err) => {
+                eprintln!("codexline: {:#}", anyhow::Error::new(err));
+                std::thread::sleep(interval);
+                continue;
+            }
+        };
+        alerts::notify(cfg, &collection.context);
+        let segment_list = hooks::apply(
+            &cfg.hooks,
+            segments::build_segments(cfg, &collection.context),
+            collection.deadline,
+        );
+        let key = match serde_json::to_string(&segment_list) {
+            Ok(key) => key,
+            Err(err) => {
+                eprintln!("codexline: {err}");
+                std::thread::sleep(interval);
+                continue;
+            }
+        };
+
+        if last_segments.as_deref() != Some(key.as_str()) {
+            #[cfg(feature = "otel")]
+            telemetry::export(cfg, &collection.context);
+            log_usage(cfg, &collection.context);
+            update_terminal(cfg, cli, &collection.context);
+            if let Some(endpoint) = &endpoint {
+                let payload = StatuslineOutput {
+                    schema_version: JSON_SCHEMA_VERSION,
+                    line: render::render_line(cfg, &segment_list, true),
+                    segments: segment_list.clone(),
+                    context: collection.context.clone(),
+                };
+                match serde_json::to_string(&payload) {
+                    Ok(json) => endpoint.update(json),
+                    Err(err) => eprintln!("codexline: {err}"),
+                }
+            }
+            let output = match render_output(cfg, segment_list, collection.context, cli, true) {
+                Ok(output) => output,
+                Err(err) => {
+                    eprintln!("codexline: {err:#}");
+                    std::thread::sleep(interval);
+                    continue;
+                }
+            };
+            let written = match pipe.as_mut() {
+                Some(pipe) => {
+                    pipe.publish(&output)?;
+                    Ok(())
+                }
+                None => writeln!(stdout, "{}", output).and_then(|_| stdout.flush()),
+            };
+            if let Err(err) = written {
+                if err.kind() == std::io::ErrorKind::BrokenPipe {
+                    return Ok(ExitStatus::Success);
+                }
+                return Err(err.into());
+            }
+            last_segments = Some(key);
+        }
+
+        std::thread::sleep(interval);
+    }
+}
+
+/// `--set-title` / `terminal.set_title` and `--iterm2-badge` / `terminal.iterm2_badge`.
+fn update_terminal(cfg: &config::Config, cli: &Cli, context: &context::StatusContext) {
+    if cli.set_title || cfg.terminal.set_title {
+        if let Some(title) = segments::title_summary(cfg, context) {
+            terminal::set_title(&title);
+        }
+    }
+    if cli.iterm2_badge || cfg.terminal.iterm2_badge {
+        if let Some(badge) = segments::context_summary(context) {
+            terminal::set_iterm2_badge(&badge);
+        }
+    }
+}
+
+/// `usage.log_csv` / `usage.sqlite`; a failing log must not break the statusline, so errors
+/// only warn.
+fn log_usage(cfg: &config::Config, context: &context::StatusContext) {
+    if let Some(path) = &cfg.usage.log_csv {
+        if let Err(err) = usage_log::append_csv(path, context) {
+            eprintln!("codexline: usage log: {err:#}");
+        }
+    }
+    #[cfg(feature = "sqlite")]
+    if let Some(path) = &cfg.usage.sqlite {
+        if let Err(err) = store::record(path, context) {
+            eprintln!("codexline: usage store: {err:#}");
+        }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+fn run_stats(cfg: &config::Config, days: u32, json: bool) -> Result<ExitStatus> {
+    let Some(path) = &cfg.usage.sqlite else {
+        anyhow::bail!("usage.sqlite is not set in the config");
+    };
+    let rows = store::daily_usage(path, days)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&rows)?);
+    } else {
+        println!(
+            "{:<12} {:>8} {:>14} {:>10}",
+            "day", "sessions", "tokens", "peak 5h"
+        );
+        for row in &rows {
+            let peak = row
+                .peak_primary_used_percent
+                .map_or_else(|| "-".to_string(), |used| format!("{used:.0}%"));
+            println!(
+                "{:<12} {:>8} {:>14} {:>10}",
+                row.day, row.sessions, row.total_tokens, peak
+            );
+        }
+    }
+    Ok(if rows.is_empty() {
+        ExitStatus::NoData
+    } else {
+        ExitStatus::Success
+    })
+}
+
+fn render_output(
+    cfg: &config::Config,
+    segment_list: Vec<segments::SegmentPiece>,
+    context: context::StatusContext,
+    cli: &Cli,
+    compact_json: bool,
+) -> Result<String> {
+    let _span = trace::span("render", "render");
+    if cli.porcelain {
+        return Ok(render::render_porcelain(&segment_list));
+    }
+
+    if cli.json {
+        let line = render::render_line(cfg, &segment_list, true);
+        let payload = StatuslineOutput {
+            schema_version: JSON_SCHEMA_VERSION,
+            line,
+            segments: segment_list,
+            context,
+        };
+        return Ok(if compact_json {
+            serde_json::to_string(&payload)?
+        } else {
+            serde_json::to_string_pretty(&payload)?
+        });
+    }
+
+    if let Some(format) = cli.format {
+        return Ok(match format {
+            cli::OutputFormat::Tmux => render::render_tmux(cfg, &segment_list),
+            cli::OutputFormat::Zsh => render::render_zsh(cfg, &segment_list),
+            cli::OutputFormat::Bash => render::render_bash(cfg, &segment_list),
+            cli::OutputFormat::Fish => render::render_fish(cfg, &segment_list),
+            cli::OutputFormat::Powershell => render::render_powershell(cfg, &segment_list),
+            cli::OutputFormat::Lualine => render::render_lualine(cfg, &segment_list),
+        });
+    }
+
+    Ok(render::render_line(
+        cfg,
+        &segment_list,
+        !use_color(cfg, cli),
+    ))
+}
+
+/// `--plain` wins, then `--color`, then `style.color`. In `auto`, colors need a terminal on
+/// stdout and no `NO_COLOR`; `--compat` hosts capture stdout but render ANSI themselves.
+fn use_color(cfg: &Config, cli: &Cli) -> bool {
+    if cli.plain {
+        return false;
+    }
+    let mode = match cli.color {
+        Some(cli::ColorChoice::Auto) => ColorMode::Auto,
+        Some(cli::ColorChoice::Always) => ColorMode::Always,
+        Some(cli::ColorChoice::Never) => ColorMode::Never,
+        None => cfg.style.color,
+    };
+    match mode {
+        ColorMode::Always => true,
+        ColorMode::Never => false,
+        ColorMode::Auto => {
+            let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+            cli.compat.is_some() || (std::io::stdout().is_terminal() && !no_color)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_schemas_require_schema_version() {
+        for schema in [
+            schemars::schema_for!(StatuslineOutput),
+            schemars::schema_for!(InspectOutput),
+        ] {
+            let value = serde_json::to_value(&schema).expect("schema to json");
+            let required = value["required"].as_array().expect("required list");
+            assert!(required.iter().any(|v| v == "schema_version"));
+        }
+    }
+
+    #[test]
+    fn color_flags_override_style_color() {
+        let mut cfg = Config::default();
+        cfg.style.color = ColorMode::Never;
+        let cli = |args: &[&str]| Cli::parse_from([&["codexline"], args].concat());
+
+        assert!(!use_color(&cfg, &cli(&[])));
+        assert!(use_color(&cfg, &cli(&["--color", "always"])));
+        assert!(!use_color(&cfg, &cli(&["--plain", "--color", "always"])));
+        cfg.style.color = ColorMode::Auto;
+        assert!(use_color(&cfg, &cli(&["--compat", "claude"])));
+    }
+
+    #[test]
+    fn config_differences_key_segments_by_id() {
+        let a = Config::default();
+        let b = themes::apply_theme(&a, "nord", &a.themes_dir()).expect("nord");
+        let mut c = a.clone();
+        c.segments[0].enabled = !c.segments[0].enabled;
+
+        assert!(config_differences(&a, &a).is_empty());
+        assert!(config_differences(&a, &b)
+            .iter()
+            .any(|line| line.starts_with("theme: \"default\" -> \"nord\"")));
+        let id = c.segments[0].id.as_str();
+        assert_eq!(
+            config_differences(&a, &c),
+            vec![format!(
+                "segments.{id}.enabled: {} -> {}",
+                a.segments[0].enabled, c.segments[0].enabled
+            )]
+        );
+    }
+
+    #[test]
+    fn only_failed_checks_fail_the_doctor() {
+        use patch_diagnose::{CheckStatus, PatchCheck};
+
+        let check = |status| PatchCheck {
+            name: "git".to_string(),
+            status,
+            detail: "git collection skipped (--no-git)".to_string(),
+        };
+        assert_eq!(
+            checks_status(&[check(CheckStatus::Ok), check(CheckStatus::Warn)]),
+            ExitStatus::Success
+        );
+        assert_eq!(
+            checks_status(&[check(CheckStatus::Warn), check(CheckStatus::Fail)]),
+            ExitStatus::ChecksFailed
+        );
+    }
+}
@@ -0,0 +1,57 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Default)]
+pub struct CleanReport {
+    pub removed: Vec<PathBuf>,
+    pub missing: Vec<PathBuf>,
+}
+
+/// Removes every target, directories recursively. Missing targets are reported, not errors.
+pub fn remove_targets(targets: &[PathBuf]) -> Result<CleanReport> {
+    let mut report = CleanReport::default();
+    for target in targets {
+        if remove_path(target)? {
+            report.removed.push(target.clone());
+        } else {
+            report.missing.push(target.clone());
+        }
+    }
+    Ok(report)
+}
+
+fn remove_path(path: &Path) -> Result<bool> {
+    let Ok(meta) = fs::symlink_metadata(path) else {
+        return Ok(false);
+    };
+    if meta.is_dir() {
+        fs::remove_dir_all(path)
+            .with_context(|| format!("failed to remove dir: {}", path.display()))?;
+    } else {
+        fs::remove_file(path)
+            .with_context(|| format!("failed to remove file: {}", path.display()))?;
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn remove_targets_reports_removed_and_missing() {
+        let dir = TempDir::new().expect("temp dir");
+        let cache = dir.path().join("cache");
+        fs::create_dir_all(cache.join("nested")).expect("mkdir");
+        fs::write(cache.join("nested").join("entry"), "x").expect("write");
+        let missing = dir.path().join("backups");
+
+        let report = remove_targets(&[cache.clone(), missing.clone()]).expect("clean");
+
+        assert_eq!(report.removed, vec![cache.clone()]);
+        assert_eq!(report.missing, vec![missing]);
+        assert!(!cache.exists());
+    }
+}
@@ -1,17 +1,48 @@
-use clap::{Parser, ValueEnum};
+use crate::collect::CollectOptions;
+use clap::{ArgGroup, Parser, ValueEnum};
+use std::path::PathBuf;
 
 #[derive(Debug, Parser)]
-#[command(name = "codexline", version, about = "Codex statusline toolkit")]
+#[command(
+    name = "codexline",
+    version,
+    disable_version_flag = true,
+    about = "Codex statusline toolkit",
+    group(ArgGroup::new("watching").args(["watch", "stream", "serve"]).multiple(true))
+)]
 pub struct Cli {
+    #[arg(short = 'V', long, help = "Print version")]
+    pub version: bool,
+
+    #[arg(
+        long,
+        requires = "version",
+        help = "With --version, include commit, build date, features and Codex CLI version"
+    )]
+    pub verbose: bool,
+
     #[arg(long, help = "Enter interactive configuration TUI")]
     pub config: bool,
 
     #[arg(long, help = "Open interactive main menu")]
     pub menu: bool,
 
+    #[arg(
+        long,
+        help = "Toggle and reorder segments in a compact inline selector (no full-screen TUI)"
+    )]
+    pub tweak: bool,
+
     #[arg(long, help = "Override theme for current execution")]
     pub theme: Option<String>,
 
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Read custom themes from this directory for current execution"
+    )]
+    pub theme_dir: Option<std::path::PathBuf>,
+
     #[arg(long, help = "Print current config as TOML")]
     pub print: bool,
 
@@ -24,12 +55,28 @@ pub struct Cli {
     #[arg(long, help = "Run environment diagnostics")]
     pub doctor: bool,
 
+    #[arg(
+        long,
+        requires = "doctor",
+        conflicts_with = "json",
+        help = "With --doctor, apply the safe remediations it suggests (asks first unless --yes)"
+    )]
+    pub fix: bool,
+
     #[arg(
         long,
         help = "Run patch compatibility diagnostics (no file modification)"
     )]
     pub patch: bool,
 
+    #[arg(
+        long,
+        requires = "patch",
+        conflicts_with = "json",
+        help = "With --patch, write the Codex notify hook and missing dirs, backing up changed files"
+    )]
+    pub apply: bool,
+
     #[arg(
         long,
         value_enum,
@@ -39,12 +86,217 @@ pub struct Cli {
     )]
     pub inspect: Option<InspectSource>,
 
+    #[arg(
+        long,
+        value_enum,
+        num_args = 0..=1,
+        default_missing_value = "statusline",
+        help = "Print the JSON schema of --json or --inspect output"
+    )]
+    pub json_schema: Option<SchemaTarget>,
+
     #[arg(long, help = "Output without ANSI colors")]
     pub plain: bool,
 
+    #[arg(
+        long,
+        value_enum,
+        value_name = "WHEN",
+        help = "Color the line: auto (only on a terminal), always or never; overrides style.color"
+    )]
+    pub color: Option<ColorChoice>,
+
     #[arg(long, help = "Output structured JSON")]
     pub json: bool,
 
+    #[arg(long, help = "Output one stable key=value line per segment field")]
+    pub porcelain: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        help = "Emit the line with color markup for a host program instead of raw ANSI"
+    )]
+    pub format: Option<OutputFormat>,
+
+    #[arg(
+        long,
+        help = "Keep running and print a new line whenever the statusline changes"
+    )]
+    pub watch: bool,
+
+    #[arg(
+        long,
+        requires = "json",
+        help = "With --json, stream one compact JSON object per update (implies --watch)"
+    )]
+    pub stream: bool,
+
+    #[arg(
+        long,
+        value_name = "PATH",
+        requires = "watching",
+        help = "With --watch (or --stream / --serve), write each update to this named pipe (created if missing) instead of stdout"
+    )]
+    pub output_pipe: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "PORT",
+        help = "Serve the latest statusline JSON on http://127.0.0.1:PORT/status (implies --watch)"
+    )]
+    pub serve: Option<u16>,
+
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        help = "Polling interval for --watch / --stream [default: 2]"
+    )]
+    pub interval: Option<u64>,
+
+    #[arg(
+        long,
+        value_enum,
+        conflicts_with_all = ["watch", "stream", "serve"],
+        help = "Read another tool's statusline stdin JSON instead of Codex rollouts"
+    )]
+    pub compat: Option<CompatMode>,
+
+    #[cfg(feature = "sqlite")]
+    #[arg(
+        long,
+        value_name = "DAYS",
+        num_args = 0..=1,
+        default_missing_value = "30",
+        help = "Print daily usage from the usage.sqlite store (default: last 30 days)"
+    )]
+    pub stats: Option<u32>,
+
+    #[cfg(feature = "sqlite")]
+    #[arg(
+        long,
+        value_name = "DAYS",
+        num_args = 0..=1,
+        default_missing_value = "14",
+        help = "Open charts of the usage.sqlite store: tokens per day and model, rate limits (default: last 14 days)"
+    )]
+    pub dashboard: Option<u32>,
+
+    #[arg(
+        long,
+        help = "Refresh the on-disk caches without printing (run in the background from a shell hook)"
+    )]
+    pub prime: bool,
+
+    /// Turn summary Codex appends when it runs `codexline --prime` as its notify hook.
+    #[arg(hide = true, requires = "prime")]
+    pub notify_payload: Option<String>,
+
+    #[arg(
+        long,
+        help = "Also set the terminal/tab title to a model + context summary"
+    )]
+    pub set_title: bool,
+
+    #[arg(long, help = "Also set the iTerm2 session badge to context usage")]
+    pub iterm2_badge: bool,
+
+    #[arg(
+        long,
+        value_name = "DIR",
+        help = "Write a synthetic CODEX_HOME with rollout files for integration tests"
+    )]
+    pub generate_fixtures: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 3,
+        requires = "generate_fixtures",
+        help = "Rollout files to generate"
+    )]
+    pub fixture_sessions: usize,
+
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 20,
+        requires = "generate_fixtures",
+        help = "Token count events per generated rollout"
+    )]
+    pub fixture_events: usize,
+
+    #[arg(
+        long,
+        value_name = "DAYS",
+        default_value_t = 7,
+        requires = "generate_fixtures",
+        help = "Age of the oldest generated rollout"
+    )]
+    pub fixture_max_age_days: u32,
+
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Append a troubleshooting log to PATH (filter with CODEXLINE_LOG, default debug)"
+    )]
+    pub log_file: Option<PathBuf>,
+
+    #[arg(long, help = "Skip git collection for this run")]
+    pub no_git: bool,
+
+    #[arg(long, help = "Skip rollout collection for this run")]
+    pub no_rollout: bool,
+
+    #[arg(
+        long,
+        help = "Render from built-in sample data instead of collecting (screenshots, previews)"
+    )]
+    pub demo: bool,
+
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Write the colored statusline as an image (.svg, or .png via rsvg-convert)"
+    )]
+    pub screenshot: Option<PathBuf>,
+
+    #[arg(
+        long,
+        num_args = 2,
+        value_names = ["A", "B"],
+        help = "Render two themes or config files one above the other and list their differences"
+    )]
+    pub compare: Option<Vec<String>>,
+
+    #[arg(
+        long,
+        value_enum,
+        value_name = "SHELL",
+        help = "Print the snippet wiring codexline into a shell prompt or tmux"
+    )]
+    pub install_shell: Option<ShellKind>,
+
+    #[arg(
+        long,
+        requires = "install_shell",
+        help = "With --install-shell, append the snippet to the shell rc file"
+    )]
+    pub append: bool,
+
+    #[arg(long, help = "Remove caches and backups")]
+    pub clean: bool,
+
+    #[arg(
+        long,
+        requires = "clean",
+        help = "With --clean, also remove the whole codexline config dir (config and themes)"
+    )]
+    pub all: bool,
+
+    #[arg(long, help = "Skip confirmation prompts")]
+    pub yes: bool,
+
     #[arg(long, help = "Apply quick profile and save config")]
     pub quick_config: bool,
 
@@ -64,6 +316,51 @@ pub enum InspectSource {
     All,
 }
 
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum SchemaTarget {
+    Statusline,
+    Inspect,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CompatMode {
+    /// Claude Code statusline protocol (session JSON on stdin)
+    Claude,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// tmux `#[fg=...,bg=...]` style directives for `status-right`
+    Tmux,
+    /// zsh `%F{..}` prompt escapes for `PROMPT` / `RPROMPT`
+    Zsh,
+    /// ANSI escapes marked as non-printing for bash `PS1`
+    Bash,
+    /// fish `set_color` script, to be piped into `source`
+    Fish,
+    /// ANSI SGR sequences as produced by PowerShell's `$PSStyle`, with backgrounds
+    Powershell,
+    /// Vim statusline items (`%#Group#`, `%%`) for lualine / heirline components
+    Lualine,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ShellKind {
+    Bash,
+    Zsh,
+    Fish,
+    Tmux,
+    /// PowerShell 7+ (`$PROFILE`)
+    Pwsh,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ValueEnum)]
 pub enum EnhancementKind {
     Git,
@@ -71,19 +368,98 @@ pub enum EnhancementKind {
 }
 
 impl Cli {
+    #[cfg(feature = "tui")]
     pub fn has_explicit_action(&self) -> bool {
-        self.config
+        #[cfg(feature = "sqlite")]
+        if self.stats.is_some() || self.dashboard.is_some() {
+            return true;
+        }
+        self.version
+            || self.config
             || self.menu
+            || self.tweak
             || self.theme.is_some()
+            || self.theme_dir.is_some()
             || self.print
             || self.init
             || self.check
             || self.doctor
             || self.patch
             || self.inspect.is_some()
+            || self.json_schema.is_some()
+            || self.install_shell.is_some()
+            || self.clean
             || self.plain
+            || self.color.is_some()
             || self.json
+            || self.porcelain
+            || self.format.is_some()
+            || self.compat.is_some()
+            || self.set_title
+            || self.prime
+            || self.generate_fixtures.is_some()
+            || self.iterm2_badge
+            || self.watch
+            || self.stream
+            || self.serve.is_some()
+            || self.no_git
+            || self.no_rollout
+            || self.demo
+            || self.screenshot.is_some()
+            || self.compare.is_some()
             || self.quick_config
             || !self.enhance.is_empty()
+            || self.interval.is_some()
+            || self.log_file.is_some()
+    }
+
+    /// Seconds between `--watch` polls, at least one.
+    pub fn interval(&self) -> u64 {
+        self.interval.unwrap_or(2).max(1)
+    }
+
+    pub fn collect_options(&self) -> CollectOptions {
+        CollectOptions {
+            skip_git: self.no_git,
+            skip_rollout: self.no_rollout,
+            demo: self.demo,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn output_pipe_accepts_any_watching_mode() {
+        let parse = |args: &[&str]| Cli::try_parse_from([&["codexline"], args].concat());
+
+        for args in [
+            &["--watch", "--output-pipe", "/tmp/line"][..],
+            &["--json", "--stream", "--output-pipe", "/tmp/line"][..],
+            &["--serve", "8080", "--output-pipe", "/tmp/line"][..],
+        ] {
+            let cli = parse(args).expect("parses");
+            assert_eq!(cli.output_pipe, Some(PathBuf::from("/tmp/line")));
+        }
+        assert!(parse(&["--output-pipe", "/tmp/line"]).is_err());
+    }
+
+    #[cfg(feature = "tui")]
+    #[test]
+    fn explicit_flags_skip_the_menu() {
+        let parse = |args: &[&str]| Cli::parse_from([&["codexline"], args].concat());
+
+        assert!(!parse(&[]).has_explicit_action());
+        for args in [
+            &["--doctor"][..],
+            &["--log-file", "/tmp/codexline.log"][..],
+            &["--interval", "5"][..],
+        ] {
+            assert!(parse(args).has_explicit_action(), "{args:?}");
+        }
+        assert_eq!(parse(&[]).interval(), 2);
+        assert_eq!(parse(&["--interval", "0"]).interval(), 1);
     }
 }
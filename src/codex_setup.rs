@@ -0,0 +1,225 @@
+//! Sanity checks on the Codex install itself (`auth.json`, `config.toml`) for `--doctor`: a
+//! Codex that cannot log in or start writes no rollouts, which shows up as an empty line.
+
+use crate::patch_diagnose::CheckStatus;
+use chrono::{DateTime, Utc};
+use std::fs;
+use std::path::Path;
+
+/// Whether Codex has credentials: an API key in `auth.json` or `OPENAI_API_KEY`, or a
+/// ChatGPT login whose access token is unexpired or refreshable. Secrets are never printed.
+pub fn auth_check(codex_home: &Path) -> (CheckStatus, String) {
+    let api_key_env = std::env::var_os("OPENAI_API_KEY").is_some_and(|key| !key.is_empty());
+    let text = fs::read_to_string(codex_home.join("auth.json")).ok();
+    auth_status(text.as_deref(), api_key_env, Utc::now())
+}
+
+fn auth_status(text: Option<&str>, api_key_env: bool, now: DateTime<Utc>) -> (CheckStatus, String) {
+    let env_fallback = |missing: &str| {
+        if api_key_env {
+            (CheckStatus::Ok, format!("{missing}, using OPENAI_API_KEY"))
+        } else {
+            (CheckStatus::Warn, format!("{missing}, run `codex login`"))
+        }
+    };
+    let Some(text) = text else {
+        return env_fallback("no Codex credentials (auth.json missing)");
+    };
+    let Ok(auth) = serde_json::from_str::<serde_json::Value>(text) else {
+        return env_fallback("auth.json is not valid JSON");
+    };
+
+    let non_empty = |value: &serde_json::Value| value.as_str().is_some_and(|s| !s.is_empty());
+    if non_empty(&auth["OPENAI_API_KEY"]) {
+        return (CheckStatus::Ok, "API key in auth.json".to_string());
+    }
+    let tokens = &auth["tokens"];
+    if !non_empty(&tokens["access_token"]) {
+        return env_fallback("auth.json holds no API key or login tokens");
+    }
+    let refreshable = non_empty(&tokens["refresh_token"]);
+    match tokens["access_token"].as_str().and_then(jwt_expiry) {
+        Some(expiry) if expiry > now => (
+            CheckStatus::Ok,
+            format!(
+                "ChatGPT login, access token valid until {}",
+                expiry.to_rfc3339()
+            ),
+        ),
+        Some(expiry) if refreshable => (
+            CheckStatus::Ok,
+            format!(
+                "ChatGPT login, access token expired {}; Codex refreshes it on its next request",
+                expiry.to_rfc3339()
+            ),
+        ),
+        Some(expiry) => (
+            CheckStatus::Warn,
+            format!(
+                "ChatGPT login expired {} and cannot be refreshed, run `codex login`",
+                expiry.to_rfc3339()
+            ),
+        ),
+        None => (CheckStatus::Ok, "ChatGPT login".to_string()),
+    }
+}
+
+/// The `exp` claim of a JWT, without verifying the signature.
+fn jwt_expiry(token: &str) -> Option<DateTime<Utc>> {
+    let payload = base64url_decode(token.split('.').nth(1)?)?;
+    let claims: serde_json::Value = serde_json::from_slice(&payload).ok()?;
+    DateTime::from_timestamp(claims["exp"].as_i64()?, 0)
+}
+
+/// Unpadded base64url, as used in JWT segments.
+fn base64url_decode(text: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(text.len() * 3 / 4);
+    let (mut buffer, mut bits) = (0u32, 0);
+    for byte in text.trim_end_matches('=').bytes() {
+        let value = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'-' | b'+' => 62,
+            b'_' | b'/' => 63,
+            _ => return None,
+        };
+        buffer = (buffer << 6) | u32::from(value);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Some(out)
+}
+
+/// Whether `$CODEX_HOME/config.toml` parses and names a model, directly or through the
+/// selected `profile`. A missing file is fine: Codex runs on its defaults.
+pub fn config_check(codex_home: &Path) -> (CheckStatus, String) {
+    let text = fs::read_to_string(codex_home.join("config.toml")).ok();
+    config_status(text.as_deref())
+}
+
+fn config_status(text: Option<&str>) -> (CheckStatus, String) {
+    let Some(text) = text else {
+        return (
+            CheckStatus::Ok,
+            "no Codex config.toml, Codex runs on its defaults".to_string(),
+        );
+    };
+    let table = match text.parse::<toml::Table>() {
+        Ok(table) => table,
+        Err(err) => {
+            let reason = err.message().to_string();
+            return (
+                CheckStatus::Warn,
+                format!("Codex config.toml does not parse ({reason}), Codex will not start"),
+            );
+        }
+    };
+
+    let profile = table.get("profile").and_then(toml::Value::as_str);
+    let profile_model = match profile {
+        Some(name) => {
+            let Some(profile) = table
+                .get("profiles")
+                .and_then(|profiles| profiles.get(name))
+            else {
+                return (
+                    CheckStatus::Warn,
+                    format!("Codex config.toml selects profile {name:?}, which is not defined"),
+                );
+            };
+            profile.get("model").and_then(toml::Value::as_str)
+        }
+        None => None,
+    };
+    match profile_model.or_else(|| table.get("model").and_then(toml::Value::as_str)) {
+        Some(model) if !model.is_empty() => match profile {
+            Some(name) => (CheckStatus::Ok, format!("model = {model} (profile {name})")),
+            None => (CheckStatus::Ok, format!("model = {model}")),
+        },
+        _ => (
+            CheckStatus::Warn,
+            "Codex config.toml sets no model, so the model segment stays empty until the \
+             first turn; set `model`"
+                .to_string(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jwt(exp: i64) -> String {
+        let claims = format!("{{\"exp\":{exp}}}");
+        const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+        let mut encoded = String::new();
+        for chunk in claims.as_bytes().chunks(3) {
+            let n = chunk
+                .iter()
+                .enumerate()
+                .fold(0u32, |n, (i, b)| n | u32::from(*b) << (16 - 8 * i));
+            for i in 0..=chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            }
+        }
+        format!("header.{encoded}.signature")
+    }
+
+    #[test]
+    fn auth_and_config_checks() {
+        let now = DateTime::from_timestamp(1_760_000_000, 0).expect("timestamp");
+        let login = |exp: i64, refresh: &str| {
+            format!(
+                "{{\"tokens\":{{\"access_token\":\"{}\",\"refresh_token\":\"{refresh}\"}}}}",
+                jwt(exp)
+            )
+        };
+
+        assert_eq!(auth_status(None, false, now).0, CheckStatus::Warn);
+        assert_eq!(auth_status(None, true, now).0, CheckStatus::Ok);
+        assert_eq!(auth_status(Some("{"), false, now).0, CheckStatus::Warn);
+        let (status, detail) = auth_status(Some("{\"OPENAI_API_KEY\":\"sk-secret\"}"), false, now);
+        assert_eq!(status, CheckStatus::Ok);
+        assert!(!detail.contains("sk-secret"));
+        let (status, detail) = auth_status(Some(&login(1_760_003_600, "")), false, now);
+        assert_eq!(status, CheckStatus::Ok);
+        assert!(detail.contains("2025-10-09T09:53:20"), "{detail}");
+        assert_eq!(
+            auth_status(Some(&login(1_759_996_400, "rt")), false, now).0,
+            CheckStatus::Ok
+        );
+        assert_eq!(
+            auth_status(Some(&login(1_759_996_400, "")), false, now).0,
+            CheckStatus::Warn
+        );
+
+        assert_eq!(config_status(None).0, CheckStatus::Ok);
+        assert_eq!(config_status(Some("model = ")).0, CheckStatus::Warn);
+        assert_eq!(
+            config_status(Some("approval_policy = \"never\"")).0,
+            CheckStatus::Warn
+        );
+        assert_eq!(
+            config_status(Some("model = \"gpt-5-codex\"")),
+            (CheckStatus::Ok, "model = gpt-5-codex".to_string())
+        );
+        assert_eq!(
+            config_status(Some(
+                "model = \"gpt-5\"\nprofile = \"fast\"\n[profiles.fast]\nmodel = \"gpt-5-mini\""
+            )),
+            (
+                CheckStatus::Ok,
+                "model = gpt-5-mini (profile fast)".to_string()
+            )
+        );
+        assert_eq!(
+            config_status(Some("profile = \"gone\"")).0,
+            CheckStatus::Warn
+        );
+    }
+}
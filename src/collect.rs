@@ -1,38 +1,104 @@
-use crate::config::{codex_home, Config};
+//! Gathers git state and the latest Codex rollout into a [`StatusContext`].
+
+use crate::config::{codex_home, Config, SegmentId};
 use crate::context::{
-    GitStatus, RateLimitSnapshot, SessionMetaSnapshot, StatusContext, TokenUsageSnapshot,
+    BudgetStatus, CollectWarning, CollectorId, DailyUsage, GitStatus, LimitForecast, PluginSegment,
+    PullRequestStatus, RateLimitSnapshot, SessionMetaSnapshot, StatusContext, TokenUsageSnapshot,
+    UsageTrend, WarningKind,
 };
-use anyhow::{Context, Result};
-use chrono::{Duration, Utc};
-use serde_json::Value;
+use crate::error::CodexlineError;
+use crate::trace::{self, Level};
+use crate::{analytics, daily, diffstat, fixtures, github, history, plugins, pricing};
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::de::IgnoredAny;
+use serde::{Deserialize, Serialize};
 use std::cmp::Reverse;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::num::NonZeroUsize;
 use std::path::{Path, PathBuf};
 use std::process::Command;
-use std::time::SystemTime;
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::{Duration as StdDuration, Instant, SystemTime};
 use walkdir::WalkDir;
 
+/// Result of [`collect_with`]: the context plus where its data came from.
 #[derive(Debug, Clone)]
 pub struct Collection {
     pub codex_home: PathBuf,
     pub sessions_dir: PathBuf,
     pub latest_rollout: Option<PathBuf>,
     pub context: StatusContext,
+    /// End of the `performance.max_runtime_ms` budget; later stages (hooks) stop here too.
+    pub deadline: Instant,
+}
+
+/// Sources to skip, as with `--no-git` / `--no-rollout`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CollectOptions {
+    pub skip_git: bool,
+    pub skip_rollout: bool,
+    /// Skip every source and use [`fixtures::demo_context`] (`--demo`).
+    pub demo: bool,
 }
 
-#[derive(Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 struct RolloutInfo {
+    #[serde(skip)]
     path: Option<PathBuf>,
     model: Option<String>,
     usage: Option<TokenUsageSnapshot>,
     limits: Option<RateLimitSnapshot>,
     session: Option<SessionMetaSnapshot>,
+    /// Timestamp of the newest line.
+    last_event_at: Option<DateTime<Utc>>,
+    /// Only the head and tail of an oversized file were parsed.
+    #[serde(default)]
+    sampled: bool,
+}
+
+impl RolloutInfo {
+    fn has_data(&self) -> bool {
+        self.model.is_some()
+            || self.usage.is_some()
+            || self.limits.is_some()
+            || self.session.is_some()
+    }
+}
+
+/// [`collect_with`] with nothing skipped.
+pub fn collect(cfg: &Config) -> Result<Collection, CodexlineError> {
+    collect_with(cfg, CollectOptions::default())
 }
 
-pub fn collect(cfg: &Config) -> Result<Collection> {
+/// Collects the context for one render from the current directory and `CODEX_HOME`;
+/// `options` skips git or rollout scanning.
+pub fn collect_with(cfg: &Config, options: CollectOptions) -> Result<Collection, CodexlineError> {
+    if options.demo {
+        let codex_home = codex_home();
+        return Ok(Collection {
+            sessions_dir: codex_home.join("sessions"),
+            codex_home,
+            latest_rollout: None,
+            context: fixtures::demo_context(Utc::now()),
+            deadline: Instant::now() + StdDuration::from_millis(cfg.performance.max_runtime_ms),
+        });
+    }
+    collect_context(cfg, options).map_err(CodexlineError::collect)
+}
+
+fn collect_context(cfg: &Config, options: CollectOptions) -> Result<Collection> {
+    let _span = trace::span("collect", "collect");
     let cwd = std::env::current_dir().context("failed to get current directory")?;
-    let git = collect_git(&cwd);
+    let started = Instant::now();
+    let runtime_deadline = started + StdDuration::from_millis(cfg.performance.max_runtime_ms);
+    let deadline = |timeout_ms: u64| {
+        (started + StdDuration::from_millis(timeout_ms.min(cfg.collect.budget_ms)))
+            .min(runtime_deadline)
+    };
 
     let codex_home_dir = codex_home();
     let sessions_dir = cfg
@@ -41,27 +107,509 @@ pub fn collect(cfg: &Config) -> Result<Collection> {
         .clone()
         .unwrap_or_else(|| codex_home_dir.join("sessions"));
 
-    let rollout = collect_rollout(cfg, &sessions_dir)?;
-
-    let context = StatusContext {
-        now: Utc::now(),
-        cwd: cwd.clone(),
-        project_root: get_git_root(&cwd),
-        model: rollout.model,
-        git,
-        usage: rollout.usage,
-        limits: rollout.limits,
-        session: rollout.session,
+    let mut env = CollectEnv {
+        cfg: cfg.clone(),
+        options,
+        sessions_dir: sessions_dir.clone(),
+        deadline: runtime_deadline,
+        context: StatusContext {
+            remote_host: ssh_session().then(host_name),
+            ..StatusContext::new(Utc::now(), cwd.clone())
+        },
     };
+    let mut latest_rollout = None;
+
+    // Collectors of one wave run side by side; one that misses its deadline is left to
+    // finish in the background and its segments are marked degraded.
+    for wave in &Registry::builtin().waves {
+        let shared = Arc::new(env.clone());
+        let pending: Vec<_> = wave
+            .iter()
+            .filter(|collector| collector.enabled(&shared))
+            .map(|collector| {
+                let (id, timeout_ms) = (collector.id(), collector.timeout_ms(cfg));
+                let collector = Arc::clone(collector);
+                let env = Arc::clone(&shared);
+                let rx = spawn_collector(move || {
+                    let _span = trace::span(collector.target(), "collect");
+                    collector.collect(&env)
+                });
+                (id, timeout_ms, rx)
+            })
+            .collect();
+
+        for (id, timeout_ms, rx) in pending {
+            let fragment = match timeout_ms {
+                Some(timeout_ms) => wait_for(&rx, deadline(timeout_ms)),
+                None => rx.recv().ok(),
+            };
+            match fragment {
+                Some(fragment) => {
+                    if let Some(path) = fragment.and_then(|f| f.apply(id, &mut env.context)) {
+                        latest_rollout = Some(path);
+                    }
+                }
+                None => {
+                    trace::event(
+                        Level::Warn,
+                        "collect",
+                        format_args!("{id:?} missed its deadline"),
+                    );
+                    env.context.degraded.push(id);
+                }
+            }
+        }
+    }
 
     Ok(Collection {
         codex_home: codex_home_dir,
         sessions_dir,
-        latest_rollout: rollout.path,
-        context,
+        latest_rollout,
+        context: env.context,
+        deadline: runtime_deadline,
     })
 }
 
+/// What a [`Collector`] sees: the config, the options and the context assembled by earlier
+/// waves.
+#[derive(Debug, Clone)]
+pub(crate) struct CollectEnv {
+    pub cfg: Config,
+    pub options: CollectOptions,
+    pub sessions_dir: PathBuf,
+    /// End of the `performance.max_runtime_ms` budget.
+    pub deadline: Instant,
+    pub context: StatusContext,
+}
+
+impl CollectEnv {
+    fn segment_enabled(&self, id: SegmentId) -> bool {
+        self.cfg
+            .segments
+            .iter()
+            .any(|segment| segment.id == id && segment.enabled)
+    }
+}
+
+/// Part of the [`StatusContext`] produced by one collector; `None` fields leave the context
+/// unchanged.
+#[derive(Debug, Default)]
+pub(crate) struct ContextFragment {
+    pub project_root: Option<PathBuf>,
+    pub model: Option<String>,
+    pub git: Option<GitStatus>,
+    pub usage: Option<TokenUsageSnapshot>,
+    pub limits: Option<RateLimitSnapshot>,
+    pub session: Option<SessionMetaSnapshot>,
+    pub pull_request: Option<PullRequestStatus>,
+    pub trend: Option<UsageTrend>,
+    pub forecast: Option<LimitForecast>,
+    pub budget: Option<BudgetStatus>,
+    pub daily: Option<DailyUsage>,
+    pub last_event_at: Option<DateTime<Utc>>,
+    pub plugins: Vec<PluginSegment>,
+    /// Rollout file the data came from, reported as [`Collection::latest_rollout`].
+    pub rollout: Option<PathBuf>,
+    /// The data is incomplete (see [`StatusContext::partial`]).
+    pub partial: bool,
+    /// The collector stopped at its deadline with part of its work done.
+    pub degraded: bool,
+    /// What went wrong, when the data is missing or out of date.
+    pub warning: Option<WarningKind>,
+}
+
+impl ContextFragment {
+    /// Merges the fragment into `ctx`, returning the rollout path it came from.
+    fn apply(self, id: CollectorId, ctx: &mut StatusContext) -> Option<PathBuf> {
+        ctx.project_root = self.project_root.or(ctx.project_root.take());
+        ctx.model = self.model.or(ctx.model.take());
+        ctx.git = self.git.or(ctx.git.take());
+        ctx.usage = self.usage.or(ctx.usage.take());
+        ctx.limits = self.limits.or(ctx.limits.take());
+        ctx.session = self.session.or(ctx.session.take());
+        ctx.pull_request = self.pull_request.or(ctx.pull_request.take());
+        ctx.trend = self.trend.or(ctx.trend.take());
+        ctx.forecast = self.forecast.or(ctx.forecast.take());
+        ctx.budget = self.budget.or(ctx.budget.take());
+        ctx.daily = self.daily.or(ctx.daily.take());
+        ctx.last_event_at = self.last_event_at.or(ctx.last_event_at.take());
+        ctx.plugins.extend(self.plugins);
+        if self.partial {
+            ctx.partial.push(id);
+        }
+        if self.degraded {
+            ctx.degraded.push(id);
+        }
+        if let Some(kind) = self.warning {
+            ctx.warnings.push(CollectWarning {
+                collector: id,
+                kind,
+            });
+        }
+        self.rollout
+    }
+}
+
+/// One data source for the statusline. Collectors run on their own thread; register new
+/// ones in [`Registry::builtin`].
+pub(crate) trait Collector: Send + Sync {
+    fn id(&self) -> CollectorId;
+
+    /// Trace target for the collector's span.
+    fn target(&self) -> &'static str;
+
+    /// Deadline from the start of collection, capped by `collect.budget_ms`. `None` means
+    /// the collector stops at [`CollectEnv::deadline`] by itself and is always waited for.
+    fn timeout_ms(&self, cfg: &Config) -> Option<u64>;
+
+    fn enabled(&self, _env: &CollectEnv) -> bool {
+        true
+    }
+
+    fn collect(&self, env: &CollectEnv) -> Option<ContextFragment>;
+}
+
+/// Collectors in waves: each wave sees the context assembled by the ones before it.
+pub(crate) struct Registry {
+    waves: Vec<Vec<Arc<dyn Collector>>>,
+}
+
+impl Registry {
+    fn builtin() -> Self {
+        Self {
+            waves: vec![
+                vec![
+                    Arc::new(GitCollector),
+                    Arc::new(RolloutCollector),
+                    Arc::new(DailyCollector),
+                ],
+                vec![
+                    Arc::new(GitHubCollector),
+                    Arc::new(DiffStatCollector),
+                    Arc::new(HistoryCollector),
+                ],
+                vec![Arc::new(PluginsCollector)],
+            ],
+        }
+    }
+}
+
+struct GitCollector;
+
+impl Collector for GitCollector {
+    fn id(&self) -> CollectorId {
+        CollectorId::Git
+    }
+
+    fn target(&self) -> &'static str {
+        "collect.git"
+    }
+
+    fn timeout_ms(&self, cfg: &Config) -> Option<u64> {
+        Some(cfg.collect.git_timeout_ms)
+    }
+
+    fn enabled(&self, env: &CollectEnv) -> bool {
+        !env.options.skip_git
+    }
+
+    fn collect(&self, env: &CollectEnv) -> Option<ContextFragment> {
+        let project_root = get_git_root(&env.cfg.cache_dir(), &env.context.cwd);
+        let git = collect_git(&env.context.cwd);
+        // Outside a repository both are `None`; inside one, a missing status is a failure.
+        let failed = project_root.is_some() && git.is_none();
+        Some(ContextFragment {
+            git,
+            project_root,
+            warning: failed.then_some(WarningKind::Failed),
+            ..ContextFragment::default()
+        })
+    }
+}
+
+struct RolloutCollector;
+
+impl Collector for RolloutCollector {
+    fn id(&self) -> CollectorId {
+        CollectorId::Rollout
+    }
+
+    fn target(&self) -> &'static str {
+        "collect.rollout"
+    }
+
+    fn timeout_ms(&self, cfg: &Config) -> Option<u64> {
+        Some(cfg.collect.rollout_timeout_ms)
+    }
+
+    fn enabled(&self, env: &CollectEnv) -> bool {
+        !env.options.skip_rollout
+    }
+
+    fn collect(&self, env: &CollectEnv) -> Option<ContextFragment> {
+        let rollout = match collect_rollout(&env.cfg, &env.sessions_dir) {
+            Ok(rollout) => rollout,
+            Err(err) => {
+                trace::event(Level::Warn, "collect.rollout", format_args!("{err:#}"));
+                return Some(ContextFragment {
+                    warning: Some(WarningKind::Failed),
+                    ..ContextFragment::default()
+                });
+            }
+        };
+        Some(ContextFragment {
+            warning: rollout.path.is_none().then_some(WarningKind::Missing),
+            model: rollout.model,
+            usage: rollout.usage,
+            limits: rollout.limits,
+            session: rollout.session,
+            last_event_at: rollout.last_event_at,
+            rollout: rollout.path,
+            partial: rollout.sampled,
+            ..ContextFragment::default()
+        })
+    }
+}
+
+/// Today's usage summed over every rollout written today, not just the latest one.
+struct DailyCollector;
+
+impl Collector for DailyCollector {
+    fn id(&self) -> CollectorId {
+        CollectorId::Daily
+    }
+
+    fn target(&self) -> &'static str {
+        "collect.daily"
+    }
+
+    fn timeout_ms(&self, cfg: &Config) -> Option<u64> {
+        Some(cfg.collect.rollout_timeout_ms)
+    }
+
+    fn enabled(&self, env: &CollectEnv) -> bool {
+        env.segment_enabled(SegmentId::Daily) && !env.options.skip_rollout
+    }
+
+    fn collect(&self, env: &CollectEnv) -> Option<ContextFragment> {
+        Some(ContextFragment {
+            daily: Some(daily::today(&env.sessions_dir, env.context.now)),
+            ..ContextFragment::default()
+        })
+    }
+}
+
+/// Open pull request of the current branch, via `gh`; needs git from the first wave.
+struct GitHubCollector;
+
+impl Collector for GitHubCollector {
+    fn id(&self) -> CollectorId {
+        CollectorId::GitHub
+    }
+
+    fn target(&self) -> &'static str {
+        "collect.github"
+    }
+
+    fn timeout_ms(&self, cfg: &Config) -> Option<u64> {
+        Some(cfg.collect.github_timeout_ms)
+    }
+
+    fn enabled(&self, env: &CollectEnv) -> bool {
+        env.segment_enabled(SegmentId::PullRequest)
+            && env.context.git.is_some()
+            && env.context.project_root.is_some()
+    }
+
+    fn collect(&self, env: &CollectEnv) -> Option<ContextFragment> {
+        let ttl = env
+            .cfg
+            .segments
+            .iter()
+            .find(|segment| segment.id == SegmentId::PullRequest)
+            .and_then(|segment| segment.options.get("cache_seconds"))
+            .and_then(|v| v.as_u64())
+            .unwrap_or(120);
+        let git = env.context.git.as_ref()?;
+        let root = env.context.project_root.as_ref()?;
+        let (pull_request, stale) = github::pull_request(root, &git.branch, ttl);
+        Some(ContextFragment {
+            pull_request,
+            warning: stale.then_some(WarningKind::Stale),
+            ..ContextFragment::default()
+        })
+    }
+}
+
+/// Lines changed since `HEAD` for the git segment's `diffstat` option; needs git from the
+/// first wave. A late result only drops the stat, the git segment itself still shows.
+struct DiffStatCollector;
+
+impl Collector for DiffStatCollector {
+    fn id(&self) -> CollectorId {
+        CollectorId::DiffStat
+    }
+
+    fn target(&self) -> &'static str {
+        "collect.diffstat"
+    }
+
+    fn timeout_ms(&self, cfg: &Config) -> Option<u64> {
+        Some(cfg.collect.diffstat_timeout_ms)
+    }
+
+    fn enabled(&self, env: &CollectEnv) -> bool {
+        let diffstat = env
+            .cfg
+            .segments
+            .iter()
+            .find(|segment| segment.id == SegmentId::Git && segment.enabled)
+            .and_then(|segment| segment.options.get("diffstat"))
+            .and_then(|v| v.as_bool())
+            == Some(true);
+        diffstat && !env.options.skip_git
+    }
+
+    fn collect(&self, env: &CollectEnv) -> Option<ContextFragment> {
+        let root = env.context.project_root.as_deref()?;
+        let mut git = env.context.git.clone()?;
+        git.diffstat = diffstat::diff_stat(root, &git);
+        Some(ContextFragment {
+            git: Some(git),
+            ..ContextFragment::default()
+        })
+    }
+}
+
+/// Records the rollout's usage in the history and the monthly ledger, and derives the
+/// trend, limit forecast and budget spend from them; needs the rollout from the first wave.
+struct HistoryCollector;
+
+impl HistoryCollector {
+    fn option<'a>(env: &'a CollectEnv, id: SegmentId, key: &str) -> Option<&'a serde_json::Value> {
+        env.cfg
+            .segments
+            .iter()
+            .find(|segment| segment.id == id && segment.enabled)
+            .and_then(|segment| segment.options.get(key))
+    }
+
+    fn forecast_enabled(env: &CollectEnv) -> bool {
+        Self::option(env, SegmentId::Limits, "forecast").and_then(|v| v.as_bool()) == Some(true)
+    }
+
+    fn monthly_budget(env: &CollectEnv) -> Option<f64> {
+        env.cfg
+            .budget
+            .monthly_usd
+            .filter(|_| env.segment_enabled(SegmentId::Budget))
+    }
+}
+
+impl Collector for HistoryCollector {
+    fn id(&self) -> CollectorId {
+        CollectorId::History
+    }
+
+    fn target(&self) -> &'static str {
+        "collect.history"
+    }
+
+    fn timeout_ms(&self, cfg: &Config) -> Option<u64> {
+        Some(cfg.collect.rollout_timeout_ms)
+    }
+
+    fn enabled(&self, env: &CollectEnv) -> bool {
+        (env.segment_enabled(SegmentId::Trend)
+            || Self::forecast_enabled(env)
+            || Self::monthly_budget(env).is_some())
+            && !env.options.skip_rollout
+    }
+
+    fn collect(&self, env: &CollectEnv) -> Option<ContextFragment> {
+        let now = env.context.now;
+        let mut fragment = ContextFragment::default();
+        if env.segment_enabled(SegmentId::Trend) || Self::forecast_enabled(env) {
+            let history = history::record(&env.context);
+            if env.segment_enabled(SegmentId::Trend) {
+                let window = Self::option(env, SegmentId::Trend, "window_minutes")
+                    .and_then(|v| v.as_u64())
+                    .map_or(10, |minutes| minutes.clamp(1, 1440) as u32);
+                fragment.trend = history.trend(now, window);
+            }
+            fragment.forecast =
+                Self::forecast_enabled(env).then(|| analytics::limit_forecast(&history, now));
+        }
+        if let Some(monthly_usd) = Self::monthly_budget(env) {
+            let usage = history::record_month(&env.cfg, &env.context, &env.sessions_dir);
+            fragment.budget = Some(pricing::budget_status(&env.cfg, &usage, monthly_usd));
+        }
+        Some(fragment)
+    }
+}
+
+/// External segment providers; they receive everything collected before them.
+struct PluginsCollector;
+
+impl Collector for PluginsCollector {
+    fn id(&self) -> CollectorId {
+        CollectorId::Plugins
+    }
+
+    fn target(&self) -> &'static str {
+        "collect.plugins"
+    }
+
+    fn timeout_ms(&self, _cfg: &Config) -> Option<u64> {
+        None
+    }
+
+    fn enabled(&self, env: &CollectEnv) -> bool {
+        env.segment_enabled(SegmentId::Plugin)
+    }
+
+    fn collect(&self, env: &CollectEnv) -> Option<ContextFragment> {
+        let (plugins, complete) = plugins::run_all(&env.cfg.plugins, &env.context, env.deadline);
+        Some(ContextFragment {
+            plugins,
+            degraded: !complete,
+            ..ContextFragment::default()
+        })
+    }
+}
+
+fn spawn_collector<T: Send + 'static>(
+    collect: impl FnOnce() -> T + Send + 'static,
+) -> mpsc::Receiver<T> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let _ = tx.send(collect());
+    });
+    rx
+}
+
+/// The collector's result, or `None` when it is not done by `deadline`.
+fn wait_for<T>(rx: &mpsc::Receiver<T>, deadline: Instant) -> Option<T> {
+    rx.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+        .ok()
+}
+
+/// True when the shell was started by sshd.
+pub fn ssh_session() -> bool {
+    ["SSH_CONNECTION", "SSH_CLIENT", "SSH_TTY"]
+        .iter()
+        .any(|key| std::env::var_os(key).is_some_and(|value| !value.is_empty()))
+}
+
+fn host_name() -> String {
+    std::env::var("HOSTNAME")
+        .ok()
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "remote".to_string())
+}
+
 fn collect_git(cwd: &Path) -> Option<GitStatus> {
     let output = run_git(cwd, ["status", "--porcelain=2", "--branch"])?;
 
@@ -130,11 +678,94 @@ fn collect_git(cwd: &Path) -> Option<GitStatus> {
         conflicted,
         ahead,
         behind,
+        diffstat: None,
     })
 }
 
-fn get_git_root(cwd: &Path) -> Option<PathBuf> {
-    run_git(cwd, ["rev-parse", "--show-toplevel"]).map(|s| PathBuf::from(s.trim()))
+/// `git rev-parse --show-toplevel`, memoized per cwd in `git-roots.json` under `cache_dir`.
+fn get_git_root(cache_dir: &Path, cwd: &Path) -> Option<PathBuf> {
+    let cache_path = cache_dir.join("git-roots.json");
+    let mut cache: GitRootCache = fs::read_to_string(&cache_path)
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default();
+    if let Some(root) = cache.lookup(cwd) {
+        trace::event(
+            Level::Trace,
+            "collect.git",
+            format_args!("git root cache hit: {}", root.display()),
+        );
+        return Some(root);
+    }
+
+    let root = run_git(cwd, ["rev-parse", "--show-toplevel"]).map(|s| PathBuf::from(s.trim()))?;
+    if cache.insert(cwd, &root) {
+        if let Ok(text) = serde_json::to_string(&cache) {
+            write_cache(&cache_path, &text);
+        }
+    }
+    Some(root)
+}
+
+/// Cached repository roots by cwd. An entry is reused only while the root still has a `.git`
+/// and no `.git` appeared between the cwd and the root, so removed repositories and nested
+/// ones created later are looked up again.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct GitRootCache {
+    roots: BTreeMap<PathBuf, PathBuf>,
+}
+
+const GIT_ROOT_CACHE_LIMIT: usize = 256;
+
+impl GitRootCache {
+    fn lookup(&self, cwd: &Path) -> Option<PathBuf> {
+        let root = self.roots.get(cwd)?;
+        git_root_holds(cwd, root).then(|| root.clone())
+    }
+
+    /// Records `root` for `cwd`; false when the root cannot be re-validated later.
+    fn insert(&mut self, cwd: &Path, root: &Path) -> bool {
+        if !git_root_holds(cwd, root) {
+            return false;
+        }
+        if self.roots.len() >= GIT_ROOT_CACHE_LIMIT {
+            self.roots.clear();
+        }
+        self.roots.insert(cwd.to_path_buf(), root.to_path_buf());
+        true
+    }
+}
+
+/// Whether `root` is still the repository containing `cwd`: it has a `.git` (directory, or
+/// file for worktrees and submodules) and no directory between them has one.
+fn git_root_holds(cwd: &Path, root: &Path) -> bool {
+    if !root.join(".git").exists() {
+        return false;
+    }
+    // `git rev-parse` reports the resolved root; a symlinked cwd is compared resolved too.
+    let cwd = if cwd.starts_with(root) {
+        cwd.to_path_buf()
+    } else {
+        match cwd.canonicalize() {
+            Ok(resolved) if resolved.starts_with(root) => resolved,
+            _ => return false,
+        }
+    };
+    !cwd.ancestors()
+        .take_while(|dir| *dir != root)
+        .any(|dir| dir.join(".git").exists())
+}
+
+/// Replaces a cache file through a temporary one in the same dir, so concurrent renders never
+/// read half of it or interleave their writes. Failures only cost the cache.
+fn write_cache(path: &Path, text: &str) {
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    let temp = path.with_extension(format!("json.{}", std::process::id()));
+    if fs::write(&temp, text).is_err() || fs::rename(&temp, path).is_err() {
+        let _ = fs::remove_file(&temp);
+    }
 }
 
 fn run_git<const N: usize>(cwd: &Path, args: [&str; N]) -> Option<String> {
@@ -152,9 +783,130 @@ fn run_git<const N: usize>(cwd: &Path, args: [&str; N]) -> Option<String> {
     Some(String::from_utf8_lossy(&output.stdout).to_string())
 }
 
+/// One parsed rollout file, as listed by the session browser.
+#[derive(Debug, Clone)]
+pub struct SessionSummary {
+    pub path: PathBuf,
+    pub modified: DateTime<Utc>,
+    pub model: Option<String>,
+    pub usage: Option<TokenUsageSnapshot>,
+    pub limits: Option<RateLimitSnapshot>,
+    pub session: Option<SessionMetaSnapshot>,
+}
+
+/// Most recent rollouts with any usable data, newest first.
+pub fn list_sessions(
+    cfg: &Config,
+    sessions_dir: &Path,
+    limit: usize,
+) -> Result<Vec<SessionSummary>, CodexlineError> {
+    Ok(usable_rollouts(cfg, sessions_dir, limit)
+        .map_err(CodexlineError::collect)?
+        .into_iter()
+        .map(|(modified, path, parsed)| SessionSummary {
+            path,
+            modified: DateTime::<Utc>::from(modified),
+            model: parsed.model,
+            usage: parsed.usage,
+            limits: parsed.limits,
+            session: parsed.session,
+        })
+        .collect())
+}
+
+/// Summaries of the rollouts at `paths`, parsed in batches through the offsets cache; files
+/// that cannot be read are left out.
+pub(crate) fn summarize_rollouts(
+    cfg: &Config,
+    paths: &[(SystemTime, PathBuf)],
+) -> Vec<SessionSummary> {
+    let batch = thread::available_parallelism().map_or(4, NonZeroUsize::get);
+    let cache_path = cfg.cache_dir().join("rollout-offsets.json");
+    let mut offsets = RolloutOffsets::load(&cache_path).with_sampling(cfg);
+    let mut summaries = Vec::new();
+    for chunk in paths.chunks(batch) {
+        let files: Vec<PathBuf> = chunk.iter().map(|(_, path)| path.clone()).collect();
+        for ((modified, path), parsed) in chunk.iter().zip(offsets.parse_all(&files)) {
+            if let Ok(parsed) = parsed {
+                summaries.push(SessionSummary {
+                    path: path.clone(),
+                    modified: DateTime::<Utc>::from(*modified),
+                    model: parsed.model,
+                    usage: parsed.usage,
+                    limits: parsed.limits,
+                    session: parsed.session,
+                });
+            }
+        }
+    }
+    offsets.save(&cache_path);
+    summaries
+}
+
 fn collect_rollout(cfg: &Config, sessions_dir: &Path) -> Result<RolloutInfo> {
-    if !sessions_dir.exists() {
+    if let Some(pinned) = cfg.rollout.pinned.as_ref().filter(|path| path.is_file()) {
+        let mut info = parse_rollout_file(cfg, pinned)?;
+        info.path = Some(pinned.clone());
+        return Ok(info);
+    }
+
+    let Some((_, path, mut info)) = usable_rollouts(cfg, sessions_dir, 1)?.pop() else {
         return Ok(RolloutInfo::default());
+    };
+    info.path = Some(path);
+    Ok(info)
+}
+
+/// Up to `limit` candidate rollouts with usable data, newest first. Candidates are parsed in
+/// batches as wide as the available parallelism, so a usable newest file costs one batch.
+fn usable_rollouts(
+    cfg: &Config,
+    sessions_dir: &Path,
+    limit: usize,
+) -> Result<Vec<(SystemTime, PathBuf, RolloutInfo)>> {
+    let candidates: Vec<_> = candidate_rollouts(cfg, sessions_dir)
+        .into_iter()
+        .take(cfg.rollout.max_files)
+        .collect();
+    let batch = thread::available_parallelism().map_or(4, NonZeroUsize::get);
+    let cache_path = cfg.cache_dir().join("rollout-offsets.json");
+    let mut offsets = RolloutOffsets::load(&cache_path).with_sampling(cfg);
+
+    let mut usable = Vec::new();
+    let mut outcome = Ok(());
+    'batches: for chunk in candidates.chunks(batch) {
+        if usable.len() >= limit {
+            break;
+        }
+        let paths: Vec<PathBuf> = chunk.iter().map(|(_, path)| path.clone()).collect();
+        for ((modified, path), parsed) in chunk.iter().zip(offsets.parse_all(&paths)) {
+            match parsed {
+                Ok(info) if !info.has_data() => trace::event(
+                    Level::Debug,
+                    "collect.rollout",
+                    format_args!("skipped {}: no model or token data", path.display()),
+                ),
+                Ok(info) => {
+                    usable.push((*modified, path.clone(), info));
+                    if usable.len() >= limit {
+                        break 'batches;
+                    }
+                }
+                Err(err) => {
+                    outcome = Err(err);
+                    break 'batches;
+                }
+            }
+        }
+    }
+    offsets.save(&cache_path);
+    outcome.map(|()| usable)
+}
+
+/// Rollout files within `scan_depth_days`, newest first.
+fn candidate_rollouts(cfg: &Config, sessions_dir: &Path) -> Vec<(SystemTime, PathBuf)> {
+    if !sessions_dir.exists() {
+        return Vec::new();
     }
 
     let max_age = Utc::now() - Duration::days(cfg.rollout.scan_depth_days as i64);
@@ -183,160 +935,640 @@ fn collect_rollout(cfg: &Config, sessions_dir: &Path) -> Result<RolloutInfo> {
         .collect();
 
     files.sort_by_key(|(mtime, _)| Reverse(*mtime));
+    files
+}
 
-    let mut info = RolloutInfo::default();
-    for (_, path) in files.into_iter().take(cfg.rollout.max_files) {
-        let parsed = parse_rollout_file(&path)?;
-        if parsed.model.is_none()
-            && parsed.usage.is_none()
-            && parsed.limits.is_none()
-            && parsed.session.is_none()
-        {
+/// Mtimes further ahead than this count as clock skew rather than a write in progress.
+const CLOCK_SKEW_TOLERANCE: StdDuration = StdDuration::from_secs(300);
+
+/// A rollout the pipeline skips, in whole or in part, as reported by `--doctor`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RolloutIssue {
+    pub path: PathBuf,
+    pub problem: RolloutProblem,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RolloutProblem {
+    /// Zero bytes, so there is nothing to read.
+    Empty,
+    /// Complete lines that are not JSON; they are skipped one by one.
+    InvalidJson {
+        lines: usize,
+        total: usize,
+        first_line: usize,
+    },
+    /// Modified in the future, so the file sorts ahead of the session actually in use.
+    FutureMtime { ahead_secs: u64 },
+}
+
+impl std::fmt::Display for RolloutProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RolloutProblem::Empty => write!(f, "empty file, skipped"),
+            RolloutProblem::InvalidJson {
+                lines,
+                total,
+                first_line,
+            } if lines == total => write!(
+                f,
+                "no line is valid JSON ({total} lines, first at line {first_line}), skipped"
+            ),
+            RolloutProblem::InvalidJson {
+                lines,
+                total,
+                first_line,
+            } => write!(
+                f,
+                "{lines} of {total} lines are not valid JSON (first at line {first_line}) and \
+                 were skipped"
+            ),
+            RolloutProblem::FutureMtime { ahead_secs } => write!(
+                f,
+                "modified {}s in the future (clock skew?), so it shadows newer sessions",
+                ahead_secs
+            ),
+        }
+    }
+}
+
+/// Checks the newest `limit` candidate rollouts for files the pipeline skips. Only the first
+/// `rollout.sample_threshold_mb` of each file is read, and an unterminated last line is a
+/// write in progress, not an error.
+pub fn rollout_issues(cfg: &Config, sessions_dir: &Path, limit: usize) -> Vec<RolloutIssue> {
+    let now = SystemTime::now();
+    let mut issues = Vec::new();
+    for (modified, path) in candidate_rollouts(cfg, sessions_dir)
+        .into_iter()
+        .take(limit)
+    {
+        let mut report = |problem| {
+            issues.push(RolloutIssue {
+                path: path.clone(),
+                problem,
+            })
+        };
+        if let Ok(ahead) = modified.duration_since(now) {
+            if ahead > CLOCK_SKEW_TOLERANCE {
+                report(RolloutProblem::FutureMtime {
+                    ahead_secs: ahead.as_secs(),
+                });
+            }
+        }
+        let Ok(file) = File::open(&path) else {
             continue;
+        };
+        let budget = cfg.rollout.sample_threshold_mb.saturating_mul(1024 * 1024);
+        let mut reader = BufReader::new(file.take(budget));
+        let (mut total, mut invalid, mut first_line) = (0, 0, 0);
+        let mut line = Vec::new();
+        let mut empty = true;
+        loop {
+            line.clear();
+            match reader.read_until(b'\n', &mut line) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => empty = false,
+            }
+            if !line.ends_with(b"\n") {
+                break;
+            }
+            total += 1;
+            if line.iter().all(u8::is_ascii_whitespace) {
+                continue;
+            }
+            if serde_json::from_slice::<IgnoredAny>(&line).is_err() {
+                invalid += 1;
+                if first_line == 0 {
+                    first_line = total;
+                }
+            }
+        }
+        if empty {
+            report(RolloutProblem::Empty);
+        } else if invalid > 0 {
+            report(RolloutProblem::InvalidJson {
+                lines: invalid,
+                total,
+                first_line,
+            });
         }
-        info.path = Some(path);
-        info.model = parsed.model;
-        info.usage = parsed.usage;
-        info.limits = parsed.limits;
-        info.session = parsed.session;
-        break;
     }
+    issues
+}
 
+/// [`RolloutOffsets::parse`] with the offsets kept in `rollout-offsets.json` in the cache dir.
+fn parse_rollout_file(cfg: &Config, path: &Path) -> Result<RolloutInfo> {
+    let cache_path = cfg.cache_dir().join("rollout-offsets.json");
+    let mut offsets = RolloutOffsets::load(&cache_path).with_sampling(cfg);
+    let info = offsets.parse(path)?;
+    offsets.save(&cache_path);
     Ok(info)
 }
 
-fn parse_rollout_file(path: &Path) -> Result<RolloutInfo> {
-    let file = File::open(path)
-        .with_context(|| format!("failed to open rollout file: {}", path.display()))?;
+/// Parse state per rollout file, identified by path, length and modification time. An
+/// unchanged file is answered from the cache without being opened; rollouts are append-only,
+/// so a grown file only has the lines written since read, and one that shrank or was
+/// rewritten at the same length is parsed again from the start.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RolloutOffsets {
+    files: BTreeMap<PathBuf, ParsedRollout>,
+    #[serde(skip)]
+    changed: bool,
+    #[serde(skip)]
+    sampling: Option<Sampling>,
+}
 
-    let mut info = RolloutInfo::default();
+/// Size guard for rollouts parsed from scratch, in bytes (see `rollout.sample_threshold_mb`).
+#[derive(Debug, Clone, Copy)]
+struct Sampling {
+    threshold: u64,
+    tail: u64,
+}
 
-    for line_result in BufReader::new(file).lines() {
-        let line = line_result?;
-        let value: Value = match serde_json::from_str(&line) {
-            Ok(v) => v,
-            Err(_) => continue,
-        };
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct ParsedRollout {
+    /// Bytes consumed, always at a line boundary.
+    offset: u64,
+    info: RolloutInfo,
+    /// File length and modification time when `result` was produced.
+    len: u64,
+    modified: Option<SystemTime>,
+    /// `info` plus any unterminated last line, as returned for an unchanged file.
+    result: RolloutInfo,
+}
 
-        let typ = value
-            .get("type")
-            .and_then(Value::as_str)
-            .unwrap_or_default();
-        let payload = value.get("payload").unwrap_or(&Value::Null);
-
-        match typ {
-            "session_meta" => {
-                info.session = Some(SessionMetaSnapshot {
-                    thread_id: payload
-                        .get("id")
-                        .and_then(Value::as_str)
-                        .map(ToOwned::to_owned),
-                    cli_version: payload
-                        .get("cli_version")
-                        .and_then(Value::as_str)
-                        .map(ToOwned::to_owned),
-                    model_provider: payload
-                        .get("model_provider")
-                        .and_then(Value::as_str)
-                        .map(ToOwned::to_owned),
-                });
+const ROLLOUT_OFFSETS_LIMIT: usize = 64;
+
+impl RolloutOffsets {
+    fn load(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    fn with_sampling(mut self, cfg: &Config) -> Self {
+        self.sampling = Some(Sampling {
+            threshold: cfg.rollout.sample_threshold_mb.saturating_mul(1024 * 1024),
+            tail: cfg.rollout.sample_tail_kb.saturating_mul(1024),
+        });
+        self
+    }
 
-                if info.model.is_none() {
-                    info.model = payload
-                        .get("model_provider")
-                        .and_then(Value::as_str)
-                        .map(ToOwned::to_owned);
+    fn save(&self, path: &Path) {
+        if !self.changed {
+            return;
+        }
+        if let Ok(text) = serde_json::to_string(self) {
+            write_cache(path, &text);
+        }
+    }
+
+    fn parse(&mut self, path: &Path) -> Result<RolloutInfo> {
+        let (parsed, info) = self.read(path)?;
+        if let Some(parsed) = parsed {
+            self.store(path, parsed);
+        }
+        Ok(info)
+    }
+
+    /// Parses `paths` on scoped threads, one per file; results keep the order of `paths`.
+    fn parse_all(&mut self, paths: &[PathBuf]) -> Vec<Result<RolloutInfo>> {
+        let offsets = &*self;
+        let results: Vec<_> = thread::scope(|scope| {
+            let handles: Vec<_> = paths
+                .iter()
+                .map(|path| scope.spawn(move || offsets.read(path)))
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| {
+                    handle
+                        .join()
+                        .unwrap_or_else(|_| Err(anyhow!("rollout parser panicked")))
+                })
+                .collect()
+        });
+        paths
+            .iter()
+            .zip(results)
+            .map(|(path, result)| {
+                let (parsed, info) = result?;
+                if let Some(parsed) = parsed {
+                    self.store(path, parsed);
                 }
+                Ok(info)
+            })
+            .collect()
+    }
+
+    /// Reads `path` from its stored offset. The new parse state is `None` when the file is
+    /// unchanged since.
+    fn read(&self, path: &Path) -> Result<(Option<ParsedRollout>, RolloutInfo)> {
+        let meta = fs::metadata(path)
+            .with_context(|| format!("failed to open rollout file: {}", path.display()))?;
+        let (len, modified) = (meta.len(), meta.modified().ok());
+        let stored = self.files.get(path);
+        if let Some(stored) = stored.filter(|s| s.len == len && s.modified == modified) {
+            if modified.is_some() {
+                return Ok((None, stored.result.clone()));
             }
-            "turn_context" => {
-                if info.model.is_none() {
-                    info.model = payload
-                        .get("model")
-                        .and_then(Value::as_str)
-                        .map(ToOwned::to_owned);
-                }
+        }
+        let mut parsed = stored
+            .filter(|s| s.offset <= len && s.len != len)
+            .cloned()
+            .unwrap_or_default();
+
+        let mut file = File::open(path)
+            .with_context(|| format!("failed to open rollout file: {}", path.display()))?;
+
+        trace::event(
+            Level::Debug,
+            "collect.rollout",
+            format_args!(
+                "parsing {} from byte {} of {}",
+                path.display(),
+                parsed.offset,
+                len
+            ),
+        );
+        let mut line = Vec::new();
+        if let Some(sampling) = self
+            .sampling
+            .filter(|sampling| parsed.offset == 0 && len > sampling.threshold)
+        {
+            // Session metadata sits on the first line; token counts are cumulative, so the
+            // tail still carries the latest totals.
+            trace::event(
+                Level::Warn,
+                "collect.rollout",
+                format_args!("sampling {} ({} bytes)", path.display(), len),
+            );
+            let mut head = BufReader::new((&file).take(sampling.tail));
+            if head.read_until(b'\n', &mut line)? > 0 && line.ends_with(b"\n") {
+                apply_rollout_line(&line, &mut parsed.info);
+            }
+            parsed.info.sampled = true;
+            parsed.offset = len - sampling.tail.min(len);
+            file.seek(SeekFrom::Start(parsed.offset))?;
+            // The first tail line is usually cut; skip to the next line boundary.
+            line.clear();
+            let mut tail = BufReader::new(&file);
+            let skipped = tail.read_until(b'\n', &mut line)?;
+            if line.ends_with(b"\n") {
+                parsed.offset += skipped as u64;
             }
-            "event_msg" => {
-                apply_event_payload(payload, &mut info);
+        }
+
+        file.seek(SeekFrom::Start(parsed.offset))?;
+        let mut reader = BufReader::new(file);
+        let mut partial = None;
+        loop {
+            line.clear();
+            let read = reader.read_until(b'\n', &mut line)?;
+            if read == 0 {
+                break;
             }
-            "token_count" => {
-                apply_token_count(payload, &mut info);
+            if !line.ends_with(b"\n") {
+                // Codex may still be writing this line: use it now, read it again next time.
+                let mut info = parsed.info.clone();
+                apply_rollout_line(&line, &mut info);
+                partial = Some(info);
+                break;
             }
-            _ => {}
+            parsed.offset += read as u64;
+            apply_rollout_line(&line, &mut parsed.info);
         }
+
+        parsed.len = len;
+        parsed.modified = modified;
+        parsed.result = finish_rollout(partial.unwrap_or_else(|| parsed.info.clone()));
+        let result = parsed.result.clone();
+        Ok((Some(parsed), result))
     }
 
-    if info.model.is_none() {
-        info.model = info
-            .session
-            .as_ref()
-            .and_then(|session| session.model_provider.clone());
+    fn store(&mut self, path: &Path, parsed: ParsedRollout) {
+        if self.files.len() >= ROLLOUT_OFFSETS_LIMIT && !self.files.contains_key(path) {
+            self.files.clear();
+        }
+        self.files.insert(path.to_path_buf(), parsed);
+        self.changed = true;
     }
+}
 
-    Ok(info)
+/// One rollout line. Every field is optional and [`Lenient`], so a line that is truncated,
+/// mistyped or from a newer Codex only contributes the parts that still make sense.
+#[derive(Deserialize)]
+struct RolloutLine {
+    #[serde(default)]
+    timestamp: Option<Lenient<DateTime<Utc>>>,
+    #[serde(rename = "type", default)]
+    kind: Option<Lenient<String>>,
+    #[serde(default)]
+    payload: Option<Lenient<Payload>>,
 }
 
-fn apply_event_payload(payload: &Value, info: &mut RolloutInfo) {
-    let event_type = payload
-        .get("type")
-        .and_then(Value::as_str)
-        .unwrap_or_default();
-    if event_type != "token_count" {
+/// Union of the `session_meta`, `turn_context`, `event_msg` and `token_count` payloads.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct Payload {
+    #[serde(rename = "type")]
+    kind: Option<Lenient<String>>,
+    id: Option<Lenient<String>>,
+    cli_version: Option<Lenient<String>>,
+    model_provider: Option<Lenient<String>>,
+    model: Option<Lenient<String>>,
+    info: Option<Lenient<UsageInfo>>,
+    /// Older rollouts put the usage fields directly in the payload instead of under `info`.
+    #[serde(flatten)]
+    usage: UsageInfo,
+    rate_limits: Option<Lenient<RateLimits>>,
+    /// `user_message` events.
+    message: Option<Lenient<String>>,
+    /// `message` response items.
+    role: Option<Lenient<String>>,
+    content: Option<Lenient<Vec<ContentItem>>>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct ContentItem {
+    text: Option<Lenient<String>>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct UsageInfo {
+    total_token_usage: Option<Lenient<TokenTotals>>,
+    model_context_window: Option<Lenient<i64>>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct TokenTotals {
+    input_tokens: Option<Lenient<i64>>,
+    cached_input_tokens: Option<Lenient<i64>>,
+    output_tokens: Option<Lenient<i64>>,
+    reasoning_output_tokens: Option<Lenient<i64>>,
+    total_tokens: Option<Lenient<i64>>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct RateLimits {
+    primary: Option<Lenient<RateWindow>>,
+    secondary: Option<Lenient<RateWindow>>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct RateWindow {
+    used_percent: Option<Lenient<f64>>,
+}
+
+/// A field that holds an unexpected type is treated as absent instead of failing the line.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Lenient<T> {
+    Valid(T),
+    Invalid(IgnoredAny),
+}
+
+fn valid<T>(field: Option<Lenient<T>>) -> Option<T> {
+    match field? {
+        Lenient::Valid(value) => Some(value),
+        Lenient::Invalid(_) => None,
+    }
+}
+
+fn apply_rollout_line(line: &[u8], info: &mut RolloutInfo) {
+    if let Some(at) = line_timestamp(line) {
+        info.last_event_at = Some(at);
+    }
+    if skippable(line) {
+        return;
+    }
+    let Ok(line) = serde_json::from_slice::<RolloutLine>(line) else {
+        return;
+    };
+    let kind = valid(line.kind).unwrap_or_default();
+    let payload = valid(line.payload).unwrap_or_default();
+
+    match kind.as_str() {
+        "session_meta" => {
+            let model_provider = valid(payload.model_provider);
+            if info.model.is_none() {
+                info.model = model_provider.clone();
+            }
+            let title = info.session.take().and_then(|session| session.title);
+            info.session = Some(SessionMetaSnapshot {
+                thread_id: valid(payload.id),
+                cli_version: valid(payload.cli_version),
+                model_provider,
+                title,
+            });
+        }
+        "turn_context" if info.model.is_none() => {
+            info.model = valid(payload.model);
+        }
+        "event_msg" if matches!(&payload.kind, Some(Lenient::Valid(kind)) if kind == "token_count") =>
+        {
+            apply_token_count(payload, info);
+        }
+        "token_count" => {
+            apply_token_count(payload, info);
+        }
+        "event_msg" | "response_item" => apply_user_message(payload, info),
+        _ => {}
+    }
+}
+
+/// The leading `"timestamp"` Codex writes on every line, read without parsing the rest, so
+/// skipped lines still count as activity.
+fn line_timestamp(line: &[u8]) -> Option<DateTime<Utc>> {
+    const KEY: &[u8] = b"\"timestamp\":\"";
+    let head = &line[..line.len().min(64)];
+    let rest = &head[find(head, KEY)? + KEY.len()..];
+    let text = std::str::from_utf8(&rest[..rest.iter().position(|b| *b == b'"')?]).ok()?;
+    DateTime::parse_from_rfc3339(text)
+        .ok()
+        .map(|at| at.with_timezone(&Utc))
+}
+
+/// Longest session title kept; the `title` segment truncates further.
+const TITLE_MAX_CHARS: usize = 200;
+
+/// Takes the session title from the first user message: a `user_message` event, or a user
+/// `message` item from rollouts without those events. Injected context such as
+/// `<environment_context>` is not a title.
+fn apply_user_message(payload: Payload, info: &mut RolloutInfo) {
+    if info.session.as_ref().is_some_and(|s| s.title.is_some()) {
+        return;
+    }
+    let text = match valid(payload.kind).as_deref() {
+        Some("user_message") => valid(payload.message),
+        Some("message") if valid(payload.role).as_deref() == Some("user") => valid(payload.content)
+            .and_then(|content| {
+                content
+                    .into_iter()
+                    .filter_map(|item| valid(item.text))
+                    .find(|text| !text.trim_start().starts_with('<'))
+            }),
+        _ => None,
+    };
+    let Some(text) = text.filter(|text| !text.trim_start().starts_with('<')) else {
+        return;
+    };
+    let title: String = text
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+        .take(TITLE_MAX_CHARS)
+        .collect();
+    if title.is_empty() {
         return;
     }
+    info.session
+        .get_or_insert(SessionMetaSnapshot {
+            thread_id: None,
+            cli_version: None,
+            model_provider: None,
+            title: None,
+        })
+        .title = Some(title);
+}
+
+/// Timestamp and cumulative `total_tokens` of a token-count line; `None` for other lines
+/// and for token counts without totals (rate-limit only updates).
+pub(crate) fn token_count_total(line: &[u8]) -> Option<(Option<DateTime<Utc>>, i64)> {
+    let (at, totals) = token_count_totals(line)?;
+    Some((at, valid(totals.total_tokens)?.max(0)))
+}
+
+/// Timestamp and cumulative input and output tokens of a token-count line, like
+/// [`token_count_total`].
+pub(crate) fn token_count_split(line: &[u8]) -> Option<(Option<DateTime<Utc>>, i64, i64)> {
+    let (at, totals) = token_count_totals(line)?;
+    let input = valid(totals.input_tokens)?.max(0);
+    let output = valid(totals.output_tokens).unwrap_or(0).max(0);
+    Some((at, input, output))
+}
 
-    apply_token_count(payload, info);
+fn token_count_totals(line: &[u8]) -> Option<(Option<DateTime<Utc>>, TokenTotals)> {
+    if skippable(line) {
+        return None;
+    }
+    let line = serde_json::from_slice::<RolloutLine>(line).ok()?;
+    let payload = valid(line.payload)?;
+    let token_count = match valid(line.kind)?.as_str() {
+        "event_msg" => matches!(&payload.kind, Some(Lenient::Valid(kind)) if kind == "token_count"),
+        kind => kind == "token_count",
+    };
+    if !token_count {
+        return None;
+    }
+    let usage = valid(payload.info).unwrap_or(payload.usage);
+    Some((valid(line.timestamp), valid(usage.total_token_usage)?))
 }
 
-fn apply_token_count(payload: &Value, info: &mut RolloutInfo) {
-    let usage_info = payload.get("info").unwrap_or(payload);
+/// Top-level record types the parser reads. Everything else (assistant messages, tool calls,
+/// reasoning) is the bulk of a rollout and is skipped before deserialization.
+const RECORD_TYPES: [&[u8]; 5] = [
+    b"session_meta",
+    b"turn_context",
+    b"event_msg",
+    b"token_count",
+    b"response_item",
+];
+
+/// Whether the line's top-level `"type":"..."` names a record the parser ignores, an
+/// `event_msg` without a token count or user message, or a `response_item` that is not a
+/// user message. Lines not in Codex's compact layout, `{"timestamp":"...","type":"...` or
+/// `{"type":"...`, are never skipped: a `"type"` found elsewhere may be the payload's.
+fn skippable(line: &[u8]) -> bool {
+    const KEY: &[u8] = b"\"type\":\"";
+    const TIMESTAMP: &[u8] = b"\"timestamp\":\"";
+    let Some(mut rest) = line.strip_prefix(b"{") else {
+        return false;
+    };
+    if let Some(value) = rest.strip_prefix(TIMESTAMP) {
+        let Some(end) = value.iter().position(|b| *b == b'"' || *b == b'\\') else {
+            return false;
+        };
+        let Some(after) = value[end..].strip_prefix(b"\",") else {
+            return false;
+        };
+        rest = after;
+    }
+    let Some(rest) = rest.strip_prefix(KEY) else {
+        return false;
+    };
+    let Some(kind) = RECORD_TYPES
+        .iter()
+        .find(|kind| rest.starts_with(kind) && rest.get(kind.len()) == Some(&b'"'))
+    else {
+        return true;
+    };
+    match *kind {
+        b"event_msg" => {
+            find(rest, b"\"token_count\"").is_none() && find(rest, b"\"user_message\"").is_none()
+        }
+        b"response_item" => find(rest, b"\"role\":\"user\"").is_none(),
+        _ => false,
+    }
+}
 
-    let total = usage_info
-        .get("total_token_usage")
-        .and_then(|v| v.get("total_tokens"))
-        .and_then(Value::as_i64)
-        .unwrap_or(0);
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
 
-    let input = usage_info
-        .get("total_token_usage")
-        .and_then(|v| v.get("input_tokens"))
-        .and_then(Value::as_i64)
-        .unwrap_or(0);
+/// Falls back to the session's model provider when no turn named the model.
+fn finish_rollout(mut info: RolloutInfo) -> RolloutInfo {
+    if info.model.is_none() {
+        info.model = info
+            .session
+            .as_ref()
+            .and_then(|session| session.model_provider.clone());
+    }
+    info
+}
 
-    let output = usage_info
-        .get("total_token_usage")
-        .and_then(|v| v.get("output_tokens"))
-        .and_then(Value::as_i64)
-        .unwrap_or(0);
+fn apply_token_count(payload: Payload, info: &mut RolloutInfo) {
+    let usage_info = valid(payload.info).unwrap_or(payload.usage);
+    let totals = valid(usage_info.total_token_usage).unwrap_or_default();
+    let count = |field| valid(field).unwrap_or(0).max(0);
+    let total = count(totals.total_tokens);
 
-    let context_window = usage_info
-        .get("model_context_window")
-        .and_then(Value::as_i64);
+    let context_window = valid(usage_info.model_context_window);
     let used_percent = context_window
         .filter(|v| *v > 0)
         .map(|v| ((total as f64 / v as f64) * 100.0).round() as i64)
         .map(|v| v.clamp(0, 100));
 
     info.usage = Some(TokenUsageSnapshot {
-        input_tokens: input,
-        output_tokens: output,
+        input_tokens: count(totals.input_tokens),
+        output_tokens: count(totals.output_tokens),
+        cached_input_tokens: count(totals.cached_input_tokens),
+        reasoning_output_tokens: count(totals.reasoning_output_tokens),
         total_tokens: total,
         model_context_window: context_window,
         used_percent,
         remaining_percent: used_percent.map(|v| 100 - v),
     });
 
-    let primary = payload
-        .get("rate_limits")
-        .and_then(|v| v.get("primary"))
-        .and_then(|v| v.get("used_percent"))
-        .and_then(Value::as_f64);
-
-    let secondary = payload
-        .get("rate_limits")
-        .and_then(|v| v.get("secondary"))
-        .and_then(|v| v.get("used_percent"))
-        .and_then(Value::as_f64);
+    let limits = valid(payload.rate_limits).unwrap_or_default();
+    let used = |window: Option<Lenient<RateWindow>>| {
+        valid(valid(window)?.used_percent)
+            .filter(|v| v.is_finite())
+            .map(|v| v.clamp(0.0, 100.0))
+    };
+    let primary = used(limits.primary);
+    let secondary = used(limits.secondary);
 
     if primary.is_some() || secondary.is_some() {
         info.limits = Some(RateLimitSnapshot {
@@ -346,6 +1578,31 @@ fn apply_token_count(payload: &Value, info: &mut RolloutInfo) {
     }
 }
 
+/// Fuzzing entry point (`fuzz/fuzz_targets/rollout_lines.rs`): parses `data` as rollout lines
+/// and panics if a derived percentage leaves `0..=100`.
+#[doc(hidden)]
+pub fn fuzz_rollout_lines(data: &[u8]) {
+    let mut info = RolloutInfo::default();
+    for line in data.split(|byte| *byte == b'\n') {
+        apply_rollout_line(line, &mut info);
+    }
+    let info = finish_rollout(info);
+    if let Some(usage) = info.usage {
+        if let Some(used) = usage.used_percent {
+            assert!((0..=100).contains(&used), "used_percent {used}");
+            assert_eq!(usage.remaining_percent, Some(100 - used));
+        }
+    }
+    if let Some(limits) = info.limits {
+        for percent in [limits.primary_used_percent, limits.secondary_used_percent]
+            .into_iter()
+            .flatten()
+        {
+            assert!((0.0..=100.0).contains(&percent), "rate limit {percent}");
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -359,22 +1616,462 @@ mod tests {
             &file,
             [
                 r#"{"timestamp":"x","type":"session_meta","payload":{"id":"abc","cli_version":"0.1.0","model_provider":"gpt-5"}}"#,
-                r#"{"timestamp":"x","type":"event_msg","payload":{"type":"token_count","info":{"model_context_window":1000,"total_token_usage":{"input_tokens":200,"output_tokens":10,"total_tokens":550}},"rate_limits":{"primary":{"used_percent":30.5}}}}"#,
+                r#"{"timestamp":"x","type":"event_msg","payload":{"type":"token_count","info":{"model_context_window":1000,"total_token_usage":{"input_tokens":200,"cached_input_tokens":150,"output_tokens":10,"reasoning_output_tokens":4,"total_tokens":550}},"rate_limits":{"primary":{"used_percent":30.5}}}}"#,
             ]
             .join("\n"),
         )
         .expect("write");
 
-        let parsed = parse_rollout_file(&file).expect("parse");
+        let parsed = RolloutOffsets::default().parse(&file).expect("parse");
         assert_eq!(parsed.model.as_deref(), Some("gpt-5"));
         assert_eq!(
             parsed.session.as_ref().and_then(|s| s.thread_id.as_deref()),
             Some("abc")
         );
         assert_eq!(parsed.usage.as_ref().and_then(|u| u.used_percent), Some(55));
+        assert_eq!(
+            parsed
+                .usage
+                .as_ref()
+                .map(|u| (u.cached_input_tokens, u.reasoning_output_tokens)),
+            Some((150, 4))
+        );
         assert_eq!(
             parsed.limits.as_ref().and_then(|l| l.primary_used_percent),
             Some(30.5)
         );
     }
+
+    #[test]
+    fn collect_with_skips_requested_sources() {
+        let cfg = Config::default();
+        let options = CollectOptions {
+            skip_git: true,
+            skip_rollout: true,
+            ..CollectOptions::default()
+        };
+        let collection = collect_with(&cfg, options).expect("collect");
+        assert!(collection.context.git.is_none());
+        assert!(collection.context.project_root.is_none());
+        assert!(collection.latest_rollout.is_none());
+        assert!(collection.context.usage.is_none());
+    }
+
+    #[test]
+    fn pinned_rollout_overrides_latest() {
+        let dir = TempDir::new().expect("temp dir");
+        let older = dir.path().join("older.jsonl");
+        let newer = dir.path().join("newer.jsonl");
+        std::fs::write(
+            &older,
+            r#"{"type":"session_meta","payload":{"id":"pinned","model_provider":"openai"}}"#,
+        )
+        .expect("write");
+        std::fs::write(
+            &newer,
+            r#"{"type":"session_meta","payload":{"id":"latest","model_provider":"openai"}}"#,
+        )
+        .expect("write");
+
+        let mut cfg = Config {
+            cache_dir: Some(dir.path().join("cache")),
+            ..Config::default()
+        };
+        let sessions = list_sessions(&cfg, dir.path(), 10).expect("list");
+        assert_eq!(sessions.len(), 2);
+
+        cfg.rollout.pinned = Some(older.clone());
+        let info = collect_rollout(&cfg, dir.path()).expect("collect");
+        assert_eq!(info.path, Some(older));
+        assert_eq!(
+            info.session.and_then(|s| s.thread_id).as_deref(),
+            Some("pinned")
+        );
+    }
+
+    #[test]
+    fn wait_for_gives_up_at_deadline() {
+        let fast = spawn_collector(|| 1);
+        assert_eq!(
+            wait_for(&fast, Instant::now() + StdDuration::from_secs(5)),
+            Some(1)
+        );
+
+        let slow = spawn_collector(|| thread::sleep(StdDuration::from_secs(2)));
+        let started = Instant::now();
+        assert_eq!(
+            wait_for(&slow, started + StdDuration::from_millis(20)),
+            None
+        );
+        assert!(started.elapsed() < StdDuration::from_secs(1));
+    }
+
+    #[test]
+    fn git_root_cache_invalidates_when_dot_git_changes() {
+        let dir = TempDir::new().expect("temp dir");
+        let root = dir.path().join("repo");
+        let cwd = root.join("src").join("nested");
+        std::fs::create_dir_all(root.join(".git")).expect("git dir");
+        std::fs::create_dir_all(&cwd).expect("cwd");
+
+        let mut cache = GitRootCache::default();
+        assert!(cache.insert(&cwd, &root));
+        assert_eq!(cache.lookup(&cwd), Some(root.clone()));
+        assert_eq!(cache.lookup(&root), None);
+
+        // A repository created later between the cwd and the cached root wins.
+        std::fs::write(root.join("src").join(".git"), "gitdir: elsewhere").expect("nested");
+        assert_eq!(cache.lookup(&cwd), None);
+        std::fs::remove_file(root.join("src").join(".git")).expect("remove nested");
+        assert_eq!(cache.lookup(&cwd), Some(root.clone()));
+
+        std::fs::remove_dir(root.join(".git")).expect("remove");
+        assert_eq!(cache.lookup(&cwd), None);
+    }
+
+    #[test]
+    fn rollout_offsets_only_read_appended_lines() {
+        let dir = TempDir::new().expect("temp dir");
+        let file = dir.path().join("sample.jsonl");
+        let meta = r#"{"type":"session_meta","payload":{"id":"abc","model_provider":"openai"}}"#;
+        let turn = r#"{"type":"turn_context","payload":{"model":"gpt-5-codex"}}"#;
+        std::fs::write(&file, format!("{meta}\n{turn}")).expect("write");
+
+        let mut offsets = RolloutOffsets::default();
+        let parsed = offsets.parse(&file).expect("parse");
+        assert_eq!(parsed.model.as_deref(), Some("openai"));
+        assert_eq!(offsets.files[&file].offset, meta.len() as u64 + 1);
+
+        // The unterminated line is re-read; earlier lines are not.
+        let usage = r#"{"type":"token_count","payload":{"info":{"total_token_usage":{"input_tokens":1,"output_tokens":2,"total_tokens":3}}}}"#;
+        let mut handle = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&file)
+            .expect("open");
+        std::io::Write::write_all(&mut handle, format!("\n{usage}\n").as_bytes()).expect("append");
+        let parsed = offsets.parse(&file).expect("parse");
+        assert_eq!(parsed.usage.as_ref().map(|u| u.total_tokens), Some(3));
+        assert_eq!(
+            offsets.files[&file].offset,
+            std::fs::metadata(&file).expect("meta").len()
+        );
+
+        std::fs::write(&file, format!("{turn}\n")).expect("truncate");
+        let parsed = offsets.parse(&file).expect("parse");
+        assert_eq!(parsed.model.as_deref(), Some("gpt-5-codex"));
+        assert!(parsed.usage.is_none());
+    }
+
+    #[test]
+    fn crlf_rollouts_parse_like_lf_ones() {
+        let dir = TempDir::new().expect("temp dir");
+        let file = dir.path().join("windows.jsonl");
+        std::fs::write(
+            &file,
+            concat!(
+                r#"{"type":"session_meta","payload":{"id":"abc","model_provider":"openai"}}"#,
+                "\r\n",
+                r#"{"type":"turn_context","payload":{"model":"gpt-5-codex"}}"#,
+                "\r\n",
+                r#"{"type":"token_count","payload":{"info":{"total_token_usage":{"input_tokens":1,"output_tokens":2,"total_tokens":3}}}}"#,
+                "\r\n",
+            ),
+        )
+        .expect("write");
+
+        let mut offsets = RolloutOffsets::default();
+        let parsed = offsets.parse(&file).expect("parse");
+        assert_eq!(
+            parsed.session.and_then(|s| s.thread_id).as_deref(),
+            Some("abc")
+        );
+        assert_eq!(parsed.usage.map(|u| u.total_tokens), Some(3));
+        assert_eq!(
+            offsets.files[&file].offset,
+            std::fs::metadata(&file).expect("meta").len()
+        );
+    }
+
+    #[test]
+    fn parse_all_keeps_candidate_order() {
+        let dir = TempDir::new().expect("temp dir");
+        let empty = dir.path().join("empty.jsonl");
+        let usable = dir.path().join("usable.jsonl");
+        std::fs::write(&empty, "not json\n").expect("write");
+        std::fs::write(
+            &usable,
+            "{\"type\":\"turn_context\",\"payload\":{\"model\":\"gpt-5-codex\"}}\n",
+        )
+        .expect("write");
+        let missing = dir.path().join("missing.jsonl");
+
+        let mut offsets = RolloutOffsets::default();
+        let results = offsets.parse_all(&[empty.clone(), usable.clone(), missing]);
+        assert!(!results[0].as_ref().expect("empty").has_data());
+        assert_eq!(
+            results[1].as_ref().expect("usable").model.as_deref(),
+            Some("gpt-5-codex")
+        );
+        assert!(results[2].is_err());
+        assert!(offsets.files.contains_key(&empty) && offsets.files.contains_key(&usable));
+    }
+
+    #[test]
+    fn oversized_rollouts_are_sampled() {
+        let dir = TempDir::new().expect("temp dir");
+        let file = dir.path().join("huge.jsonl");
+        let filler = r#"{"type":"response_item","payload":{"type":"message","content":[]}}"#;
+        let mut text = String::from(
+            "{\"type\":\"session_meta\",\"payload\":{\"id\":\"abc\",\"model_provider\":\"openai\"}}\n",
+        );
+        for _ in 0..100 {
+            text.push_str(filler);
+            text.push('\n');
+        }
+        text.push_str(r#"{"type":"token_count","payload":{"info":{"total_token_usage":{"input_tokens":1,"output_tokens":2,"total_tokens":3}}}}"#);
+        text.push('\n');
+        std::fs::write(&file, &text).expect("write");
+
+        let mut offsets = RolloutOffsets {
+            sampling: Some(Sampling {
+                threshold: 1024,
+                tail: 256,
+            }),
+            ..RolloutOffsets::default()
+        };
+        let parsed = offsets.parse(&file).expect("parse");
+        assert!(parsed.sampled);
+        assert_eq!(
+            parsed.session.and_then(|s| s.thread_id).as_deref(),
+            Some("abc")
+        );
+        assert_eq!(parsed.usage.map(|u| u.total_tokens), Some(3));
+        assert_eq!(offsets.files[&file].offset, text.len() as u64);
+    }
+
+    #[test]
+    fn malformed_rollout_lines_stay_within_bounds() {
+        let lines = [
+            r#"{"type":"session_meta","payload":{"id":42,"model_provider":"openai"}}"#,
+            r#"{"type":"event_msg","payload":{"type":"token_count","info":{"model_context_window":1000,"total_token_usage":{"input_tokens":-5,"output_tokens":"x","total_tokens":5000}},"rate_limits":{"primary":{"used_percent":250.0},"secondary":{"used_percent":-3}}}}"#,
+        ];
+        // Every truncation of every line, plus bytes that are not UTF-8.
+        for line in lines {
+            for cut in 0..=line.len() {
+                fuzz_rollout_lines(&line.as_bytes()[..cut]);
+            }
+        }
+        fuzz_rollout_lines(b"\xff\xfe{\"type\":\n{\"type\":\"token_count\",\"payload\":[]}");
+
+        let mut info = RolloutInfo::default();
+        for line in lines {
+            apply_rollout_line(line.as_bytes(), &mut info);
+        }
+        let session = info.session.expect("session");
+        assert_eq!(session.thread_id, None);
+        assert_eq!(session.model_provider.as_deref(), Some("openai"));
+        let usage = info.usage.expect("usage");
+        assert_eq!((usage.input_tokens, usage.output_tokens), (0, 0));
+        assert_eq!(usage.used_percent, Some(100));
+        let limits = info.limits.expect("limits");
+        assert_eq!(limits.primary_used_percent, Some(100.0));
+        assert_eq!(limits.secondary_used_percent, Some(0.0));
+    }
+
+    #[test]
+    fn fragments_merge_into_the_context() {
+        let mut ctx = StatusContext {
+            model: Some("gpt-5".to_string()),
+            ..StatusContext::new(Utc::now(), PathBuf::from("/work/app"))
+        };
+        let rollout = ContextFragment {
+            model: Some("gpt-5-codex".to_string()),
+            rollout: Some(PathBuf::from("/sessions/a.jsonl")),
+            partial: true,
+            ..ContextFragment::default()
+        }
+        .apply(CollectorId::Rollout, &mut ctx);
+        let git = ContextFragment {
+            project_root: Some(PathBuf::from("/work/app")),
+            degraded: true,
+            warning: Some(WarningKind::Failed),
+            ..ContextFragment::default()
+        }
+        .apply(CollectorId::Git, &mut ctx);
+
+        assert_eq!(rollout, Some(PathBuf::from("/sessions/a.jsonl")));
+        assert_eq!(git, None);
+        assert_eq!(ctx.model.as_deref(), Some("gpt-5-codex"));
+        assert_eq!(ctx.project_root, Some(PathBuf::from("/work/app")));
+        assert_eq!(ctx.partial, vec![CollectorId::Rollout]);
+        assert_eq!(ctx.degraded, vec![CollectorId::Git]);
+        assert_eq!(
+            ctx.warnings,
+            vec![CollectWarning {
+                collector: CollectorId::Git,
+                kind: WarningKind::Failed
+            }]
+        );
+    }
+
+    #[test]
+    fn rollout_cache_is_keyed_on_length_and_mtime() {
+        let dir = TempDir::new().expect("temp dir");
+        let file = dir.path().join("sample.jsonl");
+        let write = |model: &str, modified: SystemTime| {
+            std::fs::write(
+                &file,
+                format!("{{\"type\":\"turn_context\",\"payload\":{{\"model\":\"{model}\"}}}}\n"),
+            )
+            .expect("write");
+            File::options()
+                .write(true)
+                .open(&file)
+                .and_then(|handle| handle.set_modified(modified))
+                .expect("set mtime");
+        };
+        let earlier = SystemTime::UNIX_EPOCH + StdDuration::from_secs(1_700_000_000);
+        write("gpt-5-codex", earlier);
+
+        let mut offsets = RolloutOffsets::default();
+        assert_eq!(
+            offsets.parse(&file).expect("parse").model.as_deref(),
+            Some("gpt-5-codex")
+        );
+
+        // Same length and mtime: answered from the cache without reading the new content.
+        write("gpt-5-mini!", earlier);
+        assert_eq!(
+            offsets.parse(&file).expect("parse").model.as_deref(),
+            Some("gpt-5-codex")
+        );
+
+        // Rewritten in place: same length, newer mtime, parsed again from the start.
+        write("gpt-5-mini!", earlier + StdDuration::from_secs(60));
+        assert_eq!(
+            offsets.parse(&file).expect("parse").model.as_deref(),
+            Some("gpt-5-mini!")
+        );
+    }
+
+    #[test]
+    fn uninteresting_lines_are_skipped_before_parsing() {
+        assert!(skippable(
+            br#"{"timestamp":"x","type":"response_item","payload":{"type":"message"}}"#
+        ));
+        assert!(skippable(
+            br#"{"type":"event_msg","payload":{"type":"agent_message","message":"hi"}}"#
+        ));
+        assert!(!skippable(
+            br#"{"type":"event_msg","payload":{"type":"token_count","info":null}}"#
+        ));
+        assert!(!skippable(
+            br#"{"type":"turn_context","payload":{"model":"gpt-5"}}"#
+        ));
+        // Not the compact layout Codex writes: left to the parser.
+        assert!(!skippable(br#"{"type": "response_item", "payload": {}}"#));
+        // Reordered keys: the first "type" is the payload's, not the record's.
+        assert!(!skippable(
+            br#"{"payload":{"type":"token_count","info":null},"type":"event_msg"}"#
+        ));
+        assert!(!skippable(
+            br#"{"timestamp":"x","payload":{"type":"message"},"type":"turn_context"}"#
+        ));
+    }
+
+    #[test]
+    fn session_title_comes_from_the_first_user_message() {
+        let title = |lines: &[&str]| {
+            let mut info = RolloutInfo::default();
+            for line in lines {
+                apply_rollout_line(line.as_bytes(), &mut info);
+            }
+            info.session.and_then(|session| session.title)
+        };
+        let context = r#"{"type":"response_item","payload":{"type":"message","role":"user","content":[{"type":"input_text","text":"<environment_context>\n  <cwd>/work</cwd>\n</environment_context>"}]}}"#;
+        let item = r#"{"type":"response_item","payload":{"type":"message","role":"user","content":[{"type":"input_text","text":"fix flaky\n  auth tests"}]}}"#;
+        let event = r#"{"type":"event_msg","payload":{"type":"user_message","message":"  fix flaky auth tests\n","images":[]}}"#;
+        let meta = r#"{"type":"session_meta","payload":{"id":"abc"}}"#;
+        let later =
+            r#"{"type":"event_msg","payload":{"type":"user_message","message":"now the docs"}}"#;
+
+        assert!(!skippable(item.as_bytes()) && !skippable(event.as_bytes()));
+        assert_eq!(
+            title(&[context, item, meta, later]).as_deref(),
+            Some("fix flaky auth tests")
+        );
+        assert_eq!(
+            title(&[meta, event, later]).as_deref(),
+            Some("fix flaky auth tests")
+        );
+        assert_eq!(title(&[context, meta]), None);
+    }
+
+    #[test]
+    fn last_event_comes_from_any_line() {
+        let mut info = RolloutInfo::default();
+        for line in [
+            r#"{"timestamp":"2025-10-09T10:00:00.123Z","type":"session_meta","payload":{"id":"abc"}}"#,
+            r#"{"timestamp":"2025-10-09T10:14:00.000Z","type":"response_item","payload":{"type":"reasoning"}}"#,
+            r#"{"timestamp":"x","type":"event_msg","payload":{"type":"agent_message"}}"#,
+        ] {
+            apply_rollout_line(line.as_bytes(), &mut info);
+        }
+        assert_eq!(
+            info.last_event_at.map(|at| at.to_rfc3339()),
+            Some("2025-10-09T10:14:00+00:00".to_string())
+        );
+    }
+
+    #[test]
+    fn rollout_issues_report_skipped_files() {
+        let dir = TempDir::new().expect("temp dir");
+        let line = "{\"type\":\"turn_context\",\"payload\":{\"model\":\"gpt-5-codex\"}}\n";
+        std::fs::write(dir.path().join("ok.jsonl"), line).expect("write");
+        std::fs::write(dir.path().join("empty.jsonl"), "").expect("write");
+        std::fs::write(
+            dir.path().join("bad.jsonl"),
+            format!("{line}{{\"type\":\n\n{line}{{\"type\""),
+        )
+        .expect("write");
+        let future = dir.path().join("future.jsonl");
+        std::fs::write(&future, line).expect("write");
+        File::options()
+            .write(true)
+            .open(&future)
+            .and_then(|handle| {
+                handle.set_modified(SystemTime::now() + StdDuration::from_secs(3600))
+            })
+            .expect("set mtime");
+
+        let mut issues = rollout_issues(&Config::default(), dir.path(), 10);
+        issues.sort_by(|a, b| a.path.cmp(&b.path));
+        let problems: Vec<_> = issues
+            .iter()
+            .map(|issue| {
+                let name = issue.path.file_name().expect("file name");
+                (name.to_string_lossy().into_owned(), issue.problem.clone())
+            })
+            .collect();
+        assert_eq!(problems.len(), 3, "{problems:?}");
+        assert_eq!(
+            problems[0],
+            (
+                "bad.jsonl".to_string(),
+                RolloutProblem::InvalidJson {
+                    lines: 1,
+                    total: 4,
+                    first_line: 2
+                }
+            )
+        );
+        assert_eq!(
+            problems[1],
+            ("empty.jsonl".to_string(), RolloutProblem::Empty)
+        );
+        assert!(matches!(
+            problems[2],
+            (ref name, RolloutProblem::FutureMtime { ahead_secs }) if name == "future.jsonl" && ahead_secs > 3000
+        ));
+    }
 }
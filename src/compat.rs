@@ -0,0 +1,102 @@
+use crate::context::{SessionMetaSnapshot, StatusContext};
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::path::PathBuf;
+
+/// The JSON Claude Code writes to a statusline command's stdin. Only the fields codexline can
+/// show are read; everything else is ignored.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ClaudeStatusInput {
+    #[serde(default)]
+    pub session_id: Option<String>,
+    #[serde(default)]
+    pub cwd: Option<PathBuf>,
+    #[serde(default)]
+    pub model: Option<ClaudeModel>,
+    #[serde(default)]
+    pub workspace: Option<ClaudeWorkspace>,
+    #[serde(default)]
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ClaudeModel {
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub display_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ClaudeWorkspace {
+    #[serde(default)]
+    pub current_dir: Option<PathBuf>,
+    #[serde(default)]
+    pub project_dir: Option<PathBuf>,
+}
+
+pub fn parse_claude_input(text: &str) -> Result<ClaudeStatusInput> {
+    serde_json::from_str(text).context("failed to parse Claude Code statusline JSON")
+}
+
+impl ClaudeStatusInput {
+    /// Directory the statusline describes; git is collected from here.
+    pub fn current_dir(&self) -> Option<&PathBuf> {
+        self.workspace
+            .as_ref()
+            .and_then(|workspace| workspace.current_dir.as_ref())
+            .or(self.cwd.as_ref())
+    }
+
+    /// Overlays the Claude session onto a context collected without Codex rollout data.
+    pub fn apply(&self, ctx: &mut StatusContext) {
+        if let Some(model) = &self.model {
+            ctx.model = model.id.clone().or_else(|| model.display_name.clone());
+        }
+        if let Some(project_dir) = self
+            .workspace
+            .as_ref()
+            .and_then(|workspace| workspace.project_dir.clone())
+        {
+            ctx.project_root = Some(project_dir);
+        }
+        if self.session_id.is_some() || self.version.is_some() {
+            ctx.session = Some(SessionMetaSnapshot {
+                thread_id: self.session_id.clone(),
+                cli_version: self.version.clone(),
+                model_provider: Some("anthropic".to_string()),
+                title: None,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn claude_input_maps_onto_status_context() {
+        let input = parse_claude_input(
+            r#"{
+                "hook_event_name": "Status",
+                "session_id": "abc123",
+                "model": {"id": "claude-sonnet-4-20250514", "display_name": "Sonnet 4"},
+                "workspace": {"current_dir": "/work/app", "project_dir": "/work"},
+                "version": "1.0.80",
+                "cost": {"total_cost_usd": 0.01}
+            }"#,
+        )
+        .expect("parse");
+        let mut ctx = StatusContext::new(chrono::Utc::now(), PathBuf::from("/work/app"));
+
+        input.apply(&mut ctx);
+
+        assert_eq!(input.current_dir(), Some(&PathBuf::from("/work/app")));
+        assert_eq!(ctx.model.as_deref(), Some("claude-sonnet-4-20250514"));
+        assert_eq!(ctx.project_root, Some(PathBuf::from("/work")));
+        let session = ctx.session.expect("session");
+        assert_eq!(session.thread_id.as_deref(), Some("abc123"));
+        assert_eq!(session.cli_version.as_deref(), Some("1.0.80"));
+    }
+}
@@ -1,19 +1,73 @@
+//! `config.toml` model: style, segments and their options, plus load / save helpers.
+
+use crate::error::CodexlineError;
+use crate::pattern::Pattern;
 use anyhow::{bail, Context, Result};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 
+/// Parsed `config.toml`; [`Config::default`] matches a fresh `--init`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     #[serde(default = "default_theme")]
     pub theme: String,
     #[serde(default)]
+    pub theme_dir: Option<PathBuf>,
+    /// Where caches (rollout offsets, git roots) are kept instead of the default cache dir.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_dir: Option<PathBuf>,
+    #[serde(default)]
     pub style: StyleConfig,
     #[serde(default)]
+    pub format: FormatConfig,
+    /// Display names for models, keyed by a pattern matched against the lower-cased model
+    /// name; the longest match wins. Replaces the built-in aliases when set.
+    #[serde(default = "default_model_aliases")]
+    pub model_aliases: BTreeMap<String, String>,
+    #[serde(default)]
     pub rollout: RolloutConfig,
     #[serde(default)]
     pub diagnostics: DiagnosticsConfig,
+    #[serde(default)]
+    pub telemetry: TelemetryConfig,
+    #[serde(default)]
+    pub alerts: AlertsConfig,
+    #[serde(default)]
+    pub usage: UsageConfig,
+    #[serde(default)]
+    pub budget: BudgetConfig,
+    /// Token prices by model, overriding the built-in table (see `pricing`).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub pricing: BTreeMap<String, ModelPrice>,
+    #[serde(default)]
+    pub terminal: TerminalConfig,
+    #[serde(default)]
+    pub plugins: PluginsConfig,
+    #[serde(default)]
+    pub hooks: HooksConfig,
+    #[serde(default)]
+    pub collect: CollectConfig,
+    #[serde(default)]
+    pub performance: PerformanceConfig,
+    #[serde(default = "default_segments")]
+    pub segments: Vec<SegmentConfig>,
+    /// Styling for segments that share a `group`, stored as `[groups.<name>]`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub groups: BTreeMap<String, GroupConfig>,
+    /// Named snapshots of theme, style and segments, stored as `[profiles.<name>]`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Profile {
+    #[serde(default = "default_theme")]
+    pub theme: String,
+    #[serde(default)]
+    pub style: StyleConfig,
     #[serde(default = "default_segments")]
     pub segments: Vec<SegmentConfig>,
 }
@@ -24,6 +78,65 @@ pub struct StyleConfig {
     pub mode: StyleMode,
     #[serde(default = "default_separator")]
     pub separator: String,
+    /// Keep this style when a theme is applied instead of taking the theme's style.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub locked: bool,
+    /// Theme used instead of `theme` inside SSH sessions, so remote shells look different.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ssh_theme: Option<String>,
+    /// Whether the plain line carries ANSI colors; `auto` drops them when stdout is not a
+    /// terminal or `NO_COLOR` is set. Like `ssh_theme`, kept when a theme is applied.
+    #[serde(default)]
+    pub color: ColorMode,
+    /// Colors the terminal can show; hex and 256-color values are mapped to the nearest one
+    /// it has. Like `color`, kept when a theme is applied.
+    #[serde(default)]
+    pub color_depth: ColorDepth,
+    /// Powerline mode: glyph before the first segment, in its background color (e.g. the
+    /// rounded `\u{e0b6}`). None by default.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub left_cap: String,
+    /// Powerline mode: glyph after the last segment, in its background color; empty ends the
+    /// line on the last segment.
+    #[serde(default = "default_right_cap")]
+    pub right_cap: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorMode {
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorDepth {
+    /// Truecolor when `COLORTERM` says so, 256 colors for a `*256color` `TERM`, else 16.
+    #[default]
+    Auto,
+    Truecolor,
+    Ansi256,
+    Ansi16,
+}
+
+impl ColorDepth {
+    /// `Auto` decided from the environment.
+    pub fn resolve(self) -> ColorDepth {
+        if self != ColorDepth::Auto {
+            return self;
+        }
+        let colorterm = std::env::var("COLORTERM").unwrap_or_default();
+        if matches!(colorterm.as_str(), "truecolor" | "24bit") {
+            return ColorDepth::Truecolor;
+        }
+        if std::env::var("TERM").is_ok_and(|term| term.contains("256color")) {
+            return ColorDepth::Ansi256;
+        }
+        ColorDepth::Ansi16
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
@@ -41,8 +154,17 @@ pub struct RolloutConfig {
     pub scan_depth_days: u32,
     #[serde(default = "default_max_files")]
     pub max_files: usize,
+    /// Rollouts larger than this are sampled: only their first line and last
+    /// `sample_tail_kb` are parsed, and the context is flagged as partial.
+    #[serde(default = "default_sample_threshold_mb")]
+    pub sample_threshold_mb: u64,
+    #[serde(default = "default_sample_tail_kb")]
+    pub sample_tail_kb: u64,
     #[serde(default)]
     pub path_override: Option<PathBuf>,
+    /// Rollout file used instead of the most recent one, set from the session browser.
+    #[serde(default)]
+    pub pinned: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -51,6 +173,263 @@ pub struct DiagnosticsConfig {
     pub warn_once: bool,
 }
 
+/// OTLP metrics export; only acted on when built with the `otel` feature.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct TelemetryConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// OTLP/HTTP base URL such as `http://localhost:4318`; defaults to
+    /// `OTEL_EXPORTER_OTLP_ENDPOINT`.
+    #[serde(default)]
+    pub otlp_endpoint: Option<String>,
+}
+
+/// Webhook notification when usage crosses a threshold; each threshold fires once and re-arms
+/// after usage drops back below it.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct AlertsConfig {
+    /// Receives a JSON POST with Slack (`text`) and Discord (`content`) compatible fields.
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+    /// Context window used percentage that triggers an alert.
+    #[serde(default)]
+    pub context_percent: Option<f64>,
+    /// Primary or secondary rate-limit used percentage that triggers an alert.
+    #[serde(default)]
+    pub rate_limit_percent: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct UsageConfig {
+    /// CSV file that gets one timestamped token / rate-limit row per render (per change in
+    /// watch mode).
+    #[serde(default)]
+    pub log_csv: Option<PathBuf>,
+    /// SQLite history database; only used by builds with the `sqlite` feature.
+    #[serde(default)]
+    pub sqlite: Option<PathBuf>,
+}
+
+/// Spend limit shown by the `budget` segment.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct BudgetConfig {
+    /// Budget per calendar month (UTC), in USD.
+    #[serde(default)]
+    pub monthly_usd: Option<f64>,
+    /// Directories of old rollouts read into the month's spend besides the sessions dir and
+    /// `<CODEX_HOME>/archived_sessions`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub archive_dirs: Vec<PathBuf>,
+}
+
+fn default_model_aliases() -> BTreeMap<String, String> {
+    [
+        ("claude-4-sonnet", "Sonnet 4"),
+        ("claude-sonnet-4", "Sonnet 4"),
+        ("claude-3-7-sonnet", "Sonnet 3.7"),
+        ("gpt-5-codex", "gpt-5-codex"),
+        ("gpt-5", "gpt-5"),
+    ]
+    .into_iter()
+    .map(|(pattern, name)| (pattern.to_string(), name.to_string()))
+    .collect()
+}
+
+/// Price of a model in USD per million tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ModelPrice {
+    pub input: f64,
+    pub output: f64,
+}
+
+/// Terminal integrations beyond the printed line.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct TerminalConfig {
+    /// Also set the terminal / tab title to a model + context summary (same as `--set-title`).
+    #[serde(default)]
+    pub set_title: bool,
+    /// Also set the iTerm2 session badge to context usage (same as `--iterm2-badge`).
+    #[serde(default)]
+    pub iterm2_badge: bool,
+}
+
+/// How numbers in segment values are written (see `numbers`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FormatConfig {
+    /// Token counts as `1.2K` / `3.4M`; `false` writes the whole count.
+    #[serde(default = "default_true")]
+    pub compact_tokens: bool,
+    /// Decimals of compacted token counts.
+    #[serde(default = "default_token_precision")]
+    pub token_precision: u8,
+    /// Decimals of percentages.
+    #[serde(default)]
+    pub percent_precision: u8,
+    /// Separators of a locale such as `de-DE`, or `auto` for `LC_ALL` / `LC_NUMERIC` /
+    /// `LANG`; empty writes `1234.5`.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub locale: String,
+    /// Override the locale's digit grouping and decimal mark.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thousands_separator: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decimal_separator: Option<String>,
+    /// Durations as `1h 12m` or `1 hour 12 minutes`.
+    #[serde(default)]
+    pub durations: DurationStyle,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DurationStyle {
+    #[default]
+    Compact,
+    Verbose,
+}
+
+impl Default for FormatConfig {
+    fn default() -> Self {
+        Self {
+            compact_tokens: true,
+            token_precision: default_token_precision(),
+            percent_precision: 0,
+            locale: String::new(),
+            thousands_separator: None,
+            decimal_separator: None,
+            durations: DurationStyle::Compact,
+        }
+    }
+}
+
+fn default_token_precision() -> u8 {
+    1
+}
+
+/// Limits on the work done for one render.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PerformanceConfig {
+    /// Wall-clock limit for collectors, plugins and hooks together; whatever is not done by
+    /// then is skipped and its segments show a placeholder.
+    #[serde(default = "default_max_runtime_ms")]
+    pub max_runtime_ms: u64,
+}
+
+impl Default for PerformanceConfig {
+    fn default() -> Self {
+        Self {
+            max_runtime_ms: default_max_runtime_ms(),
+        }
+    }
+}
+
+fn default_max_runtime_ms() -> u64 {
+    2500
+}
+
+/// Deadlines for the collectors, which run concurrently; a collector that misses its deadline
+/// leaves a placeholder in its segments instead of delaying the line.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectConfig {
+    /// Upper bound for every collector, measured from the start of collection.
+    #[serde(default = "default_collect_budget_ms")]
+    pub budget_ms: u64,
+    #[serde(default = "default_git_timeout_ms")]
+    pub git_timeout_ms: u64,
+    #[serde(default = "default_rollout_timeout_ms")]
+    pub rollout_timeout_ms: u64,
+    /// Applies to `gh pr status` when the result is not cached.
+    #[serde(default = "default_github_timeout_ms")]
+    pub github_timeout_ms: u64,
+    /// Applies to `git diff --shortstat` (the git segment's `diffstat` option) when the
+    /// result is not cached.
+    #[serde(default = "default_diffstat_timeout_ms")]
+    pub diffstat_timeout_ms: u64,
+}
+
+impl Default for CollectConfig {
+    fn default() -> Self {
+        Self {
+            budget_ms: default_collect_budget_ms(),
+            git_timeout_ms: default_git_timeout_ms(),
+            rollout_timeout_ms: default_rollout_timeout_ms(),
+            github_timeout_ms: default_github_timeout_ms(),
+            diffstat_timeout_ms: default_diffstat_timeout_ms(),
+        }
+    }
+}
+
+fn default_collect_budget_ms() -> u64 {
+    2000
+}
+
+fn default_git_timeout_ms() -> u64 {
+    500
+}
+
+fn default_rollout_timeout_ms() -> u64 {
+    1000
+}
+
+fn default_github_timeout_ms() -> u64 {
+    1500
+}
+
+fn default_diffstat_timeout_ms() -> u64 {
+    1000
+}
+
+/// How `hooks.lua` in the config dir is run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HooksConfig {
+    /// Lua interpreter invoked as `<interpreter> -` with the hook script on stdin.
+    #[serde(default = "default_hooks_interpreter")]
+    pub interpreter: String,
+    /// Time limit for the hook; on timeout the segments are rendered unchanged.
+    #[serde(default = "default_plugin_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+impl Default for HooksConfig {
+    fn default() -> Self {
+        Self {
+            interpreter: default_hooks_interpreter(),
+            timeout_ms: default_plugin_timeout_ms(),
+        }
+    }
+}
+
+fn default_hooks_interpreter() -> String {
+    "lua".to_string()
+}
+
+/// External segment providers, shown where the `plugin` segment sits.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginsConfig {
+    /// Run `codexline-segment-*` executables found on `PATH`.
+    #[serde(default = "default_true")]
+    pub discover: bool,
+    /// Per-provider time limit; slower providers are skipped for that render.
+    #[serde(default = "default_plugin_timeout_ms")]
+    pub timeout_ms: u64,
+    /// Providers by name, in addition to (or overriding) discovered ones.
+    #[serde(default)]
+    pub commands: BTreeMap<String, PathBuf>,
+}
+
+impl Default for PluginsConfig {
+    fn default() -> Self {
+        Self {
+            discover: true,
+            timeout_ms: default_plugin_timeout_ms(),
+            commands: BTreeMap::new(),
+        }
+    }
+}
+
+fn default_plugin_timeout_ms() -> u64 {
+    500
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SegmentConfig {
     pub id: SegmentId,
@@ -64,9 +443,29 @@ pub struct SegmentConfig {
     pub styles: TextStyleConfig,
     #[serde(default)]
     pub options: HashMap<String, serde_json::Value>,
+    /// Name of the `[groups.<name>]` table this segment is drawn with; neighbouring
+    /// segments of the same group form one block.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// Separator, background and brackets shared by the segments of one group.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GroupConfig {
+    /// Joins the group's segments instead of `style.separator` (or the powerline arrow).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub separator: Option<String>,
+    /// Drawn behind the whole group, including its separator and brackets; replaces the
+    /// segments' own background in powerline mode.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub background: Option<Color>,
+    /// Text before and after the group, e.g. `["[", "]"]`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub brackets: Option<(String, String)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum SegmentId {
     Model,
@@ -77,6 +476,265 @@ pub enum SegmentId {
     Limits,
     Session,
     CodexVersion,
+    PullRequest,
+    Trend,
+    Budget,
+    Cost,
+    Daily,
+    Title,
+    Idle,
+    Warnings,
+    Plugin,
+}
+
+impl SegmentId {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SegmentId::Model => "model",
+            SegmentId::Cwd => "cwd",
+            SegmentId::Git => "git",
+            SegmentId::Context => "context",
+            SegmentId::Tokens => "tokens",
+            SegmentId::Limits => "limits",
+            SegmentId::Session => "session",
+            SegmentId::CodexVersion => "codex_version",
+            SegmentId::PullRequest => "pull_request",
+            SegmentId::Trend => "trend",
+            SegmentId::Budget => "budget",
+            SegmentId::Cost => "cost",
+            SegmentId::Daily => "daily",
+            SegmentId::Title => "title",
+            SegmentId::Idle => "idle",
+            SegmentId::Warnings => "warnings",
+            SegmentId::Plugin => "plugin",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OptionKind {
+    Bool,
+    Choice(&'static [&'static str]),
+    Text,
+    /// Text in the regex subset of [`Pattern`]; empty disables it.
+    Pattern,
+    Number {
+        min: i64,
+        max: i64,
+    },
+}
+
+/// Known option for a segment, used by validation and the configurator's option editor.
+#[derive(Debug, Clone, Copy)]
+pub struct OptionSpec {
+    pub key: &'static str,
+    pub kind: OptionKind,
+    pub default: &'static str,
+    pub help: &'static str,
+}
+
+/// Options every segment accepts, listed after its own.
+const LAYOUT_OPTION_SPECS: &[OptionSpec] = &[
+    OptionSpec {
+        key: "min_width",
+        kind: OptionKind::Number { min: 0, max: 200 },
+        default: "0",
+        help: "pad the value to at least this many characters so the line keeps its width",
+    },
+    OptionSpec {
+        key: "align",
+        kind: OptionKind::Choice(&["left", "right", "center"]),
+        default: "left",
+        help: "where a value shorter than min_width sits in its padding",
+    },
+];
+
+pub fn segment_option_specs(id: SegmentId) -> Vec<OptionSpec> {
+    own_option_specs(id)
+        .iter()
+        .chain(LAYOUT_OPTION_SPECS)
+        .copied()
+        .collect()
+}
+
+fn own_option_specs(id: SegmentId) -> &'static [OptionSpec] {
+    match id {
+        SegmentId::Cwd => &[
+            OptionSpec {
+                key: "style",
+                kind: OptionKind::Choice(&["basename", "full", "fish", "relative"]),
+                default: "basename",
+                help:
+                    "directory name, full path, fish-style ~/p/c/src, or path from the project root",
+            },
+            OptionSpec {
+                key: "basename",
+                kind: OptionKind::Bool,
+                default: "true",
+                help: "false shows the full path; superseded by style",
+            },
+            OptionSpec {
+                key: "ssh_prefix",
+                kind: OptionKind::Text,
+                default: "",
+                help: "shown before the path in SSH sessions; {host} is the host name",
+            },
+            OptionSpec {
+                key: "windows_paths",
+                kind: OptionKind::Bool,
+                default: "false",
+                help: "under WSL, show /mnt/c/... as C:\\...",
+            },
+        ],
+        SegmentId::Git => &[
+            OptionSpec {
+                key: "detailed",
+                kind: OptionKind::Bool,
+                default: "false",
+                help: "include staged/unstaged/untracked/conflicted counters",
+            },
+            OptionSpec {
+                key: "diffstat",
+                kind: OptionKind::Bool,
+                default: "false",
+                help: "append lines added/removed since HEAD (git diff --shortstat)",
+            },
+            OptionSpec {
+                key: "max_branch_len",
+                kind: OptionKind::Number { min: 0, max: 200 },
+                default: "0",
+                help: "truncate longer branch names (0 keeps them whole)",
+            },
+            OptionSpec {
+                key: "ticket_regex",
+                kind: OptionKind::Pattern,
+                default: "",
+                help: "ticket id shown instead of a truncated branch, e.g. [A-Z]+-\\d+",
+            },
+        ],
+        SegmentId::Context => &[OptionSpec {
+            key: "mode",
+            kind: OptionKind::Choice(&["remaining", "used"]),
+            default: "remaining",
+            help: "show remaining or used context percentage",
+        }],
+        SegmentId::Tokens => &[
+            OptionSpec {
+                key: "show_cached",
+                kind: OptionKind::Bool,
+                default: "false",
+                help: "add the input tokens served from the prompt cache",
+            },
+            OptionSpec {
+                key: "show_reasoning",
+                kind: OptionKind::Bool,
+                default: "false",
+                help: "add the output tokens spent on reasoning",
+            },
+        ],
+        SegmentId::Limits => &[OptionSpec {
+            key: "forecast",
+            kind: OptionKind::Bool,
+            default: "false",
+            help: "estimate when each window fills up, from the usage history",
+        }],
+        SegmentId::Session => &[OptionSpec {
+            key: "length",
+            kind: OptionKind::Number { min: 4, max: 64 },
+            default: "8",
+            help: "number of thread id characters to show",
+        }],
+        SegmentId::CodexVersion => &[OptionSpec {
+            key: "prefix",
+            kind: OptionKind::Text,
+            default: "v",
+            help: "text shown before the version number",
+        }],
+        SegmentId::PullRequest => &[OptionSpec {
+            key: "cache_seconds",
+            kind: OptionKind::Number { min: 10, max: 3600 },
+            default: "120",
+            help: "seconds to reuse the last `gh pr status` result",
+        }],
+        SegmentId::Trend => &[OptionSpec {
+            key: "window_minutes",
+            kind: OptionKind::Number { min: 1, max: 1440 },
+            default: "10",
+            help: "show tokens used over this many minutes",
+        }],
+        SegmentId::Title => &[OptionSpec {
+            key: "length",
+            kind: OptionKind::Number { min: 8, max: 120 },
+            default: "32",
+            help: "number of title characters to show before truncating",
+        }],
+        SegmentId::Idle => &[OptionSpec {
+            key: "min_minutes",
+            kind: OptionKind::Number { min: 0, max: 1440 },
+            default: "5",
+            help: "hide the segment until Codex has been idle this many minutes",
+        }],
+        _ => &[],
+    }
+}
+
+impl OptionSpec {
+    /// Parses user input into an option value, rejecting values of the wrong type.
+    pub fn parse(&self, input: &str) -> Result<serde_json::Value, CodexlineError> {
+        self.parse_input(input).map_err(CodexlineError::config)
+    }
+
+    /// Checks a value read from `config.toml` against the option's kind.
+    pub fn check(&self, value: &serde_json::Value) -> Result<(), CodexlineError> {
+        self.check_value(value).map_err(CodexlineError::config)
+    }
+
+    fn parse_input(&self, input: &str) -> Result<serde_json::Value> {
+        let input = input.trim();
+        match self.kind {
+            OptionKind::Bool => match input {
+                "true" => Ok(serde_json::Value::Bool(true)),
+                "false" => Ok(serde_json::Value::Bool(false)),
+                _ => bail!("{} must be true or false", self.key),
+            },
+            OptionKind::Choice(choices) => {
+                if choices.contains(&input) {
+                    Ok(serde_json::Value::String(input.to_string()))
+                } else {
+                    bail!("{} must be one of: {}", self.key, choices.join(", "))
+                }
+            }
+            OptionKind::Text => Ok(serde_json::Value::String(input.to_string())),
+            OptionKind::Pattern => {
+                if !input.is_empty() {
+                    Pattern::new(input).with_context(|| format!("{} is not valid", self.key))?;
+                }
+                Ok(serde_json::Value::String(input.to_string()))
+            }
+            OptionKind::Number { min, max } => match input.parse::<i64>() {
+                Ok(v) if (min..=max).contains(&v) => Ok(serde_json::Value::from(v)),
+                _ => bail!("{} must be a number between {} and {}", self.key, min, max),
+            },
+        }
+    }
+
+    fn check_value(&self, value: &serde_json::Value) -> Result<()> {
+        let text = match value {
+            serde_json::Value::String(v) => v.clone(),
+            other => other.to_string(),
+        };
+        let parsed = self.parse(&text)?;
+        let same_type = matches!(
+            (&parsed, value),
+            (serde_json::Value::Bool(_), serde_json::Value::Bool(_))
+                | (serde_json::Value::String(_), serde_json::Value::String(_))
+                | (serde_json::Value::Number(_), serde_json::Value::Number(_))
+        );
+        if !same_type {
+            bail!("{} has the wrong type", self.key);
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -90,11 +748,11 @@ pub struct IconConfig {
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct ColorConfig {
     #[serde(default)]
-    pub icon: Option<NamedColor>,
+    pub icon: Option<Color>,
     #[serde(default)]
-    pub text: Option<NamedColor>,
+    pub text: Option<Color>,
     #[serde(default)]
-    pub background: Option<NamedColor>,
+    pub background: Option<Color>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -103,7 +761,7 @@ pub struct TextStyleConfig {
     pub text_bold: bool,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum NamedColor {
     Black,
@@ -124,6 +782,250 @@ pub enum NamedColor {
     BrightWhite,
 }
 
+impl NamedColor {
+    pub const ALL: [NamedColor; 16] = [
+        NamedColor::Black,
+        NamedColor::Red,
+        NamedColor::Green,
+        NamedColor::Yellow,
+        NamedColor::Blue,
+        NamedColor::Magenta,
+        NamedColor::Cyan,
+        NamedColor::White,
+        NamedColor::BrightBlack,
+        NamedColor::BrightRed,
+        NamedColor::BrightGreen,
+        NamedColor::BrightYellow,
+        NamedColor::BrightBlue,
+        NamedColor::BrightMagenta,
+        NamedColor::BrightCyan,
+        NamedColor::BrightWhite,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            NamedColor::Black => "black",
+            NamedColor::Red => "red",
+            NamedColor::Green => "green",
+            NamedColor::Yellow => "yellow",
+            NamedColor::Blue => "blue",
+            NamedColor::Magenta => "magenta",
+            NamedColor::Cyan => "cyan",
+            NamedColor::White => "white",
+            NamedColor::BrightBlack => "bright_black",
+            NamedColor::BrightRed => "bright_red",
+            NamedColor::BrightGreen => "bright_green",
+            NamedColor::BrightYellow => "bright_yellow",
+            NamedColor::BrightBlue => "bright_blue",
+            NamedColor::BrightMagenta => "bright_magenta",
+            NamedColor::BrightCyan => "bright_cyan",
+            NamedColor::BrightWhite => "bright_white",
+        }
+    }
+}
+
+impl NamedColor {
+    pub fn parse(name: &str) -> Option<NamedColor> {
+        NamedColor::ALL
+            .into_iter()
+            .find(|color| color.as_str() == name)
+    }
+
+    /// Position in the 16-color palette (0-7 normal, 8-15 bright).
+    pub fn index(self) -> u8 {
+        NamedColor::ALL
+            .iter()
+            .position(|candidate| *candidate == self)
+            .unwrap_or(7) as u8
+    }
+}
+
+/// xterm's default RGB values of the 16 named colors, used to find the nearest one.
+const XTERM_16: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// Levels of the 6x6x6 cube in the 256-color palette (indexes 16-231).
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// A color in the config: a name (`bright_cyan`), `#rrggbb`, or a 256-color index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Named(NamedColor),
+    Indexed(u8),
+    Rgb(u8, u8, u8),
+}
+
+impl From<NamedColor> for Color {
+    fn from(color: NamedColor) -> Self {
+        Color::Named(color)
+    }
+}
+
+impl Color {
+    /// RGB value, with the palette entries at xterm's defaults.
+    pub fn rgb(self) -> (u8, u8, u8) {
+        match self {
+            Color::Named(color) => XTERM_16[usize::from(color.index())],
+            Color::Indexed(index @ 0..=15) => XTERM_16[usize::from(index)],
+            Color::Indexed(index @ 16..=231) => {
+                let cube = index - 16;
+                (
+                    CUBE_LEVELS[usize::from(cube / 36)],
+                    CUBE_LEVELS[usize::from(cube / 6 % 6)],
+                    CUBE_LEVELS[usize::from(cube % 6)],
+                )
+            }
+            Color::Indexed(index) => {
+                let level = 8 + (index - 232) * 10;
+                (level, level, level)
+            }
+            Color::Rgb(r, g, b) => (r, g, b),
+        }
+    }
+
+    /// The nearest of the 16 named colors.
+    pub fn nearest_named(self) -> NamedColor {
+        match self {
+            Color::Named(color) => color,
+            Color::Indexed(index @ 0..=15) => NamedColor::ALL[usize::from(index)],
+            color => {
+                let rgb = color.rgb();
+                NamedColor::ALL
+                    .into_iter()
+                    .min_by_key(|named| distance(rgb, XTERM_16[usize::from(named.index())]))
+                    .unwrap_or(NamedColor::White)
+            }
+        }
+    }
+
+    /// The color as `depth` can show it: the nearest cube or gray entry for 256 colors, the
+    /// nearest named color for 16.
+    pub fn downgrade(self, depth: ColorDepth) -> Color {
+        match (self, depth) {
+            (Color::Named(_), _) => self,
+            (Color::Rgb(..), ColorDepth::Ansi256) => {
+                let rgb = self.rgb();
+                let level = |value: u8| {
+                    (0..6u8)
+                        .min_by_key(|&i| CUBE_LEVELS[usize::from(i)].abs_diff(value))
+                        .unwrap_or(0)
+                };
+                let cube = Color::Indexed(16 + 36 * level(rgb.0) + 6 * level(rgb.1) + level(rgb.2));
+                let average = ((u16::from(rgb.0) + u16::from(rgb.1) + u16::from(rgb.2)) / 3) as u8;
+                let gray = Color::Indexed(232 + (average.saturating_sub(3) / 10).min(23));
+                [cube, gray]
+                    .into_iter()
+                    .min_by_key(|candidate| distance(rgb, candidate.rgb()))
+                    .unwrap_or(cube)
+            }
+            (_, ColorDepth::Ansi16) => Color::Named(self.nearest_named()),
+            _ => self,
+        }
+    }
+}
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let channel = |x: u8, y: u8| u32::from(x.abs_diff(y)).pow(2);
+    channel(a.0, b.0) + channel(a.1, b.1) + channel(a.2, b.2)
+}
+
+impl std::fmt::Display for Color {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Color::Named(color) => f.write_str(color.as_str()),
+            Color::Indexed(index) => write!(f, "{index}"),
+            Color::Rgb(r, g, b) => write!(f, "#{r:02x}{g:02x}{b:02x}"),
+        }
+    }
+}
+
+impl std::str::FromStr for Color {
+    type Err = anyhow::Error;
+
+    fn from_str(text: &str) -> Result<Self> {
+        if let Some(named) = NamedColor::parse(text) {
+            return Ok(Color::Named(named));
+        }
+        if let Some(hex) = text.strip_prefix('#') {
+            let channel = |at: usize| {
+                hex.get(at..at + 2)
+                    .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+            };
+            if let (6, Some(r), Some(g), Some(b)) = (hex.len(), channel(0), channel(2), channel(4))
+            {
+                return Ok(Color::Rgb(r, g, b));
+            }
+        }
+        if let Ok(index) = text.parse::<u8>() {
+            return Ok(Color::Indexed(index));
+        }
+        bail!("invalid color {text:?}: use a name like bright_cyan, #rrggbb or 0-255")
+    }
+}
+
+impl Serialize for Color {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Color::Indexed(index) => serializer.serialize_u8(*index),
+            color => serializer.collect_str(color),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Index(u8),
+            Text(String),
+        }
+        match Raw::deserialize(deserializer)
+            .map_err(|_| serde::de::Error::custom("expected a color name, #rrggbb or 0-255"))?
+        {
+            Raw::Index(index) => Ok(Color::Indexed(index)),
+            Raw::Text(text) => text.parse().map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+/// The JSON shapes of [`Color`], for the schema only.
+#[derive(JsonSchema)]
+#[allow(dead_code)]
+#[serde(untagged)]
+enum ColorSchema {
+    Named(NamedColor),
+    /// `#rrggbb`.
+    Hex(String),
+    Indexed(u8),
+}
+
+impl JsonSchema for Color {
+    fn schema_name() -> String {
+        "Color".to_string()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        ColorSchema::json_schema(gen)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum InitResult {
     Created,
@@ -134,10 +1036,26 @@ impl Default for Config {
     fn default() -> Self {
         Self {
             theme: default_theme(),
+            theme_dir: None,
+            cache_dir: None,
             style: StyleConfig::default(),
+            format: FormatConfig::default(),
+            model_aliases: default_model_aliases(),
             rollout: RolloutConfig::default(),
             diagnostics: DiagnosticsConfig::default(),
+            telemetry: TelemetryConfig::default(),
+            alerts: AlertsConfig::default(),
+            usage: UsageConfig::default(),
+            budget: BudgetConfig::default(),
+            pricing: BTreeMap::new(),
+            terminal: TerminalConfig::default(),
+            plugins: PluginsConfig::default(),
+            hooks: HooksConfig::default(),
+            collect: CollectConfig::default(),
+            performance: PerformanceConfig::default(),
             segments: default_segments(),
+            groups: BTreeMap::new(),
+            profiles: BTreeMap::new(),
         }
     }
 }
@@ -147,6 +1065,12 @@ impl Default for StyleConfig {
         Self {
             mode: StyleMode::NerdFont,
             separator: default_separator(),
+            locked: false,
+            ssh_theme: None,
+            color: ColorMode::Auto,
+            color_depth: ColorDepth::Auto,
+            left_cap: String::new(),
+            right_cap: default_right_cap(),
         }
     }
 }
@@ -156,7 +1080,10 @@ impl Default for RolloutConfig {
         Self {
             scan_depth_days: default_scan_depth_days(),
             max_files: default_max_files(),
+            sample_threshold_mb: default_sample_threshold_mb(),
+            sample_tail_kb: default_sample_tail_kb(),
             path_override: None,
+            pinned: None,
         }
     }
 }
@@ -181,6 +1108,25 @@ pub fn themes_dir() -> PathBuf {
     config_dir().join("themes")
 }
 
+pub fn cache_dir() -> PathBuf {
+    config_dir().join("cache")
+}
+
+pub fn backups_dir() -> PathBuf {
+    config_dir().join("backups")
+}
+
+/// Line cache written by the bridge loop scripts (`scripts/bridge`).
+pub fn bridge_cache_dir() -> PathBuf {
+    if let Some(path) = std::env::var_os("XDG_CACHE_HOME") {
+        return PathBuf::from(path).join("codexline");
+    }
+    match dirs::home_dir() {
+        Some(home) => home.join(".cache").join("codexline"),
+        None => PathBuf::from(".cache").join("codexline"),
+    }
+}
+
 pub fn codex_home() -> PathBuf {
     if let Some(path) = std::env::var_os("CODEX_HOME") {
         return PathBuf::from(path);
@@ -191,17 +1137,21 @@ pub fn codex_home() -> PathBuf {
     }
 }
 
-pub fn load() -> Result<Config> {
-    ensure_themes_exist();
+/// Reads the config without touching the disk otherwise; builtin themes are served from
+/// memory, so rendering works with a read-only home.
+pub fn load() -> Result<Config, CodexlineError> {
     let path = config_path();
     if !path.exists() {
         return Ok(Config::default());
     }
-    let cfg = load_from_path(&path)?;
-    Ok(cfg)
+    load_from_path(&path)
 }
 
-pub fn load_from_path(path: &Path) -> Result<Config> {
+pub fn load_from_path(path: &Path) -> Result<Config, CodexlineError> {
+    read_config(path).map_err(CodexlineError::config)
+}
+
+fn read_config(path: &Path) -> Result<Config> {
     let content = fs::read_to_string(path)
         .with_context(|| format!("failed to read config: {}", path.display()))?;
     let cfg: Config = toml::from_str(&content)
@@ -210,14 +1160,15 @@ pub fn load_from_path(path: &Path) -> Result<Config> {
     Ok(cfg)
 }
 
-pub fn init() -> Result<InitResult> {
+pub fn init() -> Result<InitResult, CodexlineError> {
     let path = config_path();
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent)
-            .with_context(|| format!("failed to create dir: {}", parent.display()))?;
+            .with_context(|| format!("failed to create dir: {}", parent.display()))
+            .map_err(CodexlineError::config)?;
     }
 
-    crate::themes::write_builtin_themes_if_missing(&themes_dir())?;
+    crate::themes::write_builtin_themes_if_missing(&themes_dir()).map_err(CodexlineError::theme)?;
 
     if path.exists() {
         return Ok(InitResult::AlreadyExists);
@@ -228,11 +1179,15 @@ pub fn init() -> Result<InitResult> {
     Ok(InitResult::Created)
 }
 
-pub fn save(cfg: &Config) -> Result<()> {
+pub fn save(cfg: &Config) -> Result<(), CodexlineError> {
     save_to_path(cfg, &config_path())
 }
 
-pub fn save_to_path(cfg: &Config, path: &Path) -> Result<()> {
+pub fn save_to_path(cfg: &Config, path: &Path) -> Result<(), CodexlineError> {
+    write_config(cfg, path).map_err(CodexlineError::config)
+}
+
+fn write_config(cfg: &Config, path: &Path) -> Result<()> {
     cfg.validate()?;
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent)
@@ -242,12 +1197,33 @@ pub fn save_to_path(cfg: &Config, path: &Path) -> Result<()> {
     fs::write(path, text).with_context(|| format!("failed to write config: {}", path.display()))
 }
 
+/// Writes editable copies of the builtin themes into the themes dir, for `--init` and the
+/// configurator.
 pub fn ensure_themes_exist() {
     let _ = crate::themes::write_builtin_themes_if_missing(&themes_dir());
 }
 
 impl Config {
-    pub fn validate(&self) -> Result<()> {
+    /// Directory custom themes are read from: `theme_dir` when set, else the default themes dir.
+    pub fn themes_dir(&self) -> PathBuf {
+        self.theme_dir.clone().unwrap_or_else(themes_dir)
+    }
+
+    /// Directory caches are kept in: `cache_dir` when set, else the default cache dir.
+    pub fn cache_dir(&self) -> PathBuf {
+        self.cache_dir.clone().unwrap_or_else(cache_dir)
+    }
+
+    /// A copy with the named built-in or custom theme applied; `style.locked` keeps the style.
+    pub fn with_theme(&self, name: &str) -> Result<Config, CodexlineError> {
+        crate::themes::apply_theme(self, name, &self.themes_dir()).map_err(CodexlineError::theme)
+    }
+
+    pub fn validate(&self) -> Result<(), CodexlineError> {
+        self.validate_fields().map_err(CodexlineError::config)
+    }
+
+    fn validate_fields(&self) -> Result<()> {
         if self.segments.is_empty() {
             bail!("segments cannot be empty");
         }
@@ -259,9 +1235,58 @@ impl Config {
             }
         }
 
+        for segment in &self.segments {
+            for spec in segment_option_specs(segment.id) {
+                if let Some(value) = segment.options.get(spec.key) {
+                    spec.check(value).with_context(|| {
+                        format!("invalid option {}.{}", segment.id.as_str(), spec.key)
+                    })?;
+                }
+            }
+            if let Some(group) = &segment.group {
+                if !self.groups.contains_key(group) {
+                    bail!(
+                        "segment {} uses undefined group {group:?}",
+                        segment.id.as_str()
+                    );
+                }
+            }
+        }
+
         if self.rollout.max_files == 0 {
             bail!("rollout.max_files must be greater than 0");
         }
+        if self.rollout.sample_threshold_mb == 0 || self.rollout.sample_tail_kb == 0 {
+            bail!("rollout.sample_threshold_mb and rollout.sample_tail_kb must be greater than 0");
+        }
+        if self.format.token_precision > 3 || self.format.percent_precision > 3 {
+            bail!("format.token_precision and format.percent_precision must be 0 to 3");
+        }
+        if !matches!(self.format.locale.as_str(), "" | "auto")
+            && crate::numbers::locale_separators(&self.format.locale).is_none()
+        {
+            bail!(
+                "format.locale {:?} is not a known locale",
+                self.format.locale
+            );
+        }
+        for pattern in self.model_aliases.keys() {
+            Pattern::new(pattern)
+                .with_context(|| format!("invalid model_aliases key {pattern:?}"))?;
+        }
+        if let Some(monthly) = self.budget.monthly_usd {
+            if !(monthly.is_finite() && monthly > 0.0) {
+                bail!("budget.monthly_usd must be greater than 0");
+            }
+        }
+        for (model, price) in &self.pricing {
+            if ![price.input, price.output]
+                .iter()
+                .all(|usd| usd.is_finite() && *usd >= 0.0)
+            {
+                bail!("pricing.{model} prices must be 0 or more");
+            }
+        }
 
         Ok(())
     }
@@ -317,6 +1342,60 @@ pub fn default_segments() -> Vec<SegmentConfig> {
             icon("VER", "󰀘"),
             colors(Some(NamedColor::BrightBlack), Some(NamedColor::White)),
         ),
+        segment(
+            SegmentId::PullRequest,
+            false,
+            icon("PR", "\u{f407}"),
+            colors(Some(NamedColor::Green), Some(NamedColor::BrightGreen)),
+        ),
+        segment(
+            SegmentId::Trend,
+            false,
+            icon("TRD", "\u{f0535}"),
+            colors(Some(NamedColor::Cyan), Some(NamedColor::BrightCyan)),
+        ),
+        segment(
+            SegmentId::Budget,
+            false,
+            icon("$", "\u{f0d6}"),
+            colors(Some(NamedColor::Green), Some(NamedColor::BrightGreen)),
+        ),
+        segment(
+            SegmentId::Cost,
+            false,
+            icon("USD", "\u{f155}"),
+            colors(Some(NamedColor::Yellow), Some(NamedColor::BrightYellow)),
+        ),
+        segment(
+            SegmentId::Daily,
+            false,
+            icon("DAY", "\u{f00f0}"),
+            colors(Some(NamedColor::Blue), Some(NamedColor::BrightBlue)),
+        ),
+        segment(
+            SegmentId::Title,
+            false,
+            icon("TTL", "\u{f0369}"),
+            colors(Some(NamedColor::White), Some(NamedColor::BrightWhite)),
+        ),
+        segment(
+            SegmentId::Idle,
+            false,
+            icon("IDL", "\u{f051b}"),
+            colors(Some(NamedColor::Yellow), Some(NamedColor::BrightYellow)),
+        ),
+        segment(
+            SegmentId::Warnings,
+            false,
+            icon("WARN", "\u{f071}"),
+            colors(Some(NamedColor::Red), Some(NamedColor::BrightYellow)),
+        ),
+        segment(
+            SegmentId::Plugin,
+            false,
+            icon("", ""),
+            colors(None, Some(NamedColor::White)),
+        ),
     ]
 }
 
@@ -335,6 +1414,7 @@ fn segment(id: SegmentId, enabled: bool, icon: IconConfig, colors: ColorConfig)
         colors,
         styles: TextStyleConfig::default(),
         options: HashMap::new(),
+        group: None,
     }
 }
 
@@ -347,8 +1427,8 @@ fn icon(plain: &str, nerd_font: &str) -> IconConfig {
 
 fn colors(icon_color: Option<NamedColor>, text_color: Option<NamedColor>) -> ColorConfig {
     ColorConfig {
-        icon: icon_color,
-        text: text_color,
+        icon: icon_color.map(Color::from),
+        text: text_color.map(Color::from),
         background: None,
     }
 }
@@ -361,6 +1441,10 @@ fn default_separator() -> String {
     " · ".to_string()
 }
 
+fn default_right_cap() -> String {
+    crate::render::POWERLINE_ARROW.to_string()
+}
+
 fn default_scan_depth_days() -> u32 {
     14
 }
@@ -369,6 +1453,14 @@ fn default_max_files() -> usize {
     200
 }
 
+fn default_sample_threshold_mb() -> u64 {
+    64
+}
+
+fn default_sample_tail_kb() -> u64 {
+    1024
+}
+
 fn default_true() -> bool {
     true
 }
@@ -386,6 +1478,69 @@ mod tests {
     #[test]
     fn default_segments_include_all() {
         let cfg = Config::default();
-        assert_eq!(cfg.segments.len(), 8);
+        assert_eq!(cfg.segments.len(), 17);
+    }
+
+    #[test]
+    fn theme_dir_overrides_default_themes_dir() {
+        let mut cfg = Config::default();
+        assert_eq!(cfg.themes_dir(), themes_dir());
+        cfg.theme_dir = Some(PathBuf::from("/tmp/codexline-themes"));
+        assert_eq!(cfg.themes_dir(), PathBuf::from("/tmp/codexline-themes"));
+    }
+
+    #[test]
+    fn validate_rejects_mistyped_segment_options() {
+        let mut cfg = Config::default();
+        git_options(&mut cfg).insert("detailed".to_string(), serde_json::Value::from("yes"));
+        assert!(cfg.validate().is_err());
+
+        git_options(&mut cfg).insert("detailed".to_string(), serde_json::Value::Bool(true));
+        assert!(cfg.validate().is_ok());
+
+        git_options(&mut cfg).insert("ticket_regex".to_string(), "(JIRA|GH)-\\d+".into());
+        assert!(cfg.validate().is_err());
+        git_options(&mut cfg).insert("ticket_regex".to_string(), "[A-Z]+-\\d+".into());
+        assert!(cfg.validate().is_ok());
+    }
+
+    fn git_options(cfg: &mut Config) -> &mut HashMap<String, serde_json::Value> {
+        &mut cfg
+            .segments
+            .iter_mut()
+            .find(|segment| segment.id == SegmentId::Git)
+            .expect("git segment")
+            .options
+    }
+
+    #[test]
+    fn colors_accept_hex_and_indexes_and_downgrade() {
+        let colors: ColorConfig =
+            toml::from_str("icon = \"bright_cyan\"\ntext = \"#ff8700\"\nbackground = 236")
+                .expect("colors");
+        assert_eq!(colors.icon, Some(Color::Named(NamedColor::BrightCyan)));
+        assert_eq!(colors.text, Some(Color::Rgb(0xff, 0x87, 0x00)));
+        assert_eq!(colors.background, Some(Color::Indexed(236)));
+        assert_eq!(
+            toml::to_string(&colors).expect("serialize"),
+            "icon = \"bright_cyan\"\ntext = \"#ff8700\"\nbackground = 236\n"
+        );
+        assert!(toml::from_str::<ColorConfig>("text = \"#ff87\"").is_err());
+
+        let orange = Color::Rgb(0xff, 0x87, 0x00);
+        assert_eq!(orange.downgrade(ColorDepth::Truecolor), orange);
+        assert_eq!(orange.downgrade(ColorDepth::Ansi256), Color::Indexed(208));
+        assert_eq!(
+            Color::Rgb(0x30, 0x30, 0x30).downgrade(ColorDepth::Ansi256),
+            Color::Indexed(236)
+        );
+        assert_eq!(
+            orange.downgrade(ColorDepth::Ansi16),
+            Color::Named(NamedColor::Yellow)
+        );
+        assert_eq!(
+            Color::Indexed(236).downgrade(ColorDepth::Ansi16),
+            Color::Named(NamedColor::Black)
+        );
     }
 }
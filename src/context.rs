@@ -1,8 +1,15 @@
+//! Data collected for one statusline render, independent of how it is displayed.
+
+use crate::config::Color;
 use chrono::{DateTime, Utc};
-use serde::Serialize;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
-#[derive(Debug, Clone, Serialize)]
+/// Everything segments can show; absent sources are `None`. New sources add fields, so
+/// build one with [`StatusContext::new`] and set the fields you have.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[non_exhaustive]
 pub struct StatusContext {
     pub now: DateTime<Utc>,
     pub cwd: PathBuf,
@@ -12,9 +19,69 @@ pub struct StatusContext {
     pub usage: Option<TokenUsageSnapshot>,
     pub limits: Option<RateLimitSnapshot>,
     pub session: Option<SessionMetaSnapshot>,
+    /// Open pull request of the current branch; only collected when the `pull_request`
+    /// segment is enabled.
+    pub pull_request: Option<PullRequestStatus>,
+    /// Tokens used over the `trend` segment's window, from the usage history; only collected
+    /// when that segment is enabled.
+    pub trend: Option<UsageTrend>,
+    /// When the rate-limit windows fill up at the current burn rate; only collected when
+    /// the `limits` segment's `forecast` option is on.
+    pub forecast: Option<LimitForecast>,
+    /// Spend this month against `budget.monthly_usd`; only collected when the `budget`
+    /// segment is enabled and a budget is set.
+    pub budget: Option<BudgetStatus>,
+    /// Tokens used today (local time) across every session in the sessions dir; only
+    /// collected when the `daily` segment is enabled.
+    pub daily: Option<DailyUsage>,
+    /// Timestamp of the latest rollout line, i.e. when Codex last did anything.
+    pub last_event_at: Option<DateTime<Utc>>,
+    /// Host name when running inside an SSH session.
+    pub remote_host: Option<String>,
+    /// Output of external segment providers; only collected when the `plugin` segment is
+    /// enabled.
+    pub plugins: Vec<PluginSegment>,
+    /// Collectors that missed their deadline; their segments show a placeholder.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub degraded: Vec<CollectorId>,
+    /// Collectors that returned incomplete data, such as a sampled oversized rollout; their
+    /// numbers are shown with a `~` prefix.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub partial: Vec<CollectorId>,
+    /// Collectors whose source failed, was missing or served stale data; shown by the
+    /// `warnings` segment.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<CollectWarning>,
+}
+
+impl StatusContext {
+    /// A context for `cwd` at `now` with no data from any source.
+    pub fn new(now: DateTime<Utc>, cwd: PathBuf) -> Self {
+        Self {
+            now,
+            cwd,
+            project_root: None,
+            model: None,
+            git: None,
+            usage: None,
+            limits: None,
+            session: None,
+            pull_request: None,
+            trend: None,
+            forecast: None,
+            budget: None,
+            daily: None,
+            last_event_at: None,
+            remote_host: None,
+            plugins: Vec::new(),
+            degraded: Vec::new(),
+            partial: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct GitStatus {
     pub branch: String,
     pub dirty: bool,
@@ -24,27 +91,151 @@ pub struct GitStatus {
     pub conflicted: u32,
     pub ahead: Option<i64>,
     pub behind: Option<i64>,
+    /// Lines added and removed since `HEAD`; only collected with the git segment's
+    /// `diffstat` option.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diffstat: Option<DiffStat>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct DiffStat {
+    pub insertions: u64,
+    pub deletions: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TokenUsageSnapshot {
     pub input_tokens: i64,
     pub output_tokens: i64,
+    /// Part of `input_tokens` served from the prompt cache.
+    #[serde(default)]
+    pub cached_input_tokens: i64,
+    /// Part of `output_tokens` spent on reasoning.
+    #[serde(default)]
+    pub reasoning_output_tokens: i64,
     pub total_tokens: i64,
     pub model_context_window: Option<i64>,
     pub used_percent: Option<i64>,
     pub remaining_percent: Option<i64>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct RateLimitSnapshot {
     pub primary_used_percent: Option<f64>,
     pub secondary_used_percent: Option<f64>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SessionMetaSnapshot {
     pub thread_id: Option<String>,
     pub cli_version: Option<String>,
     pub model_provider: Option<String>,
+    /// First user message of the session, on one line and capped at 200 characters.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct PullRequestStatus {
+    pub number: u64,
+    pub state: String,
+    pub draft: bool,
+    pub checks: CheckState,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckState {
+    None,
+    Pending,
+    Passing,
+    Failing,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct UsageTrend {
+    pub window_minutes: u32,
+    pub tokens: i64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct LimitForecast {
+    pub primary_full_minutes: Option<u32>,
+    pub secondary_full_minutes: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct BudgetStatus {
+    pub spent_usd: f64,
+    pub monthly_usd: f64,
+    /// Sessions on models without a known price, or whose rollout is gone; their spend is
+    /// missing from `spent_usd`.
+    pub unpriced_sessions: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct DailyUsage {
+    pub tokens: i64,
+    /// Sessions that used tokens today.
+    pub sessions: u32,
+}
+
+/// One external provider's segment; see `[plugins]` in the config.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct PluginSegment {
+    pub name: String,
+    pub icon: String,
+    pub value: String,
+    pub icon_color: Option<Color>,
+    pub text_color: Option<Color>,
+    pub background: Option<Color>,
+    pub bold: bool,
+}
+
+/// Data source run with its own deadline during collection (see `[collect]`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CollectorId {
+    Git,
+    Rollout,
+    #[serde(rename = "github")]
+    GitHub,
+    Plugins,
+    History,
+    Daily,
+    #[serde(rename = "diffstat")]
+    DiffStat,
+}
+
+impl CollectorId {
+    /// Name as serialized, e.g. in `--json` output.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CollectorId::Git => "git",
+            CollectorId::Rollout => "rollout",
+            CollectorId::GitHub => "github",
+            CollectorId::Plugins => "plugins",
+            CollectorId::History => "history",
+            CollectorId::Daily => "daily",
+            CollectorId::DiffStat => "diffstat",
+        }
+    }
+}
+
+/// Something a collector could not get right, apart from missing its deadline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+pub struct CollectWarning {
+    pub collector: CollectorId,
+    pub kind: WarningKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WarningKind {
+    /// The source returned an error, e.g. `git status` failing inside a repository.
+    Failed,
+    /// There was nothing to read, e.g. no rollout in the sessions dir.
+    Missing,
+    /// A refresh failed and an expired cached result is shown instead.
+    Stale,
 }
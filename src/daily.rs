@@ -0,0 +1,231 @@
+//! Today's token usage across every session, for the `daily` segment. Each rollout written
+//! today contributes what its cumulative total grew by since local midnight; the per-file
+//! state lives in `cache/daily-usage.json` so a render only reads the lines appended since
+//! the last one.
+
+use crate::collect;
+use crate::config;
+use crate::context::DailyUsage;
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use walkdir::WalkDir;
+
+/// Per-rollout totals for one day; a new day starts from an empty ledger.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct DailyLedger {
+    /// Local date, `YYYY-MM-DD`.
+    day: String,
+    files: BTreeMap<PathBuf, FileTotals>,
+    #[serde(skip)]
+    changed: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct FileTotals {
+    /// Bytes consumed, always at a line boundary.
+    offset: u64,
+    len: u64,
+    modified: Option<SystemTime>,
+    /// Last total reported before the day started.
+    before: i64,
+    /// Last total reported today.
+    latest: Option<i64>,
+}
+
+impl FileTotals {
+    fn today(&self) -> i64 {
+        self.latest
+            .map_or(0, |latest| (latest - self.before).max(0))
+    }
+}
+
+impl DailyLedger {
+    fn load(path: &Path, day: &str) -> Self {
+        fs::read(path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<Self>(&bytes).ok())
+            .filter(|ledger| ledger.day == day)
+            .unwrap_or_else(|| Self {
+                day: day.to_string(),
+                changed: true,
+                ..Self::default()
+            })
+    }
+
+    fn save(&self, path: &Path) {
+        if !self.changed {
+            return;
+        }
+        if let Ok(bytes) = serde_json::to_vec(self) {
+            if let Some(parent) = path.parent() {
+                let _ = fs::create_dir_all(parent);
+            }
+            let temp = path.with_extension(format!("json.{}", std::process::id()));
+            if fs::write(&temp, bytes).is_ok() {
+                let _ = fs::rename(&temp, path);
+            }
+        }
+    }
+
+    /// Brings every rollout modified since `since` up to date. Files are read from their
+    /// stored offset; one that shrank is read again from the start.
+    fn scan(&mut self, sessions_dir: &Path, since: DateTime<Utc>) {
+        let since_system = SystemTime::from(since);
+        for entry in WalkDir::new(sessions_dir).into_iter().flatten() {
+            let path = entry.path();
+            if !entry.file_type().is_file()
+                || path.extension().and_then(|ext| ext.to_str()) != Some("jsonl")
+            {
+                continue;
+            }
+            let Ok(meta) = entry.metadata() else {
+                continue;
+            };
+            let modified = meta.modified().ok();
+            if modified.is_some_and(|modified| modified < since_system) {
+                continue;
+            }
+            let stored = self.files.get(path);
+            if stored.is_some_and(|s| s.len == meta.len() && s.modified == modified) {
+                continue;
+            }
+            let mut totals = stored
+                .filter(|s| s.offset <= meta.len())
+                .cloned()
+                .unwrap_or_default();
+            if read_totals(path, since, &mut totals).is_ok() {
+                totals.len = meta.len();
+                totals.modified = modified;
+                self.files.insert(path.to_path_buf(), totals);
+                self.changed = true;
+            }
+        }
+    }
+
+    fn usage(&self) -> DailyUsage {
+        let used: Vec<i64> = self
+            .files
+            .values()
+            .map(FileTotals::today)
+            .filter(|tokens| *tokens > 0)
+            .collect();
+        DailyUsage {
+            tokens: used.iter().sum(),
+            sessions: used.len() as u32,
+        }
+    }
+}
+
+/// Reads the complete lines after `totals.offset`; an unterminated last line is left for the
+/// next scan. Token counts without a timestamp count as today's.
+fn read_totals(path: &Path, since: DateTime<Utc>, totals: &mut FileTotals) -> std::io::Result<()> {
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::Start(totals.offset))?;
+    let mut reader = BufReader::new(file);
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        let read = reader.read_until(b'\n', &mut line)?;
+        if read == 0 || !line.ends_with(b"\n") {
+            return Ok(());
+        }
+        totals.offset += read as u64;
+        match collect::token_count_total(&line) {
+            Some((Some(at), total)) if at < since => totals.before = total,
+            Some((_, total)) => totals.latest = Some(total),
+            None => {}
+        }
+    }
+}
+
+/// Today's usage (since local midnight) across `sessions_dir`. Cache write failures only
+/// cost the next render a rescan.
+pub fn today(sessions_dir: &Path, now: DateTime<Utc>) -> DailyUsage {
+    let local = now.with_timezone(&Local);
+    let since = local
+        .date_naive()
+        .and_hms_opt(0, 0, 0)
+        .and_then(|midnight| midnight.and_local_timezone(Local).earliest())
+        .map_or(now, |midnight| midnight.with_timezone(&Utc));
+    let path = config::cache_dir().join("daily-usage.json");
+    let mut ledger = DailyLedger::load(&path, &local.format("%Y-%m-%d").to_string());
+    ledger.scan(sessions_dir, since);
+    ledger.save(&path);
+    ledger.usage()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn token_count(at: &str, total: i64) -> String {
+        format!(
+            "{{\"timestamp\":\"{at}\",\"type\":\"event_msg\",\"payload\":{{\"type\":\"token_count\",\"info\":{{\"total_token_usage\":{{\"total_tokens\":{total}}}}}}}}}\n"
+        )
+    }
+
+    #[test]
+    fn daily_usage_sums_todays_growth_across_sessions() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let yesterday = dir.path().join("2025/10/08");
+        let today = dir.path().join("2025/10/09");
+        fs::create_dir_all(&yesterday).expect("mkdir");
+        fs::create_dir_all(&today).expect("mkdir");
+        let since = DateTime::parse_from_rfc3339("2025-10-09T00:00:00Z")
+            .expect("timestamp")
+            .with_timezone(&Utc);
+
+        // Started yesterday at 1000 tokens; only the 500 used after midnight count.
+        let carried = yesterday.join("rollout-a.jsonl");
+        fs::write(
+            &carried,
+            token_count("2025-10-08T22:00:00Z", 1_000)
+                + &token_count("2025-10-09T01:00:00Z", 1_500),
+        )
+        .expect("write");
+        let fresh = today.join("rollout-b.jsonl");
+        fs::write(
+            &fresh,
+            token_count("2025-10-09T02:00:00Z", 2_000)
+                + "{\"type\":\"event_msg\",\"payload\":{\"type\":\"token_count\",\"info\":null}}\n",
+        )
+        .expect("write");
+
+        let mut ledger = DailyLedger::default();
+        ledger.scan(dir.path(), since);
+        assert_eq!(
+            ledger.usage(),
+            DailyUsage {
+                tokens: 2_500,
+                sessions: 2
+            }
+        );
+
+        // Appended lines are read from the stored offset; a cut line waits for the next scan.
+        let mut file = fs::OpenOptions::new()
+            .append(true)
+            .open(&fresh)
+            .expect("open");
+        file.write_all(token_count("2025-10-09T03:00:00Z", 2_600).as_bytes())
+            .expect("append");
+        file.write_all(b"{\"timestamp\":\"2025-10-09T03:01:00Z\",\"type\":\"event")
+            .expect("append");
+        ledger.scan(dir.path(), since);
+        assert_eq!(ledger.usage().tokens, 3_100);
+        assert_eq!(
+            ledger.files[&fresh].offset,
+            fs::read_to_string(&fresh)
+                .expect("read")
+                .rfind('\n')
+                .expect("newline") as u64
+                + 1
+        );
+    }
+}
@@ -0,0 +1,122 @@
+use crate::config;
+use crate::context::{DiffStat, GitStatus};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/// How long a `git diff --shortstat` result is reused while the status counters match.
+const CACHE_SECS: i64 = 10;
+
+/// Repos remembered in `cache/git-diffstat.json`; the cache starts over beyond that.
+const CACHE_LIMIT: usize = 64;
+
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    fetched_at: i64,
+    /// Branch and counters of the status the result belongs to; a change refetches.
+    status: String,
+    stat: Option<DiffStat>,
+}
+
+/// Lines added and removed in the working tree and index since `HEAD`, cached per repo for
+/// [`CACHE_SECS`]. A clean tree is zero without running git; an unborn branch or a failing
+/// git yields `None`, cached the same way.
+pub fn diff_stat(repo_root: &Path, git: &GitStatus) -> Option<DiffStat> {
+    if !git.dirty {
+        return Some(DiffStat::default());
+    }
+    let cache_path = config::cache_dir().join("git-diffstat.json");
+    let key = repo_root.display().to_string();
+    let status = format!(
+        "{}:{}:{}:{}",
+        git.branch, git.staged, git.unstaged, git.conflicted
+    );
+    let now = Utc::now().timestamp();
+    let mut cache: BTreeMap<String, CacheEntry> = fs::read_to_string(&cache_path)
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default();
+    if let Some(entry) = cache.get(&key) {
+        if entry.status == status && (now - entry.fetched_at) < CACHE_SECS {
+            return entry.stat;
+        }
+    }
+
+    let stat = fetch(repo_root);
+    if cache.len() >= CACHE_LIMIT && !cache.contains_key(&key) {
+        cache.clear();
+    }
+    cache.insert(
+        key,
+        CacheEntry {
+            fetched_at: now,
+            status,
+            stat,
+        },
+    );
+    if let Ok(text) = serde_json::to_string(&cache) {
+        let _ = fs::create_dir_all(config::cache_dir());
+        let _ = fs::write(&cache_path, text);
+    }
+    stat
+}
+
+fn fetch(repo_root: &Path) -> Option<DiffStat> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo_root)
+        .args(["diff", "--shortstat", "HEAD"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(parse_shortstat(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// ` 3 files changed, 120 insertions(+), 34 deletions(-)`; either count is left out when
+/// zero, and the whole line when nothing changed.
+fn parse_shortstat(text: &str) -> DiffStat {
+    let mut stat = DiffStat::default();
+    for part in text.trim().split(", ") {
+        let Some((count, label)) = part.split_once(' ') else {
+            continue;
+        };
+        let Ok(count) = count.parse::<u64>() else {
+            continue;
+        };
+        if label.starts_with("insertion") {
+            stat.insertions = count;
+        } else if label.starts_with("deletion") {
+            stat.deletions = count;
+        }
+    }
+    stat
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_shortstat_reads_both_counts() {
+        assert_eq!(
+            parse_shortstat(" 3 files changed, 120 insertions(+), 34 deletions(-)\n"),
+            DiffStat {
+                insertions: 120,
+                deletions: 34
+            }
+        );
+        assert_eq!(
+            parse_shortstat(" 1 file changed, 1 deletion(-)\n"),
+            DiffStat {
+                insertions: 0,
+                deletions: 1
+            }
+        );
+        assert_eq!(parse_shortstat(""), DiffStat::default());
+    }
+}
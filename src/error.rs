@@ -0,0 +1,67 @@
+//! Error type of the library API.
+
+use std::error::Error;
+
+type BoxError = Box<dyn Error + Send + Sync + 'static>;
+
+/// Failure category of a library call, so embedders can tell a broken `config.toml` from an
+/// unreadable sessions directory. The underlying error is the [`Error::source`].
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum CodexlineError {
+    /// `config.toml` could not be read, parsed, validated or written.
+    #[error("config error")]
+    Config(#[source] BoxError),
+    /// A theme could not be found, parsed or written.
+    #[error("theme error")]
+    Theme(#[source] BoxError),
+    /// Git or rollout data could not be collected, or fixtures could not be written.
+    #[error("collect error")]
+    Collect(#[source] BoxError),
+    /// Rendered output could not be written.
+    #[error("render error")]
+    Render(#[source] BoxError),
+}
+
+impl CodexlineError {
+    pub(crate) fn config(err: impl Into<BoxError>) -> Self {
+        Self::Config(err.into())
+    }
+
+    pub(crate) fn theme(err: impl Into<BoxError>) -> Self {
+        Self::Theme(err.into())
+    }
+
+    pub(crate) fn collect(err: impl Into<BoxError>) -> Self {
+        Self::Collect(err.into())
+    }
+
+    pub(crate) fn render(err: impl Into<BoxError>) -> Self {
+        Self::Render(err.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn source_is_the_wrapped_error() {
+        let inner = Err::<(), _>(std::io::Error::other("permission denied"))
+            .context("failed to read config: /x/config.toml")
+            .unwrap_err();
+        let err = CodexlineError::config(inner);
+
+        assert!(matches!(err, CodexlineError::Config(_)));
+        assert_eq!(err.to_string(), "config error");
+        assert_eq!(
+            err.source().map(ToString::to_string).as_deref(),
+            Some("failed to read config: /x/config.toml")
+        );
+        assert_eq!(
+            format!("{:#}", anyhow::Error::new(err)),
+            "config error: failed to read config: /x/config.toml: permission denied"
+        );
+    }
+}
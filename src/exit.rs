@@ -0,0 +1,56 @@
+use std::fmt;
+
+/// Process exit codes. Scripts can branch on these instead of parsing output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitStatus {
+    Success,
+    Failure,
+    ConfigInvalid,
+    ChecksFailed,
+    NoData,
+}
+
+impl ExitStatus {
+    pub fn code(self) -> i32 {
+        match self {
+            ExitStatus::Success => 0,
+            ExitStatus::Failure => 1,
+            ExitStatus::ConfigInvalid => 2,
+            ExitStatus::ChecksFailed => 3,
+            ExitStatus::NoData => 4,
+        }
+    }
+
+    pub fn for_error(err: &anyhow::Error) -> Self {
+        if err.downcast_ref::<ConfigInvalid>().is_some() {
+            ExitStatus::ConfigInvalid
+        } else {
+            ExitStatus::Failure
+        }
+    }
+}
+
+/// Context marker attached to errors caused by an unreadable or invalid config.
+#[derive(Debug, Clone, Copy)]
+pub struct ConfigInvalid;
+
+impl fmt::Display for ConfigInvalid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid configuration")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{anyhow, Context};
+
+    #[test]
+    fn config_errors_map_to_config_invalid() {
+        let err = Err::<(), _>(anyhow!("duplicate segment id"))
+            .context(ConfigInvalid)
+            .unwrap_err();
+        assert_eq!(ExitStatus::for_error(&err), ExitStatus::ConfigInvalid);
+        assert_eq!(ExitStatus::for_error(&anyhow!("io")), ExitStatus::Failure);
+    }
+}
@@ -0,0 +1,141 @@
+use crate::config::{self, Config, StyleMode};
+use crate::patch_diagnose::{CheckStatus, PatchCheck};
+use crate::themes;
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A remediation `--doctor --fix` may apply. Each one only creates missing files, switches
+/// the style to plain, or removes cache files codexline rebuilds on its own.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fix {
+    CreateConfig,
+    MaterializeThemes,
+    CreateSessionsDir(PathBuf),
+    SwitchToPlain,
+    ClearCorruptCache(Vec<PathBuf>),
+}
+
+impl Fix {
+    pub fn describe(&self) -> String {
+        match self {
+            Fix::CreateConfig => format!("create config {}", config::config_path().display()),
+            Fix::MaterializeThemes => {
+                format!("write builtin themes to {}", config::themes_dir().display())
+            }
+            Fix::CreateSessionsDir(path) => format!("create sessions dir {}", path.display()),
+            Fix::SwitchToPlain => "set style.mode = \"plain\" (no Nerd Font detected)".to_string(),
+            Fix::ClearCorruptCache(paths) => format!(
+                "remove {} corrupt cache file(s) under {}",
+                paths.len(),
+                config::cache_dir().display()
+            ),
+        }
+    }
+
+    pub fn apply(&self, cfg: &Config) -> Result<()> {
+        match self {
+            Fix::CreateConfig => {
+                config::init()?;
+            }
+            Fix::MaterializeThemes => {
+                themes::write_builtin_themes_if_missing(&config::themes_dir())?
+            }
+            Fix::CreateSessionsDir(path) => fs::create_dir_all(path)
+                .with_context(|| format!("failed to create dir: {}", path.display()))?,
+            Fix::SwitchToPlain => {
+                // Lock the style so the configured theme does not bring its icons back.
+                let mut saved = config::load()?;
+                saved.style.separator = cfg.style.separator.clone();
+                saved.style.mode = StyleMode::Plain;
+                saved.style.locked = true;
+                config::save(&saved)?;
+            }
+            Fix::ClearCorruptCache(paths) => {
+                for path in paths {
+                    fs::remove_file(path)
+                        .with_context(|| format!("failed to remove file: {}", path.display()))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The fixes for what doctor reported, in the order they should be applied.
+pub fn plan(checks: &[PatchCheck], sessions_dir: &Path) -> Vec<Fix> {
+    let warned = |name: &str| {
+        checks
+            .iter()
+            .any(|check| check.name == name && !matches!(check.status, CheckStatus::Ok))
+    };
+    let mut fixes = Vec::new();
+    if warned("config") {
+        fixes.push(Fix::CreateConfig);
+    } else if themes_missing(&config::themes_dir()) {
+        fixes.push(Fix::MaterializeThemes);
+    }
+    if warned("sessions_dir") {
+        fixes.push(Fix::CreateSessionsDir(sessions_dir.to_path_buf()));
+    }
+    if warned("nerd_font") {
+        fixes.push(Fix::SwitchToPlain);
+    }
+    let corrupt = corrupt_cache_files(&config::cache_dir());
+    if !corrupt.is_empty() {
+        fixes.push(Fix::ClearCorruptCache(corrupt));
+    }
+    fixes
+}
+
+fn themes_missing(themes_dir: &Path) -> bool {
+    themes::builtin_theme_names()
+        .iter()
+        .any(|name| !themes_dir.join(format!("{name}.toml")).exists())
+}
+
+/// JSON cache files (top level and `gh-pr/`) that no longer parse.
+fn corrupt_cache_files(cache_dir: &Path) -> Vec<PathBuf> {
+    let mut corrupt = Vec::new();
+    for dir in [cache_dir.to_path_buf(), cache_dir.join("gh-pr")] {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        let mut paths: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        paths.sort();
+        corrupt.extend(paths.into_iter().filter(|path| {
+            fs::read(path)
+                .map(|bytes| serde_json::from_slice::<serde_json::Value>(&bytes).is_err())
+                .unwrap_or(false)
+        }));
+    }
+    corrupt
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn corrupt_cache_files_are_found() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("gh-pr")).unwrap();
+        fs::write(dir.path().join("git-roots.json"), "{}").unwrap();
+        fs::write(dir.path().join("rollout-offsets.json"), "{\"files\":").unwrap();
+        fs::write(dir.path().join("gh-pr").join("abc.json"), [0xff, 0x00]).unwrap();
+        fs::write(dir.path().join("prime.lock"), "not json").unwrap();
+
+        assert_eq!(
+            corrupt_cache_files(dir.path()),
+            vec![
+                dir.path().join("rollout-offsets.json"),
+                dir.path().join("gh-pr").join("abc.json"),
+            ]
+        );
+        assert!(corrupt_cache_files(&dir.path().join("missing")).is_empty());
+    }
+}
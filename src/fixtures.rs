@@ -0,0 +1,284 @@
+//! Synthetic `CODEX_HOME` trees for testing integrations without a real Codex install.
+//!
+//! Token counts, ids and rate-limit values depend only on [`FixtureSpec`], so the same spec
+//! always renders the same statusline; timestamps and file ages are relative to now so the
+//! files stay inside `rollout.scan_depth_days`. [`demo_context`] is the in-memory
+//! counterpart behind `--demo`.
+
+use crate::context::{
+    BudgetStatus, CheckState, DailyUsage, DiffStat, GitStatus, LimitForecast, PullRequestStatus,
+    RateLimitSnapshot, SessionMetaSnapshot, StatusContext, TokenUsageSnapshot, UsageTrend,
+};
+use crate::error::CodexlineError;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde_json::json;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Shape of the generated sessions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixtureSpec {
+    /// Number of rollout files; the first one is the newest.
+    pub sessions: usize,
+    /// `token_count` events per rollout.
+    pub events: usize,
+    /// Age of the oldest rollout; sessions are spread evenly up to it.
+    pub max_age_days: u32,
+    pub model: String,
+}
+
+impl Default for FixtureSpec {
+    fn default() -> Self {
+        Self {
+            sessions: 3,
+            events: 20,
+            max_age_days: 7,
+            model: "gpt-5-codex".to_string(),
+        }
+    }
+}
+
+/// Writes `<codex_home>/sessions/YYYY/MM/DD/rollout-*.jsonl` files and returns their paths,
+/// newest first. Point `CODEX_HOME` at `codex_home` to use them.
+pub fn generate(codex_home: &Path, spec: &FixtureSpec) -> Result<Vec<PathBuf>, CodexlineError> {
+    write_fixtures(codex_home, spec).map_err(CodexlineError::collect)
+}
+
+fn write_fixtures(codex_home: &Path, spec: &FixtureSpec) -> Result<Vec<PathBuf>> {
+    let now = Utc::now();
+    let span = Duration::days(i64::from(spec.max_age_days));
+    let mut paths = Vec::with_capacity(spec.sessions);
+    for index in 0..spec.sessions {
+        let age = if spec.sessions > 1 {
+            span * index as i32 / (spec.sessions - 1) as i32
+        } else {
+            Duration::zero()
+        };
+        paths.push(write_session(codex_home, spec, index, now - age)?);
+    }
+    Ok(paths)
+}
+
+fn write_session(
+    codex_home: &Path,
+    spec: &FixtureSpec,
+    index: usize,
+    started: DateTime<Utc>,
+) -> Result<PathBuf> {
+    let id = format!("00000000-0000-4000-8000-{:012x}", index + 1);
+    let dir = codex_home
+        .join("sessions")
+        .join(started.format("%Y/%m/%d").to_string());
+    fs::create_dir_all(&dir).with_context(|| format!("failed to create dir: {}", dir.display()))?;
+    let path = dir.join(format!(
+        "rollout-{}-{id}.jsonl",
+        started.format("%Y-%m-%dT%H-%M-%S")
+    ));
+
+    let mut lines = vec![
+        json!({
+            "timestamp": started.to_rfc3339(),
+            "type": "session_meta",
+            "payload": { "id": id, "cli_version": "0.46.0", "model_provider": "openai" },
+        }),
+        json!({
+            "timestamp": started.to_rfc3339(),
+            "type": "turn_context",
+            "payload": { "model": spec.model },
+        }),
+    ];
+    let mut rng = Lcg(index as u64 + 1);
+    let (mut input, mut output) = (0i64, 0i64);
+    for event in 0..spec.events {
+        input += rng.range(500, 5_000) as i64;
+        output += rng.range(100, 1_000) as i64;
+        let progress = (event + 1) as f64 / spec.events as f64;
+        lines.push(json!({
+            "timestamp": (started + Duration::seconds(30 * (event as i64 + 1))).to_rfc3339(),
+            "type": "event_msg",
+            "payload": {
+                "type": "token_count",
+                "info": {
+                    "model_context_window": 272_000,
+                    "total_token_usage": {
+                        "input_tokens": input,
+                        "output_tokens": output,
+                        "total_tokens": input + output,
+                    },
+                },
+                "rate_limits": {
+                    "primary": { "used_percent": (progress * 60.0).round() },
+                    "secondary": { "used_percent": (progress * 20.0).round() },
+                },
+            },
+        }));
+    }
+
+    let mut file =
+        File::create(&path).with_context(|| format!("failed to create: {}", path.display()))?;
+    for line in &lines {
+        writeln!(file, "{line}")?;
+    }
+    file.set_modified(started.into())
+        .with_context(|| format!("failed to set mtime: {}", path.display()))?;
+    Ok(path)
+}
+
+/// A context with data for every built-in segment: a dirty branch with a diffstat, 73% of
+/// the context window used, both rate limits, an open pull request and a budget. Built in
+/// memory; nothing is read or written.
+pub fn demo_context(now: DateTime<Utc>) -> StatusContext {
+    let root = PathBuf::from("/work/codexline");
+    StatusContext {
+        project_root: Some(root.clone()),
+        model: Some("gpt-5-codex".to_string()),
+        git: Some(GitStatus {
+            branch: "feature/CL-238-demo-mode".to_string(),
+            dirty: true,
+            staged: 2,
+            unstaged: 3,
+            untracked: 1,
+            conflicted: 0,
+            ahead: Some(1),
+            behind: Some(2),
+            diffstat: Some(DiffStat {
+                insertions: 120,
+                deletions: 34,
+            }),
+        }),
+        usage: Some(TokenUsageSnapshot {
+            input_tokens: 184_320,
+            output_tokens: 14_240,
+            cached_input_tokens: 61_440,
+            reasoning_output_tokens: 3_072,
+            total_tokens: 198_560,
+            model_context_window: Some(272_000),
+            used_percent: Some(73),
+            remaining_percent: Some(27),
+        }),
+        limits: Some(RateLimitSnapshot {
+            primary_used_percent: Some(41.0),
+            secondary_used_percent: Some(18.0),
+        }),
+        session: Some(SessionMetaSnapshot {
+            thread_id: Some("0199a1b2-c3d4-7e5f-8a9b-0c1d2e3f4a5b".to_string()),
+            cli_version: Some("0.46.0".to_string()),
+            model_provider: Some("openai".to_string()),
+            title: Some("Add a demo render mode".to_string()),
+        }),
+        pull_request: Some(PullRequestStatus {
+            number: 42,
+            state: "open".to_string(),
+            draft: false,
+            checks: CheckState::Passing,
+        }),
+        trend: Some(UsageTrend {
+            window_minutes: 10,
+            tokens: 12_400,
+        }),
+        forecast: Some(LimitForecast {
+            primary_full_minutes: Some(190),
+            secondary_full_minutes: None,
+        }),
+        budget: Some(BudgetStatus {
+            spent_usd: 12.4,
+            monthly_usd: 50.0,
+            unpriced_sessions: 0,
+        }),
+        daily: Some(DailyUsage {
+            tokens: 1_900_000,
+            sessions: 4,
+        }),
+        last_event_at: Some(now - Duration::minutes(14)),
+        ..StatusContext::new(now, root.join("src"))
+    }
+}
+
+/// Small deterministic generator; fixtures must not depend on a random seed.
+struct Lcg(u64);
+
+impl Lcg {
+    fn range(&mut self, low: u64, high: u64) -> u64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        low + (self.0 >> 33) % (high - low)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collect;
+    use crate::config::{Config, SegmentId};
+    use tempfile::TempDir;
+
+    #[test]
+    fn generated_sessions_are_collectable() {
+        let dir = TempDir::new().expect("temp dir");
+        let spec = FixtureSpec {
+            sessions: 2,
+            events: 5,
+            max_age_days: 3,
+            ..FixtureSpec::default()
+        };
+
+        let paths = generate(dir.path(), &spec).expect("generate");
+        assert_eq!(paths.len(), 2);
+
+        let cfg = Config {
+            cache_dir: Some(dir.path().join("cache")),
+            ..Config::default()
+        };
+        let sessions =
+            collect::list_sessions(&cfg, &dir.path().join("sessions"), 10).expect("list");
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].path, paths[0]);
+        assert_eq!(
+            sessions[0]
+                .session
+                .as_ref()
+                .and_then(|s| s.thread_id.as_deref()),
+            Some("00000000-0000-4000-8000-000000000001")
+        );
+        let usage = sessions[0].usage.as_ref().expect("usage");
+        assert!(usage.total_tokens > 0);
+        assert_eq!(
+            sessions[0]
+                .limits
+                .as_ref()
+                .and_then(|l| l.primary_used_percent),
+            Some(60.0)
+        );
+    }
+
+    #[test]
+    fn demo_context_fills_every_builtin_segment() {
+        let mut cfg = Config::default();
+        for segment in &mut cfg.segments {
+            segment.enabled = true;
+        }
+        let context = demo_context(Utc::now());
+        let rendered = crate::segments::build_segments(&cfg, &context);
+        let ids: Vec<SegmentId> = rendered.iter().map(|piece| piece.id).collect();
+        for segment in &cfg.segments {
+            // Warnings only show with a failed collector; plugins come from user commands.
+            if matches!(segment.id, SegmentId::Warnings | SegmentId::Plugin) {
+                continue;
+            }
+            assert!(ids.contains(&segment.id), "{:?} is empty", segment.id);
+        }
+        let context_piece = rendered
+            .iter()
+            .find(|piece| piece.id == SegmentId::Context)
+            .expect("context");
+        assert!(
+            context_piece.value.contains("27%"),
+            "{}",
+            context_piece.value
+        );
+    }
+}
@@ -0,0 +1,170 @@
+use crate::config;
+use crate::context::{CheckState, PullRequestStatus};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    fetched_at: i64,
+    status: Option<PullRequestStatus>,
+}
+
+/// Pull request of the current branch from `gh pr status`, cached per repo and branch for
+/// `ttl_secs` so the statusline does not hit the GitHub API on every render. A missing `gh`,
+/// no auth or no PR all yield `None` and are cached the same way, except that a failing `gh`
+/// keeps showing an expired pull request; the flag is `true` for such a stale result.
+pub fn pull_request(
+    repo_root: &Path,
+    branch: &str,
+    ttl_secs: u64,
+) -> (Option<PullRequestStatus>, bool) {
+    let cache = cache_path(repo_root, branch);
+    let now = Utc::now().timestamp();
+    let cached = fs::read_to_string(&cache)
+        .ok()
+        .and_then(|text| serde_json::from_str::<CacheEntry>(&text).ok());
+    if let Some(entry) = &cached {
+        if now - entry.fetched_at < ttl_secs as i64 {
+            return (entry.status.clone(), false);
+        }
+    }
+
+    let status = match fetch(repo_root) {
+        Some(status) => status,
+        None => match cached.and_then(|entry| entry.status) {
+            // Left expired, so the next render tries `gh` again.
+            Some(stale) => return (Some(stale), true),
+            None => None,
+        },
+    };
+    let entry = CacheEntry {
+        fetched_at: now,
+        status: status.clone(),
+    };
+    if let Ok(text) = serde_json::to_string(&entry) {
+        let _ = fs::create_dir_all(config::cache_dir().join("gh-pr"));
+        let _ = fs::write(&cache, text);
+    }
+    (status, false)
+}
+
+/// `None` when `gh` could not be run or failed; `Some(None)` when there is no pull request.
+fn fetch(repo_root: &Path) -> Option<Option<PullRequestStatus>> {
+    let output = Command::new("gh")
+        .current_dir(repo_root)
+        .args([
+            "pr",
+            "status",
+            "--json",
+            "number,state,isDraft,statusCheckRollup",
+        ])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(parse_pr_status(&String::from_utf8_lossy(&output.stdout)))
+}
+
+fn parse_pr_status(text: &str) -> Option<PullRequestStatus> {
+    let value: Value = serde_json::from_str(text).ok()?;
+    let current = value.get("currentBranch").filter(|v| !v.is_null())?;
+    let number = current.get("number")?.as_u64()?;
+    let checks = current
+        .get("statusCheckRollup")
+        .and_then(Value::as_array)
+        .map(|checks| check_state(checks))
+        .unwrap_or(CheckState::None);
+
+    Some(PullRequestStatus {
+        number,
+        state: current
+            .get("state")
+            .and_then(Value::as_str)
+            .unwrap_or("OPEN")
+            .to_lowercase(),
+        draft: current
+            .get("isDraft")
+            .and_then(Value::as_bool)
+            .unwrap_or(false),
+        checks,
+    })
+}
+
+/// Check runs report `status` / `conclusion`, commit statuses report `state`.
+fn check_state(checks: &[Value]) -> CheckState {
+    if checks.is_empty() {
+        return CheckState::None;
+    }
+    let field = |check: &Value, key: &str| {
+        check
+            .get(key)
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_uppercase()
+    };
+    let mut pending = false;
+    for check in checks {
+        let state = field(check, "state");
+        let conclusion = field(check, "conclusion");
+        let failed = matches!(state.as_str(), "FAILURE" | "ERROR")
+            || matches!(
+                conclusion.as_str(),
+                "FAILURE" | "TIMED_OUT" | "CANCELLED" | "ACTION_REQUIRED" | "STARTUP_FAILURE"
+            );
+        if failed {
+            return CheckState::Failing;
+        }
+        let running = matches!(state.as_str(), "PENDING" | "EXPECTED")
+            || (state.is_empty() && field(check, "status") != "COMPLETED");
+        pending |= running;
+    }
+    if pending {
+        CheckState::Pending
+    } else {
+        CheckState::Passing
+    }
+}
+
+fn cache_path(repo_root: &Path, branch: &str) -> PathBuf {
+    let key: String = format!("{}@{}", repo_root.display(), branch)
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    config::cache_dir()
+        .join("gh-pr")
+        .join(format!("{key}.json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_pr_status_reads_current_branch_checks() {
+        let text = r#"{
+            "createdBy": [],
+            "currentBranch": {
+                "number": 42,
+                "state": "OPEN",
+                "isDraft": false,
+                "statusCheckRollup": [
+                    {"__typename": "CheckRun", "status": "COMPLETED", "conclusion": "SUCCESS"},
+                    {"__typename": "CheckRun", "status": "IN_PROGRESS", "conclusion": ""},
+                    {"__typename": "StatusContext", "state": "SUCCESS"}
+                ]
+            },
+            "needsReview": []
+        }"#;
+
+        let status = parse_pr_status(text).expect("pr");
+        assert_eq!(status.number, 42);
+        assert_eq!(status.state, "open");
+        assert_eq!(status.checks, CheckState::Pending);
+        assert!(parse_pr_status(r#"{"currentBranch": null}"#).is_none());
+    }
+}
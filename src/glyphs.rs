@@ -0,0 +1,83 @@
+/// Curated Nerd Font glyph, offered by the configurator's icon picker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Glyph {
+    pub category: &'static str,
+    pub name: &'static str,
+    pub glyph: &'static str,
+}
+
+const fn glyph(category: &'static str, name: &'static str, glyph: &'static str) -> Glyph {
+    Glyph {
+        category,
+        name,
+        glyph,
+    }
+}
+
+pub const GLYPHS: &[Glyph] = &[
+    glyph("model", "chat", "\u{f0b79}"),
+    glyph("model", "robot", "\u{f06a9}"),
+    glyph("model", "brain", "\u{f09d1}"),
+    glyph("model", "sparkles", "\u{f0674}"),
+    glyph("model", "copilot", "\u{f4b8}"),
+    glyph("model", "magic", "\u{f0d0}"),
+    glyph("folder", "folder open", "\u{f07c}"),
+    glyph("folder", "folder", "\u{f07b}"),
+    glyph("folder", "directory", "\u{f413}"),
+    glyph("folder", "home", "\u{f015}"),
+    glyph("git", "branch", "\u{e725}"),
+    glyph("git", "git logo", "\u{e702}"),
+    glyph("git", "branch outline", "\u{f418}"),
+    glyph("git", "commit", "\u{f417}"),
+    glyph("git", "pull request", "\u{f407}"),
+    glyph("git", "github", "\u{f09b}"),
+    glyph("usage", "donut chart", "\u{f0626}"),
+    glyph("usage", "gauge", "\u{f029a}"),
+    glyph("usage", "tachometer", "\u{f0e4}"),
+    glyph("usage", "pie chart", "\u{f200}"),
+    glyph("tokens", "counter", "\u{f01a7}"),
+    glyph("tokens", "database", "\u{f1c0}"),
+    glyph("tokens", "hashtag", "\u{f292}"),
+    glyph("limits", "speedometer", "\u{f0f85}"),
+    glyph("limits", "clock", "\u{f017}"),
+    glyph("limits", "hourglass", "\u{f252}"),
+    glyph("limits", "alert", "\u{f0026}"),
+    glyph("session", "identifier", "\u{f10ac}"),
+    glyph("session", "key", "\u{f084}"),
+    glyph("session", "terminal", "\u{f120}"),
+    glyph("version", "tag", "\u{f02b}"),
+    glyph("version", "versions", "\u{f454}"),
+    glyph("version", "package", "\u{f0018}"),
+];
+
+/// Case-insensitive substring match against category and name; an empty query matches all.
+pub fn search(query: &str) -> Vec<&'static Glyph> {
+    let query = query.trim().to_lowercase();
+    GLYPHS
+        .iter()
+        .filter(|glyph| {
+            query.is_empty() || glyph.name.contains(&query) || glyph.category.contains(&query)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_matches_name_and_category() {
+        assert_eq!(search("").len(), GLYPHS.len());
+        assert!(search("GIT")
+            .iter()
+            .all(|glyph| glyph.category == "git" || glyph.name.contains("git")));
+        assert_eq!(
+            search("hourglass")
+                .iter()
+                .map(|glyph| glyph.glyph)
+                .collect::<Vec<_>>(),
+            vec!["\u{f252}"]
+        );
+        assert!(search("no-such-glyph").is_empty());
+    }
+}
@@ -0,0 +1,682 @@
+//! Usage history behind the `trend` segment: a ring buffer of usage and rate-limit snapshots
+//! in `cache/history.jsonl`, one JSON object per line, oldest first.
+//!
+//! A snapshot is recorded on every collection with the `trend` segment enabled. Renders that
+//! follow each other closely (prompts, `--watch`) overwrite the newest snapshot instead of
+//! adding one, so [`CAPACITY`] snapshots cover several hours.
+//!
+//! The `budget` segment keeps a separate ledger, [`MonthlyUsage`], of each session's token
+//! totals in the current calendar month (UTC), in `cache/budget.json`. Besides the session
+//! being rendered, it is backfilled from every rollout of the month, including archived ones,
+//! and from Codex's `history.jsonl`.
+
+use crate::collect;
+use crate::config::{self, Config};
+use crate::context::{StatusContext, UsageTrend};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use walkdir::WalkDir;
+
+/// Snapshots kept; the oldest is dropped when a new one would exceed it.
+pub const CAPACITY: usize = 1024;
+
+/// A snapshot this close to the newest one (same session) replaces it.
+const MIN_SPACING_SECS: i64 = 15;
+
+/// Seconds between two backfills of the monthly ledger; renders in between use it as it is.
+const BACKFILL_SECS: i64 = 300;
+
+/// Rollouts a render reads per backfill; the rest wait for later renders or `--prime`.
+const BACKFILL_RENDER_FILES: usize = 8;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_tokens: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_used_percent: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub primary_used_percent: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secondary_used_percent: Option<f64>,
+}
+
+impl Snapshot {
+    /// The usage in `ctx`; `None` when it has neither token counts nor rate limits.
+    pub fn from_context(ctx: &StatusContext) -> Option<Self> {
+        if ctx.usage.is_none() && ctx.limits.is_none() {
+            return None;
+        }
+        Some(Self {
+            at: ctx.now,
+            session: ctx
+                .session
+                .as_ref()
+                .and_then(|session| session.thread_id.clone()),
+            total_tokens: ctx.usage.as_ref().map(|usage| usage.total_tokens),
+            context_used_percent: ctx.usage.as_ref().and_then(|usage| usage.used_percent),
+            primary_used_percent: ctx.limits.as_ref().and_then(|l| l.primary_used_percent),
+            secondary_used_percent: ctx.limits.as_ref().and_then(|l| l.secondary_used_percent),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    snapshots: VecDeque<Snapshot>,
+}
+
+impl History {
+    pub fn path() -> PathBuf {
+        config::cache_dir().join("history.jsonl")
+    }
+
+    /// Reads the history, skipping lines that do not parse; a missing file is empty.
+    pub fn load(path: &Path) -> Self {
+        let text = fs::read_to_string(path).unwrap_or_default();
+        let mut snapshots: VecDeque<Snapshot> = text
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect();
+        while snapshots.len() > CAPACITY {
+            snapshots.pop_front();
+        }
+        Self { snapshots }
+    }
+
+    /// Rewrites the file through a temporary one, so concurrent renders never see half of it.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create dir: {}", parent.display()))?;
+        }
+        let mut text = String::new();
+        for snapshot in &self.snapshots {
+            text.push_str(&serde_json::to_string(snapshot)?);
+            text.push('\n');
+        }
+        let temp = path.with_extension(format!("jsonl.{}", std::process::id()));
+        fs::write(&temp, text).with_context(|| format!("failed to write: {}", temp.display()))?;
+        fs::rename(&temp, path).with_context(|| format!("failed to replace: {}", path.display()))
+    }
+
+    pub fn record(&mut self, snapshot: Snapshot) {
+        if let Some(last) = self.snapshots.back_mut() {
+            if last.session == snapshot.session
+                && (snapshot.at - last.at).num_seconds().abs() < MIN_SPACING_SECS
+            {
+                *last = snapshot;
+                return;
+            }
+        }
+        self.snapshots.push_back(snapshot);
+        while self.snapshots.len() > CAPACITY {
+            self.snapshots.pop_front();
+        }
+    }
+
+    /// All snapshots, oldest first.
+    pub fn snapshots(&self) -> impl DoubleEndedIterator<Item = &Snapshot> {
+        self.snapshots.iter()
+    }
+
+    /// The newest snapshot taken at or before `at`.
+    pub fn at_or_before(&self, at: DateTime<Utc>) -> Option<&Snapshot> {
+        self.snapshots().rev().find(|snapshot| snapshot.at <= at)
+    }
+
+    /// Tokens used since `since`, summed over sessions: each session counts what its total
+    /// grew by between consecutive snapshots. `None` when the history does not reach back
+    /// to `since`.
+    pub fn tokens_since(&self, since: DateTime<Utc>) -> Option<i64> {
+        let baseline = self.at_or_before(since)?;
+        let window: Vec<&Snapshot> = std::iter::once(baseline)
+            .chain(self.snapshots().filter(|snapshot| snapshot.at > since))
+            .collect();
+        Some(
+            window
+                .windows(2)
+                .filter(|pair| pair[0].session == pair[1].session)
+                .filter_map(|pair| Some(pair[1].total_tokens? - pair[0].total_tokens?))
+                .map(|grown| grown.max(0))
+                .sum(),
+        )
+    }
+
+    /// Tokens used over the `window_minutes` before `now`.
+    pub fn trend(&self, now: DateTime<Utc>, window_minutes: u32) -> Option<UsageTrend> {
+        let tokens = self.tokens_since(now - Duration::minutes(i64::from(window_minutes)))?;
+        Some(UsageTrend {
+            window_minutes,
+            tokens,
+        })
+    }
+}
+
+/// One session's cumulative token totals, as reported by its rollout.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionUsage {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    /// Totals when the month began, for sessions carried over from the previous month.
+    #[serde(default)]
+    pub baseline_input: i64,
+    #[serde(default)]
+    pub baseline_output: i64,
+}
+
+impl SessionUsage {
+    /// Input and output tokens used this month.
+    pub fn month_tokens(&self) -> (i64, i64) {
+        (
+            (self.input_tokens - self.baseline_input).max(0),
+            (self.output_tokens - self.baseline_output).max(0),
+        )
+    }
+}
+
+/// Token totals per session (thread id) for one calendar month.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MonthlyUsage {
+    /// `YYYY-MM`, UTC.
+    pub month: String,
+    #[serde(default)]
+    pub sessions: BTreeMap<String, SessionUsage>,
+    /// Sessions in Codex's `history.jsonl` this month that no rollout accounts for, so their
+    /// spend is unknown.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub untracked: BTreeSet<String>,
+    /// Rollouts already read by [`MonthlyUsage::backfill`], with the size and mtime read.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    files: BTreeMap<PathBuf, (u64, Option<SystemTime>)>,
+    /// Bytes of `history.jsonl` already read.
+    #[serde(default)]
+    prompt_history_offset: u64,
+    #[serde(default)]
+    backfilled_at: Option<DateTime<Utc>>,
+}
+
+/// A prompt in Codex's `history.jsonl`.
+#[derive(Deserialize)]
+struct PromptEntry {
+    session_id: String,
+    /// Unix seconds.
+    ts: i64,
+}
+
+impl MonthlyUsage {
+    pub fn path(cache_dir: &Path) -> PathBuf {
+        cache_dir.join("budget.json")
+    }
+
+    /// Reads the ledger; a missing or unreadable file is empty.
+    pub fn load(path: &Path) -> Self {
+        fs::read(path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create dir: {}", parent.display()))?;
+        }
+        let temp = path.with_extension(format!("json.{}", std::process::id()));
+        fs::write(&temp, serde_json::to_vec(self)?)
+            .with_context(|| format!("failed to write: {}", temp.display()))?;
+        fs::rename(&temp, path).with_context(|| format!("failed to replace: {}", path.display()))
+    }
+
+    /// Records a session's totals at `now`. Totals only grow, so an older rollout line read
+    /// by a concurrent render cannot lower them.
+    pub fn record(
+        &mut self,
+        now: DateTime<Utc>,
+        session: &str,
+        model: Option<&str>,
+        input_tokens: i64,
+        output_tokens: i64,
+    ) {
+        self.roll_month(now);
+        let entry = self.sessions.entry(session.to_string()).or_default();
+        entry.input_tokens = entry.input_tokens.max(input_tokens);
+        entry.output_tokens = entry.output_tokens.max(output_tokens);
+        if let Some(model) = model {
+            entry.model = Some(model.to_string());
+        }
+        self.untracked.remove(session);
+    }
+
+    /// Adds the sessions of this month that were never rendered: rollouts in `dirs` modified
+    /// since the month began, and sessions in `prompt_history` (Codex's `history.jsonl`) that
+    /// no rollout accounts for, as [`Self::untracked`]. Rollouts read before are skipped until
+    /// they change. At most `limit` rollouts (newest first) are read per call; the next call
+    /// carries on until all are read. A complete pass runs at most every [`BACKFILL_SECS`];
+    /// returns whether it ran.
+    pub fn backfill(
+        &mut self,
+        cfg: &Config,
+        now: DateTime<Utc>,
+        dirs: &[PathBuf],
+        prompt_history: &Path,
+        limit: Option<usize>,
+    ) -> bool {
+        self.roll_month(now);
+        if self
+            .backfilled_at
+            .is_some_and(|at| (now - at).num_seconds().abs() < BACKFILL_SECS)
+        {
+            return false;
+        }
+        let since = month_start(now);
+        let since_system = SystemTime::from(since);
+
+        let mut changed = Vec::new();
+        for dir in dirs {
+            for entry in WalkDir::new(dir).into_iter().flatten() {
+                let path = entry.path();
+                if !entry.file_type().is_file()
+                    || path.extension().and_then(|ext| ext.to_str()) != Some("jsonl")
+                {
+                    continue;
+                }
+                let Ok(meta) = entry.metadata() else {
+                    continue;
+                };
+                let modified = meta.modified().ok();
+                if modified.is_some_and(|modified| modified < since_system)
+                    || self.files.get(path) == Some(&(meta.len(), modified))
+                {
+                    continue;
+                }
+                changed.push((
+                    modified.unwrap_or(since_system),
+                    path.to_path_buf(),
+                    meta.len(),
+                    modified,
+                ));
+            }
+        }
+        changed.sort_by_key(|entry| std::cmp::Reverse(entry.0));
+        let limit = limit.unwrap_or(usize::MAX);
+        let complete = changed.len() <= limit;
+        changed.truncate(limit);
+        let changed: Vec<_> = changed
+            .into_iter()
+            .map(|(at, path, len, modified)| {
+                self.files.insert(path.clone(), (len, modified));
+                (at, path)
+            })
+            .collect();
+        for summary in collect::summarize_rollouts(cfg, &changed) {
+            let session = summary
+                .session
+                .as_ref()
+                .and_then(|session| session.thread_id.clone());
+            let (Some(session), Some(usage)) = (session, summary.usage) else {
+                continue;
+            };
+            let before = if started_before(&summary.path, since) {
+                totals_before(&summary.path, since)
+            } else {
+                (0, 0)
+            };
+            self.record(
+                now,
+                &session,
+                summary.model.as_deref(),
+                usage.input_tokens,
+                usage.output_tokens,
+            );
+            if let Some(entry) = self.sessions.get_mut(&session) {
+                entry.baseline_input = entry.baseline_input.max(before.0);
+                entry.baseline_output = entry.baseline_output.max(before.1);
+            }
+        }
+        if complete {
+            self.read_prompt_history(prompt_history, since);
+            self.backfilled_at = Some(now);
+        }
+        true
+    }
+
+    /// Reads the complete lines appended to `history.jsonl` since the last read; a file that
+    /// shrank is read again from the start.
+    fn read_prompt_history(&mut self, path: &Path, since: DateTime<Utc>) {
+        let Ok(mut file) = File::open(path) else {
+            return;
+        };
+        let len = file.metadata().map_or(0, |meta| meta.len());
+        if len < self.prompt_history_offset {
+            self.prompt_history_offset = 0;
+        }
+        if file
+            .seek(SeekFrom::Start(self.prompt_history_offset))
+            .is_err()
+        {
+            return;
+        }
+        let mut reader = BufReader::new(file);
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            match reader.read_until(b'\n', &mut line) {
+                Ok(read) if read > 0 && line.ends_with(b"\n") => {
+                    self.prompt_history_offset += read as u64
+                }
+                _ => break,
+            }
+            let Ok(entry) = serde_json::from_slice::<PromptEntry>(&line) else {
+                continue;
+            };
+            if entry.ts >= since.timestamp() && !self.sessions.contains_key(&entry.session_id) {
+                self.untracked.insert(entry.session_id);
+            }
+        }
+    }
+
+    fn roll_month(&mut self, now: DateTime<Utc>) {
+        let month = now.format("%Y-%m").to_string();
+        if self.month != month {
+            self.start_month(month);
+        }
+    }
+
+    /// Drops sessions that saw no use in the month that ended and carries the others over,
+    /// so only what they use from now on counts.
+    fn start_month(&mut self, month: String) {
+        self.sessions
+            .retain(|_, usage| usage.month_tokens() != (0, 0));
+        for usage in self.sessions.values_mut() {
+            usage.baseline_input = usage.input_tokens;
+            usage.baseline_output = usage.output_tokens;
+        }
+        self.month = month;
+        self.untracked.clear();
+        self.files.clear();
+        self.prompt_history_offset = 0;
+        self.backfilled_at = None;
+    }
+}
+
+/// Midnight UTC on the first of `now`'s month.
+fn month_start(now: DateTime<Utc>) -> DateTime<Utc> {
+    NaiveDate::from_ymd_opt(now.year(), now.month(), 1)
+        .and_then(|day| day.and_hms_opt(0, 0, 0))
+        .map_or(now, |start| start.and_utc())
+}
+
+/// Whether the session may have started before `since`, going by the start time in the
+/// rollout's name (`rollout-2025-10-01T09-30-00-<id>.jsonl`, local time, hence the day of
+/// slack); names without one are assumed to.
+fn started_before(path: &Path, since: DateTime<Utc>) -> bool {
+    let started = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.strip_prefix("rollout-"))
+        .and_then(|name| name.get(..19))
+        .and_then(|stamp| NaiveDateTime::parse_from_str(stamp, "%Y-%m-%dT%H-%M-%S").ok());
+    match started {
+        Some(started) => started.and_utc() < since + Duration::days(1),
+        None => true,
+    }
+}
+
+/// The last input and output totals a rollout reported before `since`.
+fn totals_before(path: &Path, since: DateTime<Utc>) -> (i64, i64) {
+    let Ok(file) = File::open(path) else {
+        return (0, 0);
+    };
+    let mut before = (0, 0);
+    let mut reader = BufReader::new(file);
+    let mut line = Vec::new();
+    while reader
+        .read_until(b'\n', &mut line)
+        .is_ok_and(|read| read > 0)
+    {
+        match collect::token_count_split(&line) {
+            Some((Some(at), _, _)) if at >= since => break,
+            Some((Some(_), input, output)) => before = (input, output),
+            _ => {}
+        }
+        line.clear();
+    }
+    before
+}
+
+/// Records `ctx` in the history file and returns the updated history. Failing to save only
+/// costs this snapshot.
+pub fn record(ctx: &StatusContext) -> History {
+    let path = History::path();
+    let mut history = History::load(&path);
+    if let Some(snapshot) = Snapshot::from_context(ctx) {
+        history.record(snapshot);
+        let _ = history.save(&path);
+    }
+    history
+}
+
+/// Records the session in `ctx` in the monthly ledger, backfills it from a few of the
+/// rollouts in `sessions_dir`, `<CODEX_HOME>/archived_sessions` and `budget.archive_dirs`
+/// (`--prime` reads the rest), and returns the updated ledger. Failing to save only costs
+/// this update.
+pub fn record_month(cfg: &Config, ctx: &StatusContext, sessions_dir: &Path) -> MonthlyUsage {
+    let path = MonthlyUsage::path(&cfg.cache_dir());
+    let mut usage = MonthlyUsage::load(&path);
+    let mut changed = false;
+    let session = ctx
+        .session
+        .as_ref()
+        .and_then(|session| session.thread_id.as_deref());
+    if let (Some(session), Some(tokens)) = (session, ctx.usage.as_ref()) {
+        usage.record(
+            ctx.now,
+            session,
+            ctx.model.as_deref(),
+            tokens.input_tokens,
+            tokens.output_tokens,
+        );
+        changed = true;
+    }
+    changed |= backfill(
+        cfg,
+        &mut usage,
+        ctx.now,
+        sessions_dir,
+        Some(BACKFILL_RENDER_FILES),
+    );
+    if changed {
+        let _ = usage.save(&path);
+    }
+    usage
+}
+
+/// Backfills the monthly ledger from every rollout not read yet; run by `--prime`, off the
+/// render path.
+pub fn backfill_month(cfg: &Config, now: DateTime<Utc>, sessions_dir: &Path) {
+    let path = MonthlyUsage::path(&cfg.cache_dir());
+    let mut usage = MonthlyUsage::load(&path);
+    if backfill(cfg, &mut usage, now, sessions_dir, None) {
+        let _ = usage.save(&path);
+    }
+}
+
+fn backfill(
+    cfg: &Config,
+    usage: &mut MonthlyUsage,
+    now: DateTime<Utc>,
+    sessions_dir: &Path,
+    limit: Option<usize>,
+) -> bool {
+    let codex_home = config::codex_home();
+    let mut dirs = vec![
+        sessions_dir.to_path_buf(),
+        codex_home.join("archived_sessions"),
+    ];
+    dirs.extend(cfg.budget.archive_dirs.iter().cloned());
+    usage.backfill(cfg, now, &dirs, &codex_home.join("history.jsonl"), limit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(minute: i64, session: &str, total_tokens: i64) -> Snapshot {
+        Snapshot {
+            at: DateTime::from_timestamp(1_760_000_000 + minute * 60, 0).expect("timestamp"),
+            session: Some(session.to_string()),
+            total_tokens: Some(total_tokens),
+            context_used_percent: None,
+            primary_used_percent: None,
+            secondary_used_percent: None,
+        }
+    }
+
+    #[test]
+    fn history_is_a_ring_buffer_queried_by_time() {
+        let mut history = History::default();
+        for minute in 0..(CAPACITY as i64 + 5) {
+            history.record(snapshot(minute, "a", minute * 100));
+        }
+        assert_eq!(history.snapshots().count(), CAPACITY);
+        assert_eq!(history.snapshots().next(), Some(&snapshot(5, "a", 500)));
+
+        let mut history = History::default();
+        history.record(snapshot(0, "a", 1_000));
+        history.record(snapshot(5, "a", 3_000));
+        // Within MIN_SPACING_SECS of the newest snapshot: replaces it.
+        let mut close = snapshot(5, "a", 3_500);
+        close.at += Duration::seconds(5);
+        history.record(close);
+        history.record(snapshot(8, "b", 2_000));
+        history.record(snapshot(10, "b", 6_000));
+        assert_eq!(history.snapshots().count(), 4);
+
+        let start = snapshot(0, "a", 0).at;
+        assert_eq!(
+            history
+                .at_or_before(start + Duration::minutes(7))
+                .map(|s| s.total_tokens),
+            Some(Some(3_500))
+        );
+        // a: 1000 -> 3500, then session b: 2000 -> 6000; the switch itself is not counted.
+        assert_eq!(history.tokens_since(start), Some(6_500));
+        assert_eq!(
+            history.tokens_since(start + Duration::minutes(9)),
+            Some(4_000)
+        );
+        assert_eq!(history.tokens_since(start - Duration::minutes(1)), None);
+
+        let dir = tempfile::tempdir().expect("temp dir");
+        let path = dir.path().join("history.jsonl");
+        history.save(&path).expect("save");
+        fs::write(
+            &path,
+            format!("{}not json\n", fs::read_to_string(&path).expect("read")),
+        )
+        .expect("write");
+        assert_eq!(
+            History::load(&path).snapshots().collect::<Vec<_>>(),
+            history.snapshots().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn monthly_usage_carries_sessions_over_month_ends() {
+        let at = |text: &str| {
+            DateTime::parse_from_rfc3339(text)
+                .expect("timestamp")
+                .with_timezone(&Utc)
+        };
+        let mut usage = MonthlyUsage::default();
+        usage.record(at("2025-09-30T10:00:00Z"), "a", Some("gpt-5"), 1_000, 100);
+        usage.record(at("2025-09-30T11:00:00Z"), "b", None, 500, 50);
+        usage.record(at("2025-09-30T23:00:00Z"), "a", Some("gpt-5"), 4_000, 300);
+        // A stale read cannot lower the totals.
+        usage.record(at("2025-09-30T23:00:01Z"), "a", None, 2_000, 200);
+        assert_eq!(usage.sessions["a"].month_tokens(), (4_000, 300));
+        assert_eq!(usage.sessions["a"].model.as_deref(), Some("gpt-5"));
+
+        usage.record(at("2025-10-01T08:00:00Z"), "a", None, 6_000, 400);
+        assert_eq!(usage.month, "2025-10");
+        assert_eq!(usage.sessions["a"].month_tokens(), (2_000, 100));
+        assert_eq!(usage.sessions["b"].month_tokens(), (0, 0));
+
+        // b stayed idle through October, so it is gone in November.
+        usage.record(at("2025-11-01T08:00:00Z"), "c", None, 10, 1);
+        assert_eq!(usage.sessions.keys().collect::<Vec<_>>(), vec!["a", "c"]);
+        assert_eq!(usage.sessions["a"].month_tokens(), (0, 0));
+    }
+
+    #[test]
+    fn backfill_reads_archived_rollouts_and_prompt_history() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let archived = dir.path().join("archived_sessions");
+        fs::create_dir_all(&archived).expect("mkdir");
+        let line = |at: &str, input: i64, output: i64| {
+            format!(
+                "{{\"timestamp\":\"{at}\",\"type\":\"event_msg\",\"payload\":{{\"type\":\"token_count\",\"info\":{{\"total_token_usage\":{{\"input_tokens\":{input},\"output_tokens\":{output},\"total_tokens\":{}}}}}}}}}\n",
+                input + output
+            )
+        };
+        let meta = |id: &str| {
+            format!("{{\"type\":\"session_meta\",\"payload\":{{\"id\":\"{id}\"}}}}\n{{\"type\":\"turn_context\",\"payload\":{{\"model\":\"gpt-5\"}}}}\n")
+        };
+        // Started in September: only what it used after October 1st counts.
+        fs::write(
+            archived.join("rollout-2025-09-30T20-00-00-carried.jsonl"),
+            meta("carried")
+                + &line("2025-09-30T21:00:00Z", 1_000, 100)
+                + &line("2025-10-01T09:00:00Z", 1_600, 150),
+        )
+        .expect("write");
+        fs::write(
+            archived.join("rollout-2025-10-05T10-00-00-fresh.jsonl"),
+            meta("fresh") + &line("2025-10-05T10:01:00Z", 300, 30),
+        )
+        .expect("write");
+        let prompts = dir.path().join("history.jsonl");
+        fs::write(
+            &prompts,
+            "{\"session_id\":\"fresh\",\"ts\":1759658400,\"text\":\"hi\"}\n\
+             {\"session_id\":\"gone\",\"ts\":1759658400,\"text\":\"hi\"}\n\
+             {\"session_id\":\"september\",\"ts\":1759000000,\"text\":\"hi\"}\n",
+        )
+        .expect("write");
+
+        let now = DateTime::parse_from_rfc3339("2025-10-09T12:00:00Z")
+            .expect("timestamp")
+            .with_timezone(&Utc);
+        let mut usage = MonthlyUsage::default();
+        let cfg = Config {
+            cache_dir: Some(dir.path().join("cache")),
+            ..Config::default()
+        };
+        let dirs = [archived];
+
+        // Capped: the newest rollout first, and no prompt history until all are read.
+        assert!(usage.backfill(&cfg, now, &dirs, &prompts, Some(1)));
+        assert_eq!(usage.sessions.keys().collect::<Vec<_>>(), vec!["fresh"]);
+        assert!(usage.untracked.is_empty());
+        assert!(usage.backfill(&cfg, now, &dirs, &prompts, Some(1)));
+        assert_eq!(usage.sessions["carried"].month_tokens(), (600, 50));
+        assert_eq!(usage.sessions["fresh"].month_tokens(), (300, 30));
+        assert_eq!(usage.sessions["fresh"].model.as_deref(), Some("gpt-5"));
+        assert_eq!(usage.untracked, BTreeSet::from(["gone".to_string()]));
+
+        // Within the interval the ledger is used as it is.
+        assert!(!usage.backfill(&cfg, now + Duration::minutes(1), &dirs, &prompts, None));
+    }
+}
@@ -0,0 +1,147 @@
+//! `hooks.lua` post-processing of rendered segments.
+//!
+//! When `<config dir>/hooks.lua` exists and defines a global `format(segments)` function, it is
+//! run through the `hooks.interpreter` (`lua` by default) before the line is rendered. Each
+//! segment is a table `{index, id, plugin, icon, value}`; the function may edit `icon`/`value`,
+//! reorder or drop entries and returns the new list (or edits it in place). Colors follow the
+//! segment's `index`, so hooks cannot invent new segments. Any failure leaves the segments
+//! untouched.
+
+use crate::config::{self, HooksConfig};
+use crate::plugins;
+use crate::segments::SegmentPiece;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+pub fn hooks_path() -> PathBuf {
+    config::config_dir().join("hooks.lua")
+}
+
+/// Applies `hooks.lua`, if present, to `segments`; skipped once `deadline` has passed.
+pub fn apply(
+    cfg: &HooksConfig,
+    segments: Vec<SegmentPiece>,
+    deadline: Instant,
+) -> Vec<SegmentPiece> {
+    let path = hooks_path();
+    let remaining = deadline.saturating_duration_since(Instant::now());
+    if !path.is_file() || remaining.is_zero() {
+        return segments;
+    }
+    let _span = crate::trace::span("hooks", "hooks.lua");
+    let mut command = Command::new(&cfg.interpreter);
+    command.arg("-");
+    let script = build_script(&path, &segments);
+    match plugins::run_with_timeout(
+        command,
+        script.as_bytes(),
+        Duration::from_millis(cfg.timeout_ms).min(remaining),
+    ) {
+        Some(output) => parse_output(&output, &segments).unwrap_or(segments),
+        None => segments,
+    }
+}
+
+fn build_script(hooks: &Path, segments: &[SegmentPiece]) -> String {
+    let mut script = String::from("local segments = {\n");
+    for (index, piece) in segments.iter().enumerate() {
+        let plugin = piece
+            .plugin
+            .as_deref()
+            .map(lua_string)
+            .unwrap_or_else(|| "nil".to_string());
+        let _ = writeln!(
+            script,
+            "  {{index = {}, id = {}, plugin = {}, icon = {}, value = {}}},",
+            index + 1,
+            lua_string(piece.id.as_str()),
+            plugin,
+            lua_string(&piece.icon),
+            lua_string(&piece.value),
+        );
+    }
+    let _ = writeln!(
+        script,
+        "}}\ndofile({})\n{}",
+        lua_string(&hooks.to_string_lossy()),
+        RUNNER
+    );
+    script
+}
+
+const RUNNER: &str = r#"if type(format) == "function" then
+  local result = format(segments)
+  if type(result) == "table" then segments = result end
+end
+local function clean(text) return (tostring(text or ""):gsub("[\t\r\n]", " ")) end
+for _, s in ipairs(segments) do
+  io.write(tostring(s.index), "\t", clean(s.icon), "\t", clean(s.value), "\n")
+end"#;
+
+/// Quotes `text` as a Lua string literal, escaping every byte outside `[A-Za-z0-9 ]`.
+fn lua_string(text: &str) -> String {
+    let mut quoted = String::from("\"");
+    for byte in text.bytes() {
+        if byte.is_ascii_alphanumeric() || byte == b' ' {
+            quoted.push(byte as char);
+        } else {
+            let _ = write!(quoted, "\\{byte:03}");
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Maps `index\ticon\tvalue` lines back onto the original pieces.
+fn parse_output(output: &str, segments: &[SegmentPiece]) -> Option<Vec<SegmentPiece>> {
+    output
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let mut fields = line.splitn(3, '\t');
+            let index: usize = fields.next()?.parse().ok()?;
+            let mut piece = segments.get(index.checked_sub(1)?)?.clone();
+            piece.icon = fields.next()?.to_string();
+            piece.value = fields.next()?.to_string();
+            Some(piece)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SegmentId;
+
+    fn piece(id: SegmentId, value: &str) -> SegmentPiece {
+        SegmentPiece {
+            id,
+            icon: String::new(),
+            value: value.to_string(),
+            icon_color: None,
+            text_color: None,
+            background: None,
+            bold: false,
+            plugin: None,
+            group: None,
+        }
+    }
+
+    #[test]
+    fn script_escapes_values_and_output_maps_back_to_pieces() {
+        let segments = vec![
+            piece(SegmentId::Model, "gpt-5 \"codex\""),
+            piece(SegmentId::Cwd, "~/work"),
+        ];
+        let script = build_script(Path::new("/tmp/hooks.lua"), &segments);
+        assert!(script.contains(r#"value = "gpt\0455 \034codex\034""#));
+
+        let edited = parse_output("2\t\tWORK\n1\tM\tgpt-5\n", &segments).expect("parsed");
+        assert_eq!(edited[0].id, SegmentId::Cwd);
+        assert_eq!(edited[0].value, "WORK");
+        assert_eq!(edited[1].icon, "M");
+        assert!(parse_output("3\t\tx\n", &segments).is_none());
+    }
+}
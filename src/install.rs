@@ -0,0 +1,132 @@
+use crate::cli::ShellKind;
+use anyhow::{Context, Result};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+const BEGIN_MARKER: &str = "# >>> codexline >>>";
+const END_MARKER: &str = "# <<< codexline <<<";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppendResult {
+    Appended,
+    AlreadyInstalled,
+}
+
+/// Snippet wiring codexline into the given shell or tmux.
+///
+/// Shells print the line above the prompt instead of embedding it into PS1, so ANSI colors are
+/// safe there (PowerShell gets `--format powershell` so powerline backgrounds survive); tmux does not interpret ANSI sequences in `#()` and gets `--format tmux` output.
+pub fn snippet(shell: ShellKind) -> String {
+    let body = match shell {
+        ShellKind::Bash => {
+            r#"__codexline_prompt() {
+  local line
+  line="$(codexline --color always 2>/dev/null)" || return 0
+  [ -n "$line" ] && printf '%s\n' "$line"
+}
+PROMPT_COMMAND="__codexline_prompt${PROMPT_COMMAND:+;$PROMPT_COMMAND}""#
+        }
+        ShellKind::Zsh => {
+            r#"autoload -Uz add-zsh-hook
+__codexline_precmd() {
+  local line
+  line="$(codexline --color always 2>/dev/null)" || return 0
+  [[ -n "$line" ]] && print -r -- "$line"
+}
+add-zsh-hook precmd __codexline_precmd"#
+        }
+        ShellKind::Fish => {
+            r#"function __codexline_prompt --on-event fish_prompt
+    set -l script (codexline --format fish 2>/dev/null); or return
+    test -n "$script"; and string join \n $script | source; and echo
+end"#
+        }
+        ShellKind::Pwsh => {
+            r#"[Console]::OutputEncoding = [System.Text.Encoding]::UTF8
+if (-not $global:__codexline_prompt) { $global:__codexline_prompt = $function:prompt }
+function global:prompt {
+    $line = (codexline --format powershell 2>$null) -join "`n"
+    if ($LASTEXITCODE -eq 0 -and $line) { Write-Host $line }
+    & $global:__codexline_prompt
+}"#
+        }
+        ShellKind::Tmux => {
+            r##"set -g status-interval 5
+set -g status-right-length 120
+set -g status-right '#(cd "#{pane_current_path}" && codexline --format tmux)'"##
+        }
+    };
+    format!("{BEGIN_MARKER}\n{body}\n{END_MARKER}\n")
+}
+
+pub fn rc_path(shell: ShellKind) -> PathBuf {
+    let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
+    match shell {
+        ShellKind::Bash => home.join(".bashrc"),
+        ShellKind::Zsh => home.join(".zshrc"),
+        ShellKind::Fish => home.join(".config").join("fish").join("config.fish"),
+        ShellKind::Tmux => home.join(".tmux.conf"),
+        ShellKind::Pwsh => powershell_profile(&home),
+    }
+}
+
+/// `$PROFILE` (CurrentUserCurrentHost) of PowerShell 7+.
+fn powershell_profile(home: &Path) -> PathBuf {
+    let dir = if cfg!(windows) {
+        dirs::document_dir()
+            .unwrap_or_else(|| home.join("Documents"))
+            .join("PowerShell")
+    } else {
+        home.join(".config").join("powershell")
+    };
+    dir.join("Microsoft.PowerShell_profile.ps1")
+}
+
+pub fn append_snippet(shell: ShellKind, path: &Path) -> Result<AppendResult> {
+    if is_installed(path) {
+        return Ok(AppendResult::AlreadyInstalled);
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create dir: {}", parent.display()))?;
+    }
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("failed to open: {}", path.display()))?;
+    write!(file, "\n{}", snippet(shell))
+        .with_context(|| format!("failed to write: {}", path.display()))?;
+    Ok(AppendResult::Appended)
+}
+
+fn is_installed(path: &Path) -> bool {
+    fs::read_to_string(path)
+        .map(|content| content.contains(BEGIN_MARKER))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn append_snippet_is_idempotent() {
+        let dir = TempDir::new().expect("temp dir");
+        let rc = dir.path().join(".zshrc");
+        fs::write(&rc, "export FOO=1\n").expect("write");
+
+        let first = append_snippet(ShellKind::Zsh, &rc).expect("append");
+        let second = append_snippet(ShellKind::Zsh, &rc).expect("append");
+
+        assert_eq!(first, AppendResult::Appended);
+        assert_eq!(second, AppendResult::AlreadyInstalled);
+        let content = fs::read_to_string(&rc).expect("read");
+        assert!(content.starts_with("export FOO=1\n"));
+        assert_eq!(content.matches(BEGIN_MARKER).count(), 1);
+    }
+}
@@ -1,6 +1,12 @@
+use codexline::ExitStatus;
+
 fn main() {
-    if let Err(err) = codexline::run() {
-        eprintln!("codexline: {err:#}");
-        std::process::exit(1);
-    }
+    let status = match codexline::run() {
+        Ok(status) => status,
+        Err(err) => {
+            eprintln!("codexline: {err:#}");
+            ExitStatus::for_error(&err)
+        }
+    };
+    std::process::exit(status.code());
 }
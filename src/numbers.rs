@@ -0,0 +1,241 @@
+//! Numbers in segment values, written as the `[format]` table asks: token compaction,
+//! decimals of percentages, and a locale's digit grouping and decimal mark.
+
+use crate::config::{DurationStyle, FormatConfig};
+use std::fmt::Write as _;
+
+/// Thousands separator and decimal mark by locale, tried as `lang_region` and then `lang`.
+const LOCALES: [(&str, &str, &str); 24] = [
+    ("c", "", "."),
+    ("posix", "", "."),
+    ("en", ",", "."),
+    ("ja", ",", "."),
+    ("ko", ",", "."),
+    ("zh", ",", "."),
+    ("de", ".", ","),
+    ("de_ch", "'", "."),
+    ("da", ".", ","),
+    ("es", ".", ","),
+    ("id", ".", ","),
+    ("it", ".", ","),
+    ("nl", ".", ","),
+    ("pt", ".", ","),
+    ("tr", ".", ","),
+    ("cs", "\u{a0}", ","),
+    ("fi", "\u{a0}", ","),
+    ("fr", "\u{202f}", ","),
+    ("fr_ch", "\u{202f}", "."),
+    ("nb", "\u{a0}", ","),
+    ("pl", "\u{a0}", ","),
+    ("ru", "\u{a0}", ","),
+    ("sv", "\u{a0}", ","),
+    ("uk", "\u{a0}", ","),
+];
+
+/// Separators for a locale name such as `de-DE`, `de_DE.UTF-8` or `fr`.
+pub fn locale_separators(locale: &str) -> Option<(&'static str, &'static str)> {
+    let name = locale
+        .split(['.', '@'])
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+        .replace('-', "_");
+    let language = name.split('_').next().unwrap_or_default();
+    [name.as_str(), language].iter().find_map(|candidate| {
+        LOCALES
+            .iter()
+            .find(|(known, _, _)| known == candidate)
+            .map(|(_, thousands, decimal)| (*thousands, *decimal))
+    })
+}
+
+#[derive(Debug, Clone)]
+pub struct NumberFormat {
+    compact_tokens: bool,
+    token_precision: usize,
+    percent_precision: usize,
+    thousands: String,
+    decimal: String,
+    durations: DurationStyle,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self::new(&FormatConfig::default())
+    }
+}
+
+impl NumberFormat {
+    /// An unknown locale (including one from the environment) keeps `1234.5`; explicit
+    /// separators win over the locale's.
+    pub fn new(format: &FormatConfig) -> Self {
+        let locale = match format.locale.as_str() {
+            "auto" => ["LC_ALL", "LC_NUMERIC", "LANG"]
+                .iter()
+                .filter_map(|key| std::env::var(key).ok())
+                .find(|value| !value.is_empty())
+                .unwrap_or_default(),
+            locale => locale.to_string(),
+        };
+        let (thousands, decimal) = locale_separators(&locale).unwrap_or(("", "."));
+        Self {
+            compact_tokens: format.compact_tokens,
+            token_precision: usize::from(format.token_precision),
+            percent_precision: usize::from(format.percent_precision),
+            thousands: format
+                .thousands_separator
+                .clone()
+                .unwrap_or_else(|| thousands.to_string()),
+            decimal: format
+                .decimal_separator
+                .clone()
+                .unwrap_or_else(|| decimal.to_string()),
+            durations: format.durations,
+        }
+    }
+
+    /// `45m`, `1h 12m`, `3d 4h` (or `1 hour 12 minutes` when verbose): the largest unit and,
+    /// unless it is zero, the next one down. Shared by every time-based value.
+    pub fn duration(&self, minutes: u64) -> String {
+        const UNITS: [(u64, &str, &str); 3] =
+            [(1440, "d", "day"), (60, "h", "hour"), (1, "m", "minute")];
+        let largest = UNITS
+            .iter()
+            .position(|(size, _, _)| minutes >= *size)
+            .unwrap_or(UNITS.len() - 1);
+        let mut rest = minutes;
+        let mut out = String::new();
+        for (index, (size, short, long)) in UNITS.iter().enumerate().skip(largest).take(2) {
+            let count = rest / size;
+            rest %= size;
+            if index > largest && count == 0 {
+                break;
+            }
+            if !out.is_empty() {
+                out.push(' ');
+            }
+            match self.durations {
+                DurationStyle::Compact => {
+                    let _ = write!(out, "{count}{short}");
+                }
+                DurationStyle::Verbose => {
+                    let plural = if count == 1 { "" } else { "s" };
+                    let _ = write!(out, "{count} {long}{plural}");
+                }
+            }
+        }
+        out
+    }
+
+    /// `999`, `1.2K`, `3.4M`; the whole grouped count when compaction is off.
+    pub fn tokens(&self, value: i64) -> String {
+        let abs = value.unsigned_abs();
+        if self.compact_tokens && abs >= 1_000_000 {
+            let millions = self.decimal(value as f64 / 1_000_000.0, self.token_precision);
+            format!("{millions}M")
+        } else if self.compact_tokens && abs >= 1_000 {
+            let thousands = self.decimal(value as f64 / 1_000.0, self.token_precision);
+            format!("{thousands}K")
+        } else {
+            self.decimal(value as f64, 0)
+        }
+    }
+
+    /// A percentage without the `%` sign, e.g. `62` or `62.5`.
+    pub fn percent(&self, value: f64) -> String {
+        self.decimal(value, self.percent_precision)
+    }
+
+    /// Whether percentages carry decimals, which whole-number sources can't provide.
+    pub fn fractional_percent(&self) -> bool {
+        self.percent_precision > 0
+    }
+
+    /// `value` rounded to `precision` decimals, with grouped thousands and the decimal mark.
+    pub fn decimal(&self, value: f64, precision: usize) -> String {
+        // Round half away from zero, as `f64::round` does, before formatting rounds to even.
+        let scale = 10f64.powi(precision as i32);
+        let value = (value * scale).round() / scale;
+        let text = format!("{value:.precision$}");
+        let (sign, digits) = match text.strip_prefix('-') {
+            Some(digits) => ("-", digits),
+            None => ("", text.as_str()),
+        };
+        let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+        let mut out = String::with_capacity(text.len() + whole.len() / 3 * self.thousands.len());
+        out.push_str(sign);
+        for (index, digit) in whole.chars().enumerate() {
+            if index > 0 && (whole.len() - index) % 3 == 0 {
+                out.push_str(&self.thousands);
+            }
+            out.push(digit);
+        }
+        if !fraction.is_empty() {
+            out.push_str(&self.decimal);
+            out.push_str(fraction);
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_follow_the_format_table() {
+        let plain = NumberFormat::default();
+        assert_eq!(plain.tokens(999), "999");
+        assert_eq!(plain.tokens(1_234_567), "1.2M");
+        assert_eq!(plain.decimal(1234.5, 1), "1234.5");
+        assert_eq!(plain.percent(62.5), "63");
+
+        let german = NumberFormat::new(&FormatConfig {
+            compact_tokens: false,
+            percent_precision: 1,
+            locale: "de_DE.UTF-8".to_string(),
+            ..FormatConfig::default()
+        });
+        assert_eq!(german.tokens(1_234_567), "1.234.567");
+        assert_eq!(german.tokens(-999), "-999");
+        assert_eq!(german.percent(62.46), "62,5");
+
+        let custom = NumberFormat::new(&FormatConfig {
+            token_precision: 2,
+            locale: "en-US".to_string(),
+            thousands_separator: Some(" ".to_string()),
+            ..FormatConfig::default()
+        });
+        assert_eq!(custom.tokens(1_234_567), "1.23M");
+        assert_eq!(custom.tokens(4_321_000_000), "4 321.00M");
+
+        assert_eq!(locale_separators("de-CH"), Some(("'", ".")));
+        assert_eq!(locale_separators("xx"), None);
+    }
+
+    #[test]
+    fn durations_show_the_two_largest_units() {
+        let compact = NumberFormat::default();
+        for (minutes, expected) in [
+            (0, "0m"),
+            (59, "59m"),
+            (60, "1h"),
+            (72, "1h 12m"),
+            (1439, "23h 59m"),
+            (1440, "1d"),
+            (1445, "1d"),
+            (4560, "3d 4h"),
+            (u64::MAX, "12810238940076077d 12h"),
+        ] {
+            assert_eq!(compact.duration(minutes), expected, "{minutes}");
+        }
+
+        let verbose = NumberFormat::new(&FormatConfig {
+            durations: DurationStyle::Verbose,
+            ..FormatConfig::default()
+        });
+        assert_eq!(verbose.duration(1), "1 minute");
+        assert_eq!(verbose.duration(72), "1 hour 12 minutes");
+        assert_eq!(verbose.duration(2880), "2 days");
+    }
+}
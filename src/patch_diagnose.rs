@@ -1,8 +1,8 @@
 use crate::collect::Collection;
-use crate::config::{config_path, Config};
-use chrono::Utc;
+use crate::config::{self, config_path, Config};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
 use serde::Serialize;
-use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -22,7 +22,7 @@ pub struct PatchCheck {
     pub detail: String,
 }
 
-#[derive(Debug, Clone, Copy, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub enum CheckStatus {
     Ok,
@@ -30,6 +30,14 @@ pub enum CheckStatus {
     Fail,
 }
 
+impl PatchDiagnosticReport {
+    pub fn has_failures(&self) -> bool {
+        self.checks
+            .iter()
+            .any(|check| matches!(check.status, CheckStatus::Fail))
+    }
+}
+
 pub fn run_patch_diagnostics(_cfg: &Config, collection: &Collection) -> PatchDiagnosticReport {
     let mut checks: Vec<PatchCheck> = Vec::new();
     let mut suggestions: Vec<String> = Vec::new();
@@ -78,7 +86,7 @@ pub fn run_patch_diagnostics(_cfg: &Config, collection: &Collection) -> PatchDia
         suggestions.push("Use codexline --inspect rollout to debug rollout parsing".to_string());
     }
 
-    match find_executable("codex") {
+    match crate::platform::find_executable("codex") {
         Some(path) => checks.push(ok("codex_binary", format!("{}", path.display()))),
         None => {
             checks.push(warn(
@@ -89,6 +97,45 @@ pub fn run_patch_diagnostics(_cfg: &Config, collection: &Collection) -> PatchDia
         }
     }
 
+    let snippet = format!("notify = [{:?}, \"--prime\"]", notify_program());
+    match notify_hook(&codex_config_path(collection)) {
+        NotifyHook::Installed(program) => {
+            match stale_hook(
+                &program,
+                resolve_program(&program),
+                std::env::current_exe().ok(),
+            ) {
+                None => checks.push(ok(
+                    "codex_notify",
+                    format!("Codex runs {program} --prime after each turn"),
+                )),
+                Some(problem) => {
+                    checks.push(warn("codex_notify", problem));
+                    suggestions.push(format!(
+                        "Point the notify hook in {} at this binary: {snippet}",
+                        codex_config_path(collection).display()
+                    ));
+                }
+            }
+        }
+        NotifyHook::Missing => {
+            checks.push(warn(
+                "codex_notify",
+                "Codex config has no notify hook for codexline".to_string(),
+            ));
+            suggestions.push(format!(
+                "Run codexline --patch --apply, or add `{snippet}` at the top of {}, to refresh \
+                 codexline after each Codex turn",
+                codex_config_path(collection).display()
+            ));
+        }
+        NotifyHook::Other(command) => checks.push(warn(
+            "codex_notify",
+            format!("notify already runs {command}; add `codexline --prime` to it by hand"),
+        )),
+        NotifyHook::Unreadable(reason) => checks.push(warn("codex_notify", reason)),
+    }
+
     let writable =
         collection.codex_home.is_dir() && is_dir_writable(&collection.codex_home).unwrap_or(false);
     if writable {
@@ -127,6 +174,228 @@ pub fn run_patch_diagnostics(_cfg: &Config, collection: &Collection) -> PatchDia
     }
 }
 
+/// A change `--patch --apply` makes to bring the checks above to OK.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatchAction {
+    CreateCodexHome(PathBuf),
+    CreateSessionsDir(PathBuf),
+    CreateConfig,
+    /// Prepends `notify = [program, "--prime"]` to the Codex config, which runs it with the
+    /// turn's JSON appended after every agent turn.
+    AddNotifyHook {
+        codex_config: PathBuf,
+        program: String,
+    },
+}
+
+impl PatchAction {
+    pub fn describe(&self) -> String {
+        match self {
+            PatchAction::CreateCodexHome(path) => format!("create {}", path.display()),
+            PatchAction::CreateSessionsDir(path) => format!("create {}", path.display()),
+            PatchAction::CreateConfig => format!("create {}", config_path().display()),
+            PatchAction::AddNotifyHook {
+                codex_config,
+                program,
+            } => format!(
+                "set notify = [{program:?}, \"--prime\"] in {}",
+                codex_config.display()
+            ),
+        }
+    }
+}
+
+/// What `--patch --apply` changed, with the backup taken of every file it modified.
+#[derive(Debug, Clone, Default)]
+pub struct PatchApplyReport {
+    pub applied: Vec<String>,
+    pub backups: Vec<PathBuf>,
+}
+
+/// The actions that would fix the failing or warning checks, in the order to apply them.
+/// `program` is how Codex should invoke codexline from its notify hook.
+pub fn plan_apply(collection: &Collection, program: &str) -> Vec<PatchAction> {
+    let mut actions = Vec::new();
+    if !collection.codex_home.exists() {
+        actions.push(PatchAction::CreateCodexHome(collection.codex_home.clone()));
+    }
+    if !collection.sessions_dir.exists() {
+        actions.push(PatchAction::CreateSessionsDir(
+            collection.sessions_dir.clone(),
+        ));
+    }
+    if !config_path().exists() {
+        actions.push(PatchAction::CreateConfig);
+    }
+    let codex_config = codex_config_path(collection);
+    if notify_hook(&codex_config) == NotifyHook::Missing {
+        actions.push(PatchAction::AddNotifyHook {
+            codex_config,
+            program: program.to_string(),
+        });
+    }
+    actions
+}
+
+/// Applies `actions`, copying every existing file to `backups_dir` (suffixed with `now`)
+/// before it is modified. Stops at the first failure; earlier actions stay applied.
+pub fn apply(
+    actions: &[PatchAction],
+    backups_dir: &Path,
+    now: DateTime<Utc>,
+) -> Result<PatchApplyReport> {
+    let mut report = PatchApplyReport::default();
+    for action in actions {
+        match action {
+            PatchAction::CreateCodexHome(path) | PatchAction::CreateSessionsDir(path) => {
+                fs::create_dir_all(path)
+                    .with_context(|| format!("failed to create dir: {}", path.display()))?;
+            }
+            PatchAction::CreateConfig => {
+                config::init()?;
+            }
+            PatchAction::AddNotifyHook {
+                codex_config,
+                program,
+            } => {
+                let existing = match fs::read_to_string(codex_config) {
+                    Ok(text) => {
+                        report.backups.push(backup(
+                            codex_config,
+                            "codex-config.toml",
+                            backups_dir,
+                            now,
+                        )?);
+                        text
+                    }
+                    Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+                    Err(err) => {
+                        return Err(err)
+                            .with_context(|| format!("failed to read {}", codex_config.display()))
+                    }
+                };
+                // Top-level keys must come before the first table, so the hook goes first.
+                let hook = toml::Value::Array(vec![
+                    toml::Value::String(program.clone()),
+                    toml::Value::String("--prime".to_string()),
+                ]);
+                let text = format!(
+                    "# Added by codexline --patch --apply: refresh codexline after each turn.\n\
+                     notify = {hook}\n\n{existing}"
+                );
+                fs::write(codex_config, text)
+                    .with_context(|| format!("failed to write {}", codex_config.display()))?;
+            }
+        }
+        report.applied.push(action.describe());
+    }
+    Ok(report)
+}
+
+fn backup(path: &Path, name: &str, backups_dir: &Path, now: DateTime<Utc>) -> Result<PathBuf> {
+    fs::create_dir_all(backups_dir)
+        .with_context(|| format!("failed to create dir: {}", backups_dir.display()))?;
+    let target = backups_dir.join(format!("{name}.{}", now.format("%Y%m%dT%H%M%SZ")));
+    fs::copy(path, &target).with_context(|| {
+        format!(
+            "failed to back up {} to {}",
+            path.display(),
+            target.display()
+        )
+    })?;
+    Ok(target)
+}
+
+fn codex_config_path(collection: &Collection) -> PathBuf {
+    collection.codex_home.join("config.toml")
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum NotifyHook {
+    /// `notify` runs codexline, through this program path or name.
+    Installed(String),
+    Missing,
+    /// `notify` runs another program; left alone rather than replaced.
+    Other(String),
+    Unreadable(String),
+}
+
+fn notify_hook(codex_config: &Path) -> NotifyHook {
+    let text = match fs::read_to_string(codex_config) {
+        Ok(text) => text,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return NotifyHook::Missing,
+        Err(err) => return NotifyHook::Unreadable(format!("cannot read Codex config: {err}")),
+    };
+    let table = match text.parse::<toml::Table>() {
+        Ok(table) => table,
+        Err(err) => {
+            return NotifyHook::Unreadable(format!(
+                "Codex config does not parse: {}",
+                err.message()
+            ))
+        }
+    };
+    let Some(notify) = table.get("notify") else {
+        return NotifyHook::Missing;
+    };
+    let program = notify
+        .as_array()
+        .and_then(|argv| argv.first())
+        .and_then(toml::Value::as_str)
+        .filter(|program| {
+            Path::new(program)
+                .file_stem()
+                .is_some_and(|stem| stem == "codexline")
+        });
+    match program {
+        Some(program) => NotifyHook::Installed(program.to_string()),
+        None => NotifyHook::Other(notify.to_string()),
+    }
+}
+
+/// How the notify hook should invoke codexline: the bare name when it is on `PATH`, which
+/// keeps working when an upgrade moves the binary, else this executable's path.
+pub fn notify_program() -> String {
+    if crate::platform::find_executable("codexline").is_some() {
+        return "codexline".to_string();
+    }
+    std::env::current_exe()
+        .map(|path| path.display().to_string())
+        .unwrap_or_else(|_| "codexline".to_string())
+}
+
+/// Where `program` runs from: a path as given, a bare name through `PATH`.
+fn resolve_program(program: &str) -> Option<PathBuf> {
+    let path = Path::new(program);
+    if path.components().count() > 1 {
+        return path.is_file().then(|| path.to_path_buf());
+    }
+    crate::platform::find_executable(program)
+}
+
+/// Why a hook running `program` (found at `resolved`) would not run `current`, if it would not.
+fn stale_hook(
+    program: &str,
+    resolved: Option<PathBuf>,
+    current: Option<PathBuf>,
+) -> Option<String> {
+    let Some(resolved) = resolved else {
+        return Some(format!(
+            "notify runs {program}, which does not exist or is not on PATH"
+        ));
+    };
+    let canonical = |path: &Path| fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let current = current?;
+    if canonical(&resolved) == canonical(&current) {
+        return None;
+    }
+    Some(format!(
+        "notify runs {}, but this codexline is {}",
+        resolved.display(),
+        current.display()
+    ))
+}
+
 pub fn render_text(report: &PatchDiagnosticReport) -> String {
     let mut lines: Vec<String> = Vec::new();
     lines.push("Codex Patch Compatibility Diagnostic".to_string());
@@ -177,24 +446,6 @@ fn fail(name: impl Into<String>, detail: String) -> PatchCheck {
     }
 }
 
-fn find_executable(bin: &str) -> Option<PathBuf> {
-    let path_env = env::var_os("PATH")?;
-    for dir in env::split_paths(&path_env) {
-        let candidate = dir.join(bin);
-        if candidate.exists() {
-            return Some(candidate);
-        }
-        #[cfg(windows)]
-        {
-            let candidate_exe = dir.join(format!("{}.exe", bin));
-            if candidate_exe.exists() {
-                return Some(candidate_exe);
-            }
-        }
-    }
-    None
-}
-
 fn is_dir_writable(path: &Path) -> Option<bool> {
     let probe = path.join(".codexline_write_probe");
     let result = fs::write(&probe, "probe").is_ok();
@@ -211,9 +462,75 @@ mod tests {
 
     #[test]
     fn diagnostics_mode_is_non_mutating() {
-        let cfg = Config::default();
+        let cache = tempfile::tempdir().expect("temp dir");
+        let cfg = Config {
+            cache_dir: Some(cache.path().to_path_buf()),
+            ..Config::default()
+        };
         let collection = collect::collect(&cfg).expect("collect");
         let report = run_patch_diagnostics(&cfg, &collection);
         assert_eq!(report.mode, "diagnostic_only");
     }
+
+    #[test]
+    fn apply_adds_notify_hook_with_backup() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let codex_config = dir.path().join("config.toml");
+        fs::write(&codex_config, "model = \"gpt-5-codex\"\n\n[tui]\nfoo = 1\n").expect("write");
+        assert_eq!(notify_hook(&codex_config), NotifyHook::Missing);
+
+        let backups = dir.path().join("backups");
+        let now = DateTime::from_timestamp(1_760_000_000, 0).expect("timestamp");
+        let action = PatchAction::AddNotifyHook {
+            codex_config: codex_config.clone(),
+            program: "codexline".to_string(),
+        };
+        let report = apply(&[action], &backups, now).expect("apply");
+
+        assert_eq!(
+            report.backups,
+            vec![backups.join("codex-config.toml.20251009T085320Z")]
+        );
+        assert_eq!(
+            fs::read_to_string(&report.backups[0]).expect("backup"),
+            "model = \"gpt-5-codex\"\n\n[tui]\nfoo = 1\n"
+        );
+        assert_eq!(
+            notify_hook(&codex_config),
+            NotifyHook::Installed("codexline".to_string())
+        );
+        let table: toml::Table = fs::read_to_string(&codex_config)
+            .expect("read")
+            .parse()
+            .expect("parse");
+        assert_eq!(table["model"].as_str(), Some("gpt-5-codex"));
+        assert_eq!(table["tui"]["foo"].as_integer(), Some(1));
+
+        fs::write(&codex_config, "notify = [\"notify-send\"]\n").expect("write");
+        assert!(matches!(notify_hook(&codex_config), NotifyHook::Other(_)));
+    }
+
+    #[test]
+    fn stale_notify_hooks_are_reported() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let installed = dir.path().join("codexline");
+        let other = dir.path().join("old-codexline");
+        fs::write(&installed, "").expect("write");
+        fs::write(&other, "").expect("write");
+
+        assert_eq!(
+            stale_hook(
+                "codexline",
+                Some(installed.clone()),
+                Some(installed.clone())
+            ),
+            None
+        );
+        assert!(stale_hook("/gone/codexline", None, Some(installed.clone()))
+            .expect("missing")
+            .contains("does not exist"));
+        assert!(stale_hook("codexline", Some(other), Some(installed))
+            .expect("stale")
+            .contains("but this codexline is"));
+    }
 }
@@ -0,0 +1,217 @@
+//! The small regex subset accepted by pattern options such as `git.ticket_regex`: literals,
+//! `.`, classes (`[A-Z0-9]`, `[^/]`), `\d` `\w` `\s` and their negations, the quantifiers
+//! `*` `+` `?` `{n}` `{n,}` `{n,m}`, and the anchors `^` and `$`. Groups and alternation are
+//! rejected rather than misread.
+
+use anyhow::{bail, Result};
+
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    items: Vec<(Atom, usize, usize)>,
+    start_anchored: bool,
+    end_anchored: bool,
+}
+
+#[derive(Debug, Clone)]
+enum Atom {
+    Any,
+    Char(char),
+    Class {
+        ranges: Vec<(char, char)>,
+        negated: bool,
+    },
+}
+
+impl Atom {
+    fn matches(&self, c: char) -> bool {
+        match self {
+            Atom::Any => true,
+            Atom::Char(expected) => c == *expected,
+            Atom::Class { ranges, negated } => {
+                ranges.iter().any(|(lo, hi)| (*lo..=*hi).contains(&c)) != *negated
+            }
+        }
+    }
+}
+
+const DIGIT: &[(char, char)] = &[('0', '9')];
+const WORD: &[(char, char)] = &[('a', 'z'), ('A', 'Z'), ('0', '9'), ('_', '_')];
+const SPACE: &[(char, char)] = &[(' ', ' '), ('\t', '\r')];
+
+/// Ranges for `\d`, `\w` and `\s` (negated for the upper-case forms), or `None` for an
+/// escaped literal.
+fn shorthand(c: char) -> Option<(&'static [(char, char)], bool)> {
+    match c {
+        'd' => Some((DIGIT, false)),
+        'w' => Some((WORD, false)),
+        's' => Some((SPACE, false)),
+        'D' => Some((DIGIT, true)),
+        'W' => Some((WORD, true)),
+        'S' => Some((SPACE, true)),
+        _ => None,
+    }
+}
+
+impl Pattern {
+    pub fn new(text: &str) -> Result<Self> {
+        let mut chars = text.chars().peekable();
+        let mut pattern = Pattern {
+            items: Vec::new(),
+            start_anchored: chars.next_if_eq(&'^').is_some(),
+            end_anchored: false,
+        };
+        while let Some(c) = chars.next() {
+            let atom = match c {
+                '$' if chars.peek().is_none() => {
+                    pattern.end_anchored = true;
+                    break;
+                }
+                '.' => Atom::Any,
+                '\\' => {
+                    let Some(escaped) = chars.next() else {
+                        bail!("pattern ends with a lone backslash");
+                    };
+                    match shorthand(escaped) {
+                        Some((ranges, negated)) => Atom::Class {
+                            ranges: ranges.to_vec(),
+                            negated,
+                        },
+                        None => Atom::Char(escaped),
+                    }
+                }
+                '[' => {
+                    let negated = chars.next_if_eq(&'^').is_some();
+                    let mut ranges = Vec::new();
+                    loop {
+                        let Some(c) = chars.next() else {
+                            bail!("unclosed [ in pattern");
+                        };
+                        let lo = match c {
+                            ']' if !ranges.is_empty() => break,
+                            '\\' => {
+                                let Some(escaped) = chars.next() else {
+                                    bail!("unclosed [ in pattern");
+                                };
+                                if let Some((class, false)) = shorthand(escaped) {
+                                    ranges.extend_from_slice(class);
+                                    continue;
+                                }
+                                escaped
+                            }
+                            c => c,
+                        };
+                        let mut ahead = chars.clone();
+                        match (ahead.next(), ahead.next()) {
+                            (Some('-'), Some(hi)) if hi != ']' => {
+                                if hi < lo {
+                                    bail!("invalid range {lo}-{hi} in pattern");
+                                }
+                                chars.nth(1);
+                                ranges.push((lo, hi));
+                            }
+                            _ => ranges.push((lo, lo)),
+                        }
+                    }
+                    Atom::Class { ranges, negated }
+                }
+                '(' | ')' | '|' => bail!("groups and alternation are not supported in patterns"),
+                '*' | '+' | '?' | '{' => bail!("quantifier {c:?} has nothing to repeat"),
+                c => Atom::Char(c),
+            };
+            let (min, max) = match chars.peek() {
+                Some('*') => (0, usize::MAX),
+                Some('+') => (1, usize::MAX),
+                Some('?') => (0, 1),
+                Some('{') => {
+                    let mut counts = String::new();
+                    chars.next();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => counts.push(c),
+                            None => bail!("unclosed {{ in pattern"),
+                        }
+                    }
+                    let parse = |text: &str| {
+                        text.parse::<usize>()
+                            .map_err(|_| anyhow::anyhow!("invalid repetition {{{counts}}}"))
+                    };
+                    let (min, max) = match counts.split_once(',') {
+                        None => (parse(&counts)?, parse(&counts)?),
+                        Some((min, "")) => (parse(min)?, usize::MAX),
+                        Some((min, max)) => (parse(min)?, parse(max)?),
+                    };
+                    if max < min {
+                        bail!("invalid repetition {{{counts}}}");
+                    }
+                    pattern.items.push((atom, min, max));
+                    continue;
+                }
+                _ => (1, 1),
+            };
+            if (min, max) != (1, 1) {
+                chars.next();
+            }
+            pattern.items.push((atom, min, max));
+        }
+        Ok(pattern)
+    }
+
+    /// The leftmost match in `text`, each quantifier as long as the rest still matches.
+    pub fn find<'a>(&self, text: &'a str) -> Option<&'a str> {
+        let chars: Vec<(usize, char)> = text.char_indices().collect();
+        let byte = |index: usize| chars.get(index).map_or(text.len(), |(at, _)| *at);
+        let starts = if self.start_anchored {
+            0..1
+        } else {
+            0..chars.len() + 1
+        };
+        starts.into_iter().find_map(|start| {
+            let end = self.match_at(&chars, 0, start)?;
+            Some(&text[byte(start)..byte(end)])
+        })
+    }
+
+    fn match_at(&self, chars: &[(usize, char)], item: usize, pos: usize) -> Option<usize> {
+        let Some((atom, min, max)) = self.items.get(item) else {
+            return (!self.end_anchored || pos == chars.len()).then_some(pos);
+        };
+        let available = chars[pos..]
+            .iter()
+            .take(*max)
+            .take_while(|(_, c)| atom.matches(*c))
+            .count();
+        (*min..=available)
+            .rev()
+            .find_map(|count| self.match_at(chars, item + 1, pos + count))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn patterns_find_the_leftmost_match() {
+        let find = |pattern: &str, text: &str| {
+            Pattern::new(pattern)
+                .expect("pattern")
+                .find(text)
+                .map(str::to_string)
+        };
+        assert_eq!(
+            find(r"[A-Z][A-Z0-9]+-\d+", "feature/JIRA-1234-login").as_deref(),
+            Some("JIRA-1234")
+        );
+        assert_eq!(find(r"\d{2,3}", "a1b2345").as_deref(), Some("234"));
+        assert_eq!(find(r"^[^/]+", "fix/é-thing").as_deref(), Some("fix"));
+        assert_eq!(find(r"-\w*$", "a-b-cd").as_deref(), Some("-cd"));
+        assert_eq!(find(r"x?y", "zzy").as_deref(), Some("y"));
+        assert_eq!(find(r"[-.]", "a.b").as_deref(), Some("."));
+        assert_eq!(find(r"^\d", "a1"), None);
+
+        for invalid in ["(a|b)", "[a-", "a{2", r"a\", "*a", "[z-a]", "a{3,1}"] {
+            assert!(Pattern::new(invalid).is_err(), "{invalid}");
+        }
+    }
+}
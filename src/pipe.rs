@@ -0,0 +1,107 @@
+use anyhow::{Context, Result};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// `--output-pipe` target for watch mode.
+///
+/// A FIFO is kept open and gets one line per update; when the reader goes away the next update
+/// blocks until a new reader opens the pipe and then receives the latest line. A regular file is
+/// rewritten so it always holds only the latest line.
+pub struct OutputPipe {
+    path: PathBuf,
+    fifo: bool,
+    writer: Option<File>,
+}
+
+impl OutputPipe {
+    /// Opens `path`, creating a FIFO (via `mkfifo`) when it does not exist on Unix.
+    pub fn open(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            create_fifo(path)?;
+        }
+        Ok(Self {
+            path: path.to_path_buf(),
+            fifo: is_fifo(path),
+            writer: None,
+        })
+    }
+
+    pub fn publish(&mut self, line: &str) -> Result<()> {
+        if !self.fifo {
+            return fs::write(&self.path, format!("{line}\n"))
+                .with_context(|| format!("failed to write: {}", self.path.display()));
+        }
+
+        // Retry once: a reader that disconnected since the last update shows up as BrokenPipe.
+        for _ in 0..2 {
+            if self.writer.is_none() {
+                let file = OpenOptions::new()
+                    .write(true)
+                    .open(&self.path)
+                    .with_context(|| format!("failed to open pipe: {}", self.path.display()))?;
+                self.writer = Some(file);
+            }
+            let Some(writer) = self.writer.as_mut() else {
+                continue;
+            };
+            match writeln!(writer, "{line}").and_then(|_| writer.flush()) {
+                Ok(()) => return Ok(()),
+                Err(err) if err.kind() == std::io::ErrorKind::BrokenPipe => self.writer = None,
+                Err(err) => return Err(err.into()),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+fn create_fifo(path: &Path) -> Result<()> {
+    let status = std::process::Command::new("mkfifo")
+        .arg(path)
+        .status()
+        .context("failed to run mkfifo")?;
+    if !status.success() {
+        anyhow::bail!("mkfifo failed for {}", path.display());
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn create_fifo(path: &Path) -> Result<()> {
+    // No named pipes at a filesystem path here; fall back to a regular file.
+    File::create(path).with_context(|| format!("failed to create: {}", path.display()))?;
+    Ok(())
+}
+
+#[cfg(unix)]
+fn is_fifo(path: &Path) -> bool {
+    use std::os::unix::fs::FileTypeExt;
+    fs::metadata(path)
+        .map(|meta| meta.file_type().is_fifo())
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_fifo(_path: &Path) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn regular_file_holds_only_latest_line() {
+        let dir = TempDir::new().expect("temp dir");
+        let path = dir.path().join("status.txt");
+        fs::write(&path, "").expect("write");
+
+        let mut pipe = OutputPipe::open(&path).expect("open");
+        pipe.publish("first").expect("publish");
+        pipe.publish("second").expect("publish");
+
+        assert_eq!(fs::read_to_string(&path).expect("read"), "second\n");
+    }
+}
@@ -0,0 +1,113 @@
+//! Windows path and executable lookup rules, written as plain string handling so they are
+//! testable on every platform.
+
+use std::path::{Path, PathBuf};
+
+/// Extensions `cmd.exe` tries for a bare command name, from `PATHEXT`.
+const DEFAULT_PATHEXT: &str = ".COM;.EXE;.BAT;.CMD";
+
+/// `path` for display: verbatim prefixes from canonicalized paths are removed
+/// (`\\?\C:\src` -> `C:\src`, `\\?\UNC\server\share` -> `\\server\share`).
+pub fn display_path(path: &Path) -> String {
+    let text = path.to_string_lossy();
+    if let Some(unc) = text.strip_prefix(r"\\?\UNC\") {
+        format!(r"\\{unc}")
+    } else if let Some(rest) = text.strip_prefix(r"\\?\") {
+        rest.to_string()
+    } else {
+        text.into_owned()
+    }
+}
+
+/// File names to look for when resolving `bin` on `PATH`: the name itself, then (on Windows)
+/// the name with each `PATHEXT` extension unless it already has one of them.
+pub fn executable_names(bin: &str, pathext: Option<&str>) -> Vec<String> {
+    let mut names = vec![bin.to_string()];
+    let Some(pathext) = pathext else {
+        return names;
+    };
+    let extensions: Vec<&str> = pathext
+        .split(';')
+        .map(str::trim)
+        .filter(|ext| ext.starts_with('.') && ext.len() > 1)
+        .collect();
+    let lower = bin.to_ascii_lowercase();
+    if extensions
+        .iter()
+        .any(|ext| lower.ends_with(&ext.to_ascii_lowercase()))
+    {
+        return names;
+    }
+    names.extend(
+        extensions
+            .iter()
+            .map(|ext| format!("{bin}{}", ext.to_ascii_lowercase())),
+    );
+    names
+}
+
+/// `PATHEXT` on Windows (with cmd's default when unset); `None` elsewhere.
+pub fn pathext() -> Option<String> {
+    if cfg!(windows) {
+        Some(std::env::var("PATHEXT").unwrap_or_else(|_| DEFAULT_PATHEXT.to_string()))
+    } else {
+        None
+    }
+}
+
+/// True when `path` has one of the `PATHEXT` extensions.
+#[cfg_attr(unix, allow(dead_code))] // unix uses the executable bit instead
+pub fn has_executable_extension(path: &Path, pathext: &str) -> bool {
+    let Some(ext) = path.extension().and_then(|ext| ext.to_str()) else {
+        return false;
+    };
+    pathext
+        .split(';')
+        .filter_map(|candidate| candidate.trim().strip_prefix('.'))
+        .any(|candidate| candidate.eq_ignore_ascii_case(ext))
+}
+
+/// First file named `bin` (or `bin` plus a `PATHEXT` extension on Windows) on `PATH`.
+pub fn find_executable(bin: &str) -> Option<PathBuf> {
+    let path_env = std::env::var_os("PATH")?;
+    let names = executable_names(bin, pathext().as_deref());
+    std::env::split_paths(&path_env)
+        .flat_map(|dir| names.iter().map(move |name| dir.join(name)))
+        .find(|candidate| candidate.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn windows_paths_and_pathext_lookup() {
+        assert_eq!(display_path(Path::new(r"\\?\C:\src\repo")), r"C:\src\repo");
+        assert_eq!(
+            display_path(Path::new(r"\\?\UNC\server\share\repo")),
+            r"\\server\share\repo"
+        );
+        assert_eq!(
+            display_path(Path::new(r"\\server\share")),
+            r"\\server\share"
+        );
+
+        assert_eq!(executable_names("codex", None), vec!["codex"]);
+        assert_eq!(
+            executable_names("codex", Some(".EXE;.CMD")),
+            vec!["codex", "codex.exe", "codex.cmd"]
+        );
+        assert_eq!(
+            executable_names("codex.CMD", Some(".EXE;.CMD")),
+            vec!["codex.CMD"]
+        );
+        assert!(has_executable_extension(
+            Path::new("codexline-segment-todo.Cmd"),
+            DEFAULT_PATHEXT
+        ));
+        assert!(!has_executable_extension(
+            Path::new("codexline-segment-todo.ps1"),
+            DEFAULT_PATHEXT
+        ));
+    }
+}
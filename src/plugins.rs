@@ -0,0 +1,259 @@
+//! External segment providers.
+//!
+//! A provider is any executable named `codexline-segment-<name>` on `PATH` (when
+//! `plugins.discover` is on) or listed under `[plugins.commands]`. It receives the
+//! [`StatusContext`] as JSON on stdin and prints one JSON object shaped like a segment:
+//! `{"value": "...", "icon": "...", "text_color": "green", "icon_color": ..., "background": ...,
+//! "bold": false}`. Only `value` is required; an empty value, a non-zero exit, invalid JSON or
+//! exceeding `plugins.timeout_ms` hides the provider for that render.
+
+use crate::config::{Color, PluginsConfig};
+use crate::context::{PluginSegment, StatusContext};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const PREFIX: &str = "codexline-segment-";
+
+#[derive(Deserialize)]
+struct PluginOutput {
+    value: String,
+    #[serde(default)]
+    icon: String,
+    #[serde(default)]
+    icon_color: Option<Color>,
+    #[serde(default)]
+    text_color: Option<Color>,
+    #[serde(default)]
+    background: Option<Color>,
+    #[serde(default)]
+    bold: bool,
+}
+
+/// Runs every provider, ordered by name; configured commands win over discovered ones.
+/// Providers still pending at `deadline` are skipped, which the returned flag reports as
+/// `false`.
+pub fn run_all(
+    cfg: &PluginsConfig,
+    ctx: &StatusContext,
+    deadline: Instant,
+) -> (Vec<PluginSegment>, bool) {
+    let Ok(input) = serde_json::to_vec(ctx) else {
+        return (Vec::new(), true);
+    };
+    let mut complete = true;
+    let mut segments = Vec::new();
+    for (name, path) in providers(cfg) {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            complete = false;
+            break;
+        }
+        let timeout = Duration::from_millis(cfg.timeout_ms).min(remaining);
+        segments.extend(run_plugin(&name, &path, &input, timeout));
+    }
+    (segments, complete)
+}
+
+fn providers(cfg: &PluginsConfig) -> BTreeMap<String, PathBuf> {
+    let mut found = BTreeMap::new();
+    if cfg.discover {
+        if let Some(path) = std::env::var_os("PATH") {
+            for dir in std::env::split_paths(&path) {
+                for (name, file) in discover_in(&dir) {
+                    found.entry(name).or_insert(file);
+                }
+            }
+        }
+    }
+    for (name, command) in &cfg.commands {
+        found.insert(name.clone(), command.clone());
+    }
+    found
+}
+
+fn discover_in(dir: &Path) -> Vec<(String, PathBuf)> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let path = entry.path();
+            let name = path
+                .file_stem()?
+                .to_str()?
+                .strip_prefix(PREFIX)?
+                .to_string();
+            (!name.is_empty() && is_executable(&path)).then_some((name, path))
+        })
+        .collect()
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path)
+        .map(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    let pathext = crate::platform::pathext().unwrap_or_default();
+    path.is_file() && crate::platform::has_executable_extension(path, &pathext)
+}
+
+fn run_plugin(name: &str, path: &Path, input: &[u8], timeout: Duration) -> Option<PluginSegment> {
+    let stdout = run_with_timeout(Command::new(path), input, timeout)?;
+    let output: PluginOutput = serde_json::from_str(stdout.trim()).ok()?;
+    if output.value.trim().is_empty() {
+        return None;
+    }
+    Some(PluginSegment {
+        name: name.to_string(),
+        icon: output.icon,
+        value: output.value,
+        icon_color: output.icon_color,
+        text_color: output.text_color,
+        background: output.background,
+        bold: output.bold,
+    })
+}
+
+/// Runs `command` with `input` on stdin and returns its stdout, or `None` when it cannot be
+/// started, exits non-zero or is still running after `timeout` (it is killed then).
+///
+/// Stdin is written and stdout drained on helper threads, so a child that never reads its
+/// input, or writes more than a pipe buffer, still ends at `timeout`.
+pub(crate) fn run_with_timeout(
+    mut command: Command,
+    input: &[u8],
+    timeout: Duration,
+) -> Option<String> {
+    let deadline = Instant::now() + timeout;
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    // A child that ignores stdin must not block us, so write errors are ignored.
+    if let Some(mut stdin) = child.stdin.take() {
+        let input = input.to_vec();
+        thread::spawn(move || {
+            let _ = stdin.write_all(&input);
+        });
+    }
+    let (sender, stdout) = mpsc::channel();
+    if let Some(mut pipe) = child.stdout.take() {
+        thread::spawn(move || {
+            let mut text = String::new();
+            let _ = sender.send(pipe.read_to_string(&mut text).map(|_| text));
+        });
+    }
+
+    let status = loop {
+        match child.try_wait().ok()? {
+            Some(status) => break status,
+            None if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
+            None => thread::sleep(Duration::from_millis(5)),
+        }
+    };
+    if !status.success() {
+        return None;
+    }
+    // A background process the child left behind can hold stdout open; it gets until the
+    // deadline to close it.
+    stdout
+        .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+        .ok()?
+        .ok()
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::config::NamedColor;
+    use std::os::unix::fs::PermissionsExt;
+    use tempfile::TempDir;
+
+    #[test]
+    fn run_plugin_reads_segment_json_from_stdout() {
+        let dir = TempDir::new().expect("temp dir");
+        let script = dir.path().join("codexline-segment-echo");
+        std::fs::write(
+            &script,
+            "#!/bin/sh\ncwd=$(sed 's/.*\"cwd\":\"\\([^\"]*\\)\".*/\\1/')\n\
+             printf '{\"value\":\"%s\",\"text_color\":\"green\"}' \"$cwd\"\n",
+        )
+        .expect("write");
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).expect("chmod");
+
+        assert_eq!(
+            discover_in(dir.path()),
+            vec![("echo".to_string(), script.clone())]
+        );
+        let segment = run_plugin(
+            "echo",
+            &script,
+            br#"{"cwd":"/work/app"}"#,
+            Duration::from_secs(5),
+        )
+        .expect("segment");
+        assert_eq!(segment.value, "/work/app");
+        assert_eq!(segment.text_color, Some(Color::Named(NamedColor::Green)));
+    }
+
+    #[test]
+    fn run_all_skips_providers_past_the_deadline() {
+        let cfg = PluginsConfig {
+            discover: false,
+            timeout_ms: 500,
+            commands: BTreeMap::from([("slow".to_string(), PathBuf::from("/bin/sleep"))]),
+        };
+        let ctx = StatusContext::new(chrono::Utc::now(), PathBuf::from("/work/app"));
+        let (segments, complete) = run_all(&cfg, &ctx, Instant::now());
+        assert!(segments.is_empty());
+        assert!(!complete);
+    }
+
+    #[test]
+    fn run_with_timeout_survives_large_output_and_unread_input() {
+        let dir = TempDir::new().expect("temp dir");
+        let script = dir.path().join("codexline-segment-big");
+        std::fs::write(
+            &script,
+            "#!/bin/sh
+printf '{\"value\":\"'
+head -c 100000 /dev/zero | tr '\\0' x
+printf '\"}'
+",
+        )
+        .expect("write");
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).expect("chmod");
+
+        // More input than a pipe buffer, which the provider never reads.
+        let input = vec![b' '; 256 * 1024];
+        let segment = run_plugin("big", &script, &input, Duration::from_secs(5)).expect("segment");
+        assert_eq!(segment.value.len(), 100_000);
+
+        let started = Instant::now();
+        let mut sleeper = Command::new("/bin/sleep");
+        sleeper.arg("5");
+        assert_eq!(
+            run_with_timeout(sleeper, &input, Duration::from_millis(100)),
+            None
+        );
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+}
@@ -0,0 +1,94 @@
+//! Turns token counts into spend. Prices are USD per million tokens: `[pricing.<model>]` in
+//! the config first, then the built-in table of public API prices. Codex counts cached input
+//! as input, so spend is an upper bound for sessions with long cached prompts.
+
+use crate::config::{Config, ModelPrice};
+use crate::context::BudgetStatus;
+use crate::history::MonthlyUsage;
+
+const fn price(input: f64, output: f64) -> ModelPrice {
+    ModelPrice { input, output }
+}
+
+/// Matched by the longest prefix of the model name, so dated snapshots
+/// (`gpt-5-mini-2025-08-07`) find their family.
+const BUILTIN: [(&str, ModelPrice); 14] = [
+    ("gpt-5", price(1.25, 10.0)),
+    ("gpt-5-codex", price(1.25, 10.0)),
+    ("gpt-5-mini", price(0.25, 2.0)),
+    ("gpt-5-nano", price(0.05, 0.4)),
+    ("gpt-4.1", price(2.0, 8.0)),
+    ("gpt-4.1-mini", price(0.4, 1.6)),
+    ("gpt-4.1-nano", price(0.1, 0.4)),
+    ("gpt-4o", price(2.5, 10.0)),
+    ("gpt-4o-mini", price(0.15, 0.6)),
+    ("o3", price(2.0, 8.0)),
+    ("o3-mini", price(1.1, 4.4)),
+    ("o4-mini", price(1.1, 4.4)),
+    ("codex-mini", price(1.5, 6.0)),
+    ("codex-mini-latest", price(1.5, 6.0)),
+];
+
+pub fn price_for(cfg: &Config, model: &str) -> Option<ModelPrice> {
+    if let Some(price) = cfg.pricing.get(model) {
+        return Some(*price);
+    }
+    BUILTIN
+        .iter()
+        .filter(|(prefix, _)| model.starts_with(prefix))
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map(|(_, price)| *price)
+}
+
+pub fn cost_usd(price: ModelPrice, input_tokens: i64, output_tokens: i64) -> f64 {
+    (input_tokens.max(0) as f64 * price.input + output_tokens.max(0) as f64 * price.output)
+        / 1_000_000.0
+}
+
+/// Spend recorded in `usage` against `monthly_usd`. Sessions on models without a price, and
+/// sessions without token data ([`MonthlyUsage::untracked`]), are counted in
+/// `unpriced_sessions` instead.
+pub fn budget_status(cfg: &Config, usage: &MonthlyUsage, monthly_usd: f64) -> BudgetStatus {
+    let mut status = BudgetStatus {
+        spent_usd: 0.0,
+        monthly_usd,
+        unpriced_sessions: usage.untracked.len() as u32,
+    };
+    for session in usage.sessions.values() {
+        let (input, output) = session.month_tokens();
+        if input == 0 && output == 0 {
+            continue;
+        }
+        match session
+            .model
+            .as_deref()
+            .and_then(|model| price_for(cfg, model))
+        {
+            Some(price) => status.spent_usd += cost_usd(price, input, output),
+            None => status.unpriced_sessions += 1,
+        }
+    }
+    status
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prices_match_the_longest_prefix_and_config_wins() {
+        let mut cfg = Config::default();
+        assert_eq!(
+            price_for(&cfg, "gpt-5-mini-2025-08-07"),
+            Some(price(0.25, 2.0))
+        );
+        assert_eq!(price_for(&cfg, "gpt-5-codex"), Some(price(1.25, 10.0)));
+        assert_eq!(price_for(&cfg, "o3-mini"), Some(price(1.1, 4.4)));
+        assert_eq!(price_for(&cfg, "openai"), None);
+
+        cfg.pricing
+            .insert("gpt-5-codex".to_string(), price(1.0, 5.0));
+        assert_eq!(price_for(&cfg, "gpt-5-codex"), Some(price(1.0, 5.0)));
+        assert!((cost_usd(price(1.0, 5.0), 2_000_000, 100_000) - 2.5).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,94 @@
+//! `--prime`: warm the on-disk caches from a shell hook, in the background.
+//!
+//! Collection fills the git root, rollout offset and `gh` pull request caches as a side
+//! effect, and the monthly budget ledger is backfilled from every rollout, so a prime run between prompts leaves the next synchronous render with nothing
+//! slow to do. Priming prints nothing and never fails the hook.
+
+use crate::collect::{self, CollectOptions};
+use crate::config::{self, Config};
+use crate::exit::ExitStatus;
+use crate::history;
+use std::fs::{self, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Priming runs off the prompt's critical path, so collectors get this long instead of the
+/// render deadlines.
+const PRIME_TIMEOUT_MS: u64 = 30_000;
+
+/// A lock older than this belongs to a prime run that died.
+const STALE_LOCK: Duration = Duration::from_secs(60);
+
+pub fn run(cfg: &Config, options: CollectOptions) -> ExitStatus {
+    let Some(_lock) = PrimeLock::acquire(config::cache_dir().join("prime.lock")) else {
+        return ExitStatus::Success;
+    };
+    let mut cfg = cfg.clone();
+    cfg.performance.max_runtime_ms = PRIME_TIMEOUT_MS;
+    cfg.collect.budget_ms = PRIME_TIMEOUT_MS;
+    cfg.collect.git_timeout_ms = PRIME_TIMEOUT_MS;
+    cfg.collect.rollout_timeout_ms = PRIME_TIMEOUT_MS;
+    cfg.collect.github_timeout_ms = PRIME_TIMEOUT_MS;
+    cfg.collect.diffstat_timeout_ms = PRIME_TIMEOUT_MS;
+    if let Ok(collection) = collect::collect_with(&cfg, options) {
+        // The render path reads only a few rollouts into the monthly ledger; finish here.
+        if collection.context.budget.is_some() && !options.demo {
+            history::backfill_month(&cfg, collection.context.now, &collection.sessions_dir);
+        }
+    }
+    ExitStatus::Success
+}
+
+/// Keeps prompts fired in quick succession from stacking up prime runs.
+struct PrimeLock {
+    path: PathBuf,
+}
+
+impl PrimeLock {
+    fn acquire(path: PathBuf) -> Option<Self> {
+        if let Some(parent) = path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        if is_stale(&path) {
+            let _ = fs::remove_file(&path);
+        }
+        OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .ok()
+            .map(|_| Self { path })
+    }
+}
+
+impl Drop for PrimeLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+fn is_stale(path: &Path) -> bool {
+    fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_some_and(|age| age > STALE_LOCK)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn prime_lock_is_exclusive_until_dropped() {
+        let dir = TempDir::new().expect("temp dir");
+        let path = dir.path().join("cache").join("prime.lock");
+
+        let lock = PrimeLock::acquire(path.clone()).expect("first lock");
+        assert!(PrimeLock::acquire(path.clone()).is_none());
+        drop(lock);
+        assert!(!path.exists());
+        assert!(PrimeLock::acquire(path).is_some());
+    }
+}
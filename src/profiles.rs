@@ -1,4 +1,8 @@
+#[cfg(feature = "tui")]
+use crate::config::Profile;
 use crate::config::{self, Config, SegmentId};
+#[cfg(feature = "tui")]
+use anyhow::{bail, Result};
 use serde_json::Value;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -7,7 +11,7 @@ pub enum Enhancement {
     Observability,
 }
 
-const QUICK_ORDER: [SegmentId; 8] = [
+const QUICK_ORDER: [SegmentId; 17] = [
     SegmentId::Model,
     SegmentId::Cwd,
     SegmentId::Git,
@@ -16,6 +20,15 @@ const QUICK_ORDER: [SegmentId; 8] = [
     SegmentId::Limits,
     SegmentId::Session,
     SegmentId::CodexVersion,
+    SegmentId::PullRequest,
+    SegmentId::Trend,
+    SegmentId::Budget,
+    SegmentId::Cost,
+    SegmentId::Daily,
+    SegmentId::Title,
+    SegmentId::Idle,
+    SegmentId::Warnings,
+    SegmentId::Plugin,
 ];
 
 pub fn apply_quick_config(cfg: &mut Config) {
@@ -33,7 +46,7 @@ pub fn apply_quick_config(cfg: &mut Config) {
         );
     }
 
-    set_option_bool(cfg, SegmentId::Cwd, "basename", true);
+    set_option_string(cfg, SegmentId::Cwd, "style", "basename");
     set_option_bool(cfg, SegmentId::Git, "detailed", false);
     set_option_string(cfg, SegmentId::Context, "mode", "used");
 }
@@ -52,6 +65,7 @@ pub fn apply_enhancement(cfg: &mut Config, enhancement: Enhancement) {
                 SegmentId::Limits,
                 SegmentId::Session,
                 SegmentId::CodexVersion,
+                SegmentId::Warnings,
             ] {
                 ensure_segment(cfg, id);
                 set_enabled(cfg, id, true);
@@ -62,6 +76,71 @@ pub fn apply_enhancement(cfg: &mut Config, enhancement: Enhancement) {
     }
 }
 
+#[cfg(feature = "tui")]
+/// Stores the current theme, style and segments as a new named profile.
+pub fn create_profile(cfg: &mut Config, name: &str) -> Result<()> {
+    check_profile_name(name)?;
+    if cfg.profiles.contains_key(name) {
+        bail!("profile already exists: {}", name);
+    }
+    let profile = Profile {
+        theme: cfg.theme.clone(),
+        style: cfg.style.clone(),
+        segments: cfg.segments.clone(),
+    };
+    cfg.profiles.insert(name.to_string(), profile);
+    Ok(())
+}
+
+#[cfg(feature = "tui")]
+pub fn rename_profile(cfg: &mut Config, from: &str, to: &str) -> Result<()> {
+    check_profile_name(to)?;
+    if cfg.profiles.contains_key(to) {
+        bail!("profile already exists: {}", to);
+    }
+    let Some(profile) = cfg.profiles.remove(from) else {
+        bail!("unknown profile: {}", from);
+    };
+    cfg.profiles.insert(to.to_string(), profile);
+    Ok(())
+}
+
+#[cfg(feature = "tui")]
+pub fn delete_profile(cfg: &mut Config, name: &str) -> Result<()> {
+    if cfg.profiles.remove(name).is_none() {
+        bail!("unknown profile: {}", name);
+    }
+    Ok(())
+}
+
+#[cfg(feature = "tui")]
+/// Copies the profile's theme, style and segments over the top-level config.
+pub fn apply_profile(cfg: &mut Config, name: &str) -> Result<()> {
+    let Some(profile) = cfg.profiles.get(name).cloned() else {
+        bail!("unknown profile: {}", name);
+    };
+    cfg.theme = profile.theme;
+    cfg.style = profile.style;
+    cfg.segments = profile.segments;
+    Ok(())
+}
+
+#[cfg(feature = "tui")]
+/// Profile names become TOML table keys, so keep them to bare-key characters.
+fn check_profile_name(name: &str) -> Result<()> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        bail!(
+            "invalid profile name {:?}: use letters, digits, '-' or '_'",
+            name
+        );
+    }
+    Ok(())
+}
+
 fn ensure_all_segments(cfg: &mut Config) {
     for id in QUICK_ORDER {
         ensure_segment(cfg, id);
@@ -191,4 +270,28 @@ mod tests {
         assert!(get_segment(&cfg, SegmentId::Session).enabled);
         assert!(get_segment(&cfg, SegmentId::CodexVersion).enabled);
     }
+
+    #[cfg(feature = "tui")]
+    #[test]
+    fn profiles_round_trip_through_create_rename_apply() {
+        let mut cfg = Config {
+            theme: "nord".to_string(),
+            ..Config::default()
+        };
+        create_profile(&mut cfg, "work").expect("create");
+        assert!(create_profile(&mut cfg, "work").is_err());
+        assert!(create_profile(&mut cfg, "has space").is_err());
+
+        rename_profile(&mut cfg, "work", "office").expect("rename");
+        cfg.theme = "default".to_string();
+        apply_profile(&mut cfg, "office").expect("apply");
+        assert_eq!(cfg.theme, "nord");
+
+        let text = toml::to_string_pretty(&cfg).expect("serialize");
+        let parsed: Config = toml::from_str(&text).expect("parse");
+        assert!(parsed.profiles.contains_key("office"));
+
+        delete_profile(&mut cfg, "office").expect("delete");
+        assert!(cfg.profiles.is_empty());
+    }
 }
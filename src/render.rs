@@ -1,74 +1,665 @@
-use crate::config::{Config, NamedColor};
+//! Formats segments as ANSI, plain text, porcelain or a host program's markup.
+//!
+//! Every renderer writes into one output `String`; the paint helpers append to it instead of
+//! returning intermediate strings.
+
+use crate::config::{Color, ColorDepth, Config, NamedColor, StyleMode};
+use crate::error::CodexlineError;
 use crate::segments::SegmentPiece;
+use std::borrow::Cow;
+use std::fmt::Write as _;
 
+/// Segments joined with `style.separator`, colored with ANSI escapes unless `plain`. Grouped
+/// segments are joined with their group's separator inside its brackets, on its background.
+/// In powerline mode the colored line is drawn on segment backgrounds like the host formats.
 pub fn render_line(cfg: &Config, segments: &[SegmentPiece], plain: bool) -> String {
-    let rendered: Vec<String> = segments
-        .iter()
-        .map(|segment| {
+    if cfg.style.mode == StyleMode::Powerline && !plain {
+        return render_markup(cfg, segments, &Ansi);
+    }
+    let depth = cfg.style.color_depth.resolve();
+    let segments = &*downgraded(segments, depth);
+    let mut out = String::with_capacity(line_capacity(segments));
+    for (index, run) in group_runs(cfg, segments, depth).iter().enumerate() {
+        if index > 0 {
+            out.push_str(&cfg.style.separator);
+        }
+        let background = run.background.filter(|_| !plain);
+        let text = |out: &mut String, text: &str| {
+            if text.is_empty() {
+                return;
+            }
+            if background.is_some() {
+                push_sgr(out, None, background, false);
+            }
+            out.push_str(text);
+        };
+        text(&mut out, run.open);
+        for (position, segment) in run.segments.iter().enumerate() {
+            if position > 0 {
+                text(&mut out, run.separator.unwrap_or(&cfg.style.separator));
+            }
             if plain {
-                segment.plain_text()
+                if !segment.icon.is_empty() {
+                    out.push_str(&segment.icon);
+                    out.push(' ');
+                }
+                out.push_str(&segment.value);
             } else {
-                render_segment(segment)
+                render_segment(&mut out, segment, background);
+            }
+        }
+        text(&mut out, run.close);
+        if background.is_some() {
+            out.push_str("\x1b[0m");
+        }
+    }
+    out
+}
+
+/// [`render_line`] written to `writer` followed by a newline.
+pub fn write_line(
+    writer: &mut impl std::io::Write,
+    cfg: &Config,
+    segments: &[SegmentPiece],
+    plain: bool,
+) -> Result<(), CodexlineError> {
+    writeln!(writer, "{}", render_line(cfg, segments, plain)).map_err(CodexlineError::render)
+}
+
+/// Stable `key=value` output, one line per segment field:
+/// `<segment_id>.icon=<icon>` then `<segment_id>.value=<value>`, in render order; provider
+/// segments use `plugin.<name>` as the id.
+/// Newlines in values are replaced with spaces so each field stays on one line.
+pub fn render_porcelain(segments: &[SegmentPiece]) -> String {
+    let mut out = String::with_capacity(line_capacity(segments) * 2);
+    for segment in segments {
+        for (field, value) in [("icon", &segment.icon), ("value", &segment.value)] {
+            if !out.is_empty() {
+                out.push('\n');
+            }
+            match &segment.plugin {
+                Some(name) => {
+                    out.push_str("plugin.");
+                    out.push_str(name);
+                }
+                None => out.push_str(segment.id.as_str()),
+            }
+            out.push('.');
+            out.push_str(field);
+            out.push('=');
+            push_escaped(&mut out, value, |c| matches!(c, '\r' | '\n').then_some(" "));
+        }
+    }
+    out
+}
+
+/// Neighbouring segments of one group, drawn as a block with the group's styling.
+struct GroupRun<'a> {
+    segments: &'a [SegmentPiece],
+    separator: Option<&'a str>,
+    background: Option<Color>,
+    open: &'a str,
+    close: &'a str,
+}
+
+/// `segments` split into runs of the same group; ungrouped segments form runs of one, and
+/// segments of a group without a `[groups.<name>]` table are drawn as if ungrouped.
+fn group_runs<'a>(
+    cfg: &'a Config,
+    segments: &'a [SegmentPiece],
+    depth: ColorDepth,
+) -> Vec<GroupRun<'a>> {
+    segments
+        .chunk_by(|a, b| a.group.is_some() && a.group == b.group)
+        .map(|segments| {
+            let group = segments[0]
+                .group
+                .as_ref()
+                .and_then(|name| cfg.groups.get(name));
+            let (open, close) = group
+                .and_then(|group| group.brackets.as_ref())
+                .map_or(("", ""), |(open, close)| (open.as_str(), close.as_str()));
+            GroupRun {
+                segments,
+                separator: group.and_then(|group| group.separator.as_deref()),
+                background: group
+                    .and_then(|group| group.background)
+                    .map(|color| color.downgrade(depth)),
+                open,
+                close,
             }
         })
-        .collect();
-    rendered.join(&cfg.style.separator)
+        .collect()
+}
+
+/// `segments` with their colors brought down to what `depth` can show; borrowed when they
+/// already fit.
+fn downgraded(segments: &[SegmentPiece], depth: ColorDepth) -> Cow<'_, [SegmentPiece]> {
+    let colors =
+        |segment: &SegmentPiece| [segment.icon_color, segment.text_color, segment.background];
+    let fits = segments
+        .iter()
+        .flat_map(colors)
+        .flatten()
+        .all(|color| color.downgrade(depth) == color);
+    if fits {
+        return Cow::Borrowed(segments);
+    }
+    let downgrade = |color: Option<Color>| color.map(|color| color.downgrade(depth));
+    Cow::Owned(
+        segments
+            .iter()
+            .map(|segment| SegmentPiece {
+                icon_color: downgrade(segment.icon_color),
+                text_color: downgrade(segment.text_color),
+                background: downgrade(segment.background),
+                ..segment.clone()
+            })
+            .collect(),
+    )
 }
 
-fn render_segment(segment: &SegmentPiece) -> String {
-    let mut out = String::new();
+/// Rough output size, so most lines are written without growing the buffer.
+fn line_capacity(segments: &[SegmentPiece]) -> usize {
+    segments
+        .iter()
+        .map(|segment| segment.icon.len() + segment.value.len() + 32)
+        .sum()
+}
 
-    if !segment.icon.is_empty() {
-        out.push_str(&paint(&segment.icon, segment.icon_color, segment.bold));
-        out.push_str(" ");
+/// Appends `text`, replacing each char for which `escape` returns a substitute.
+fn push_escaped(out: &mut String, text: &str, escape: impl Fn(char) -> Option<&'static str>) {
+    for c in text.chars() {
+        match escape(c) {
+            Some(replacement) => out.push_str(replacement),
+            None => out.push(c),
+        }
+    }
+}
+
+pub(crate) const POWERLINE_ARROW: &str = "\u{e0b0}";
+
+/// Color markup of a host program, used instead of raw ANSI escapes.
+trait Markup {
+    fn style(&self, out: &mut String, fg: Option<Color>, bg: Option<Color>, bold: bool);
+    fn reset(&self) -> &'static str;
+    /// Literal text made of `parts`, quoted the way the host program needs it.
+    fn text(&self, out: &mut String, parts: &[&str]);
+}
+
+/// Plain ANSI escapes, one SGR sequence per style change.
+struct Ansi;
+
+impl Markup for Ansi {
+    fn style(&self, out: &mut String, fg: Option<Color>, bg: Option<Color>, bold: bool) {
+        push_sgr(out, fg, bg, bold);
+    }
+
+    fn reset(&self) -> &'static str {
+        "\x1b[0m"
+    }
+
+    fn text(&self, out: &mut String, parts: &[&str]) {
+        parts.iter().for_each(|part| out.push_str(part));
+    }
+}
+
+/// tmux `#[fg=colourN,bg=colourN]` directives (`#rrggbb` for hex colors); `#` in text is
+/// written `##`.
+struct Tmux;
+
+impl Markup for Tmux {
+    fn style(&self, out: &mut String, fg: Option<Color>, bg: Option<Color>, bold: bool) {
+        let colour = |out: &mut String, color: Option<Color>| match color {
+            Some(Color::Named(color)) => {
+                let _ = write!(out, "colour{}", color.index());
+            }
+            Some(Color::Indexed(index)) => {
+                let _ = write!(out, "colour{index}");
+            }
+            Some(color) => {
+                let _ = write!(out, "{color}");
+            }
+            None => out.push_str("default"),
+        };
+        out.push_str("#[fg=");
+        colour(out, fg);
+        out.push_str(",bg=");
+        colour(out, bg);
+        out.push_str(if bold { ",bold]" } else { ",nobold]" });
+    }
+
+    fn reset(&self) -> &'static str {
+        "#[default]"
+    }
+
+    fn text(&self, out: &mut String, parts: &[&str]) {
+        for part in parts {
+            push_escaped(out, part, |c| (c == '#').then_some("##"));
+        }
+    }
+}
+
+/// zsh prompt escapes (`%F{..}`, `%K{..}`, `%B`), which zsh excludes from the prompt width.
+struct Zsh;
+
+impl Markup for Zsh {
+    fn style(&self, out: &mut String, fg: Option<Color>, bg: Option<Color>, bold: bool) {
+        match fg {
+            Some(color) => {
+                out.push_str("%F{");
+                push_zsh_color(out, color);
+                out.push('}');
+            }
+            None => out.push_str("%f"),
+        }
+        match bg {
+            Some(color) => {
+                out.push_str("%K{");
+                push_zsh_color(out, color);
+                out.push('}');
+            }
+            None => out.push_str("%k"),
+        }
+        out.push_str(if bold { "%B" } else { "%b" });
+    }
+
+    fn reset(&self) -> &'static str {
+        "%f%k%b"
+    }
+
+    fn text(&self, out: &mut String, parts: &[&str]) {
+        for part in parts {
+            push_escaped(out, part, |c| (c == '%').then_some("%%"));
+        }
+    }
+}
+
+/// ANSI escapes wrapped in `\x01` / `\x02`, the raw bytes readline uses for PS1's `\[` `\]`.
+///
+/// Intended for `PROMPT_COMMAND='PS1="$(codexline --format bash)\n\$ "'`, where the output is
+/// decoded and expanded again as part of PS1, so `\`, `$` and `` ` `` are escaped.
+struct Bash;
+
+impl Markup for Bash {
+    fn style(&self, out: &mut String, fg: Option<Color>, bg: Option<Color>, bold: bool) {
+        out.push('\x01');
+        push_sgr(out, fg, bg, bold);
+        out.push('\x02');
+    }
+
+    fn reset(&self) -> &'static str {
+        "\x01\x1b[0m\x02"
+    }
+
+    fn text(&self, out: &mut String, parts: &[&str]) {
+        for part in parts {
+            push_escaped(out, part, |c| match c {
+                '\\' => Some("\\\\"),
+                '$' => Some("\\$"),
+                '`' => Some("\\`"),
+                _ => None,
+            });
+        }
+    }
+}
+
+/// A fish script of `set_color` / `printf` commands, meant to be piped into `source` from a
+/// `fish_prompt` handler (see `--install-shell fish`).
+struct Fish;
+
+impl Markup for Fish {
+    fn style(&self, out: &mut String, fg: Option<Color>, bg: Option<Color>, bold: bool) {
+        out.push_str("set_color normal; ");
+        if fg.is_none() && bg.is_none() && !bold {
+            return;
+        }
+        out.push_str("set_color");
+        if bold {
+            out.push_str(" -o");
+        }
+        if let Some(color) = bg {
+            out.push_str(" -b ");
+            push_fish_color(out, color);
+        }
+        if let Some(color) = fg {
+            out.push(' ');
+            push_fish_color(out, color);
+        }
+        out.push_str("; ");
+    }
+
+    fn reset(&self) -> &'static str {
+        "set_color normal; "
+    }
+
+    fn text(&self, out: &mut String, parts: &[&str]) {
+        out.push_str("printf '%s' '");
+        for part in parts {
+            push_escaped(out, part, |c| match c {
+                '\\' => Some("\\\\"),
+                '\'' => Some("\\'"),
+                _ => None,
+            });
+        }
+        out.push_str("'; ");
+    }
+}
+
+/// The SGR sequences `$PSStyle` produces (`` `e[96m ``, `` `e[1m ``, `` `e[0m ``), written for a
+/// PowerShell `prompt` function that prints the captured line with `Write-Host`.
+struct Powershell;
+
+impl Markup for Powershell {
+    fn style(&self, out: &mut String, fg: Option<Color>, bg: Option<Color>, bold: bool) {
+        let weight = if bold { 1 } else { 22 };
+        let _ = write!(out, "\x1b[{weight}m\x1b[");
+        push_sgr_color(out, fg, false);
+        out.push_str("m\x1b[");
+        push_sgr_color(out, bg, true);
+        out.push('m');
+    }
+
+    fn reset(&self) -> &'static str {
+        "\x1b[0m"
+    }
+
+    fn text(&self, out: &mut String, parts: &[&str]) {
+        parts.iter().for_each(|part| out.push_str(part));
+    }
+}
+
+/// Vim statusline items: `%#Codexline<fg>_<bg>[b]#` highlight groups named after palette
+/// indexes (`x` for the default color), reset with `%*`; literal `%` is written `%%`. Hex and
+/// 256-color values use the nearest of the 16 colors, so the groups stay a fixed set.
+struct Lualine;
+
+impl Markup for Lualine {
+    fn style(&self, out: &mut String, fg: Option<Color>, bg: Option<Color>, bold: bool) {
+        let index = |out: &mut String, color: Option<Color>| match color {
+            Some(color) => {
+                let _ = write!(out, "{}", color.nearest_named().index());
+            }
+            None => out.push('x'),
+        };
+        out.push_str("%#Codexline");
+        index(out, fg);
+        out.push('_');
+        index(out, bg);
+        if bold {
+            out.push('b');
+        }
+        out.push('#');
+    }
+
+    fn reset(&self) -> &'static str {
+        "%*"
+    }
+
+    fn text(&self, out: &mut String, parts: &[&str]) {
+        for part in parts {
+            push_escaped(out, part, |c| (c == '%').then_some("%%"));
+        }
+    }
+}
+
+/// tmux `status-right` output.
+pub fn render_tmux(cfg: &Config, segments: &[SegmentPiece]) -> String {
+    render_markup(cfg, segments, &Tmux)
+}
+
+/// zsh `PROMPT` / `RPROMPT` output.
+pub fn render_zsh(cfg: &Config, segments: &[SegmentPiece]) -> String {
+    render_markup(cfg, segments, &Zsh)
+}
+
+/// bash `PS1` output.
+pub fn render_bash(cfg: &Config, segments: &[SegmentPiece]) -> String {
+    render_markup(cfg, segments, &Bash)
+}
+
+/// fish `set_color` script.
+pub fn render_fish(cfg: &Config, segments: &[SegmentPiece]) -> String {
+    render_markup(cfg, segments, &Fish)
+}
+
+/// PowerShell prompt output.
+pub fn render_powershell(cfg: &Config, segments: &[SegmentPiece]) -> String {
+    render_markup(cfg, segments, &Powershell)
+}
+
+/// Neovim statusline component output (lualine, heirline).
+pub fn render_lualine(cfg: &Config, segments: &[SegmentPiece]) -> String {
+    render_markup(cfg, segments, &Lualine)
+}
+
+/// In powerline mode each segment is drawn on its background color (a group's background
+/// when it has one) and segments are joined with arrows whose foreground is the previous
+/// background, so the transitions line up; a group's own separator replaces the arrows
+/// inside it. `style.left_cap` / `style.right_cap` open and close the line in the first and
+/// last background.
+fn render_markup(cfg: &Config, segments: &[SegmentPiece], markup: &dyn Markup) -> String {
+    let depth = cfg.style.color_depth.resolve();
+    let segments = &*downgraded(segments, depth);
+    let mut out = String::with_capacity(line_capacity(segments) * 2);
+    let runs = group_runs(cfg, segments, depth);
+    if cfg.style.mode == StyleMode::Powerline {
+        let first_bg = |run: &GroupRun| run.background.or(run.segments[0].background);
+        if let Some(first) = runs.first().filter(|_| !cfg.style.left_cap.is_empty()) {
+            markup.style(&mut out, first_bg(first), None, false);
+            markup.text(&mut out, &[&cfg.style.left_cap]);
+        }
+        for (index, run) in runs.iter().enumerate() {
+            for (position, segment) in run.segments.iter().enumerate() {
+                let bg = run.background.or(segment.background);
+                if position == 0 && !run.open.is_empty() {
+                    markup.style(&mut out, segment.text_color, bg, segment.bold);
+                    markup.text(&mut out, &[run.open]);
+                }
+                if !segment.icon.is_empty() {
+                    markup.style(&mut out, segment.icon_color, bg, segment.bold);
+                    markup.text(&mut out, &[" ", &segment.icon]);
+                }
+                markup.style(&mut out, segment.text_color, bg, segment.bold);
+                markup.text(&mut out, &[" ", &segment.value, " "]);
+                let next = run.segments.get(position + 1);
+                if next.is_none() {
+                    if !run.close.is_empty() {
+                        markup.text(&mut out, &[run.close]);
+                    }
+                } else if let Some(separator) = run.separator {
+                    markup.style(&mut out, None, bg, false);
+                    markup.text(&mut out, &[separator]);
+                    continue;
+                }
+                let (next_bg, glyph) = match (next, runs.get(index + 1)) {
+                    (Some(next), _) => (run.background.or(next.background), POWERLINE_ARROW),
+                    (None, Some(next_run)) => (first_bg(next_run), POWERLINE_ARROW),
+                    (None, None) => (None, cfg.style.right_cap.as_str()),
+                };
+                if !glyph.is_empty() {
+                    markup.style(&mut out, bg, next_bg, false);
+                    markup.text(&mut out, &[glyph]);
+                }
+            }
+        }
+        if !out.is_empty() {
+            out.push_str(markup.reset());
+        }
+        return out;
     }
-    out.push_str(&paint(&segment.value, segment.text_color, segment.bold));
 
+    // On a group background the style stays set until the end of the group.
+    let paint = |out: &mut String,
+                 text: &str,
+                 color: Option<Color>,
+                 background: Option<Color>,
+                 bold: bool| {
+        if text.is_empty() {
+            return;
+        }
+        if background.is_some() {
+            markup.style(out, color, background, bold);
+            markup.text(out, &[text]);
+            return;
+        }
+        if color.is_none() && !bold {
+            markup.text(out, &[text]);
+            return;
+        }
+        markup.style(out, color, None, bold);
+        markup.text(out, &[text]);
+        out.push_str(markup.reset());
+    };
+    for (index, run) in runs.iter().enumerate() {
+        if index > 0 {
+            markup.text(&mut out, &[&cfg.style.separator]);
+        }
+        let background = run.background;
+        paint(&mut out, run.open, None, background, false);
+        for (position, segment) in run.segments.iter().enumerate() {
+            if position > 0 {
+                let separator = run.separator.unwrap_or(&cfg.style.separator);
+                paint(&mut out, separator, None, background, false);
+            }
+            if !segment.icon.is_empty() {
+                paint(
+                    &mut out,
+                    &segment.icon,
+                    segment.icon_color,
+                    background,
+                    segment.bold,
+                );
+                markup.text(&mut out, &[" "]);
+            }
+            paint(
+                &mut out,
+                &segment.value,
+                segment.text_color,
+                background,
+                segment.bold,
+            );
+        }
+        paint(&mut out, run.close, None, background, false);
+        if background.is_some() {
+            out.push_str(markup.reset());
+        }
+    }
     out
 }
 
-fn paint(text: &str, color: Option<NamedColor>, bold: bool) -> String {
-    let mut codes: Vec<String> = Vec::new();
-    if bold {
-        codes.push("1".to_string());
+/// fish spells bright colors `brred`, `brcyan`, ...; other colors are given as `rrggbb`.
+fn push_fish_color(out: &mut String, color: Color) {
+    let Color::Named(color) = color else {
+        let (r, g, b) = color.rgb();
+        let _ = write!(out, "{r:02x}{g:02x}{b:02x}");
+        return;
+    };
+    match color.as_str().strip_prefix("bright_") {
+        Some(base) => {
+            out.push_str("br");
+            out.push_str(base);
+        }
+        None => out.push_str(color.as_str()),
+    }
+}
+
+/// zsh understands the eight basic color names; other colors use their palette index or
+/// `#rrggbb`.
+fn push_zsh_color(out: &mut String, color: Color) {
+    match color {
+        Color::Named(named) if named.index() < 8 => out.push_str(named.as_str()),
+        Color::Named(named) => {
+            let _ = write!(out, "{}", named.index());
+        }
+        color => {
+            let _ = write!(out, "{color}");
+        }
+    }
+}
+
+/// On a group `background` every part carries it, and the caller resets after the group.
+fn render_segment(out: &mut String, segment: &SegmentPiece, background: Option<Color>) {
+    if let Some(background) = background {
+        if !segment.icon.is_empty() {
+            push_sgr(out, segment.icon_color, Some(background), segment.bold);
+            out.push_str(&segment.icon);
+            out.push(' ');
+        }
+        push_sgr(out, segment.text_color, Some(background), segment.bold);
+        out.push_str(&segment.value);
+        return;
     }
-    if let Some(color_code) = color.map(color_code) {
-        codes.push(color_code);
+    if !segment.icon.is_empty() {
+        paint(out, &segment.icon, segment.icon_color, segment.bold);
+        out.push(' ');
     }
+    paint(out, &segment.value, segment.text_color, segment.bold);
+}
 
-    if codes.is_empty() {
-        return text.to_string();
+fn paint(out: &mut String, text: &str, color: Option<Color>, bold: bool) {
+    if color.is_none() && !bold {
+        out.push_str(text);
+        return;
     }
+    out.push_str(if bold { "\x1b[1" } else { "\x1b[" });
+    if let Some(color) = color {
+        if bold {
+            out.push(';');
+        }
+        push_sgr_color(out, Some(color), false);
+    }
+    let _ = write!(out, "m{text}\x1b[0m");
+}
+
+/// Weight, foreground and background as one SGR sequence, defaults where unset.
+fn push_sgr(out: &mut String, fg: Option<Color>, bg: Option<Color>, bold: bool) {
+    out.push_str(if bold { "\x1b[1;" } else { "\x1b[22;" });
+    push_sgr_color(out, fg, false);
+    out.push(';');
+    push_sgr_color(out, bg, true);
+    out.push('m');
+}
 
-    format!("\x1b[{}m{}\x1b[0m", codes.join(";"), text)
+/// SGR parameters of `color` as a foreground or `background`: the 16-color codes for named
+/// colors, `38;5;N` for 256-color indexes and `38;2;R;G;B` for hex (48 for backgrounds).
+fn push_sgr_color(out: &mut String, color: Option<Color>, background: bool) {
+    // Background codes are the foreground ones shifted by 10 (31 -> 41, 91 -> 101, 38 -> 48).
+    let shift = if background { 10 } else { 0 };
+    let _ = match color {
+        None => write!(out, "{}", 39 + shift),
+        Some(Color::Named(color)) => write!(out, "{}", sgr_code(color) + shift),
+        Some(Color::Indexed(index)) => write!(out, "{};5;{index}", 38 + shift),
+        Some(Color::Rgb(r, g, b)) => write!(out, "{};2;{r};{g};{b}", 38 + shift),
+    };
 }
 
-fn color_code(color: NamedColor) -> String {
+/// Foreground SGR code of `color`.
+fn sgr_code(color: NamedColor) -> u8 {
     match color {
-        NamedColor::Black => "30",
-        NamedColor::Red => "31",
-        NamedColor::Green => "32",
-        NamedColor::Yellow => "33",
-        NamedColor::Blue => "34",
-        NamedColor::Magenta => "35",
-        NamedColor::Cyan => "36",
-        NamedColor::White => "37",
-        NamedColor::BrightBlack => "90",
-        NamedColor::BrightRed => "91",
-        NamedColor::BrightGreen => "92",
-        NamedColor::BrightYellow => "93",
-        NamedColor::BrightBlue => "94",
-        NamedColor::BrightMagenta => "95",
-        NamedColor::BrightCyan => "96",
-        NamedColor::BrightWhite => "97",
-    }
-    .to_string()
+        NamedColor::Black => 30,
+        NamedColor::Red => 31,
+        NamedColor::Green => 32,
+        NamedColor::Yellow => 33,
+        NamedColor::Blue => 34,
+        NamedColor::Magenta => 35,
+        NamedColor::Cyan => 36,
+        NamedColor::White => 37,
+        NamedColor::BrightBlack => 90,
+        NamedColor::BrightRed => 91,
+        NamedColor::BrightGreen => 92,
+        NamedColor::BrightYellow => 93,
+        NamedColor::BrightBlue => 94,
+        NamedColor::BrightMagenta => 95,
+        NamedColor::BrightCyan => 96,
+        NamedColor::BrightWhite => 97,
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::{SegmentId, StyleConfig, StyleMode};
+    use crate::config::{ColorMode, SegmentId, StyleConfig, StyleMode};
 
     #[test]
     fn render_line_without_trailing_separator() {
@@ -76,6 +667,12 @@ mod tests {
             style: StyleConfig {
                 mode: StyleMode::Plain,
                 separator: " | ".to_string(),
+                locked: false,
+                ssh_theme: None,
+                color: ColorMode::Auto,
+                color_depth: ColorDepth::Auto,
+                left_cap: String::new(),
+                right_cap: POWERLINE_ARROW.to_string(),
             },
             ..Config::default()
         };
@@ -87,6 +684,9 @@ mod tests {
                 value: "gpt-5".to_string(),
                 icon_color: None,
                 text_color: None,
+                background: None,
+                plugin: None,
+                group: None,
                 bold: false,
             },
             SegmentPiece {
@@ -95,10 +695,402 @@ mod tests {
                 value: "main".to_string(),
                 icon_color: None,
                 text_color: None,
+                background: None,
+                plugin: None,
+                group: None,
                 bold: false,
             },
         ];
 
         assert_eq!(render_line(&cfg, &segments, true), "M gpt-5 | GIT main");
     }
+
+    #[test]
+    fn render_porcelain_emits_key_value_lines() {
+        let segments = vec![SegmentPiece {
+            id: SegmentId::CodexVersion,
+            icon: "VER".to_string(),
+            value: "v1\n2".to_string(),
+            icon_color: None,
+            text_color: None,
+            background: None,
+            plugin: None,
+            group: None,
+            bold: false,
+        }];
+
+        assert_eq!(
+            render_porcelain(&segments),
+            "codex_version.icon=VER\ncodex_version.value=v1 2"
+        );
+    }
+
+    #[test]
+    fn render_tmux_emits_powerline_transitions() {
+        let cfg = Config {
+            style: StyleConfig {
+                mode: StyleMode::Powerline,
+                separator: " ".to_string(),
+                locked: false,
+                ssh_theme: None,
+                color: ColorMode::Auto,
+                color_depth: ColorDepth::Auto,
+                left_cap: String::new(),
+                right_cap: POWERLINE_ARROW.to_string(),
+            },
+            ..Config::default()
+        };
+        let piece = |id, value: &str, background| SegmentPiece {
+            id,
+            icon: String::new(),
+            value: value.to_string(),
+            icon_color: None,
+            text_color: Some(NamedColor::White.into()),
+            background,
+            bold: false,
+            plugin: None,
+            group: None,
+        };
+        let segments = vec![
+            piece(SegmentId::Model, "gpt-5", Some(NamedColor::Blue.into())),
+            piece(SegmentId::Git, "#1", Some(NamedColor::Black.into())),
+        ];
+
+        assert_eq!(
+            render_tmux(&cfg, &segments),
+            "#[fg=colour7,bg=colour4,nobold] gpt-5 #[fg=colour4,bg=colour0,nobold]\u{e0b0}\
+             #[fg=colour7,bg=colour0,nobold] ##1 #[fg=colour0,bg=default,nobold]\u{e0b0}#[default]"
+        );
+    }
+
+    #[test]
+    fn grouped_segments_render_as_one_block() {
+        let mut cfg = Config::default();
+        cfg.style.separator = " | ".to_string();
+        cfg.groups.insert(
+            "usage".to_string(),
+            crate::config::GroupConfig {
+                separator: Some(" ".to_string()),
+                background: Some(NamedColor::Black.into()),
+                brackets: Some(("[".to_string(), "]".to_string())),
+            },
+        );
+        let piece = |id, value: &str, background, group: Option<&str>| SegmentPiece {
+            id,
+            icon: String::new(),
+            value: value.to_string(),
+            icon_color: None,
+            text_color: Some(NamedColor::White.into()),
+            background,
+            bold: false,
+            plugin: None,
+            group: group.map(str::to_string),
+        };
+        let segments = vec![
+            piece(
+                SegmentId::Model,
+                "gpt-5",
+                Some(NamedColor::Blue.into()),
+                None,
+            ),
+            piece(SegmentId::Context, "40%", None, Some("usage")),
+            piece(
+                SegmentId::Tokens,
+                "1.2k",
+                Some(NamedColor::Red.into()),
+                Some("usage"),
+            ),
+        ];
+
+        assert_eq!(render_line(&cfg, &segments, true), "gpt-5 | [40% 1.2k]");
+
+        cfg.style.mode = StyleMode::Powerline;
+        assert_eq!(
+            render_tmux(&cfg, &segments),
+            "#[fg=colour7,bg=colour4,nobold] gpt-5 #[fg=colour4,bg=colour0,nobold]\u{e0b0}\
+             #[fg=colour7,bg=colour0,nobold][#[fg=colour7,bg=colour0,nobold] 40% \
+             #[fg=default,bg=colour0,nobold] #[fg=colour7,bg=colour0,nobold] 1.2k ]\
+             #[fg=colour0,bg=default,nobold]\u{e0b0}#[default]"
+        );
+    }
+
+    #[test]
+    fn render_zsh_wraps_colors_in_prompt_escapes() {
+        let cfg = Config {
+            style: StyleConfig {
+                mode: StyleMode::NerdFont,
+                separator: " | ".to_string(),
+                locked: false,
+                ssh_theme: None,
+                color: ColorMode::Auto,
+                color_depth: ColorDepth::Auto,
+                left_cap: String::new(),
+                right_cap: POWERLINE_ARROW.to_string(),
+            },
+            ..Config::default()
+        };
+        let segments = vec![SegmentPiece {
+            id: SegmentId::Context,
+            icon: "CTX".to_string(),
+            value: "40%".to_string(),
+            icon_color: Some(NamedColor::Cyan.into()),
+            text_color: Some(NamedColor::BrightCyan.into()),
+            background: None,
+            plugin: None,
+            group: None,
+            bold: false,
+        }];
+
+        assert_eq!(
+            render_zsh(&cfg, &segments),
+            "%F{cyan}%k%bCTX%f%k%b %F{14}%k%b40%%%f%k%b"
+        );
+    }
+
+    #[test]
+    fn render_bash_marks_escapes_as_non_printing() {
+        let cfg = Config::default();
+        let segments = vec![SegmentPiece {
+            id: SegmentId::Cwd,
+            icon: String::new(),
+            value: "$HOME".to_string(),
+            icon_color: None,
+            text_color: Some(NamedColor::Blue.into()),
+            background: None,
+            plugin: None,
+            group: None,
+            bold: true,
+        }];
+
+        assert_eq!(
+            render_bash(&cfg, &segments),
+            "\x01\x1b[1;34;49m\x02\\$HOME\x01\x1b[0m\x02"
+        );
+    }
+
+    #[test]
+    fn render_fish_emits_set_color_script() {
+        let cfg = Config::default();
+        let segments = vec![SegmentPiece {
+            id: SegmentId::Git,
+            icon: String::new(),
+            value: "it's".to_string(),
+            icon_color: None,
+            text_color: Some(NamedColor::BrightMagenta.into()),
+            background: None,
+            plugin: None,
+            group: None,
+            bold: false,
+        }];
+
+        assert_eq!(
+            render_fish(&cfg, &segments),
+            "set_color normal; set_color brmagenta; printf '%s' 'it\\'s'; set_color normal; "
+        );
+    }
+
+    #[test]
+    fn render_lualine_uses_highlight_groups() {
+        let cfg = Config::default();
+        let segments = vec![SegmentPiece {
+            id: SegmentId::Context,
+            icon: String::new(),
+            value: "40%".to_string(),
+            icon_color: None,
+            text_color: Some(NamedColor::BrightCyan.into()),
+            background: None,
+            plugin: None,
+            group: None,
+            bold: true,
+        }];
+
+        assert_eq!(render_lualine(&cfg, &segments), "%#Codexline14_xb#40%%%*");
+    }
+
+    #[test]
+    fn render_powershell_emits_psstyle_sequences() {
+        let cfg = Config::default();
+        let segments = vec![SegmentPiece {
+            id: SegmentId::Model,
+            icon: String::new(),
+            value: "gpt-5".to_string(),
+            icon_color: None,
+            text_color: Some(NamedColor::BrightCyan.into()),
+            background: None,
+            plugin: None,
+            group: None,
+            bold: false,
+        }];
+
+        assert_eq!(
+            render_powershell(&cfg, &segments),
+            "\x1b[22m\x1b[96m\x1b[49mgpt-5\x1b[0m"
+        );
+    }
+
+    /// Fixed context shared by the theme snapshots.
+    fn snapshot_context() -> crate::context::StatusContext {
+        use crate::context::{
+            CheckState, GitStatus, PullRequestStatus, RateLimitSnapshot, SessionMetaSnapshot,
+            StatusContext, TokenUsageSnapshot,
+        };
+        StatusContext {
+            project_root: Some("/work/codexline".into()),
+            model: Some("gpt-5-codex".to_string()),
+            git: Some(GitStatus {
+                branch: "feature/render".to_string(),
+                dirty: true,
+                staged: 2,
+                unstaged: 3,
+                untracked: 1,
+                conflicted: 0,
+                ahead: Some(1),
+                behind: Some(4),
+                diffstat: None,
+            }),
+            usage: Some(TokenUsageSnapshot {
+                input_tokens: 120_000,
+                output_tokens: 8_000,
+                cached_input_tokens: 0,
+                reasoning_output_tokens: 0,
+                total_tokens: 128_000,
+                model_context_window: Some(272_000),
+                used_percent: Some(47),
+                remaining_percent: Some(53),
+            }),
+            limits: Some(RateLimitSnapshot {
+                primary_used_percent: Some(31.5),
+                secondary_used_percent: Some(12.0),
+            }),
+            session: Some(SessionMetaSnapshot {
+                thread_id: Some("0199a1b2-c3d4-7e5f-8a9b-0c1d2e3f4a5b".to_string()),
+                cli_version: Some("0.46.0".to_string()),
+                model_provider: Some("openai".to_string()),
+                title: None,
+            }),
+            pull_request: Some(PullRequestStatus {
+                number: 42,
+                state: "OPEN".to_string(),
+                draft: false,
+                checks: CheckState::Passing,
+            }),
+            ..StatusContext::new(
+                chrono::DateTime::from_timestamp(1_760_000_000, 0).expect("timestamp"),
+                "/work/codexline".into(),
+            )
+        }
+    }
+
+    /// Compares `actual` with `src/snapshots/render__<name>.snap`; set
+    /// `CODEXLINE_UPDATE_SNAPSHOTS=1` to write the current output instead.
+    fn assert_snapshot(name: &str, actual: &str) {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("src/snapshots")
+            .join(format!("render__{name}.snap"));
+        if std::env::var_os("CODEXLINE_UPDATE_SNAPSHOTS").is_some() {
+            std::fs::create_dir_all(path.parent().expect("parent")).expect("snapshot dir");
+            std::fs::write(&path, actual).expect("write snapshot");
+            return;
+        }
+        let expected = std::fs::read_to_string(&path)
+            .unwrap_or_else(|_| panic!("missing snapshot {}", path.display()))
+            .replace("\r\n", "\n");
+        assert!(
+            actual == expected,
+            "snapshot {} changed (rerun with CODEXLINE_UPDATE_SNAPSHOTS=1 to accept):\n{}",
+            path.display(),
+            actual
+        );
+    }
+
+    #[test]
+    fn builtin_themes_match_snapshots() {
+        let ctx = snapshot_context();
+        let no_user_themes = std::path::Path::new("/nonexistent-codexline-themes");
+        for theme in crate::themes::builtin_theme_names() {
+            let base = crate::themes::apply_theme(&Config::default(), &theme, no_user_themes)
+                .expect("builtin theme");
+            let mut snapshot = format!("---\nsource: src/render.rs\ntheme: {theme}\n---\n");
+            for mode in [StyleMode::Plain, StyleMode::NerdFont, StyleMode::Powerline] {
+                let mut cfg = base.clone();
+                cfg.style.mode = mode;
+                cfg.style.color_depth = ColorDepth::Truecolor;
+                let segments = crate::segments::build_segments(&cfg, &ctx);
+                let line = render_line(&cfg, &segments, false).replace('\x1b', "\\e");
+                writeln!(snapshot, "[{mode:?}]\n{line}").expect("write");
+            }
+            assert_snapshot(&theme, &snapshot);
+        }
+    }
+
+    #[test]
+    fn hex_colors_emit_truecolor_or_the_nearest_palette_color() {
+        let mut cfg = Config::default();
+        cfg.style.separator = " ".to_string();
+        let segments = vec![SegmentPiece {
+            id: SegmentId::Model,
+            icon: String::new(),
+            value: "gpt-5".to_string(),
+            icon_color: None,
+            text_color: Some(Color::Rgb(0xff, 0x87, 0x00)),
+            background: None,
+            bold: true,
+            plugin: None,
+            group: None,
+        }];
+
+        cfg.style.color_depth = ColorDepth::Truecolor;
+        assert_eq!(
+            render_line(&cfg, &segments, false),
+            "\x1b[1;38;2;255;135;0mgpt-5\x1b[0m"
+        );
+        assert_eq!(
+            render_tmux(&cfg, &segments),
+            "#[fg=#ff8700,bg=default,bold]gpt-5#[default]"
+        );
+        cfg.style.color_depth = ColorDepth::Ansi256;
+        assert_eq!(
+            render_line(&cfg, &segments, false),
+            "\x1b[1;38;5;208mgpt-5\x1b[0m"
+        );
+        cfg.style.color_depth = ColorDepth::Ansi16;
+        assert_eq!(
+            render_line(&cfg, &segments, false),
+            "\x1b[1;33mgpt-5\x1b[0m"
+        );
+        assert_eq!(
+            render_fish(&cfg, &segments)
+                .matches("set_color -o yellow")
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn render_line_draws_powerline_transitions_and_caps() {
+        let mut cfg = Config::default();
+        cfg.style.mode = StyleMode::Powerline;
+        cfg.style.left_cap = "\u{e0b6}".to_string();
+        let piece = |value: &str, background: NamedColor| SegmentPiece {
+            id: SegmentId::Model,
+            icon: String::new(),
+            value: value.to_string(),
+            icon_color: None,
+            text_color: Some(NamedColor::White.into()),
+            background: Some(background.into()),
+            bold: false,
+            plugin: None,
+            group: None,
+        };
+        let segments = vec![piece("a", NamedColor::Blue), piece("b", NamedColor::Red)];
+
+        assert_eq!(
+            render_line(&cfg, &segments, false),
+            "\x1b[22;34;49m\u{e0b6}\x1b[22;37;44m a \x1b[22;34;41m\u{e0b0}\
+             \x1b[22;37;41m b \x1b[22;31;49m\u{e0b0}\x1b[0m"
+        );
+        cfg.style.right_cap = String::new();
+        assert!(render_line(&cfg, &segments, false).ends_with(" b \x1b[0m"));
+        assert_eq!(render_line(&cfg, &segments, true), "a · b");
+    }
 }
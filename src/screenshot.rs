@@ -0,0 +1,255 @@
+//! `--screenshot`: the colored statusline as an SVG image (PNG through `rsvg-convert`), so
+//! theme previews look the same whichever terminal took them.
+//!
+//! The line is rendered with the PowerShell markup, which spells out every style change as
+//! plain SGR, and laid out on a monospace grid of one cell per char, always in truecolor. The
+//! 16 named colors come from a fixed dark palette instead of the terminal's.
+
+use crate::config::{Color, ColorDepth, Config};
+use crate::render;
+use crate::segments::SegmentPiece;
+use anyhow::{bail, Context, Result};
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+const FONT_FAMILY: &str =
+    "'JetBrainsMono Nerd Font', 'Symbols Nerd Font Mono', Menlo, Consolas, monospace";
+const FONT_SIZE: f64 = 14.0;
+const CELL_WIDTH: f64 = 8.4;
+const LINE_HEIGHT: f64 = 22.0;
+const PADDING: f64 = 12.0;
+const DEFAULT_FG: &str = "#cccccc";
+const DEFAULT_BG: &str = "#1e1e1e";
+
+/// The 16-color palette, in [`NamedColor::ALL`](crate::config::NamedColor::ALL) order.
+const PALETTE: [&str; 16] = [
+    "#000000", "#cd3131", "#0dbc79", "#e5e510", "#2472c8", "#bc3fbc", "#11a8cd", "#e5e5e5",
+    "#666666", "#f14c4c", "#23d18b", "#f5f543", "#3b8eea", "#d670d6", "#29b8db", "#ffffff",
+];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Style {
+    fg: Option<Color>,
+    bg: Option<Color>,
+    bold: bool,
+}
+
+impl Style {
+    /// Applies the parameters of one SGR sequence; the renderer only emits weight, colors and
+    /// resets.
+    fn apply(&mut self, params: &str) {
+        let codes: Vec<u8> = params
+            .split(';')
+            .map(|code| code.parse().unwrap_or(0))
+            .collect();
+        let mut rest = codes.as_slice();
+        while let [code, tail @ ..] = rest {
+            rest = tail;
+            let code = *code;
+            match code {
+                0 => *self = Style::default(),
+                1 => self.bold = true,
+                22 => self.bold = false,
+                30..=37 => self.fg = Some(Color::Indexed(code - 30)),
+                90..=97 => self.fg = Some(Color::Indexed(code - 90 + 8)),
+                39 => self.fg = None,
+                40..=47 => self.bg = Some(Color::Indexed(code - 40)),
+                100..=107 => self.bg = Some(Color::Indexed(code - 100 + 8)),
+                49 => self.bg = None,
+                38 | 48 => {
+                    let color = match rest {
+                        [5, index, tail @ ..] => {
+                            rest = tail;
+                            Color::Indexed(*index)
+                        }
+                        [2, r, g, b, tail @ ..] => {
+                            rest = tail;
+                            Color::Rgb(*r, *g, *b)
+                        }
+                        _ => break,
+                    };
+                    if code == 38 {
+                        self.fg = Some(color);
+                    } else {
+                        self.bg = Some(color);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// SVG fill of `color`, with the first 16 palette entries taken from [`PALETTE`].
+fn svg_color(color: Color) -> String {
+    match color {
+        Color::Indexed(index @ 0..=15) => PALETTE[usize::from(index)].to_string(),
+        Color::Named(named) => PALETTE[usize::from(named.index())].to_string(),
+        color => {
+            let (r, g, b) = color.rgb();
+            format!("#{r:02x}{g:02x}{b:02x}")
+        }
+    }
+}
+
+/// `ansi` split into runs of text with the same style.
+fn spans(ansi: &str) -> Vec<(Style, String)> {
+    let mut spans: Vec<(Style, String)> = Vec::new();
+    let mut style = Style::default();
+    let mut rest = ansi;
+    while !rest.is_empty() {
+        let (text, after) = match rest.find("\x1b[") {
+            Some(at) => (&rest[..at], &rest[at + 2..]),
+            None => (rest, ""),
+        };
+        if !text.is_empty() {
+            match spans.last_mut() {
+                Some((last, run)) if *last == style => run.push_str(text),
+                _ => spans.push((style, text.to_string())),
+            }
+        }
+        let Some(end) = after.find('m') else {
+            break;
+        };
+        style.apply(&after[..end]);
+        rest = &after[end + 1..];
+    }
+    spans
+}
+
+/// The statusline of `segments` as a standalone SVG document.
+pub fn svg(cfg: &Config, segments: &[SegmentPiece]) -> String {
+    let mut cfg = cfg.clone();
+    cfg.style.color_depth = ColorDepth::Truecolor;
+    let spans = spans(&render::render_powershell(&cfg, segments));
+    let columns: usize = spans.iter().map(|(_, text)| text.chars().count()).sum();
+    let width = PADDING * 2.0 + columns as f64 * CELL_WIDTH;
+    let height = PADDING * 2.0 + LINE_HEIGHT;
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width:.1}" height="{height:.1}" viewBox="0 0 {width:.1} {height:.1}" font-family="{FONT_FAMILY}" font-size="{FONT_SIZE}" xml:space="preserve">"#
+    );
+    let _ = writeln!(
+        out,
+        r#"<rect width="100%" height="100%" rx="6" fill="{DEFAULT_BG}"/>"#
+    );
+    let mut column = 0usize;
+    for (style, text) in &spans {
+        let cells = text.chars().count();
+        let x = PADDING + column as f64 * CELL_WIDTH;
+        let span_width = cells as f64 * CELL_WIDTH;
+        column += cells;
+        if let Some(bg) = style.bg {
+            let _ = writeln!(
+                out,
+                r#"<rect x="{x:.1}" y="{PADDING:.1}" width="{span_width:.1}" height="{LINE_HEIGHT:.1}" fill="{}"/>"#,
+                svg_color(bg)
+            );
+        }
+        if text.trim().is_empty() {
+            continue;
+        }
+        let fill = style.fg.map_or_else(|| DEFAULT_FG.to_string(), svg_color);
+        let weight = if style.bold { "bold" } else { "normal" };
+        let baseline = PADDING + LINE_HEIGHT * 0.7;
+        // textLength keeps every span on the grid even when a glyph comes from a fallback font.
+        let _ = write!(
+            out,
+            r#"<text x="{x:.1}" y="{baseline:.1}" fill="{fill}" font-weight="{weight}" textLength="{span_width:.1}" lengthAdjust="spacingAndGlyphs">"#
+        );
+        for c in text.chars() {
+            match c {
+                '&' => out.push_str("&amp;"),
+                '<' => out.push_str("&lt;"),
+                '>' => out.push_str("&gt;"),
+                c => out.push(c),
+            }
+        }
+        out.push_str("</text>\n");
+    }
+    out.push_str("</svg>\n");
+    out
+}
+
+/// Writes `svg` to `path`: as is for `.svg`, rasterized by `rsvg-convert` (librsvg) at 2x
+/// for `.png`.
+pub fn write(path: &Path, svg: &str) -> Result<()> {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_ascii_lowercase);
+    match extension.as_deref() {
+        Some("svg") => std::fs::write(path, svg)
+            .with_context(|| format!("failed to write: {}", path.display())),
+        Some("png") => {
+            let mut child = Command::new("rsvg-convert")
+                .args(["--format", "png", "--zoom", "2", "--output"])
+                .arg(path)
+                .stdin(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn()
+                .context("failed to run rsvg-convert (install librsvg, or write an .svg)")?;
+            child
+                .stdin
+                .take()
+                .context("rsvg-convert stdin unavailable")?
+                .write_all(svg.as_bytes())?;
+            let output = child.wait_with_output()?;
+            if !output.status.success() {
+                bail!(
+                    "rsvg-convert failed: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                );
+            }
+            Ok(())
+        }
+        _ => bail!(
+            "unsupported screenshot format: {} (use .svg or .png)",
+            path.display()
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{NamedColor, SegmentId, StyleMode};
+
+    fn piece(value: &str, background: Option<Color>) -> SegmentPiece {
+        SegmentPiece {
+            id: SegmentId::Model,
+            icon: String::new(),
+            value: value.to_string(),
+            icon_color: None,
+            text_color: Some(NamedColor::BrightCyan.into()),
+            background,
+            bold: true,
+            plugin: None,
+            group: None,
+        }
+    }
+
+    #[test]
+    fn svg_lays_spans_on_the_cell_grid() {
+        let mut cfg = Config::default();
+        cfg.style.mode = StyleMode::Powerline;
+        let svg = svg(&cfg, &[piece("a<b", Some(NamedColor::Blue.into()))]);
+
+        // " a<b " on blue, then the arrow in blue on the default background.
+        assert!(svg
+            .contains(r##"<rect x="12.0" y="12.0" width="42.0" height="22.0" fill="#2472c8"/>"##));
+        assert!(svg.contains(r##"fill="#29b8db" font-weight="bold" textLength="42.0" lengthAdjust="spacingAndGlyphs"> a&lt;b </text>"##));
+        assert!(svg.contains(r##"<text x="54.0" y="27.4" fill="#2472c8" font-weight="normal""##));
+        assert!(svg.contains(r#"width="74.4" height="46.0""#));
+
+        let dir = tempfile::tempdir().expect("temp dir");
+        assert!(write(&dir.path().join("line.txt"), &svg).is_err());
+        let path = dir.path().join("line.svg");
+        write(&path, &svg).expect("write");
+        assert_eq!(std::fs::read_to_string(path).expect("read"), svg);
+    }
+}
@@ -1,15 +1,36 @@
-use crate::config::{Config, NamedColor, SegmentConfig, SegmentId, StyleMode};
-use crate::context::{GitStatus, StatusContext};
+//! Turns a [`StatusContext`] into the enabled segments' display values.
+
+use crate::config::{Color, Config, SegmentConfig, SegmentId, StyleMode};
+use crate::context::{
+    BudgetStatus, CheckState, CollectorId, DailyUsage, GitStatus, PullRequestStatus, StatusContext,
+    UsageTrend, WarningKind,
+};
+use crate::numbers::NumberFormat;
+use crate::pattern::Pattern;
+use crate::pricing;
+use schemars::JsonSchema;
 use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::{Path, MAIN_SEPARATOR, MAIN_SEPARATOR_STR};
 
-#[derive(Debug, Clone, Serialize)]
+/// One rendered segment: icon and value text plus the colors to draw them with.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct SegmentPiece {
     pub id: SegmentId,
     pub icon: String,
     pub value: String,
-    pub icon_color: Option<NamedColor>,
-    pub text_color: Option<NamedColor>,
+    pub icon_color: Option<Color>,
+    pub text_color: Option<Color>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub background: Option<Color>,
     pub bold: bool,
+    /// Provider name for pieces produced by the `plugin` segment.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plugin: Option<String>,
+    /// Group the segment is drawn in, from its `group` setting.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
 }
 
 impl SegmentPiece {
@@ -22,37 +43,220 @@ impl SegmentPiece {
     }
 }
 
+/// Enabled segments in config order; segments without data are left out.
 pub fn build_segments(cfg: &Config, ctx: &StatusContext) -> Vec<SegmentPiece> {
     cfg.segments
         .iter()
         .filter(|segment| segment.enabled)
-        .filter_map(|segment| build_segment(cfg.style.mode, segment, ctx))
+        .flat_map(|segment| {
+            let pieces: Vec<SegmentPiece> = match segment.id {
+                SegmentId::Plugin => match plugin_pieces(segment, ctx) {
+                    pieces if pieces.is_empty() => degraded_piece(cfg.style.mode, segment, ctx)
+                        .into_iter()
+                        .collect(),
+                    pieces => pieces,
+                },
+                _ => build_segment(cfg, segment, ctx)
+                    .or_else(|| degraded_piece(cfg.style.mode, segment, ctx))
+                    .into_iter()
+                    .collect(),
+            };
+            pieces.into_iter().map(|mut piece| {
+                piece.value = pad_value(segment, piece.value);
+                piece
+            })
+        })
+        .collect()
+}
+
+/// `value` padded with spaces to the segment's `min_width` characters, placed by `align`,
+/// so changing numbers (9% to 10%) don't shift the rest of the line.
+fn pad_value(segment: &SegmentConfig, value: String) -> String {
+    let min_width = segment
+        .options
+        .get("min_width")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0) as usize;
+    let Some(padding) = min_width
+        .checked_sub(value.chars().count())
+        .filter(|n| *n > 0)
+    else {
+        return value;
+    };
+    let before = match segment.options.get("align").and_then(|v| v.as_str()) {
+        Some("right") => padding,
+        Some("center") => padding / 2,
+        _ => 0,
+    };
+    format!(
+        "{}{value}{}",
+        " ".repeat(before),
+        " ".repeat(padding - before)
+    )
+}
+
+/// Provider output, falling back to the `plugin` segment's colors for unset fields.
+fn plugin_pieces(segment: &SegmentConfig, ctx: &StatusContext) -> Vec<SegmentPiece> {
+    ctx.plugins
+        .iter()
+        .map(|plugin| SegmentPiece {
+            id: SegmentId::Plugin,
+            icon: plugin.icon.clone(),
+            value: plugin.value.clone(),
+            icon_color: plugin.icon_color.or(segment.colors.icon),
+            text_color: plugin.text_color.or(segment.colors.text),
+            background: plugin.background.or(segment.colors.background),
+            bold: plugin.bold || segment.styles.text_bold,
+            plugin: Some(plugin.name.clone()),
+            group: segment.group.clone(),
+        })
         .collect()
 }
 
+/// Why a segment shows what it shows; used by the configurator's inspector pane.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SegmentSource {
+    WorkingDirectory,
+    Git,
+    Rollout,
+    GitHub,
+    Plugins,
+    Collection,
+}
+
+#[derive(Debug, Clone)]
+pub struct SegmentInsight {
+    pub value: Option<String>,
+    pub source: SegmentSource,
+    pub hidden_reason: Option<String>,
+}
+
+pub fn inspect_segment(
+    cfg: &Config,
+    segment: &SegmentConfig,
+    ctx: &StatusContext,
+) -> SegmentInsight {
+    let source = match segment.id {
+        SegmentId::Cwd => SegmentSource::WorkingDirectory,
+        SegmentId::Git => SegmentSource::Git,
+        SegmentId::PullRequest => SegmentSource::GitHub,
+        SegmentId::Plugin => SegmentSource::Plugins,
+        SegmentId::Warnings => SegmentSource::Collection,
+        _ => SegmentSource::Rollout,
+    };
+    let value = match segment.id {
+        SegmentId::Plugin => Some(
+            plugin_pieces(segment, ctx)
+                .iter()
+                .map(SegmentPiece::plain_text)
+                .collect::<Vec<_>>()
+                .join(&cfg.style.separator),
+        )
+        .filter(|joined| !joined.is_empty()),
+        _ => build_segment(cfg, segment, ctx).map(|piece| piece.plain_text()),
+    };
+
+    let hidden_reason = if !segment.enabled {
+        Some("segment is disabled".to_string())
+    } else if value.is_none() {
+        Some(
+            match segment.id {
+                SegmentId::Model => "latest rollout has no model",
+                SegmentId::Cwd => "working directory is unavailable",
+                SegmentId::Git => "current directory is not a git repository",
+                SegmentId::Context => "rollout has no context window usage yet",
+                SegmentId::Tokens => "rollout has no token usage yet",
+                SegmentId::Limits => "rollout has no rate limit snapshot",
+                SegmentId::Session => "rollout has no session id",
+                SegmentId::CodexVersion => "rollout has no Codex CLI version",
+                SegmentId::PullRequest => {
+                    "no open pull request for this branch (or gh unavailable)"
+                }
+                SegmentId::Trend => "usage history does not cover the window yet",
+                SegmentId::Budget => "budget.monthly_usd is not set",
+                SegmentId::Cost => "no token usage yet, or the model has no price",
+                SegmentId::Daily => "no session used tokens today",
+                SegmentId::Title => "rollout has no user message yet",
+                SegmentId::Idle => {
+                    "Codex was active within idle.min_minutes (or rollout has no timestamps)"
+                }
+                SegmentId::Warnings => "every collector finished with complete data",
+                SegmentId::Plugin => "no segment provider produced output",
+            }
+            .to_string(),
+        )
+    } else {
+        None
+    };
+
+    SegmentInsight {
+        value,
+        source,
+        hidden_reason,
+    }
+}
+
 fn build_segment(
-    mode: StyleMode,
+    cfg: &Config,
     segment: &SegmentConfig,
     ctx: &StatusContext,
 ) -> Option<SegmentPiece> {
+    let mode = cfg.style.mode;
+    let numbers = NumberFormat::new(&cfg.format);
     let value = match segment.id {
-        SegmentId::Model => ctx.model.as_ref().map(|name| simplify_model_name(name)),
+        SegmentId::Model => ctx
+            .model
+            .as_ref()
+            .map(|name| simplify_model_name(&cfg.model_aliases, name)),
         SegmentId::Cwd => Some(render_cwd(segment, ctx)),
         SegmentId::Git => ctx.git.as_ref().map(|git| render_git(mode, segment, git)),
-        SegmentId::Context => render_context(segment, ctx),
-        SegmentId::Tokens => render_tokens(ctx),
-        SegmentId::Limits => render_limits(ctx),
+        SegmentId::Context => render_context(&numbers, segment, ctx),
+        SegmentId::Tokens => render_tokens(&numbers, segment, ctx),
+        SegmentId::Limits => render_limits(&numbers, mode, ctx),
         SegmentId::Session => ctx
             .session
             .as_ref()
             .and_then(|s| s.thread_id.as_ref())
-            .map(|id| shorten_uuid(id).to_string()),
+            .map(|id| render_session(segment, id)),
         SegmentId::CodexVersion => ctx
             .session
             .as_ref()
             .and_then(|s| s.cli_version.as_ref())
-            .map(|version| format!("v{version}")),
+            .map(|version| render_codex_version(segment, version)),
+        SegmentId::PullRequest => ctx
+            .pull_request
+            .as_ref()
+            .map(|pr| render_pull_request(mode, pr)),
+        SegmentId::Trend => ctx
+            .trend
+            .as_ref()
+            .map(|trend| render_trend(&numbers, trend)),
+        SegmentId::Budget => ctx
+            .budget
+            .as_ref()
+            .map(|budget| render_budget(&numbers, budget)),
+        SegmentId::Cost => render_cost(&numbers, cfg, ctx),
+        SegmentId::Daily => ctx
+            .daily
+            .as_ref()
+            .and_then(|daily| render_daily(&numbers, daily)),
+        SegmentId::Title => ctx
+            .session
+            .as_ref()
+            .and_then(|s| s.title.as_ref())
+            .map(|title| render_title(mode, segment, title)),
+        SegmentId::Idle => render_idle(&numbers, segment, ctx),
+        SegmentId::Warnings => render_warnings(ctx),
+        SegmentId::Plugin => None,
     }?;
+    let value = match segment.id {
+        SegmentId::Context | SegmentId::Tokens | SegmentId::Limits | SegmentId::Cost
+            if ctx.partial.contains(&CollectorId::Rollout) =>
+        {
+            format!("~{value}")
+        }
+        _ => value,
+    };
 
     Some(SegmentPiece {
         id: segment.id,
@@ -60,7 +264,44 @@ fn build_segment(
         value,
         icon_color: segment.colors.icon,
         text_color: segment.colors.text,
+        background: segment.colors.background,
         bold: segment.styles.text_bold,
+        plugin: None,
+        group: segment.group.clone(),
+    })
+}
+
+/// Placeholder for a segment whose collector missed its deadline.
+fn degraded_piece(
+    mode: StyleMode,
+    segment: &SegmentConfig,
+    ctx: &StatusContext,
+) -> Option<SegmentPiece> {
+    let collector = match segment.id {
+        SegmentId::Cwd | SegmentId::Warnings => return None,
+        SegmentId::Plugin => CollectorId::Plugins,
+        SegmentId::Git => CollectorId::Git,
+        SegmentId::PullRequest => CollectorId::GitHub,
+        SegmentId::Trend | SegmentId::Budget => CollectorId::History,
+        SegmentId::Daily => CollectorId::Daily,
+        _ => CollectorId::Rollout,
+    };
+    if !ctx.degraded.contains(&collector) {
+        return None;
+    }
+    Some(SegmentPiece {
+        id: segment.id,
+        icon: icon_for_mode(mode, segment),
+        value: match mode {
+            StyleMode::Plain => "...".to_string(),
+            StyleMode::NerdFont | StyleMode::Powerline => "\u{2026}".to_string(),
+        },
+        icon_color: segment.colors.icon,
+        text_color: segment.colors.text,
+        background: segment.colors.background,
+        bold: false,
+        plugin: None,
+        group: segment.group.clone(),
     })
 }
 
@@ -78,17 +319,96 @@ fn icon_for_mode(mode: StyleMode, segment: &SegmentConfig) -> String {
 }
 
 fn render_cwd(segment: &SegmentConfig, ctx: &StatusContext) -> String {
-    let basename = segment
+    // `basename = false` predates `style` and still means the full path.
+    let legacy_style = match segment.options.get("basename").and_then(|v| v.as_bool()) {
+        Some(false) => "full",
+        _ => "basename",
+    };
+    let style = segment
+        .options
+        .get("style")
+        .and_then(|v| v.as_str())
+        .unwrap_or(legacy_style);
+    let windows_paths = segment
         .options
-        .get("basename")
+        .get("windows_paths")
         .and_then(|v| v.as_bool())
-        .unwrap_or(true);
-    if basename {
-        if let Some(name) = ctx.cwd.file_name().and_then(|n| n.to_str()) {
-            return name.to_string();
-        }
+        .unwrap_or(false);
+    let windows_path = || {
+        windows_paths
+            .then(|| crate::wsl::windows_path(&ctx.cwd))
+            .flatten()
+    };
+    let home = dirs::home_dir();
+    let path = match style {
+        "full" => windows_path().unwrap_or_else(|| tilde_path(&ctx.cwd, home.as_deref())),
+        "fish" => fish_path(&ctx.cwd, home.as_deref()),
+        "relative" => relative_path(&ctx.cwd, ctx.project_root.as_deref(), home.as_deref()),
+        _ => match ctx.cwd.file_name().and_then(|n| n.to_str()) {
+            Some(name) => name.to_string(),
+            None => windows_path().unwrap_or_else(|| crate::platform::display_path(&ctx.cwd)),
+        },
+    };
+    let prefix = segment
+        .options
+        .get("ssh_prefix")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default();
+    match &ctx.remote_host {
+        Some(host) if !prefix.is_empty() => format!("{}{}", prefix.replace("{host}", host), path),
+        _ => path,
+    }
+}
+
+/// `path` with the home directory written as `~`.
+fn tilde_path(path: &Path, home: Option<&Path>) -> String {
+    match home.and_then(|home| path.strip_prefix(home).ok()) {
+        Some(rest) if rest.as_os_str().is_empty() => "~".to_string(),
+        Some(rest) => format!("~{MAIN_SEPARATOR}{}", crate::platform::display_path(rest)),
+        None => crate::platform::display_path(path),
+    }
+}
+
+/// Fish-style: every directory but the last cut to its first letter, `~/p/c/src`. Hidden
+/// directories keep the dot (`.c`) and drive prefixes (`C:`) stay whole.
+fn fish_path(path: &Path, home: Option<&Path>) -> String {
+    let full = tilde_path(path, home);
+    let parts: Vec<&str> = full.split(MAIN_SEPARATOR).collect();
+    let last = parts.len().saturating_sub(1);
+    parts
+        .iter()
+        .enumerate()
+        .map(|(index, part)| {
+            if index == last || part.ends_with(':') {
+                return part.to_string();
+            }
+            let keep = if part.starts_with('.') { 2 } else { 1 };
+            part.chars().take(keep).collect()
+        })
+        .collect::<Vec<String>>()
+        .join(MAIN_SEPARATOR_STR)
+}
+
+/// The path from the project root, named after the root: `codexline/src/ui`. Outside a
+/// project it is the full path.
+fn relative_path(path: &Path, project_root: Option<&Path>, home: Option<&Path>) -> String {
+    let Some((root, rest)) =
+        project_root.and_then(|root| Some((root, path.strip_prefix(root).ok()?)))
+    else {
+        return tilde_path(path, home);
+    };
+    let name = root.file_name().map_or_else(
+        || tilde_path(root, home),
+        |name| name.to_string_lossy().into_owned(),
+    );
+    if rest.as_os_str().is_empty() {
+        name
+    } else {
+        format!(
+            "{name}{MAIN_SEPARATOR}{}",
+            crate::platform::display_path(rest)
+        )
     }
-    ctx.cwd.display().to_string()
 }
 
 fn render_git(mode: StyleMode, segment: &SegmentConfig, git: &GitStatus) -> String {
@@ -119,107 +439,373 @@ fn render_git(mode: StyleMode, segment: &SegmentConfig, git: &GitStatus) -> Stri
         clean_symbol
     };
 
-    let mut parts = vec![git.branch.clone(), status_symbol.to_string()];
+    let mut value = String::with_capacity(git.branch.len() + 16);
+    value.push_str(&branch_label(mode, segment, &git.branch));
+    value.push(' ');
+    value.push_str(status_symbol);
 
+    // Plain mode stays ASCII: conhost's default fonts lack the arrow glyphs.
+    let (ahead_symbol, behind_symbol) = match mode {
+        StyleMode::Plain => ("^", "v"),
+        StyleMode::NerdFont | StyleMode::Powerline => ("↑", "↓"),
+    };
     if let Some(v) = git.ahead.filter(|v| *v > 0) {
-        parts.push(format!("↑{v}"));
+        let _ = write!(value, " {ahead_symbol}{v}");
     }
     if let Some(v) = git.behind.filter(|v| *v > 0) {
-        parts.push(format!("↓{v}"));
+        let _ = write!(value, " {behind_symbol}{v}");
+    }
+
+    if let Some(stat) = git
+        .diffstat
+        .filter(|stat| stat.insertions + stat.deletions > 0)
+    {
+        let minus = match mode {
+            StyleMode::Plain => "-",
+            StyleMode::NerdFont | StyleMode::Powerline => "\u{2212}",
+        };
+        let _ = write!(value, " +{} {minus}{}", stat.insertions, stat.deletions);
     }
 
     if detailed {
-        if git.staged > 0 {
-            parts.push(format!("S{}", git.staged));
-        }
-        if git.unstaged > 0 {
-            parts.push(format!("U{}", git.unstaged));
-        }
-        if git.untracked > 0 {
-            parts.push(format!("N{}", git.untracked));
-        }
-        if git.conflicted > 0 {
-            parts.push(format!("C{}", git.conflicted));
+        for (prefix, count) in [
+            ('S', git.staged),
+            ('U', git.unstaged),
+            ('N', git.untracked),
+            ('C', git.conflicted),
+        ] {
+            if count > 0 {
+                let _ = write!(value, " {prefix}{count}");
+            }
         }
     }
 
-    parts.join(" ")
+    value
+}
+
+/// Context window usage as `ctx 42%`, used by the title and the iTerm2 badge.
+pub fn context_summary(ctx: &StatusContext) -> Option<String> {
+    let used = ctx.usage.as_ref()?.used_percent?;
+    Some(format!("ctx {used}%"))
+}
+
+/// Compact model + context summary for the terminal title, e.g. `gpt-5 · ctx 42%`.
+pub fn title_summary(cfg: &Config, ctx: &StatusContext) -> Option<String> {
+    let mut parts = Vec::new();
+    if let Some(model) = &ctx.model {
+        parts.push(simplify_model_name(&cfg.model_aliases, model));
+    }
+    parts.extend(context_summary(ctx));
+    if parts.is_empty() {
+        None
+    } else {
+        Some(parts.join(" · "))
+    }
 }
 
-fn render_context(segment: &SegmentConfig, ctx: &StatusContext) -> Option<String> {
+fn render_pull_request(mode: StyleMode, pr: &PullRequestStatus) -> String {
+    let checks = match (mode, pr.checks) {
+        (_, CheckState::None) => None,
+        (StyleMode::Plain, CheckState::Pending) => Some("pending"),
+        (StyleMode::Plain, CheckState::Passing) => Some("ok"),
+        (StyleMode::Plain, CheckState::Failing) => Some("fail"),
+        (_, CheckState::Pending) => Some("…"),
+        (_, CheckState::Passing) => Some("✓"),
+        (_, CheckState::Failing) => Some("✗"),
+    };
+    let mut value = format!("#{}", pr.number);
+    if pr.draft {
+        value.push_str(" draft");
+    } else if pr.state != "open" {
+        value.push(' ');
+        value.push_str(&pr.state);
+    }
+    if let Some(checks) = checks {
+        value.push(' ');
+        value.push_str(checks);
+    }
+    value
+}
+
+fn render_context(
+    numbers: &NumberFormat,
+    segment: &SegmentConfig,
+    ctx: &StatusContext,
+) -> Option<String> {
     let usage = ctx.usage.as_ref()?;
     let mode = segment
         .options
         .get("mode")
         .and_then(|v| v.as_str())
         .unwrap_or("remaining");
+    // The rollout's percentages are whole numbers; decimals come from the token counts.
+    let used = match usage.model_context_window.filter(|window| *window > 0) {
+        Some(window) if numbers.fractional_percent() => {
+            Some((usage.total_tokens as f64 / window as f64 * 100.0).clamp(0.0, 100.0))
+        }
+        _ => usage.used_percent.map(|v| v as f64),
+    };
 
     match mode {
-        "used" => usage.used_percent.map(|v| format!("{v}% used")),
-        _ => usage.remaining_percent.map(|v| format!("{v}% left")),
+        "used" => used.map(|v| format!("{}% used", numbers.percent(v))),
+        _ => match used {
+            Some(used) if numbers.fractional_percent() => {
+                Some(format!("{}% left", numbers.percent(100.0 - used)))
+            }
+            _ => usage
+                .remaining_percent
+                .map(|v| format!("{}% left", numbers.percent(v as f64))),
+        },
+    }
+}
+
+/// `120.0K in 8.0K out 128.0K total`; `show_cached` / `show_reasoning` add the cached share
+/// of the input and the reasoning share of the output when the rollout reports them
+/// (`120.0K in (96.0K cached) 8.0K out (2.0K reasoning) 128.0K total`).
+fn render_tokens(
+    numbers: &NumberFormat,
+    segment: &SegmentConfig,
+    ctx: &StatusContext,
+) -> Option<String> {
+    let usage = ctx.usage.as_ref()?;
+    if usage.total_tokens <= 0 {
+        return None;
+    }
+    let option = |key| {
+        segment
+            .options
+            .get(key)
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false)
+    };
+    let mut value = format!("{} in", numbers.tokens(usage.input_tokens));
+    if option("show_cached") && usage.cached_input_tokens > 0 {
+        let _ = write!(
+            value,
+            " ({} cached)",
+            numbers.tokens(usage.cached_input_tokens)
+        );
     }
+    let _ = write!(value, " {} out", numbers.tokens(usage.output_tokens));
+    if option("show_reasoning") && usage.reasoning_output_tokens > 0 {
+        let _ = write!(
+            value,
+            " ({} reasoning)",
+            numbers.tokens(usage.reasoning_output_tokens)
+        );
+    }
+    let _ = write!(value, " {} total", numbers.tokens(usage.total_tokens));
+    Some(value)
+}
+
+fn render_trend(numbers: &NumberFormat, trend: &UsageTrend) -> String {
+    format!(
+        "+{} tok / {}",
+        numbers.tokens(trend.tokens),
+        numbers.duration(u64::from(trend.window_minutes))
+    )
 }
 
-fn render_tokens(ctx: &StatusContext) -> Option<String> {
+/// `$12.40/$50 (25%)`; `~` when sessions on unpriced models are missing from the spend.
+fn render_budget(numbers: &NumberFormat, budget: &BudgetStatus) -> String {
+    let usd = |amount: f64| {
+        let precision = if amount.fract() == 0.0 { 0 } else { 2 };
+        format!("${}", numbers.decimal(amount, precision))
+    };
+    let percent = budget.spent_usd / budget.monthly_usd * 100.0;
+    let approx = if budget.unpriced_sessions > 0 {
+        "~"
+    } else {
+        ""
+    };
+    format!(
+        "{approx}${}/{} ({}%)",
+        numbers.decimal(budget.spent_usd, 2),
+        usd(budget.monthly_usd),
+        numbers.percent(percent)
+    )
+}
+
+/// `$0.42`: the session's tokens at the model's `[pricing]` or built-in price.
+fn render_cost(numbers: &NumberFormat, cfg: &Config, ctx: &StatusContext) -> Option<String> {
     let usage = ctx.usage.as_ref()?;
     if usage.total_tokens <= 0 {
         return None;
     }
+    let price = pricing::price_for(cfg, ctx.model.as_deref()?)?;
+    let cost = pricing::cost_usd(price, usage.input_tokens, usage.output_tokens);
+    Some(format!("${}", numbers.decimal(cost, 2)))
+}
+
+/// `1.2M tok today (3 sessions)`.
+fn render_daily(numbers: &NumberFormat, daily: &DailyUsage) -> Option<String> {
+    if daily.tokens <= 0 {
+        return None;
+    }
+    let plural = if daily.sessions == 1 { "" } else { "s" };
     Some(format!(
-        "{} in {} out {} total",
-        compact_tokens(usage.input_tokens),
-        compact_tokens(usage.output_tokens),
-        compact_tokens(usage.total_tokens)
+        "{} tok today ({} session{plural})",
+        numbers.tokens(daily.tokens),
+        daily.sessions
     ))
 }
 
-fn render_limits(ctx: &StatusContext) -> Option<String> {
+/// `idle 14m`, once the latest rollout line is `min_minutes` old.
+fn render_idle(
+    numbers: &NumberFormat,
+    segment: &SegmentConfig,
+    ctx: &StatusContext,
+) -> Option<String> {
+    let min_minutes = segment
+        .options
+        .get("min_minutes")
+        .and_then(|v| v.as_i64())
+        .unwrap_or(5);
+    let idle = (ctx.now - ctx.last_event_at?).num_minutes().max(0);
+    (idle >= min_minutes).then(|| format!("idle {}", numbers.duration(idle as u64)))
+}
+
+/// `git timeout, rollout missing`: collectors that missed their deadline, then those that
+/// recorded a warning.
+fn render_warnings(ctx: &StatusContext) -> Option<String> {
+    let timeouts = ctx
+        .degraded
+        .iter()
+        .map(|collector| format!("{} timeout", collector.as_str()));
+    let warnings = ctx.warnings.iter().map(|warning| {
+        let kind = match warning.kind {
+            WarningKind::Failed => "failed",
+            WarningKind::Missing => "missing",
+            WarningKind::Stale => "stale",
+        };
+        format!("{} {kind}", warning.collector.as_str())
+    });
+    let value = timeouts.chain(warnings).collect::<Vec<_>>().join(", ");
+    (!value.is_empty()).then_some(value)
+}
+
+fn render_limits(numbers: &NumberFormat, mode: StyleMode, ctx: &StatusContext) -> Option<String> {
     let limits = ctx.limits.as_ref()?;
-    let mut parts: Vec<String> = Vec::new();
-    if let Some(v) = limits.primary_used_percent {
-        parts.push(format!("5h {}%", v.round() as i64));
-    }
-    if let Some(v) = limits.secondary_used_percent {
-        parts.push(format!("weekly {}%", v.round() as i64));
+    let forecast = ctx.forecast.clone().unwrap_or_default();
+    let arrow = match mode {
+        StyleMode::Plain => "->",
+        StyleMode::NerdFont | StyleMode::Powerline => "\u{2192}",
+    };
+    let mut value = String::new();
+    for (label, percent, full_minutes) in [
+        (
+            "5h",
+            limits.primary_used_percent,
+            forecast.primary_full_minutes,
+        ),
+        (
+            "weekly",
+            limits.secondary_used_percent,
+            forecast.secondary_full_minutes,
+        ),
+    ] {
+        if let Some(v) = percent {
+            if !value.is_empty() {
+                value.push(' ');
+            }
+            let _ = write!(value, "{label} {}%", numbers.percent(v));
+            if let Some(minutes) = full_minutes {
+                let _ = write!(
+                    value,
+                    " {arrow} full ~{}",
+                    numbers.duration(u64::from(minutes))
+                );
+            }
+        }
     }
 
-    if parts.is_empty() {
-        None
-    } else {
-        Some(parts.join(" "))
-    }
+    (!value.is_empty()).then_some(value)
 }
 
-fn simplify_model_name(model: &str) -> String {
-    let lower = model.to_lowercase();
-    if lower.contains("claude-4-sonnet") || lower.contains("claude-sonnet-4") {
-        return "Sonnet 4".to_string();
-    }
-    if lower.contains("claude-3-7-sonnet") {
-        return "Sonnet 3.7".to_string();
+fn render_session(segment: &SegmentConfig, thread_id: &str) -> String {
+    let length = segment
+        .options
+        .get("length")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(8) as usize;
+    thread_id.chars().take(length).collect()
+}
+
+/// The branch, cut to `max_branch_len` characters. A longer branch containing a
+/// `ticket_regex` match shows just the ticket id instead: `JIRA-1234…`.
+fn branch_label(mode: StyleMode, segment: &SegmentConfig, branch: &str) -> String {
+    let max_len = segment
+        .options
+        .get("max_branch_len")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0) as usize;
+    if max_len == 0 || branch.chars().count() <= max_len {
+        return branch.to_string();
     }
-    if lower.contains("gpt-5-codex") {
-        return "gpt-5-codex".to_string();
+    let ellipsis = match mode {
+        StyleMode::Plain => "...",
+        StyleMode::NerdFont | StyleMode::Powerline => "\u{2026}",
+    };
+    let ticket = segment
+        .options
+        .get("ticket_regex")
+        .and_then(|v| v.as_str())
+        .filter(|pattern| !pattern.is_empty())
+        .and_then(|pattern| Pattern::new(pattern).ok())
+        .and_then(|pattern| pattern.find(branch).map(str::to_string))
+        .filter(|ticket| !ticket.is_empty());
+    match ticket {
+        Some(ticket) => format!("{ticket}{ellipsis}"),
+        None => {
+            let kept: String = branch.chars().take(max_len).collect();
+            format!("{kept}{ellipsis}")
+        }
     }
-    if lower.contains("gpt-5") {
-        return "gpt-5".to_string();
+}
+
+fn render_title(mode: StyleMode, segment: &SegmentConfig, title: &str) -> String {
+    let length = segment
+        .options
+        .get("length")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(32) as usize;
+    if title.chars().count() <= length {
+        return title.to_string();
     }
-    model.to_string()
+    let ellipsis = match mode {
+        StyleMode::Plain => "...",
+        StyleMode::NerdFont | StyleMode::Powerline => "\u{2026}",
+    };
+    let kept: String = title.chars().take(length).collect();
+    format!("{}{ellipsis}", kept.trim_end())
 }
 
-fn shorten_uuid(value: &str) -> &str {
-    value.get(0..8).unwrap_or(value)
+fn render_codex_version(segment: &SegmentConfig, version: &str) -> String {
+    let prefix = segment
+        .options
+        .get("prefix")
+        .and_then(|v| v.as_str())
+        .unwrap_or("v");
+    format!("{prefix}{version}")
+}
+
+/// The alias whose pattern matches the most of the lower-cased name (so `gpt-5-codex` beats
+/// `gpt-5`), or the name itself.
+fn simplify_model_name(aliases: &BTreeMap<String, String>, model: &str) -> String {
+    let lower = model.to_lowercase();
+    aliases
+        .iter()
+        .filter_map(|(pattern, name)| {
+            let matched = Pattern::new(pattern).ok()?.find(&lower)?;
+            Some(((matched.len(), pattern.len()), name))
+        })
+        .max_by_key(|(rank, _)| *rank)
+        .map_or_else(|| model.to_string(), |(_, name)| name.clone())
 }
 
+/// Token count with the default `[format]`: `999`, `1.2K`, `3.4M`.
 pub fn compact_tokens(value: i64) -> String {
-    let abs = value.unsigned_abs() as f64;
-    if abs >= 1_000_000.0 {
-        format!("{:.1}M", value as f64 / 1_000_000.0)
-    } else if abs >= 1_000.0 {
-        format!("{:.1}K", value as f64 / 1_000.0)
-    } else {
-        value.to_string()
-    }
+    NumberFormat::default().tokens(value)
 }
 
 #[cfg(test)]
@@ -235,7 +821,302 @@ mod tests {
 
     #[test]
     fn simplify_model_name_maps_known_values() {
-        assert_eq!(simplify_model_name("claude-4-sonnet-202501"), "Sonnet 4");
-        assert_eq!(simplify_model_name("gpt-5-codex"), "gpt-5-codex");
+        let mut aliases = Config::default().model_aliases;
+        let simplify =
+            |aliases: &BTreeMap<String, String>, model| simplify_model_name(aliases, model);
+        assert_eq!(simplify(&aliases, "claude-4-sonnet-202501"), "Sonnet 4");
+        assert_eq!(simplify(&aliases, "GPT-5-Codex"), "gpt-5-codex");
+        assert_eq!(simplify(&aliases, "gpt-5-mini"), "gpt-5");
+        assert_eq!(simplify(&aliases, "o3"), "o3");
+
+        aliases.insert(r"^acme-\d+b$".to_string(), "Acme".to_string());
+        assert_eq!(simplify(&aliases, "acme-70b"), "Acme");
+    }
+
+    #[test]
+    fn title_summary_combines_model_and_context() {
+        let mut ctx = StatusContext {
+            model: Some("gpt-5-codex".to_string()),
+            ..StatusContext::new(chrono::Utc::now(), std::path::PathBuf::from("/work"))
+        };
+        assert_eq!(
+            title_summary(&Config::default(), &ctx).as_deref(),
+            Some("gpt-5-codex")
+        );
+
+        ctx.usage = Some(crate::context::TokenUsageSnapshot {
+            input_tokens: 0,
+            output_tokens: 0,
+            cached_input_tokens: 0,
+            reasoning_output_tokens: 0,
+            total_tokens: 0,
+            model_context_window: Some(200_000),
+            used_percent: Some(42),
+            remaining_percent: Some(58),
+        });
+        assert_eq!(
+            title_summary(&Config::default(), &ctx).as_deref(),
+            Some("gpt-5-codex · ctx 42%")
+        );
+    }
+
+    #[test]
+    fn cwd_shows_ssh_prefix_only_remotely() {
+        let mut segment = crate::config::default_segment_for(SegmentId::Cwd);
+        segment
+            .options
+            .insert("ssh_prefix".to_string(), "{host}:".into());
+        let mut ctx = StatusContext::new(
+            chrono::Utc::now(),
+            std::path::PathBuf::from("/work/codexline"),
+        );
+        assert_eq!(render_cwd(&segment, &ctx), "codexline");
+
+        ctx.remote_host = Some("devbox".to_string());
+        assert_eq!(render_cwd(&segment, &ctx), "devbox:codexline");
+    }
+
+    #[test]
+    fn inspect_segment_explains_hidden_segments() {
+        let cfg = Config::default();
+        let ctx = StatusContext::new(
+            chrono::Utc::now(),
+            std::path::PathBuf::from("/work/codexline"),
+        );
+        let segment = |id: SegmentId| {
+            cfg.segments
+                .iter()
+                .find(|segment| segment.id == id)
+                .expect("segment")
+        };
+
+        let cwd = inspect_segment(&cfg, segment(SegmentId::Cwd), &ctx);
+        assert_eq!(cwd.source, SegmentSource::WorkingDirectory);
+        assert!(cwd.value.expect("value").ends_with("codexline"));
+
+        let git = inspect_segment(&cfg, segment(SegmentId::Git), &ctx);
+        assert_eq!(git.source, SegmentSource::Git);
+        assert!(git.value.is_none());
+        assert!(git.hidden_reason.is_some());
+    }
+
+    #[test]
+    fn degraded_collector_leaves_placeholder() {
+        let mut cfg = Config::default();
+        cfg.style.mode = StyleMode::Plain;
+        let ctx = StatusContext {
+            degraded: vec![CollectorId::Git],
+            ..StatusContext::new(
+                chrono::Utc::now(),
+                std::path::PathBuf::from("/work/codexline"),
+            )
+        };
+
+        let pieces = build_segments(&cfg, &ctx);
+        let git = pieces
+            .iter()
+            .find(|piece| piece.id == SegmentId::Git)
+            .expect("git placeholder");
+        assert_eq!(git.value, "...");
+        assert!(pieces.iter().all(|piece| piece.id != SegmentId::Model));
+    }
+
+    #[test]
+    fn limits_show_the_forecast_when_collected() {
+        let mut ctx = StatusContext {
+            limits: Some(crate::context::RateLimitSnapshot {
+                primary_used_percent: Some(62.0),
+                secondary_used_percent: Some(20.0),
+            }),
+            ..StatusContext::new(chrono::Utc::now(), std::path::PathBuf::from("/work"))
+        };
+        assert_eq!(
+            render_limits(&NumberFormat::default(), StyleMode::NerdFont, &ctx).as_deref(),
+            Some("5h 62% weekly 20%")
+        );
+
+        ctx.forecast = Some(crate::context::LimitForecast {
+            primary_full_minutes: Some(190),
+            secondary_full_minutes: None,
+        });
+        assert_eq!(
+            render_limits(&NumberFormat::default(), StyleMode::NerdFont, &ctx).as_deref(),
+            Some("5h 62% \u{2192} full ~3h 10m weekly 20%")
+        );
+        assert_eq!(
+            render_limits(&NumberFormat::default(), StyleMode::Plain, &ctx).as_deref(),
+            Some("5h 62% -> full ~3h 10m weekly 20%")
+        );
+    }
+
+    #[test]
+    fn idle_shows_once_past_the_threshold() {
+        let mut segment = crate::config::default_segments()
+            .into_iter()
+            .find(|segment| segment.id == SegmentId::Idle)
+            .expect("idle segment");
+        let now = chrono::Utc::now();
+        let mut ctx = StatusContext::new(now, std::path::PathBuf::from("/work"));
+        assert_eq!(render_idle(&NumberFormat::default(), &segment, &ctx), None);
+
+        ctx.last_event_at = Some(now - chrono::Duration::minutes(3));
+        assert_eq!(render_idle(&NumberFormat::default(), &segment, &ctx), None);
+        ctx.last_event_at = Some(now - chrono::Duration::minutes(14));
+        assert_eq!(
+            render_idle(&NumberFormat::default(), &segment, &ctx).as_deref(),
+            Some("idle 14m")
+        );
+
+        segment
+            .options
+            .insert("min_minutes".to_string(), serde_json::Value::from(0));
+        ctx.last_event_at = Some(now + chrono::Duration::minutes(1));
+        assert_eq!(
+            render_idle(&NumberFormat::default(), &segment, &ctx).as_deref(),
+            Some("idle 0m")
+        );
+    }
+
+    #[test]
+    fn long_branches_are_cut_to_their_ticket() {
+        let mut segment = crate::config::default_segments()
+            .into_iter()
+            .find(|segment| segment.id == SegmentId::Git)
+            .expect("git segment");
+        let branch = "feature/JIRA-1234-add-login-flow";
+        assert_eq!(branch_label(StyleMode::NerdFont, &segment, branch), branch);
+
+        segment
+            .options
+            .insert("max_branch_len".to_string(), serde_json::Value::from(12));
+        assert_eq!(
+            branch_label(StyleMode::Plain, &segment, branch),
+            "feature/JIRA..."
+        );
+        segment
+            .options
+            .insert("ticket_regex".to_string(), r"[A-Z]+-\d+".into());
+        assert_eq!(
+            branch_label(StyleMode::NerdFont, &segment, branch),
+            "JIRA-1234\u{2026}"
+        );
+        assert_eq!(branch_label(StyleMode::NerdFont, &segment, "main"), "main");
+    }
+
+    #[test]
+    fn cwd_styles_shorten_the_path() {
+        let home = Path::new("/home/ada");
+        let cwd = Path::new("/home/ada/projects/codexline/src");
+        let root = Path::new("/home/ada/projects/codexline");
+        let native = |text: &str| text.replace('/', MAIN_SEPARATOR_STR);
+
+        assert_eq!(
+            tilde_path(cwd, Some(home)),
+            native("~/projects/codexline/src")
+        );
+        assert_eq!(tilde_path(home, Some(home)), "~");
+        assert_eq!(
+            tilde_path(Path::new("/srv/app"), Some(home)),
+            native("/srv/app")
+        );
+        assert_eq!(fish_path(cwd, Some(home)), native("~/p/c/src"));
+        assert_eq!(
+            fish_path(Path::new("/etc/.config/nvim"), None),
+            native("/e/.c/nvim")
+        );
+        assert_eq!(
+            relative_path(cwd, Some(root), Some(home)),
+            native("codexline/src")
+        );
+        assert_eq!(relative_path(root, Some(root), Some(home)), "codexline");
+        assert_eq!(
+            relative_path(cwd, None, Some(home)),
+            native("~/projects/codexline/src")
+        );
+    }
+
+    #[test]
+    fn min_width_pads_values_by_alignment() {
+        let mut segment = crate::config::default_segment_for(SegmentId::Context);
+        assert_eq!(pad_value(&segment, "9%".to_string()), "9%");
+
+        segment
+            .options
+            .insert("min_width".to_string(), serde_json::Value::from(4));
+        assert_eq!(pad_value(&segment, "9%".to_string()), "9%  ");
+        segment.options.insert("align".to_string(), "right".into());
+        assert_eq!(pad_value(&segment, "9%".to_string()), "  9%");
+        segment.options.insert("align".to_string(), "center".into());
+        assert_eq!(pad_value(&segment, "9%".to_string()), " 9% ");
+        assert_eq!(pad_value(&segment, "100%".to_string()), "100%");
+        assert_eq!(pad_value(&segment, "1000%".to_string()), "1000%");
+    }
+
+    #[test]
+    fn warnings_list_degraded_collectors() {
+        let mut cfg = Config::default();
+        cfg.style.mode = StyleMode::Plain;
+        for segment in &mut cfg.segments {
+            segment.enabled = segment.id == SegmentId::Warnings;
+        }
+        let mut ctx = StatusContext::new(chrono::Utc::now(), std::path::PathBuf::from("/work"));
+        assert!(build_segments(&cfg, &ctx).is_empty());
+
+        ctx.degraded.push(CollectorId::Git);
+        ctx.warnings.push(crate::context::CollectWarning {
+            collector: CollectorId::Rollout,
+            kind: WarningKind::Missing,
+        });
+        ctx.warnings.push(crate::context::CollectWarning {
+            collector: CollectorId::GitHub,
+            kind: WarningKind::Stale,
+        });
+        let pieces = build_segments(&cfg, &ctx);
+        assert_eq!(
+            pieces[0].plain_text(),
+            "WARN git timeout, rollout missing, github stale"
+        );
+    }
+
+    #[test]
+    fn cost_prices_the_session_tokens_by_model() {
+        let mut cfg = Config::default();
+        let mut ctx = crate::fixtures::demo_context(chrono::Utc::now());
+        let numbers = NumberFormat::default();
+        assert_eq!(render_cost(&numbers, &cfg, &ctx).as_deref(), Some("$0.37"));
+
+        cfg.pricing.insert(
+            "gpt-5-codex".to_string(),
+            crate::config::ModelPrice {
+                input: 2.0,
+                output: 20.0,
+            },
+        );
+        assert_eq!(render_cost(&numbers, &cfg, &ctx).as_deref(), Some("$0.65"));
+
+        ctx.model = Some("local-llama".to_string());
+        assert_eq!(render_cost(&numbers, &cfg, &ctx), None);
+    }
+
+    #[test]
+    fn tokens_show_cached_and_reasoning_on_request() {
+        let mut segment = crate::config::default_segment_for(SegmentId::Tokens);
+        let ctx = crate::fixtures::demo_context(chrono::Utc::now());
+        let numbers = NumberFormat::default();
+        assert_eq!(
+            render_tokens(&numbers, &segment, &ctx).as_deref(),
+            Some("184.3K in 14.2K out 198.6K total")
+        );
+
+        segment
+            .options
+            .insert("show_cached".to_string(), true.into());
+        segment
+            .options
+            .insert("show_reasoning".to_string(), true.into());
+        assert_eq!(
+            render_tokens(&numbers, &segment, &ctx).as_deref(),
+            Some("184.3K in (61.4K cached) 14.2K out (3.1K reasoning) 198.6K total")
+        );
     }
 }
@@ -0,0 +1,102 @@
+use anyhow::{Context, Result};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// How long a client may take to send its request line or read the response.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Loopback-only HTTP endpoint serving the latest statusline JSON, for editor status-bar
+/// extensions that poll instead of spawning codexline on every refresh. No CORS header is
+/// sent, so web pages open in a browser cannot read the payload (cwd, branch, session title).
+pub struct StatusEndpoint {
+    addr: SocketAddr,
+    body: Arc<Mutex<String>>,
+}
+
+impl StatusEndpoint {
+    pub fn bind(port: u16) -> Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", port))
+            .with_context(|| format!("failed to listen on 127.0.0.1:{port}"))?;
+        let addr = listener.local_addr()?;
+        let body = Arc::new(Mutex::new(String::from("{}")));
+        let shared = Arc::clone(&body);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let payload = shared.lock().map(|b| b.clone()).unwrap_or_default();
+                // Each client on its own thread, so one that never sends a request cannot
+                // hold up the others; a client that hangs up must not stop the endpoint.
+                thread::spawn(move || {
+                    let _ = respond(stream, &payload);
+                });
+            }
+        });
+        Ok(Self { addr, body })
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    pub fn update(&self, json: String) {
+        if let Ok(mut body) = self.body.lock() {
+            *body = json;
+        }
+    }
+}
+
+fn respond(mut stream: TcpStream, payload: &str) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+    let mut request_line = String::new();
+    BufReader::new(&stream).read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let path = parts.next().unwrap_or_default();
+
+    let (status, body) = match (method, path) {
+        ("GET", "/" | "/status") => ("200 OK", payload),
+        ("GET", _) => ("404 Not Found", r#"{"error":"not found"}"#),
+        _ => (
+            "405 Method Not Allowed",
+            r#"{"error":"method not allowed"}"#,
+        ),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+         Cache-Control: no-store\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    stream.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    #[test]
+    fn endpoint_serves_latest_payload() {
+        let endpoint = StatusEndpoint::bind(0).expect("bind");
+        endpoint.update(r#"{"line":"gpt-5"}"#.to_string());
+
+        let get = |path: &str| {
+            let mut stream = TcpStream::connect(endpoint.addr()).expect("connect");
+            write!(stream, "GET {path} HTTP/1.1\r\nHost: localhost\r\n\r\n").expect("write");
+            let mut response = String::new();
+            stream.read_to_string(&mut response).expect("read");
+            response
+        };
+
+        // A client that connects and sends nothing does not block the others.
+        let _idle = TcpStream::connect(endpoint.addr()).expect("connect");
+        let status = get("/status");
+        assert!(status.starts_with("HTTP/1.1 200 OK"));
+        assert!(!status.contains("Access-Control-Allow-Origin"));
+        assert!(status.ends_with(r#"{"line":"gpt-5"}"#));
+        assert!(get("/other").starts_with("HTTP/1.1 404"));
+    }
+}
@@ -0,0 +1,271 @@
+use crate::context::StatusContext;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// SQLite usage history (`usage.sqlite`), driven through the `sqlite3` CLI like `curl` and `git`
+/// elsewhere, so the feature adds no native dependency to the build.
+const SCHEMA: &str = "CREATE TABLE IF NOT EXISTS usage_snapshots (
+    ts TEXT NOT NULL,
+    session TEXT,
+    model TEXT,
+    input_tokens INTEGER,
+    output_tokens INTEGER,
+    total_tokens INTEGER,
+    context_used_percent INTEGER,
+    primary_used_percent REAL,
+    secondary_used_percent REAL
+);
+CREATE INDEX IF NOT EXISTS usage_snapshots_ts ON usage_snapshots (ts);";
+
+/// Usage aggregated per UTC day. Token counts are cumulative per session, so a day's total is
+/// the sum of each session's highest count seen that day.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DailyUsage {
+    pub day: String,
+    pub sessions: u32,
+    pub total_tokens: i64,
+    pub peak_primary_used_percent: Option<f64>,
+}
+
+/// Inserts one snapshot; snapshots without token or rate-limit data are skipped.
+pub fn record(path: &Path, ctx: &StatusContext) -> Result<()> {
+    if ctx.usage.is_none() && ctx.limits.is_none() {
+        return Ok(());
+    }
+    let usage = ctx.usage.as_ref();
+    let limits = ctx.limits.as_ref();
+    let values = [
+        sql_text(Some(&ctx.now.to_rfc3339())),
+        sql_text(
+            ctx.session
+                .as_ref()
+                .and_then(|session| session.thread_id.as_deref()),
+        ),
+        sql_text(ctx.model.as_deref()),
+        sql_number(usage.map(|usage| usage.input_tokens)),
+        sql_number(usage.map(|usage| usage.output_tokens)),
+        sql_number(usage.map(|usage| usage.total_tokens)),
+        sql_number(usage.and_then(|usage| usage.used_percent)),
+        sql_number(limits.and_then(|limits| limits.primary_used_percent)),
+        sql_number(limits.and_then(|limits| limits.secondary_used_percent)),
+    ];
+    let sql = format!(
+        "{SCHEMA}\nINSERT INTO usage_snapshots VALUES ({});\n",
+        values.join(", ")
+    );
+    run_sqlite(path, &[], &sql).map(|_| ())
+}
+
+/// Daily totals for the last `days` days, oldest first.
+pub fn daily_usage(path: &Path, days: u32) -> Result<Vec<DailyUsage>> {
+    query(
+        path,
+        &format!(
+            "SELECT day, COUNT(*) AS sessions, SUM(tokens) AS total_tokens,
+       MAX(peak_primary) AS peak_primary_used_percent
+FROM (
+    SELECT substr(ts, 1, 10) AS day, session,
+           COALESCE(MAX(total_tokens), 0) AS tokens,
+           MAX(primary_used_percent) AS peak_primary
+    FROM usage_snapshots
+    WHERE ts >= strftime('%Y-%m-%d', 'now', '-{days} days')
+    GROUP BY day, session
+)
+GROUP BY day ORDER BY day;"
+        ),
+    )
+}
+
+/// Usage of one model over a range of days.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(not(feature = "tui"), allow(dead_code))] // only the dashboard reads these
+pub struct ModelUsage {
+    pub model: String,
+    pub sessions: u32,
+    pub total_tokens: i64,
+}
+
+/// Tokens per model over the last `days` days, largest first, counted like [`daily_usage`].
+#[cfg_attr(not(feature = "tui"), allow(dead_code))]
+pub fn model_usage(path: &Path, days: u32) -> Result<Vec<ModelUsage>> {
+    query(
+        path,
+        &format!(
+            "SELECT model, COUNT(*) AS sessions, SUM(tokens) AS total_tokens
+FROM (
+    SELECT COALESCE(model, 'unknown') AS model, session,
+           COALESCE(MAX(total_tokens), 0) AS tokens
+    FROM usage_snapshots
+    WHERE ts >= strftime('%Y-%m-%d', 'now', '-{days} days')
+    GROUP BY substr(ts, 1, 10), model, session
+)
+GROUP BY model ORDER BY total_tokens DESC, model;"
+        ),
+    )
+}
+
+/// Highest rate-limit usage seen in each UTC hour (`YYYY-MM-DDTHH`) that has any.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(not(feature = "tui"), allow(dead_code))]
+pub struct LimitPoint {
+    pub hour: String,
+    pub primary_used_percent: Option<f64>,
+    pub secondary_used_percent: Option<f64>,
+}
+
+/// Hourly rate-limit trajectory over the last `days` days, oldest first.
+#[cfg_attr(not(feature = "tui"), allow(dead_code))]
+pub fn limit_trajectory(path: &Path, days: u32) -> Result<Vec<LimitPoint>> {
+    query(
+        path,
+        &format!(
+            "SELECT substr(ts, 1, 13) AS hour,
+       MAX(primary_used_percent) AS primary_used_percent,
+       MAX(secondary_used_percent) AS secondary_used_percent
+FROM usage_snapshots
+WHERE ts >= strftime('%Y-%m-%d', 'now', '-{days} days')
+  AND (primary_used_percent IS NOT NULL OR secondary_used_percent IS NOT NULL)
+GROUP BY hour ORDER BY hour;"
+        ),
+    )
+}
+
+/// Rows of a `SELECT` against the store; a missing store has none.
+fn query<T: serde::de::DeserializeOwned>(path: &Path, select: &str) -> Result<Vec<T>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let output = run_sqlite(path, &["-json"], &format!("{SCHEMA}\n{select}\n"))?;
+    if output.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    serde_json::from_str(&output).context("failed to parse sqlite3 output")
+}
+
+fn run_sqlite(path: &Path, args: &[&str], sql: &str) -> Result<String> {
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create dir: {}", parent.display()))?;
+    }
+    let mut child = Command::new("sqlite3")
+        .args(args)
+        .arg(path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("failed to run sqlite3 (is it installed?)")?;
+    child
+        .stdin
+        .take()
+        .context("sqlite3 stdin unavailable")?
+        .write_all(sql.as_bytes())?;
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        bail!(
+            "sqlite3 failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn sql_text(value: Option<&str>) -> String {
+    match value {
+        Some(value) => format!("'{}'", value.replace('\'', "''")),
+        None => "NULL".to_string(),
+    }
+}
+
+fn sql_number<T: ToString>(value: Option<T>) -> String {
+    value.map_or_else(|| "NULL".to_string(), |value| value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::{RateLimitSnapshot, SessionMetaSnapshot, TokenUsageSnapshot};
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    fn snapshot(session: &str, model: &str, total_tokens: i64) -> StatusContext {
+        StatusContext {
+            model: Some(model.to_string()),
+            usage: Some(TokenUsageSnapshot {
+                input_tokens: total_tokens,
+                output_tokens: 0,
+                cached_input_tokens: 0,
+                reasoning_output_tokens: 0,
+                total_tokens,
+                model_context_window: None,
+                used_percent: None,
+                remaining_percent: None,
+            }),
+            session: Some(SessionMetaSnapshot {
+                thread_id: Some(session.to_string()),
+                cli_version: None,
+                model_provider: None,
+                title: None,
+            }),
+            ..StatusContext::new(chrono::Utc::now(), PathBuf::from("/work"))
+        }
+    }
+
+    #[test]
+    fn daily_usage_sums_latest_count_per_session() {
+        let dir = TempDir::new().expect("temp dir");
+        let path = dir.path().join("usage.db");
+        record(&path, &snapshot("a'1", "gpt-5", 100)).expect("record");
+        record(&path, &snapshot("a'1", "gpt-5", 250)).expect("record");
+        record(&path, &snapshot("b", "gpt-5", 40)).expect("record");
+
+        let days = daily_usage(&path, 1).expect("query");
+        assert_eq!(days.len(), 1);
+        assert_eq!(days[0].sessions, 2);
+        assert_eq!(days[0].total_tokens, 290);
+        assert_eq!(days[0].peak_primary_used_percent, None);
+    }
+
+    #[test]
+    fn model_usage_and_limit_trajectory() {
+        let dir = TempDir::new().expect("temp dir");
+        let path = dir.path().join("usage.db");
+        record(&path, &snapshot("a", "gpt-5", 100)).expect("record");
+        record(&path, &snapshot("b", "gpt-5-codex", 300)).expect("record");
+        let mut limited = snapshot("b", "gpt-5-codex", 400);
+        limited.limits = Some(RateLimitSnapshot {
+            primary_used_percent: Some(30.0),
+            secondary_used_percent: Some(5.0),
+        });
+        record(&path, &limited).expect("record");
+
+        let models = model_usage(&path, 1).expect("query");
+        assert_eq!(
+            models,
+            vec![
+                ModelUsage {
+                    model: "gpt-5-codex".to_string(),
+                    sessions: 1,
+                    total_tokens: 400,
+                },
+                ModelUsage {
+                    model: "gpt-5".to_string(),
+                    sessions: 1,
+                    total_tokens: 100,
+                },
+            ]
+        );
+        let points = limit_trajectory(&path, 1).expect("query");
+        assert_eq!(points.len(), 1);
+        assert_eq!(points[0].primary_used_percent, Some(30.0));
+        assert!(model_usage(&dir.path().join("missing.db"), 1)
+            .expect("query")
+            .is_empty());
+    }
+}
@@ -0,0 +1,161 @@
+use crate::config::Config;
+use crate::context::StatusContext;
+use serde_json::{json, Value};
+use std::process::{Command, Stdio};
+use std::thread;
+
+/// Pushes the current usage and rate-limit gauges to an OTLP/HTTP collector.
+///
+/// Runs `curl` in the background without waiting so the statusline is never delayed, and reaps
+/// it on a detached thread; export failures are ignored.
+pub fn export(cfg: &Config, ctx: &StatusContext) {
+    if !cfg.telemetry.enabled {
+        return;
+    }
+    let Some(endpoint) = cfg
+        .telemetry
+        .otlp_endpoint
+        .clone()
+        .or_else(|| std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT").ok())
+    else {
+        return;
+    };
+    let Some(payload) = metrics_payload(ctx) else {
+        return;
+    };
+
+    let url = format!("{}/v1/metrics", endpoint.trim_end_matches('/'));
+    let Ok(mut child) = Command::new("curl")
+        .args(["-fsS", "--max-time", "2", "-X", "POST"])
+        .args(["-H", "Content-Type: application/json"])
+        .args(["--data-binary", &payload.to_string(), &url])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+    else {
+        return;
+    };
+    // Reaped in the background so `--watch` does not collect zombies.
+    thread::spawn(move || {
+        let _ = child.wait();
+    });
+}
+
+/// OTLP JSON `ExportMetricsServiceRequest` with one gauge per known value, or `None` when the
+/// rollout had nothing to report.
+fn metrics_payload(ctx: &StatusContext) -> Option<Value> {
+    let mut gauges: Vec<(&str, &str, Value)> = Vec::new();
+    if let Some(usage) = &ctx.usage {
+        gauges.push((
+            "codex.tokens.input",
+            "{token}",
+            json!({ "asInt": usage.input_tokens.to_string() }),
+        ));
+        gauges.push((
+            "codex.tokens.output",
+            "{token}",
+            json!({ "asInt": usage.output_tokens.to_string() }),
+        ));
+        gauges.push((
+            "codex.tokens.total",
+            "{token}",
+            json!({ "asInt": usage.total_tokens.to_string() }),
+        ));
+        if let Some(used) = usage.used_percent {
+            gauges.push((
+                "codex.context.used",
+                "%",
+                json!({ "asDouble": used as f64 }),
+            ));
+        }
+    }
+    if let Some(limits) = &ctx.limits {
+        if let Some(primary) = limits.primary_used_percent {
+            gauges.push((
+                "codex.rate_limit.primary.used",
+                "%",
+                json!({ "asDouble": primary }),
+            ));
+        }
+        if let Some(secondary) = limits.secondary_used_percent {
+            gauges.push((
+                "codex.rate_limit.secondary.used",
+                "%",
+                json!({ "asDouble": secondary }),
+            ));
+        }
+    }
+    if gauges.is_empty() {
+        return None;
+    }
+
+    let time = ctx
+        .now
+        .timestamp_nanos_opt()
+        .unwrap_or_default()
+        .to_string();
+    let mut attributes = Vec::new();
+    if let Some(model) = &ctx.model {
+        attributes.push(json!({ "key": "codex.model", "value": { "stringValue": model } }));
+    }
+    if let Some(thread) = ctx.session.as_ref().and_then(|s| s.thread_id.as_ref()) {
+        attributes.push(json!({ "key": "codex.session.id", "value": { "stringValue": thread } }));
+    }
+
+    let metrics: Vec<Value> = gauges
+        .into_iter()
+        .map(|(name, unit, value)| {
+            let mut point = json!({ "timeUnixNano": time, "attributes": attributes });
+            if let (Some(point), Some(value)) = (point.as_object_mut(), value.as_object()) {
+                point.extend(value.clone());
+            }
+            json!({ "name": name, "unit": unit, "gauge": { "dataPoints": [point] } })
+        })
+        .collect();
+
+    Some(json!({
+        "resourceMetrics": [{
+            "resource": {
+                "attributes": [
+                    { "key": "service.name", "value": { "stringValue": "codexline" } },
+                    { "key": "service.version", "value": { "stringValue": env!("CARGO_PKG_VERSION") } }
+                ]
+            },
+            "scopeMetrics": [{
+                "scope": { "name": "codexline" },
+                "metrics": metrics
+            }]
+        }]
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::TokenUsageSnapshot;
+
+    #[test]
+    fn metrics_payload_contains_token_gauges() {
+        let mut ctx = StatusContext {
+            model: Some("gpt-5".to_string()),
+            ..StatusContext::new(chrono::Utc::now(), std::path::PathBuf::from("/work"))
+        };
+        assert!(metrics_payload(&ctx).is_none());
+
+        ctx.usage = Some(TokenUsageSnapshot {
+            input_tokens: 10,
+            output_tokens: 5,
+            cached_input_tokens: 0,
+            reasoning_output_tokens: 0,
+            total_tokens: 15,
+            model_context_window: None,
+            used_percent: Some(40),
+            remaining_percent: Some(60),
+        });
+        let payload = metrics_payload(&ctx).expect("payload");
+        let metrics = &payload["resourceMetrics"][0]["scopeMetrics"][0]["metrics"];
+        assert_eq!(metrics.as_array().map(Vec::len), Some(4));
+        assert_eq!(metrics[2]["name"], "codex.tokens.total");
+        assert_eq!(metrics[2]["gauge"]["dataPoints"][0]["asInt"], "15");
+    }
+}
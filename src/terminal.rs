@@ -0,0 +1,276 @@
+use crate::config::StyleMode;
+use crate::patch_diagnose::CheckStatus;
+use serde::Serialize;
+use std::io::Write;
+use std::process::Command;
+
+/// Sets the terminal / tab title (OSC 0 via crossterm). Written to the controlling terminal,
+/// not stdout, so shells capturing the line with `$(codexline)` do not swallow the sequence.
+#[cfg(feature = "tui")]
+pub fn set_title(title: &str) {
+    let _ = crossterm::execute!(tty(), crossterm::terminal::SetTitle(title));
+}
+
+/// Sets the terminal / tab title with a raw OSC 0 sequence (builds without crossterm).
+#[cfg(not(feature = "tui"))]
+pub fn set_title(title: &str) {
+    let mut out = tty();
+    let _ = write!(out, "\x1b]0;{title}\x07").and_then(|_| out.flush());
+}
+
+/// Sets the iTerm2 session badge (`OSC 1337 ; SetBadgeFormat`), an overlay that stays visible
+/// without taking prompt space. Other terminals ignore the sequence.
+pub fn set_iterm2_badge(text: &str) {
+    let mut out = tty();
+    let _ = out
+        .write_all(iterm2_badge_sequence(text).as_bytes())
+        .and_then(|_| out.flush());
+}
+
+fn iterm2_badge_sequence(text: &str) -> String {
+    format!("\x1b]1337;SetBadgeFormat={}\x07", base64(text.as_bytes()))
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |acc, (i, byte)| {
+            acc | (u32::from(*byte) << (16 - 8 * i))
+        });
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Whether Nerd Font glyphs are expected to render, with the reason.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Support {
+    Likely(String),
+    Unlikely(String),
+    Unknown,
+}
+
+/// Guesses Nerd Font support from the terminal and, where fontconfig exists, the installed
+/// fonts. Terminals cannot be asked for their font, so this is a heuristic.
+pub fn nerd_font_support() -> Support {
+    nerd_font_support_from(|name| std::env::var(name).ok(), nerd_font_installed())
+}
+
+fn nerd_font_support_from(
+    env: impl Fn(&str) -> Option<String>,
+    installed: Option<bool>,
+) -> Support {
+    if env("TERM").as_deref() == Some("linux") {
+        return Support::Unlikely("Linux virtual console fonts have no Nerd Font glyphs".into());
+    }
+    // These terminals ship the Nerd Font symbols as a fallback font.
+    let program = env("TERM_PROGRAM").unwrap_or_default();
+    if program.eq_ignore_ascii_case("wezterm") || program.eq_ignore_ascii_case("ghostty") {
+        return Support::Likely(format!("{program} bundles the Nerd Font symbols"));
+    }
+    if env("KITTY_WINDOW_ID").is_some() {
+        return Support::Likely("kitty bundles the Nerd Font symbols".into());
+    }
+    match installed {
+        Some(true) => Support::Likely("a Nerd Font is installed (fc-list)".into()),
+        Some(false) => Support::Unlikely("no Nerd Font found by fc-list".into()),
+        None => Support::Unknown,
+    }
+}
+
+/// One `--doctor` terminal capability with its remediation hint.
+#[derive(Debug, Clone, Serialize)]
+pub struct Capability {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<&'static str>,
+}
+
+/// Truecolor, unicode width, powerline glyph (powerline mode only) and size checks for the
+/// current terminal; `line_width` is the rendered statusline in columns.
+pub fn capabilities(mode: StyleMode, line_width: usize) -> Vec<Capability> {
+    let env = |name: &str| std::env::var(name).ok();
+    let glyphs = (mode == StyleMode::Powerline).then(nerd_font_support);
+    capabilities_from(env, glyphs, size(), line_width)
+}
+
+fn capabilities_from(
+    env: impl Fn(&str) -> Option<String>,
+    powerline: Option<Support>,
+    size: Option<(u16, u16)>,
+    line_width: usize,
+) -> Vec<Capability> {
+    let ok = |name, detail: String| Capability {
+        name,
+        status: CheckStatus::Ok,
+        detail,
+        hint: None,
+    };
+    let warn = |name, detail: String, hint| Capability {
+        name,
+        status: CheckStatus::Warn,
+        detail,
+        hint: Some(hint),
+    };
+    let mut checks = Vec::new();
+
+    let colorterm = env("COLORTERM").unwrap_or_default();
+    // Informational: segment colors are the 16 named ANSI colors, which need no truecolor.
+    checks.push(if matches!(colorterm.as_str(), "truecolor" | "24bit") {
+        ok("truecolor", format!("COLORTERM={colorterm}"))
+    } else {
+        ok(
+            "truecolor",
+            "not announced by COLORTERM; not needed, segments use the 16 ANSI colors".to_string(),
+        )
+    });
+
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .into_iter()
+        .find_map(|name| env(name).filter(|value| !value.is_empty()))
+        .unwrap_or_default();
+    let utf8 = locale
+        .to_ascii_lowercase()
+        .replace('-', "")
+        .contains("utf8");
+    checks.push(if utf8 || env("WT_SESSION").is_some() {
+        ok("unicode", format!("UTF-8 locale ({locale})"))
+    } else {
+        warn(
+            "unicode",
+            format!("locale {locale:?} is not UTF-8, so glyph widths may be miscounted"),
+            "set LANG to a UTF-8 locale such as en_US.UTF-8, or use style.mode = \"plain\"",
+        )
+    });
+
+    match powerline {
+        Some(Support::Unlikely(reason)) => checks.push(warn(
+            "powerline_glyphs",
+            reason,
+            "install a Nerd Font or Powerline-patched font, or use style.mode = \"nerd_font\"",
+        )),
+        Some(Support::Likely(reason)) => checks.push(ok("powerline_glyphs", reason)),
+        Some(Support::Unknown) => checks.push(ok(
+            "powerline_glyphs",
+            "could not detect the terminal font".to_string(),
+        )),
+        None => {}
+    }
+
+    checks.push(match size {
+        Some((columns, rows)) if line_width > usize::from(columns) => warn(
+            "size",
+            format!("{columns}x{rows}, narrower than the {line_width}-column statusline"),
+            "disable segments or shorten style.separator so the line fits",
+        ),
+        Some((columns, rows)) => ok("size", format!("{columns}x{rows}")),
+        None => ok("size", "not a terminal".to_string()),
+    });
+    checks
+}
+
+/// Columns and rows of the terminal, falling back to `COLUMNS` / `LINES`.
+fn size() -> Option<(u16, u16)> {
+    #[cfg(feature = "tui")]
+    if let Ok(size) = crossterm::terminal::size() {
+        return Some(size);
+    }
+    let var = |name| std::env::var(name).ok()?.parse().ok();
+    Some((var("COLUMNS")?, var("LINES")?))
+}
+
+/// `fc-list` lookup; `None` without fontconfig (macOS, Windows).
+fn nerd_font_installed() -> Option<bool> {
+    let output = Command::new("fc-list")
+        .args([":", "family"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let families = String::from_utf8_lossy(&output.stdout).to_ascii_lowercase();
+    Some(families.contains("nerd font") || families.contains("nerdfont"))
+}
+
+fn tty() -> Box<dyn Write> {
+    #[cfg(unix)]
+    if let Ok(file) = std::fs::OpenOptions::new().write(true).open("/dev/tty") {
+        return Box::new(file);
+    }
+    Box::new(std::io::stderr())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nerd_font_support_uses_terminal_then_installed_fonts() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(key, _)| *key == name)
+                    .map(|(_, value)| value.to_string())
+            }
+        };
+        assert!(matches!(
+            nerd_font_support_from(env(&[("TERM", "linux")]), Some(true)),
+            Support::Unlikely(_)
+        ));
+        assert!(matches!(
+            nerd_font_support_from(env(&[("TERM_PROGRAM", "WezTerm")]), Some(false)),
+            Support::Likely(_)
+        ));
+        assert!(matches!(
+            nerd_font_support_from(env(&[("TERM_PROGRAM", "Apple_Terminal")]), Some(false)),
+            Support::Unlikely(_)
+        ));
+        assert_eq!(nerd_font_support_from(env(&[]), None), Support::Unknown);
+    }
+
+    #[test]
+    fn capabilities_warn_with_hints() {
+        let vars = [("COLORTERM", "truecolor"), ("LANG", "C")];
+        let env = |name: &str| {
+            vars.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.to_string())
+        };
+        let checks = capabilities_from(
+            env,
+            Some(Support::Unlikely("no Nerd Font".into())),
+            Some((40, 20)),
+            60,
+        );
+        let status = |name| {
+            let check = checks.iter().find(|check| check.name == name).expect(name);
+            (
+                matches!(check.status, CheckStatus::Ok),
+                check.hint.is_some(),
+            )
+        };
+        assert_eq!(status("truecolor"), (true, false));
+        assert_eq!(status("unicode"), (false, true));
+        assert_eq!(status("powerline_glyphs"), (false, true));
+        assert_eq!(status("size"), (false, true));
+    }
+
+    #[test]
+    fn iterm2_badge_is_base64_encoded() {
+        assert_eq!(base64(b"ctx 42%"), "Y3R4IDQyJQ==");
+        assert_eq!(base64(b"abc"), "YWJj");
+        assert_eq!(
+            iterm2_badge_sequence("ctx"),
+            "\x1b]1337;SetBadgeFormat=Y3R4\x07"
+        );
+    }
+}
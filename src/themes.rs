@@ -1,11 +1,17 @@
 use crate::config::{
-    ColorConfig, Config, IconConfig, NamedColor, SegmentId, StyleConfig, StyleMode,
+    Color, ColorConfig, ColorDepth, ColorMode, Config, IconConfig, NamedColor, SegmentId,
+    StyleConfig, StyleMode,
 };
+use crate::render::POWERLINE_ARROW;
 use anyhow::{bail, Context, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
+#[cfg(feature = "tui")]
+use std::path::PathBuf;
+#[cfg(feature = "tui")]
+use std::process::Command;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ThemeSpec {
@@ -38,6 +44,7 @@ pub fn builtin_theme_names() -> Vec<String> {
     ]
 }
 
+#[cfg(feature = "tui")]
 pub fn list_theme_names(themes_dir: &Path) -> Result<Vec<String>> {
     let mut names: Vec<String> = builtin_theme_names();
 
@@ -98,8 +105,13 @@ pub fn apply_theme(config: &Config, theme_name: &str, themes_dir: &Path) -> Resu
     let mut merged = config.clone();
     merged.theme = theme_name.to_string();
 
-    if let Some(style) = theme.style {
-        merged.style = style;
+    if let Some(style) = theme.style.filter(|_| !merged.style.locked) {
+        merged.style = StyleConfig {
+            ssh_theme: merged.style.ssh_theme.take(),
+            color: merged.style.color,
+            color_depth: merged.style.color_depth,
+            ..style
+        };
     }
 
     let mut by_id: HashMap<SegmentId, usize> = HashMap::new();
@@ -139,6 +151,62 @@ pub fn load_theme(theme_name: &str, themes_dir: &Path) -> Result<Option<ThemeSpe
     Ok(Some(theme))
 }
 
+#[cfg(feature = "tui")]
+/// Reads theme TOML from pasted text, an `http(s)://` URL (fetched with `curl`), or a file path.
+pub fn read_theme_source(source: &str) -> Result<String> {
+    let source = source.trim();
+    if source.contains('\n') || source.contains('=') {
+        return Ok(source.to_string());
+    }
+    if source.starts_with("http://") || source.starts_with("https://") {
+        let output = Command::new("curl")
+            .args(["-fsSL", "--max-time", "10", source])
+            .output()
+            .context("failed to run curl")?;
+        if !output.status.success() {
+            bail!(
+                "failed to download theme: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        return String::from_utf8(output.stdout).context("theme download is not UTF-8");
+    }
+    let path = PathBuf::from(source);
+    fs::read_to_string(&path)
+        .with_context(|| format!("failed to read theme file: {}", path.display()))
+}
+
+#[cfg(feature = "tui")]
+/// Validates theme TOML and writes it to `<themes_dir>/<name>.toml`, returning the theme name.
+pub fn import_theme(content: &str, themes_dir: &Path) -> Result<String> {
+    let theme: ThemeSpec = toml::from_str(content).context("failed to parse theme TOML")?;
+    let name = theme.name.trim();
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        bail!(
+            "invalid theme name {:?}: use letters, digits, '-' or '_'",
+            theme.name
+        );
+    }
+    if builtin_theme(name).is_some() {
+        bail!("theme name {} is reserved for a built-in theme", name);
+    }
+
+    let path = themes_dir.join(format!("{}.toml", name));
+    if path.exists() {
+        bail!("theme already exists: {}", path.display());
+    }
+    fs::create_dir_all(themes_dir)
+        .with_context(|| format!("failed to create themes dir: {}", themes_dir.display()))?;
+    let text = toml::to_string_pretty(&theme).context("failed to serialize theme")?;
+    fs::write(&path, text)
+        .with_context(|| format!("failed to write theme file: {}", path.display()))?;
+    Ok(name.to_string())
+}
+
 pub fn builtin_theme(name: &str) -> Option<ThemeSpec> {
     match name {
         "default" => Some(default_theme()),
@@ -159,6 +227,12 @@ fn default_theme() -> ThemeSpec {
         style: Some(StyleConfig {
             mode: StyleMode::NerdFont,
             separator: " · ".to_string(),
+            locked: false,
+            ssh_theme: None,
+            color: ColorMode::Auto,
+            color_depth: ColorDepth::Auto,
+            left_cap: String::new(),
+            right_cap: POWERLINE_ARROW.to_string(),
         }),
         segments: vec![],
     }
@@ -170,6 +244,12 @@ fn minimal_theme() -> ThemeSpec {
         style: Some(StyleConfig {
             mode: StyleMode::Plain,
             separator: " | ".to_string(),
+            locked: false,
+            ssh_theme: None,
+            color: ColorMode::Auto,
+            color_depth: ColorDepth::Auto,
+            left_cap: String::new(),
+            right_cap: POWERLINE_ARROW.to_string(),
         }),
         segments: vec![],
     }
@@ -181,6 +261,12 @@ fn gruvbox_theme() -> ThemeSpec {
         style: Some(StyleConfig {
             mode: StyleMode::NerdFont,
             separator: " ❯ ".to_string(),
+            locked: false,
+            ssh_theme: None,
+            color: ColorMode::Auto,
+            color_depth: ColorDepth::Auto,
+            left_cap: String::new(),
+            right_cap: POWERLINE_ARROW.to_string(),
         }),
         segments: vec![
             seg_color(SegmentId::Model, NamedColor::BrightYellow),
@@ -199,6 +285,12 @@ fn nord_theme() -> ThemeSpec {
         style: Some(StyleConfig {
             mode: StyleMode::NerdFont,
             separator: " • ".to_string(),
+            locked: false,
+            ssh_theme: None,
+            color: ColorMode::Auto,
+            color_depth: ColorDepth::Auto,
+            left_cap: String::new(),
+            right_cap: POWERLINE_ARROW.to_string(),
         }),
         segments: vec![
             seg_color(SegmentId::Model, NamedColor::Cyan),
@@ -217,14 +309,24 @@ fn powerline_dark_theme() -> ThemeSpec {
         style: Some(StyleConfig {
             mode: StyleMode::Powerline,
             separator: "  ".to_string(),
+            locked: false,
+            ssh_theme: None,
+            color: ColorMode::Auto,
+            color_depth: ColorDepth::Auto,
+            left_cap: String::new(),
+            right_cap: POWERLINE_ARROW.to_string(),
         }),
         segments: vec![
-            seg_color(SegmentId::Model, NamedColor::BrightWhite),
-            seg_color(SegmentId::Cwd, NamedColor::BrightBlue),
-            seg_color(SegmentId::Git, NamedColor::BrightMagenta),
-            seg_color(SegmentId::Context, NamedColor::BrightYellow),
-            seg_color(SegmentId::Tokens, NamedColor::BrightGreen),
-            seg_color(SegmentId::Limits, NamedColor::BrightRed),
+            seg_on(
+                SegmentId::Model,
+                NamedColor::BrightWhite,
+                NamedColor::BrightBlack,
+            ),
+            seg_on(SegmentId::Cwd, NamedColor::BrightWhite, NamedColor::Blue),
+            seg_on(SegmentId::Git, NamedColor::BrightWhite, NamedColor::Magenta),
+            seg_on(SegmentId::Context, NamedColor::Black, NamedColor::Yellow),
+            seg_on(SegmentId::Tokens, NamedColor::Black, NamedColor::Green),
+            seg_on(SegmentId::Limits, NamedColor::BrightWhite, NamedColor::Red),
         ],
     }
 }
@@ -235,61 +337,105 @@ fn powerline_light_theme() -> ThemeSpec {
         style: Some(StyleConfig {
             mode: StyleMode::Powerline,
             separator: "  ".to_string(),
+            locked: false,
+            ssh_theme: None,
+            color: ColorMode::Auto,
+            color_depth: ColorDepth::Auto,
+            left_cap: String::new(),
+            right_cap: POWERLINE_ARROW.to_string(),
         }),
         segments: vec![
-            seg_color(SegmentId::Model, NamedColor::Blue),
-            seg_color(SegmentId::Cwd, NamedColor::Cyan),
-            seg_color(SegmentId::Git, NamedColor::Magenta),
-            seg_color(SegmentId::Context, NamedColor::Yellow),
-            seg_color(SegmentId::Tokens, NamedColor::Green),
-            seg_color(SegmentId::Limits, NamedColor::Red),
+            seg_on(SegmentId::Model, NamedColor::BrightWhite, NamedColor::Blue),
+            seg_on(SegmentId::Cwd, NamedColor::Black, NamedColor::BrightCyan),
+            seg_on(SegmentId::Git, NamedColor::BrightWhite, NamedColor::Magenta),
+            seg_on(
+                SegmentId::Context,
+                NamedColor::Black,
+                NamedColor::BrightYellow,
+            ),
+            seg_on(
+                SegmentId::Tokens,
+                NamedColor::Black,
+                NamedColor::BrightGreen,
+            ),
+            seg_on(SegmentId::Limits, NamedColor::BrightWhite, NamedColor::Red),
         ],
     }
 }
 
 fn powerline_rose_pine_theme() -> ThemeSpec {
+    let base = Color::Rgb(0x19, 0x17, 0x24);
+    let text = Color::Rgb(0xe0, 0xde, 0xf4);
     ThemeSpec {
         name: "powerline-rose-pine".to_string(),
         style: Some(StyleConfig {
             mode: StyleMode::Powerline,
             separator: "  ".to_string(),
+            locked: false,
+            ssh_theme: None,
+            color: ColorMode::Auto,
+            color_depth: ColorDepth::Auto,
+            left_cap: "\u{e0b6}".to_string(),
+            right_cap: "\u{e0b4}".to_string(),
         }),
         segments: vec![
-            seg_color(SegmentId::Model, NamedColor::BrightMagenta),
-            seg_color(SegmentId::Cwd, NamedColor::BrightCyan),
-            seg_color(SegmentId::Git, NamedColor::BrightYellow),
-            seg_color(SegmentId::Context, NamedColor::BrightBlue),
-            seg_color(SegmentId::Tokens, NamedColor::BrightGreen),
-            seg_color(SegmentId::Limits, NamedColor::BrightRed),
+            seg_on(SegmentId::Model, base, Color::Rgb(0xc4, 0xa7, 0xe7)),
+            seg_on(SegmentId::Cwd, base, Color::Rgb(0x9c, 0xcf, 0xd8)),
+            seg_on(SegmentId::Git, text, Color::Rgb(0x31, 0x74, 0x8f)),
+            seg_on(SegmentId::Context, base, Color::Rgb(0xf6, 0xc1, 0x77)),
+            seg_on(SegmentId::Tokens, base, Color::Rgb(0xeb, 0xbc, 0xba)),
+            seg_on(SegmentId::Limits, base, Color::Rgb(0xeb, 0x6f, 0x92)),
         ],
     }
 }
 
 fn powerline_tokyo_night_theme() -> ThemeSpec {
+    let base = Color::Rgb(0x1a, 0x1b, 0x26);
+    let text = Color::Rgb(0xc0, 0xca, 0xf5);
     ThemeSpec {
         name: "powerline-tokyo-night".to_string(),
         style: Some(StyleConfig {
             mode: StyleMode::Powerline,
             separator: "  ".to_string(),
+            locked: false,
+            ssh_theme: None,
+            color: ColorMode::Auto,
+            color_depth: ColorDepth::Auto,
+            left_cap: String::new(),
+            right_cap: POWERLINE_ARROW.to_string(),
         }),
         segments: vec![
-            seg_color(SegmentId::Model, NamedColor::BrightCyan),
-            seg_color(SegmentId::Cwd, NamedColor::BrightBlue),
-            seg_color(SegmentId::Git, NamedColor::BrightMagenta),
-            seg_color(SegmentId::Context, NamedColor::BrightWhite),
-            seg_color(SegmentId::Tokens, NamedColor::BrightGreen),
-            seg_color(SegmentId::Limits, NamedColor::BrightRed),
+            seg_on(SegmentId::Model, base, Color::Rgb(0x7d, 0xcf, 0xff)),
+            seg_on(SegmentId::Cwd, base, Color::Rgb(0x7a, 0xa2, 0xf7)),
+            seg_on(SegmentId::Git, base, Color::Rgb(0xbb, 0x9a, 0xf7)),
+            seg_on(SegmentId::Context, text, Color::Rgb(0x41, 0x48, 0x68)),
+            seg_on(SegmentId::Tokens, base, Color::Rgb(0x9e, 0xce, 0x6a)),
+            seg_on(SegmentId::Limits, base, Color::Rgb(0xf7, 0x76, 0x8e)),
         ],
     }
 }
 
-fn seg_color(id: SegmentId, text: NamedColor) -> ThemeSegment {
+/// Powerline segment: `text` on `background`, icon included.
+fn seg_on(id: SegmentId, text: impl Into<Color>, background: impl Into<Color>) -> ThemeSegment {
+    let text = text.into();
     ThemeSegment {
         id,
         icon: None,
         colors: Some(ColorConfig {
             icon: Some(text),
             text: Some(text),
+            background: Some(background.into()),
+        }),
+    }
+}
+
+fn seg_color(id: SegmentId, text: NamedColor) -> ThemeSegment {
+    ThemeSegment {
+        id,
+        icon: None,
+        colors: Some(ColorConfig {
+            icon: Some(text.into()),
+            text: Some(text.into()),
             background: None,
         }),
     }
@@ -308,4 +454,29 @@ mod tests {
         let themed = apply_theme(&cfg, "minimal", dir.path()).expect("apply");
         assert_eq!(themed.style.mode, StyleMode::Plain);
     }
+
+    #[test]
+    fn apply_theme_keeps_locked_style() {
+        let mut cfg = Config::default();
+        cfg.style.locked = true;
+        cfg.style.separator = " >> ".to_string();
+        let dir = TempDir::new().expect("temp");
+        let themed = apply_theme(&cfg, "minimal", dir.path()).expect("apply");
+        assert_eq!(themed.style.mode, StyleMode::NerdFont);
+        assert_eq!(themed.style.separator, " >> ");
+    }
+
+    #[cfg(feature = "tui")]
+    #[test]
+    fn import_theme_writes_custom_theme_and_rejects_builtin_names() {
+        let dir = TempDir::new().expect("temp");
+        let content = "name = \"ocean\"\n\n[style]\nmode = \"plain\"\n";
+
+        let name =
+            import_theme(&read_theme_source(content).expect("source"), dir.path()).expect("import");
+        assert_eq!(name, "ocean");
+        assert!(load_theme("ocean", dir.path()).expect("load").is_some());
+        assert!(import_theme(content, dir.path()).is_err());
+        assert!(import_theme("name = \"nord\"\n", dir.path()).is_err());
+    }
 }
@@ -0,0 +1,197 @@
+//! Troubleshooting log: timed spans and events around collection, themes and rendering.
+//!
+//! Disabled unless `--log-file` is given or `CODEXLINE_LOG` is set. `CODEXLINE_LOG` is a
+//! comma-separated filter of `level` or `target=level` directives (`debug`,
+//! `collect=trace,render=off`); the levels are `off`, `error`, `warn`, `info`, `debug` and
+//! `trace`. Without a log file, output goes to stderr.
+
+use anyhow::{Context, Result};
+use chrono::{SecondsFormat, Utc};
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Error = 1,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Level {
+    fn as_str(self) -> &'static str {
+        match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE",
+        }
+    }
+}
+
+/// Parsed `CODEXLINE_LOG`; `None` levels mean `off`.
+#[derive(Debug, Clone, PartialEq)]
+struct Filter {
+    default: Option<Level>,
+    targets: Vec<(String, Option<Level>)>,
+}
+
+impl Filter {
+    fn parse(spec: &str) -> Result<Self> {
+        let mut filter = Filter {
+            default: None,
+            targets: Vec::new(),
+        };
+        for directive in spec.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            match directive.split_once('=') {
+                Some((target, level)) => filter
+                    .targets
+                    .push((target.trim().to_string(), parse_level(level)?)),
+                None => filter.default = parse_level(directive)?,
+            }
+        }
+        Ok(filter)
+    }
+
+    /// The longest matching target directive wins over the default level.
+    fn enabled(&self, target: &str, level: Level) -> bool {
+        let max = self
+            .targets
+            .iter()
+            .filter(|(prefix, _)| {
+                target
+                    .strip_prefix(prefix.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+            })
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map_or(self.default, |(_, level)| *level);
+        max.is_some_and(|max| level <= max)
+    }
+}
+
+fn parse_level(text: &str) -> Result<Option<Level>> {
+    Ok(match text.trim().to_ascii_lowercase().as_str() {
+        "off" => None,
+        "error" => Some(Level::Error),
+        "warn" => Some(Level::Warn),
+        "info" => Some(Level::Info),
+        "debug" => Some(Level::Debug),
+        "trace" => Some(Level::Trace),
+        other => anyhow::bail!("unknown log level {other:?} in CODEXLINE_LOG"),
+    })
+}
+
+struct Logger {
+    filter: Filter,
+    sink: Mutex<Box<dyn Write + Send>>,
+}
+
+static LOGGER: OnceLock<Logger> = OnceLock::new();
+
+/// Sets up logging for this process from `--log-file` and `CODEXLINE_LOG`; a log file without
+/// a filter records `debug` and above.
+pub fn init(log_file: Option<&Path>) -> Result<()> {
+    let spec = std::env::var("CODEXLINE_LOG").ok();
+    let filter = match (&spec, log_file) {
+        (Some(spec), _) => Filter::parse(spec)?,
+        (None, Some(_)) => Filter::parse("debug")?,
+        (None, None) => return Ok(()),
+    };
+    let sink: Box<dyn Write + Send> = match log_file {
+        Some(path) => Box::new(
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .with_context(|| format!("failed to open log file: {}", path.display()))?,
+        ),
+        None => Box::new(std::io::stderr()),
+    };
+    let _ = LOGGER.set(Logger {
+        filter,
+        sink: Mutex::new(sink),
+    });
+    event(
+        Level::Info,
+        "codexline",
+        format_args!(
+            "start version={} args={:?}",
+            env!("CARGO_PKG_VERSION"),
+            std::env::args().skip(1).collect::<Vec<_>>()
+        ),
+    );
+    Ok(())
+}
+
+/// Writes one log line when `target` is enabled at `level`.
+pub fn event(level: Level, target: &str, message: fmt::Arguments<'_>) {
+    let Some(logger) = LOGGER.get() else {
+        return;
+    };
+    if !logger.filter.enabled(target, level) {
+        return;
+    }
+    if let Ok(mut sink) = logger.sink.lock() {
+        let _ = writeln!(
+            sink,
+            "{} {:<5} {}: {}",
+            Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+            level.as_str(),
+            target,
+            message
+        );
+        let _ = sink.flush();
+    }
+}
+
+/// Logs `name` at `trace` on entry and with its duration at `debug` when dropped.
+pub struct Span {
+    target: &'static str,
+    name: &'static str,
+    started: Instant,
+}
+
+pub fn span(target: &'static str, name: &'static str) -> Span {
+    event(Level::Trace, target, format_args!("enter {name}"));
+    Span {
+        target,
+        name,
+        started: Instant::now(),
+    }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        event(
+            Level::Debug,
+            self.target,
+            format_args!(
+                "{} took {:.2}ms",
+                self.name,
+                self.started.elapsed().as_secs_f64() * 1000.0
+            ),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filter_prefers_the_most_specific_target() {
+        let filter = Filter::parse("info, collect=trace, collect.github=off").expect("filter");
+        assert!(filter.enabled("render", Level::Info));
+        assert!(!filter.enabled("render", Level::Debug));
+        assert!(filter.enabled("collect.git", Level::Trace));
+        assert!(!filter.enabled("collect.github", Level::Error));
+        assert!(!filter.enabled("collection", Level::Trace));
+        assert!(Filter::parse("loud").is_err());
+    }
+}
@@ -1,51 +1,103 @@
-use crate::config::{self, Config};
+use crate::collect::{self, CollectOptions, SessionSummary};
+use crate::config::{
+    self, ColorConfig, Config, NamedColor, OptionKind, OptionSpec, SegmentId, StyleConfig,
+    StyleMode,
+};
+use crate::glyphs;
+use crate::patch_diagnose::{CheckStatus, PatchCheck};
+use crate::profiles;
 use crate::render;
 use crate::segments;
+#[cfg(feature = "sqlite")]
+use crate::store;
 use crate::themes;
 use anyhow::{Context, Result};
-use crossterm::event::{self, Event, KeyCode, KeyEvent};
+use crossterm::event::{
+    self, DisableBracketedPaste, EnableBracketedPaste, Event, KeyCode, KeyEvent,
+};
 use crossterm::execute;
 use crossterm::terminal::{
     disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
 };
 use ratatui::backend::CrosstermBackend;
-use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
+#[cfg(feature = "sqlite")]
+use ratatui::widgets::{Bar, BarChart, BarGroup, Sparkline};
 use ratatui::widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph};
-use ratatui::Terminal;
+use ratatui::{Terminal, TerminalOptions, Viewport};
 use std::io::{self, Stdout};
+#[cfg(feature = "sqlite")]
+use std::path::Path;
 
 #[derive(Debug, Clone, Copy)]
 pub enum MainMenuAction {
     Render,
     Configure,
+    Sessions,
+    Profiles,
+    Diagnostics,
     Init,
     Check,
     Patch,
     Exit,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Focus {
     Themes,
     Segments,
     Actions,
 }
 
-pub fn run_main_menu() -> Result<MainMenuAction> {
+pub fn run_main_menu(cfg: &Config) -> Result<MainMenuAction> {
+    let preview = current_statusline(cfg);
     let mut guard = TerminalGuard::new()?;
     let mut selected = 0usize;
+    let mut show_help = false;
     let items = [
-        ("Render Statusline", "Render one-line status output now"),
-        ("Open Configurator", "Enter full TUI config editor"),
-        ("Init Config", "Create default config and themes"),
-        ("Check Config", "Validate current configuration"),
+        (
+            "Render Statusline",
+            "Render one-line status output now",
+            MainMenuAction::Render,
+        ),
+        (
+            "Open Configurator",
+            "Enter full TUI config editor",
+            MainMenuAction::Configure,
+        ),
+        (
+            "Sessions",
+            "Browse recent sessions and pin the statusline source",
+            MainMenuAction::Sessions,
+        ),
+        (
+            "Profiles",
+            "Create, rename, apply and delete named profiles",
+            MainMenuAction::Profiles,
+        ),
+        (
+            "Diagnostics",
+            "Run doctor and patch checks with details",
+            MainMenuAction::Diagnostics,
+        ),
+        (
+            "Init Config",
+            "Create default config and themes",
+            MainMenuAction::Init,
+        ),
+        (
+            "Check Config",
+            "Validate current configuration",
+            MainMenuAction::Check,
+        ),
         (
             "Patch Diagnostics",
             "Run Codex patch compatibility diagnostics",
+            MainMenuAction::Patch,
         ),
-        ("Exit", "Quit without action"),
+        ("Exit", "Quit without action", MainMenuAction::Exit),
     ];
 
     loop {
@@ -71,13 +123,14 @@ pub fn run_main_menu() -> Result<MainMenuAction> {
                     "Interactive Main Menu",
                     Style::default().fg(Color::Gray),
                 )),
+                preview.clone(),
             ])
             .block(Block::default().borders(Borders::ALL).title("Welcome"));
             frame.render_widget(header, chunks[0]);
 
             let list_items: Vec<ListItem> = items
                 .iter()
-                .map(|(title, desc)| {
+                .map(|(title, desc, _)| {
                     ListItem::new(Line::from(vec![
                         Span::styled(*title, Style::default().fg(Color::White)),
                         Span::raw(" - "),
@@ -94,13 +147,22 @@ pub fn run_main_menu() -> Result<MainMenuAction> {
                 .block(Block::default().borders(Borders::ALL).title("Actions"));
             frame.render_stateful_widget(list, chunks[1], &mut state);
 
-            let help = Paragraph::new("↑/↓ select, Enter confirm, q exit")
+            let help = Paragraph::new("↑/↓ select, Enter confirm, ? help, q exit")
                 .block(Block::default().borders(Borders::ALL).title("Help"));
             frame.render_widget(help, chunks[2]);
+
+            if show_help {
+                render_help_overlay(frame, "Main menu keys", MAIN_MENU_HELP);
+            }
         })?;
 
         if let Event::Key(key) = event::read()? {
+            if show_help {
+                show_help = false;
+                continue;
+            }
             match key.code {
+                KeyCode::Char('?') => show_help = true,
                 KeyCode::Up => {
                     if selected == 0 {
                         selected = items.len() - 1;
@@ -111,21 +173,10 @@ pub fn run_main_menu() -> Result<MainMenuAction> {
                 KeyCode::Down => {
                     selected = (selected + 1) % items.len();
                 }
-                KeyCode::Enter => {
-                    return Ok(match selected {
-                        0 => MainMenuAction::Render,
-                        1 => MainMenuAction::Configure,
-                        2 => MainMenuAction::Init,
-                        3 => MainMenuAction::Check,
-                        4 => MainMenuAction::Patch,
-                        _ => MainMenuAction::Exit,
-                    });
-                }
+                KeyCode::Enter => return Ok(items[selected].2),
                 KeyCode::Esc => return Ok(MainMenuAction::Exit),
-                KeyCode::Char(c) => {
-                    if c.to_string().eq_ignore_ascii_case("q") {
-                        return Ok(MainMenuAction::Exit);
-                    }
+                KeyCode::Char(c) if c.eq_ignore_ascii_case(&'q') => {
+                    return Ok(MainMenuAction::Exit);
                 }
                 _ => {}
             }
@@ -133,10 +184,12 @@ pub fn run_main_menu() -> Result<MainMenuAction> {
     }
 }
 
-pub fn run_configurator(base: &Config) -> Result<Option<Config>> {
+/// `options` apply to the preview, so `--config --demo` previews themes on the demo context.
+pub fn run_configurator(base: &Config, options: CollectOptions) -> Result<Option<Config>> {
+    config::ensure_themes_exist();
     let mut guard = TerminalGuard::new()?;
 
-    let themes_dir = config::themes_dir();
+    let themes_dir = base.themes_dir();
     let mut theme_names = themes::list_theme_names(&themes_dir)?;
     if theme_names.is_empty() {
         theme_names.push("default".to_string());
@@ -151,28 +204,34 @@ pub fn run_configurator(base: &Config) -> Result<Option<Config>> {
     let mut selected_segment = 0usize;
     let mut selected_action = 0usize;
     let mut focus = Focus::Segments;
-    let mut footer_message = String::from("Tab switch focus, Space toggle segment, J/K reorder, Enter run action, S save, R reset, Q quit");
+    let mut footer_message = String::from("Tab switch focus, Space toggle segment, J/K reorder, Enter edit options/run action, C colors, I icon, S save, R reset, ? help, Q quit");
 
-    let actions = ["Save", "Reset", "Quit"];
+    let actions = ["Edit Style", "Import Theme", "Save", "Reset", "Quit"];
+    let mut style_override: Option<StyleConfig> = None;
+    let mut theme_filter = ThemeFilter::default();
+    let mut popup: Option<Popup> = None;
 
     loop {
-        let preview_config =
-            themes::apply_theme(&base_config, &theme_names[theme_index], &themes_dir)
-                .unwrap_or_else(|_| base_config.clone());
-        let preview_context = crate::collect::collect(&preview_config)?.context;
-        let preview_segments = segments::build_segments(&preview_config, &preview_context);
-        let preview_text = render::render_line(&preview_config, &preview_segments, true);
+        let preview_config = merge_theme(
+            &base_config,
+            &theme_names[theme_index],
+            &themes_dir,
+            style_override.as_ref(),
+        )
+        .unwrap_or_else(|_| base_config.clone());
+        let preview_collection = collect::collect_with(&preview_config, options)?;
+        let preview_context = &preview_collection.context;
+        let preview_lines = mode_preview_lines(&preview_config, preview_context);
+
+        let changes = pending_changes(
+            base,
+            &base_config,
+            &theme_names[theme_index],
+            style_override.as_ref(),
+        );
 
         guard.terminal.draw(|frame| {
-            let area = frame.size();
-            let rows = Layout::default()
-                .direction(Direction::Vertical)
-                .constraints([
-                    Constraint::Length(3),
-                    Constraint::Min(10),
-                    Constraint::Length(5),
-                ])
-                .split(area);
+            let layout = configurator_layout(frame.size(), focus);
 
             let header = Paragraph::new(vec![Line::from(vec![
                 Span::styled(
@@ -186,35 +245,77 @@ pub fn run_configurator(base: &Config) -> Result<Option<Config>> {
                     format!("theme: {}", theme_names[theme_index]),
                     Style::default().fg(Color::Yellow),
                 ),
+                Span::raw("  "),
+                if changes.is_empty() {
+                    Span::styled("no changes", Style::default().fg(Color::DarkGray))
+                } else {
+                    Span::styled(
+                        format!("[modified] {}", changes.join(", ")),
+                        Style::default()
+                            .fg(Color::LightRed)
+                            .add_modifier(Modifier::BOLD),
+                    )
+                },
             ])])
             .block(Block::default().borders(Borders::ALL).title("Configurator"));
-            frame.render_widget(header, rows[0]);
+            if let Some(area) = layout.header {
+                frame.render_widget(header, area);
+            }
 
-            let cols = Layout::default()
-                .direction(Direction::Horizontal)
-                .constraints([
-                    Constraint::Percentage(28),
-                    Constraint::Percentage(44),
-                    Constraint::Percentage(28),
-                ])
-                .split(rows[1]);
+            if let Some(area) = layout.tabs {
+                let mut tabs: Vec<Span> = [
+                    (Focus::Themes, "Themes"),
+                    (Focus::Segments, "Segments"),
+                    (Focus::Actions, "Actions"),
+                ]
+                .into_iter()
+                .map(|(tab, label)| {
+                    if tab == focus {
+                        Span::styled(
+                            format!(" {} ", label),
+                            Style::default().bg(Color::Cyan).fg(Color::Black),
+                        )
+                    } else {
+                        Span::raw(format!(" {} ", label))
+                    }
+                })
+                .collect();
+                if !changes.is_empty() {
+                    tabs.push(Span::styled(
+                        " [modified]",
+                        Style::default().fg(Color::LightRed),
+                    ));
+                }
+                frame.render_widget(Paragraph::new(Line::from(tabs)), area);
+            }
 
-            let theme_items: Vec<ListItem> = theme_names
+            let visible_themes = theme_filter.visible(&theme_names);
+            let theme_items: Vec<ListItem> = visible_themes
                 .iter()
-                .map(|name| ListItem::new(name.as_str()))
+                .map(|index| ListItem::new(theme_names[*index].as_str()))
                 .collect();
             let mut theme_state = ListState::default();
-            theme_state.select(Some(theme_index));
+            theme_state.select(
+                visible_themes
+                    .iter()
+                    .position(|index| *index == theme_index),
+            );
+            let mut theme_title = String::from("Themes");
+            if matches!(focus, Focus::Themes) {
+                theme_title.push_str(" *");
+            }
+            if theme_filter.typing || !theme_filter.query.is_empty() {
+                theme_title.push_str(&format!(" /{}", theme_filter.query));
+                if theme_filter.typing {
+                    theme_title.push('_');
+                }
+            }
             let theme_list = List::new(theme_items)
-                .block(Block::default().borders(Borders::ALL).title(
-                    if matches!(focus, Focus::Themes) {
-                        "Themes *"
-                    } else {
-                        "Themes"
-                    },
-                ))
+                .block(Block::default().borders(Borders::ALL).title(theme_title))
                 .highlight_style(Style::default().bg(Color::Blue).fg(Color::White));
-            frame.render_stateful_widget(theme_list, cols[0], &mut theme_state);
+            if let Some(area) = layout.themes {
+                frame.render_stateful_widget(theme_list, area, &mut theme_state);
+            }
 
             let segment_items: Vec<ListItem> = base_config
                 .segments
@@ -238,7 +339,22 @@ pub fn run_configurator(base: &Config) -> Result<Option<Config>> {
                     },
                 ))
                 .highlight_style(Style::default().bg(Color::Cyan).fg(Color::Black));
-            frame.render_stateful_widget(segment_list, cols[1], &mut segment_state);
+            if let Some(area) = layout.segments {
+                frame.render_stateful_widget(segment_list, area, &mut segment_state);
+            }
+            if let (Some(area), Some(segment)) = (
+                layout.inspector,
+                base_config
+                    .segments
+                    .get(selected_segment.min(base_config.segments.len().saturating_sub(1))),
+            ) {
+                let insight = segments::inspect_segment(&preview_config, segment, preview_context);
+                let inspector =
+                    Paragraph::new(inspector_lines(segment.id, &insight, &preview_collection))
+                        .wrap(ratatui::widgets::Wrap { trim: true })
+                        .block(Block::default().borders(Borders::ALL).title("Inspector"));
+                frame.render_widget(inspector, area);
+            }
 
             let action_items: Vec<ListItem> = actions.iter().map(|v| ListItem::new(*v)).collect();
             let mut action_state = ListState::default();
@@ -252,31 +368,197 @@ pub fn run_configurator(base: &Config) -> Result<Option<Config>> {
                     },
                 ))
                 .highlight_style(Style::default().bg(Color::Magenta).fg(Color::White));
-            frame.render_stateful_widget(action_list, cols[2], &mut action_state);
+            if let Some(area) = layout.actions {
+                frame.render_stateful_widget(action_list, area, &mut action_state);
+            }
 
-            let footer = Paragraph::new(vec![
-                Line::from(Span::styled(
-                    format!("Preview: {}", preview_text),
-                    Style::default().fg(Color::White),
-                )),
-                Line::from(Span::styled(
-                    footer_message.as_str(),
-                    Style::default().fg(Color::DarkGray),
-                )),
-            ])
-            .block(Block::default().borders(Borders::ALL).title("Preview"));
-            frame.render_widget(Clear, rows[2]);
-            frame.render_widget(footer, rows[2]);
+            let mut footer_lines = preview_lines.clone();
+            footer_lines.push(Line::from(Span::styled(
+                footer_message.as_str(),
+                Style::default().fg(Color::DarkGray),
+            )));
+            let footer = Paragraph::new(footer_lines)
+                .block(Block::default().borders(Borders::ALL).title("Preview"));
+            frame.render_widget(Clear, layout.footer);
+            frame.render_widget(footer, layout.footer);
+
+            match &popup {
+                Some(Popup::Options(editor)) => render_option_editor(
+                    frame,
+                    centered_rect(60, 60, layout.body),
+                    &base_config,
+                    editor,
+                ),
+                Some(Popup::Colors(picker)) => render_color_picker(
+                    frame,
+                    centered_rect(60, 90, layout.body),
+                    &base_config,
+                    picker,
+                ),
+                Some(Popup::Icons(picker)) => render_icon_picker(
+                    frame,
+                    centered_rect(60, 90, layout.body),
+                    &base_config,
+                    picker,
+                ),
+                Some(Popup::Style(editor)) => {
+                    render_style_editor(frame, centered_rect(60, 90, layout.body), editor)
+                }
+                Some(Popup::ImportTheme(input)) => {
+                    render_theme_import(frame, centered_rect(70, 60, layout.body), input)
+                }
+                Some(Popup::ConfirmQuit(selected)) => {
+                    render_confirm_quit(frame, centered_rect(40, 30, layout.body), *selected)
+                }
+                Some(Popup::Help) => {
+                    render_help_overlay(frame, "Configurator keys", CONFIGURATOR_HELP)
+                }
+                None => {}
+            }
         })?;
 
-        if let Event::Key(key) = event::read()? {
+        let event = event::read()?;
+        if let (Event::Paste(text), Some(Popup::ImportTheme(input))) = (&event, popup.as_mut()) {
+            input.push_str(text);
+            continue;
+        }
+        if let Event::Key(key) = event {
+            if let Some(Popup::ImportTheme(input)) = popup.as_mut() {
+                match key.code {
+                    KeyCode::Char(c) => input.push(c),
+                    KeyCode::Backspace => {
+                        input.pop();
+                    }
+                    KeyCode::Esc => popup = None,
+                    KeyCode::Enter => {
+                        let imported = themes::read_theme_source(input)
+                            .and_then(|content| themes::import_theme(&content, &themes_dir));
+                        footer_message = match imported {
+                            Ok(name) => {
+                                theme_names = themes::list_theme_names(&themes_dir)?;
+                                theme_index = theme_names
+                                    .iter()
+                                    .position(|theme| theme == &name)
+                                    .unwrap_or(theme_index);
+                                format!("Imported theme {}", name)
+                            }
+                            Err(err) => format!("Import failed: {err:#}"),
+                        };
+                        popup = None;
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+
+            if let Some(Popup::ConfirmQuit(selected)) = popup.as_mut() {
+                match handle_confirm_quit_keys(&key, selected) {
+                    Some(QuitChoice::Save) => match merge_for_save(
+                        &base_config,
+                        &theme_names[theme_index],
+                        &themes_dir,
+                        style_override.as_ref(),
+                    ) {
+                        Ok(merged) => {
+                            config::save(&merged)?;
+                            return Ok(Some(merged));
+                        }
+                        Err(err) => {
+                            footer_message = format!("Cannot save: {err:#}");
+                            popup = None;
+                        }
+                    },
+                    Some(QuitChoice::Discard) => return Ok(None),
+                    Some(QuitChoice::Cancel) => popup = None,
+                    None => {}
+                }
+                continue;
+            }
+
+            if let Some(active) = popup.as_mut() {
+                let outcome = match active {
+                    Popup::Options(editor) => {
+                        handle_option_editor_keys(&key, editor, &mut base_config)
+                    }
+                    Popup::Colors(picker) => {
+                        handle_color_picker_keys(&key, picker, &mut base_config)
+                    }
+                    Popup::Icons(picker) => handle_icon_picker_keys(&key, picker, &mut base_config),
+                    Popup::Help | Popup::ConfirmQuit(_) | Popup::ImportTheme(_) => {
+                        PopupOutcome::Close
+                    }
+                    Popup::Style(editor) => {
+                        let outcome = handle_style_editor_keys(&key, editor);
+                        // Every style change reports a message; lock the style only then.
+                        if matches!(outcome, PopupOutcome::Message(_)) {
+                            let mut style = editor.style.clone();
+                            style.locked = true;
+                            style_override = Some(style);
+                        }
+                        outcome
+                    }
+                };
+                match outcome {
+                    PopupOutcome::Continue => {}
+                    PopupOutcome::Message(message) => footer_message = message,
+                    PopupOutcome::Close => popup = None,
+                }
+                continue;
+            }
+
+            if theme_filter.typing {
+                if let Some(message) = theme_filter.handle_key(&key, &theme_names, &mut theme_index)
+                {
+                    footer_message = message;
+                }
+                continue;
+            }
+
+            if matches!(focus, Focus::Themes) && key.code == KeyCode::Char('/') {
+                theme_filter.typing = true;
+                continue;
+            }
+
+            if key.code == KeyCode::Char('?') {
+                popup = Some(Popup::Help);
+                continue;
+            }
+
+            if matches!(focus, Focus::Segments) && !base_config.segments.is_empty() {
+                let segment = selected_segment.min(base_config.segments.len() - 1);
+                match key.code {
+                    KeyCode::Enter => {
+                        popup = Some(Popup::Options(OptionEditor {
+                            segment,
+                            selected: 0,
+                            input: None,
+                        }));
+                        continue;
+                    }
+                    KeyCode::Char('c') | KeyCode::Char('C') => {
+                        popup = Some(Popup::Colors(ColorPicker::new(&base_config, segment)));
+                        continue;
+                    }
+                    KeyCode::Char('i') | KeyCode::Char('I') => {
+                        popup = Some(Popup::Icons(IconPicker {
+                            segment,
+                            query: String::new(),
+                            selected: 0,
+                        }));
+                        continue;
+                    }
+                    _ => {}
+                }
+            }
+
             if handle_global_key(&key, &mut focus) {
                 continue;
             }
 
             match focus {
                 Focus::Themes => {
-                    if handle_theme_keys(&key, &mut theme_index, theme_names.len()) {
+                    let visible = theme_filter.visible(&theme_names);
+                    if handle_theme_keys(&key, &mut theme_index, &visible) {
                         continue;
                     }
                 }
@@ -296,16 +578,30 @@ pub fn run_configurator(base: &Config) -> Result<Option<Config>> {
                 KeyCode::Enter => {
                     if matches!(focus, Focus::Actions) {
                         match actions[selected_action] {
-                            "Save" => {
-                                let merged = themes::apply_theme(
-                                    &base_config,
-                                    &theme_names[theme_index],
-                                    &themes_dir,
-                                )?;
-                                config::save(&merged)?;
-                                return Ok(Some(merged));
+                            "Save" => match merge_for_save(
+                                &base_config,
+                                &theme_names[theme_index],
+                                &themes_dir,
+                                style_override.as_ref(),
+                            ) {
+                                Ok(merged) => {
+                                    config::save(&merged)?;
+                                    return Ok(Some(merged));
+                                }
+                                Err(err) => footer_message = format!("Cannot save: {err:#}"),
+                            },
+                            "Import Theme" => {
+                                popup = Some(Popup::ImportTheme(String::new()));
+                            }
+                            "Edit Style" => {
+                                popup = Some(Popup::Style(StyleEditor {
+                                    style: preview_config.style.clone(),
+                                    selected: 0,
+                                    input: None,
+                                }));
                             }
                             "Reset" => {
+                                style_override = None;
                                 base_config = base.clone();
                                 theme_index = theme_names
                                     .iter()
@@ -315,27 +611,48 @@ pub fn run_configurator(base: &Config) -> Result<Option<Config>> {
                                 footer_message = "Configuration reset to original".to_string();
                             }
                             "Quit" => {
-                                return Ok(None);
+                                if !has_unsaved_changes(
+                                    base,
+                                    &base_config,
+                                    &theme_names[theme_index],
+                                    style_override.as_ref(),
+                                ) {
+                                    return Ok(None);
+                                }
+                                popup = Some(Popup::ConfirmQuit(0));
                             }
                             _ => {}
                         }
                     }
                 }
-                KeyCode::Esc => return Ok(None),
-                KeyCode::Char(c) => {
-                    if c.to_string().eq_ignore_ascii_case("q") {
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('Q') => {
+                    if !has_unsaved_changes(
+                        base,
+                        &base_config,
+                        &theme_names[theme_index],
+                        style_override.as_ref(),
+                    ) {
                         return Ok(None);
                     }
+                    popup = Some(Popup::ConfirmQuit(0));
+                }
+                KeyCode::Char(c) => {
                     if c.to_string().eq_ignore_ascii_case("s") {
-                        let merged = themes::apply_theme(
+                        match merge_for_save(
                             &base_config,
                             &theme_names[theme_index],
                             &themes_dir,
-                        )?;
-                        config::save(&merged)?;
-                        return Ok(Some(merged));
+                            style_override.as_ref(),
+                        ) {
+                            Ok(merged) => {
+                                config::save(&merged)?;
+                                return Ok(Some(merged));
+                            }
+                            Err(err) => footer_message = format!("Cannot save: {err:#}"),
+                        }
                     }
                     if c.to_string().eq_ignore_ascii_case("r") {
+                        style_override = None;
                         base_config = base.clone();
                         theme_index = theme_names
                             .iter()
@@ -351,120 +668,1894 @@ pub fn run_configurator(base: &Config) -> Result<Option<Config>> {
     }
 }
 
-fn handle_global_key(key: &KeyEvent, focus: &mut Focus) -> bool {
-    match key.code {
-        KeyCode::Tab => {
-            *focus = match focus {
-                Focus::Themes => Focus::Segments,
-                Focus::Segments => Focus::Actions,
-                Focus::Actions => Focus::Themes,
-            };
-            true
+/// Freshly collected statusline for the main menu header; collection errors are shown inline.
+fn current_statusline(cfg: &Config) -> Line<'static> {
+    let label = Span::styled("Now: ", Style::default().fg(Color::DarkGray));
+    let rendered = match collect::collect(cfg) {
+        Ok(collection) => {
+            let pieces = segments::build_segments(cfg, &collection.context);
+            if pieces.is_empty() {
+                Line::from(Span::styled(
+                    "(no data yet)",
+                    Style::default().fg(Color::DarkGray),
+                ))
+            } else {
+                ansi_line(&render::render_line(cfg, &pieces, false))
+            }
         }
-        _ => false,
-    }
+        Err(err) => Line::from(Span::styled(
+            format!("(unavailable: {err:#})"),
+            Style::default().fg(Color::Red),
+        )),
+    };
+    Line::from(
+        std::iter::once(label)
+            .chain(rendered.spans)
+            .collect::<Vec<_>>(),
+    )
 }
 
-fn handle_theme_keys(key: &KeyEvent, selected: &mut usize, total: usize) -> bool {
-    if total == 0 {
-        return false;
+const MAIN_MENU_HELP: &[(&str, &str)] = &[
+    ("↑ / ↓", "Move selection"),
+    ("Enter", "Run the selected action"),
+    ("?", "Show this help"),
+    ("q / Esc", "Exit"),
+];
+
+const CONFIGURATOR_HELP: &[(&str, &str)] = &[
+    ("Tab", "Cycle focus: Themes → Segments → Actions"),
+    ("↑ / ↓", "Move selection in the focused panel"),
+    (
+        "Themes panel",
+        "Selecting a theme updates the preview immediately",
+    ),
+    ("Space", "Segments: toggle the selected segment"),
+    ("J / K", "Segments: move the selected segment down / up"),
+    ("Enter", "Segments: edit options; Actions: run the action"),
+    ("C", "Segments: pick icon/text/background colors"),
+    ("I", "Segments: browse Nerd Font glyphs for the icon"),
+    ("Edit Style", "Actions: change style mode and separator"),
+    ("S", "Save configuration and exit"),
+    ("R", "Reset to the configuration loaded at start"),
+    (
+        "Esc",
+        "Close popup, or quit (asks first if there are unsaved changes)",
+    ),
+    ("?", "Show this help"),
+    (
+        "q",
+        "Quit; prompts Save / Discard / Cancel on unsaved changes",
+    ),
+];
+
+fn render_help_overlay(frame: &mut ratatui::Frame, title: &str, entries: &[(&str, &str)]) {
+    let area = frame.size();
+    let key_width = entries
+        .iter()
+        .map(|(key, _)| key.chars().count())
+        .max()
+        .unwrap_or(0);
+    let mut lines: Vec<Line> = entries
+        .iter()
+        .map(|(key, description)| {
+            Line::from(vec![
+                Span::styled(
+                    format!("{:<width$}  ", key, width = key_width),
+                    Style::default()
+                        .fg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(*description, Style::default().fg(Color::White)),
+            ])
+        })
+        .collect();
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "Press any key to close",
+        Style::default().fg(Color::DarkGray),
+    )));
+
+    let help = Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(title));
+    frame.render_widget(Clear, area);
+    frame.render_widget(help, area);
+}
+
+struct OptionEditor {
+    segment: usize,
+    selected: usize,
+    input: Option<String>,
+}
+
+enum Popup {
+    Options(OptionEditor),
+    Colors(ColorPicker),
+    Icons(IconPicker),
+    Style(StyleEditor),
+    ConfirmQuit(usize),
+    /// Pasted theme TOML, or a path / URL to read it from.
+    ImportTheme(String),
+    Help,
+}
+
+enum PopupOutcome {
+    Continue,
+    Message(String),
+    Close,
+}
+
+fn render_option_editor(
+    frame: &mut ratatui::Frame,
+    area: Rect,
+    cfg: &Config,
+    editor: &OptionEditor,
+) {
+    let segment = &cfg.segments[editor.segment];
+    let specs = config::segment_option_specs(segment.id);
+
+    let mut lines: Vec<Line> = Vec::new();
+    if specs.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "This segment has no options",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+    for (index, spec) in specs.iter().enumerate() {
+        let value = match (&editor.input, index == editor.selected) {
+            (Some(input), true) => format!("{}_", input),
+            _ => option_display(segment.options.get(spec.key), spec),
+        };
+        let style = if index == editor.selected {
+            Style::default().bg(Color::Cyan).fg(Color::Black)
+        } else {
+            Style::default().fg(Color::White)
+        };
+        lines.push(Line::from(Span::styled(
+            format!("{}: {}", spec.key, value),
+            style,
+        )));
+        lines.push(Line::from(Span::styled(
+            format!("  {}", spec.help),
+            Style::default().fg(Color::DarkGray),
+        )));
     }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "Enter/Space edit, D default, Esc close",
+        Style::default().fg(Color::DarkGray),
+    )));
 
-    match key.code {
-        KeyCode::Up => {
-            if *selected == 0 {
-                *selected = total - 1;
-            } else {
-                *selected = selected.saturating_sub(1);
-            }
-            true
-        }
-        KeyCode::Down => {
-            *selected = (*selected + 1) % total;
-            true
-        }
-        _ => false,
+    let panel = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!("{} options", segment.id.as_str())),
+    );
+    frame.render_widget(Clear, area);
+    frame.render_widget(panel, area);
+}
+
+fn option_display(value: Option<&serde_json::Value>, spec: &OptionSpec) -> String {
+    match value {
+        Some(serde_json::Value::String(v)) => v.clone(),
+        Some(v) => v.to_string(),
+        None => format!("{} (default)", spec.default),
     }
 }
 
-fn handle_segment_keys(key: &KeyEvent, cfg: &mut Config, selected: &mut usize) -> bool {
-    if cfg.segments.is_empty() {
-        return false;
+fn handle_option_editor_keys(
+    key: &KeyEvent,
+    editor: &mut OptionEditor,
+    cfg: &mut Config,
+) -> PopupOutcome {
+    let segment = &mut cfg.segments[editor.segment];
+    let specs = config::segment_option_specs(segment.id);
+    let Some(spec) = specs.get(editor.selected) else {
+        return match key.code {
+            KeyCode::Esc | KeyCode::Enter => PopupOutcome::Close,
+            _ => PopupOutcome::Continue,
+        };
+    };
+
+    if let Some(input) = editor.input.as_mut() {
+        match key.code {
+            KeyCode::Esc => editor.input = None,
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(c) => input.push(c),
+            KeyCode::Enter => match spec.parse(input) {
+                Ok(value) => {
+                    segment.options.insert(spec.key.to_string(), value);
+                    editor.input = None;
+                    return PopupOutcome::Message(format!("{} updated", spec.key));
+                }
+                Err(err) => return PopupOutcome::Message(err.to_string()),
+            },
+            _ => {}
+        }
+        return PopupOutcome::Continue;
     }
 
     match key.code {
+        KeyCode::Esc => return PopupOutcome::Close,
         KeyCode::Up => {
-            if *selected == 0 {
-                *selected = cfg.segments.len() - 1;
-            } else {
-                *selected = selected.saturating_sub(1);
-            }
-            true
+            editor.selected = editor.selected.checked_sub(1).unwrap_or(specs.len() - 1);
         }
-        KeyCode::Down => {
-            *selected = (*selected + 1) % cfg.segments.len();
-            true
+        KeyCode::Down => editor.selected = (editor.selected + 1) % specs.len(),
+        KeyCode::Char('d') | KeyCode::Char('D') => {
+            segment.options.remove(spec.key);
+            return PopupOutcome::Message(format!("{} reset to default", spec.key));
         }
-        KeyCode::Char(c) => {
-            let text = c.to_string();
-            if text == " " {
-                let idx = (*selected).min(cfg.segments.len() - 1);
-                cfg.segments[idx].enabled = !cfg.segments[idx].enabled;
-                return true;
-            }
-            if text.eq_ignore_ascii_case("j") {
-                let idx = (*selected).min(cfg.segments.len() - 1);
-                if idx + 1 < cfg.segments.len() {
-                    cfg.segments.swap(idx, idx + 1);
-                    *selected = idx + 1;
+        KeyCode::Enter | KeyCode::Char(' ') => {
+            let current = option_display(segment.options.get(spec.key), spec);
+            let current = current.trim_end_matches(" (default)").to_string();
+            match spec.kind {
+                OptionKind::Bool => {
+                    let value = current != "true";
+                    segment
+                        .options
+                        .insert(spec.key.to_string(), serde_json::Value::Bool(value));
                 }
-                return true;
-            }
-            if text.eq_ignore_ascii_case("k") {
-                let idx = (*selected).min(cfg.segments.len() - 1);
-                if idx > 0 {
-                    cfg.segments.swap(idx, idx - 1);
-                    *selected = idx - 1;
+                OptionKind::Choice(choices) => {
+                    let index = choices.iter().position(|c| *c == current).unwrap_or(0);
+                    let next = choices[(index + 1) % choices.len()];
+                    segment.options.insert(
+                        spec.key.to_string(),
+                        serde_json::Value::String(next.to_string()),
+                    );
+                }
+                OptionKind::Text | OptionKind::Pattern | OptionKind::Number { .. } => {
+                    editor.input = Some(current)
                 }
-                return true;
             }
-            false
         }
-        _ => false,
+        _ => {}
     }
+    PopupOutcome::Continue
 }
 
-fn handle_action_nav(key: &KeyEvent, selected: &mut usize, total: usize) -> bool {
-    if total == 0 {
-        return false;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColorTarget {
+    Icon,
+    Text,
+    Background,
+}
+
+impl ColorTarget {
+    fn label(self) -> &'static str {
+        match self {
+            ColorTarget::Icon => "icon",
+            ColorTarget::Text => "text",
+            ColorTarget::Background => "background",
+        }
     }
 
-    match key.code {
-        KeyCode::Up => {
-            if *selected == 0 {
-                *selected = total - 1;
-            } else {
-                *selected = selected.saturating_sub(1);
-            }
-            true
+    fn next(self) -> Self {
+        match self {
+            ColorTarget::Icon => ColorTarget::Text,
+            ColorTarget::Text => ColorTarget::Background,
+            ColorTarget::Background => ColorTarget::Icon,
         }
-        KeyCode::Down => {
-            *selected = (*selected + 1) % total;
-            true
+    }
+
+    fn get(self, colors: &ColorConfig) -> Option<config::Color> {
+        match self {
+            ColorTarget::Icon => colors.icon,
+            ColorTarget::Text => colors.text,
+            ColorTarget::Background => colors.background,
+        }
+    }
+
+    fn slot(self, colors: &mut ColorConfig) -> &mut Option<config::Color> {
+        match self {
+            ColorTarget::Icon => &mut colors.icon,
+            ColorTarget::Text => &mut colors.text,
+            ColorTarget::Background => &mut colors.background,
         }
-        _ => false,
     }
 }
 
-struct TerminalGuard {
-    terminal: Terminal<CrosstermBackend<Stdout>>,
+/// Picker rows: index 0 clears the color, indexes 1..=16 map to `NamedColor::ALL`. Hex and
+/// 256-color values from the config have no row, so the picker opens on `none` for them.
+struct ColorPicker {
+    segment: usize,
+    target: ColorTarget,
+    selected: usize,
 }
 
-impl TerminalGuard {
-    fn new() -> Result<Self> {
-        enable_raw_mode().context("failed to enable raw mode")?;
-        let mut stdout = io::stdout();
-        execute!(stdout, EnterAlternateScreen).context("failed to enter alternate screen")?;
+impl ColorPicker {
+    fn new(cfg: &Config, segment: usize) -> Self {
+        let mut picker = Self {
+            segment,
+            target: ColorTarget::Text,
+            selected: 0,
+        };
+        picker.sync_selection(cfg);
+        picker
+    }
+
+    fn sync_selection(&mut self, cfg: &Config) {
+        self.selected = self
+            .target
+            .get(&cfg.segments[self.segment].colors)
+            .and_then(|color| match color {
+                config::Color::Named(color) => NamedColor::ALL.iter().position(|c| *c == color),
+                _ => None,
+            })
+            .map(|index| index + 1)
+            .unwrap_or(0);
+    }
+}
+
+fn render_color_picker(frame: &mut ratatui::Frame, area: Rect, cfg: &Config, picker: &ColorPicker) {
+    let segment = &cfg.segments[picker.segment];
+
+    let mut items = vec![ListItem::new(Line::from(vec![
+        Span::raw("    "),
+        Span::raw(" none"),
+    ]))];
+    for color in NamedColor::ALL {
+        items.push(ListItem::new(Line::from(vec![
+            Span::styled("    ", Style::default().bg(tui_color(color))),
+            Span::raw(" "),
+            Span::styled(color.as_str(), Style::default().fg(tui_color(color))),
+        ])));
+    }
+
+    let title = format!(
+        "{} {} color (Tab target, Enter apply, Esc close)",
+        segment.id.as_str(),
+        picker.target.label()
+    );
+    let mut state = ListState::default();
+    state.select(Some(picker.selected));
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .highlight_symbol("▶ ");
+    frame.render_widget(Clear, area);
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+fn handle_color_picker_keys(
+    key: &KeyEvent,
+    picker: &mut ColorPicker,
+    cfg: &mut Config,
+) -> PopupOutcome {
+    let total = NamedColor::ALL.len() + 1;
+    match key.code {
+        KeyCode::Esc => return PopupOutcome::Close,
+        KeyCode::Tab | KeyCode::Right | KeyCode::Left => {
+            picker.target = picker.target.next();
+            picker.sync_selection(cfg);
+        }
+        KeyCode::Up => picker.selected = picker.selected.checked_sub(1).unwrap_or(total - 1),
+        KeyCode::Down => picker.selected = (picker.selected + 1) % total,
+        KeyCode::Enter => {
+            let color = picker
+                .selected
+                .checked_sub(1)
+                .map(|index| NamedColor::ALL[index]);
+            let segment = &mut cfg.segments[picker.segment];
+            *picker.target.slot(&mut segment.colors) = color.map(config::Color::from);
+            return PopupOutcome::Message(format!(
+                "{} {} color set to {}",
+                segment.id.as_str(),
+                picker.target.label(),
+                color.map(NamedColor::as_str).unwrap_or("none")
+            ));
+        }
+        _ => {}
+    }
+    PopupOutcome::Continue
+}
+
+struct IconPicker {
+    segment: usize,
+    query: String,
+    selected: usize,
+}
+
+fn render_icon_picker(frame: &mut ratatui::Frame, area: Rect, cfg: &Config, picker: &IconPicker) {
+    let segment = &cfg.segments[picker.segment];
+    let matches = glyphs::search(&picker.query);
+
+    let items: Vec<ListItem> = matches
+        .iter()
+        .map(|glyph| {
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!(" {} ", glyph.glyph),
+                    Style::default().fg(Color::Yellow),
+                ),
+                Span::raw(format!("{:<8} ", glyph.category)),
+                Span::styled(glyph.name, Style::default().fg(Color::White)),
+            ]))
+        })
+        .collect();
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(3)])
+        .split(area);
+
+    let search = Paragraph::new(format!("{}_", picker.query)).block(
+        Block::default().borders(Borders::ALL).title(format!(
+            "{} icon search (current: {})",
+            segment.id.as_str(),
+            segment.icon.nerd_font
+        )),
+    );
+    let mut state = ListState::default();
+    if !matches.is_empty() {
+        state.select(Some(picker.selected.min(matches.len() - 1)));
+    }
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Type to filter, Enter apply, Esc close"),
+        )
+        .highlight_style(Style::default().bg(Color::Cyan).fg(Color::Black));
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(search, chunks[0]);
+    frame.render_stateful_widget(list, chunks[1], &mut state);
+}
+
+fn handle_icon_picker_keys(
+    key: &KeyEvent,
+    picker: &mut IconPicker,
+    cfg: &mut Config,
+) -> PopupOutcome {
+    let matches = glyphs::search(&picker.query);
+    match key.code {
+        KeyCode::Esc => return PopupOutcome::Close,
+        KeyCode::Up if !matches.is_empty() => {
+            picker.selected = picker.selected.checked_sub(1).unwrap_or(matches.len() - 1);
+        }
+        KeyCode::Down if !matches.is_empty() => {
+            picker.selected = (picker.selected + 1) % matches.len();
+        }
+        KeyCode::Backspace => {
+            picker.query.pop();
+            picker.selected = 0;
+        }
+        KeyCode::Char(c) => {
+            picker.query.push(c);
+            picker.selected = 0;
+        }
+        KeyCode::Enter => {
+            let Some(glyph) = matches.get(picker.selected) else {
+                return PopupOutcome::Message("no matching glyph".to_string());
+            };
+            let segment = &mut cfg.segments[picker.segment];
+            segment.icon.nerd_font = glyph.glyph.to_string();
+            return PopupOutcome::Message(format!(
+                "{} icon set to {} ({})",
+                segment.id.as_str(),
+                glyph.glyph,
+                glyph.name
+            ));
+        }
+        _ => {}
+    }
+    PopupOutcome::Continue
+}
+
+/// Translates ANSI SGR sequences (as produced by `render::render_line`) into styled spans,
+/// so the preview shows exactly what the terminal will get.
+fn ansi_line(text: &str) -> Line<'static> {
+    let mut spans = Vec::new();
+    let mut style = Style::default();
+    let mut buffer = String::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find("\x1b[") {
+        buffer.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find('m') else {
+            buffer.push_str(&rest[start..]);
+            rest = "";
+            break;
+        };
+        if !buffer.is_empty() {
+            spans.push(Span::styled(std::mem::take(&mut buffer), style));
+        }
+        style = apply_sgr(style, &after[..end]);
+        rest = &after[end + 1..];
+    }
+    buffer.push_str(rest);
+    if !buffer.is_empty() {
+        spans.push(Span::styled(buffer, style));
+    }
+    Line::from(spans)
+}
+
+fn apply_sgr(mut style: Style, params: &str) -> Style {
+    let codes: Vec<u16> = params
+        .split(';')
+        .map(|code| code.parse().unwrap_or(0))
+        .collect();
+    let mut index = 0;
+    while index < codes.len() {
+        let code = codes[index];
+        match code {
+            0 => style = Style::default(),
+            1 => style = style.add_modifier(Modifier::BOLD),
+            30..=37 => style = style.fg(Color::Indexed((code - 30) as u8)),
+            90..=97 => style = style.fg(Color::Indexed((code - 90 + 8) as u8)),
+            40..=47 => style = style.bg(Color::Indexed((code - 40) as u8)),
+            100..=107 => style = style.bg(Color::Indexed((code - 100 + 8) as u8)),
+            39 => style.fg = None,
+            49 => style.bg = None,
+            38 | 48 => {
+                let color = match codes.get(index + 1) {
+                    Some(5) => {
+                        let color = codes.get(index + 2).map(|v| Color::Indexed(*v as u8));
+                        index += 2;
+                        color
+                    }
+                    Some(2) => {
+                        let rgb = (
+                            codes.get(index + 2),
+                            codes.get(index + 3),
+                            codes.get(index + 4),
+                        );
+                        index += 4;
+                        match rgb {
+                            (Some(r), Some(g), Some(b)) => {
+                                Some(Color::Rgb(*r as u8, *g as u8, *b as u8))
+                            }
+                            _ => None,
+                        }
+                    }
+                    _ => None,
+                };
+                if let Some(color) = color {
+                    style = if code == 38 {
+                        style.fg(color)
+                    } else {
+                        style.bg(color)
+                    };
+                }
+            }
+            _ => {}
+        }
+        index += 1;
+    }
+    style
+}
+
+/// Applies the selected theme, then any style edited in the Style panel on top of it.
+fn merge_theme(
+    base: &Config,
+    theme: &str,
+    themes_dir: &std::path::Path,
+    style_override: Option<&StyleConfig>,
+) -> Result<Config> {
+    let mut merged = themes::apply_theme(base, theme, themes_dir)?;
+    if let Some(style) = style_override {
+        merged.style = style.clone();
+    }
+    Ok(merged)
+}
+
+fn merge_for_save(
+    base: &Config,
+    theme_name: &str,
+    themes_dir: &std::path::Path,
+    style_override: Option<&StyleConfig>,
+) -> Result<Config> {
+    let merged = merge_theme(base, theme_name, themes_dir, style_override)?;
+    merged.validate()?;
+    Ok(merged)
+}
+
+fn has_unsaved_changes(
+    original: &Config,
+    working: &Config,
+    theme_name: &str,
+    style_override: Option<&StyleConfig>,
+) -> bool {
+    !pending_changes(original, working, theme_name, style_override).is_empty()
+}
+
+/// Human-readable summary of what Save would change, e.g. `["2 segments changed", "theme changed"]`.
+///
+/// Configs are compared through their serialized form because `Config` does not implement
+/// `PartialEq`.
+fn pending_changes(
+    original: &Config,
+    working: &Config,
+    theme_name: &str,
+    style_override: Option<&StyleConfig>,
+) -> Vec<String> {
+    let mut changes = Vec::new();
+
+    let changed_segments = working
+        .segments
+        .iter()
+        .filter(|segment| {
+            let before = original
+                .segments
+                .iter()
+                .find(|other| other.id == segment.id);
+            before.map(|before| serde_json::to_value(before).ok())
+                != Some(serde_json::to_value(segment).ok())
+        })
+        .count();
+    match changed_segments {
+        0 => {}
+        1 => changes.push("1 segment changed".to_string()),
+        count => changes.push(format!("{} segments changed", count)),
+    }
+    let order = |cfg: &Config| cfg.segments.iter().map(|s| s.id).collect::<Vec<_>>();
+    if changed_segments == 0 && order(original) != order(working) {
+        changes.push("segment order changed".to_string());
+    }
+    if theme_name != original.theme {
+        changes.push("theme changed".to_string());
+    }
+    if style_override.is_some() {
+        changes.push("style changed".to_string());
+    }
+    if changes.is_empty()
+        && serde_json::to_value(original).ok() != serde_json::to_value(working).ok()
+    {
+        changes.push("settings changed".to_string());
+    }
+    changes
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum QuitChoice {
+    Save,
+    Discard,
+    Cancel,
+}
+
+const QUIT_CHOICES: [(QuitChoice, &str); 3] = [
+    (QuitChoice::Save, "Save"),
+    (QuitChoice::Discard, "Discard"),
+    (QuitChoice::Cancel, "Cancel"),
+];
+
+fn render_theme_import(frame: &mut ratatui::Frame, area: Rect, input: &str) {
+    let mut lines = vec![
+        Line::from("Paste theme TOML, or type a file path or http(s) URL."),
+        Line::from(""),
+    ];
+    lines.extend(input.lines().map(|line| Line::from(line.to_string())));
+    if input.is_empty() || input.ends_with('\n') {
+        lines.push(Line::from("_"));
+    } else if let Some(last) = lines.last_mut() {
+        last.spans.push(Span::raw("_"));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "Enter import, Backspace delete, Esc cancel",
+        Style::default().fg(Color::DarkGray),
+    )));
+    let dialog = Paragraph::new(lines)
+        .wrap(ratatui::widgets::Wrap { trim: false })
+        .block(Block::default().borders(Borders::ALL).title("Import Theme"));
+    frame.render_widget(Clear, area);
+    frame.render_widget(dialog, area);
+}
+
+fn render_confirm_quit(frame: &mut ratatui::Frame, area: Rect, selected: usize) {
+    let mut spans = Vec::new();
+    for (index, (_, label)) in QUIT_CHOICES.iter().enumerate() {
+        let style = if index == selected {
+            Style::default().bg(Color::Magenta).fg(Color::White)
+        } else {
+            Style::default()
+        };
+        spans.push(Span::styled(format!(" {} ", label), style));
+        spans.push(Span::raw("  "));
+    }
+    let dialog = Paragraph::new(vec![
+        Line::from("You have unsaved changes."),
+        Line::from(""),
+        Line::from(spans),
+        Line::from(""),
+        Line::from(Span::styled(
+            "←/→ select, Enter confirm, S save, D discard, Esc cancel",
+            Style::default().fg(Color::DarkGray),
+        )),
+    ])
+    .block(Block::default().borders(Borders::ALL).title("Quit"));
+    frame.render_widget(Clear, area);
+    frame.render_widget(dialog, area);
+}
+
+fn handle_confirm_quit_keys(key: &KeyEvent, selected: &mut usize) -> Option<QuitChoice> {
+    match key.code {
+        KeyCode::Left => {
+            *selected = selected.checked_sub(1).unwrap_or(QUIT_CHOICES.len() - 1);
+            None
+        }
+        KeyCode::Right | KeyCode::Tab => {
+            *selected = (*selected + 1) % QUIT_CHOICES.len();
+            None
+        }
+        KeyCode::Enter => Some(QUIT_CHOICES[*selected].0),
+        KeyCode::Char('s') | KeyCode::Char('S') => Some(QuitChoice::Save),
+        KeyCode::Char('d') | KeyCode::Char('D') => Some(QuitChoice::Discard),
+        KeyCode::Esc | KeyCode::Char('c') | KeyCode::Char('C') => Some(QuitChoice::Cancel),
+        _ => None,
+    }
+}
+
+const SEPARATOR_PRESETS: [&str; 6] = [" · ", " | ", " ❯ ", " • ", " \u{e0b0} ", " \u{e0b1} "];
+const STYLE_MODES: [StyleMode; 3] = [StyleMode::Plain, StyleMode::NerdFont, StyleMode::Powerline];
+
+/// Style panel rows: 0 mode, 1 separator, 2.. separator presets.
+struct StyleEditor {
+    style: StyleConfig,
+    selected: usize,
+    input: Option<String>,
+}
+
+fn style_mode_name(mode: StyleMode) -> &'static str {
+    match mode {
+        StyleMode::Plain => "plain",
+        StyleMode::NerdFont => "nerd_font",
+        StyleMode::Powerline => "powerline",
+    }
+}
+
+/// Renders the config once per style mode so degradation (e.g. powerline → plain) is visible
+/// before saving. The active mode is marked with `*`.
+fn mode_preview_lines(cfg: &Config, ctx: &crate::context::StatusContext) -> Vec<Line<'static>> {
+    STYLE_MODES
+        .iter()
+        .map(|mode| {
+            let mut mode_config = cfg.clone();
+            mode_config.style.mode = *mode;
+            let pieces = segments::build_segments(&mode_config, ctx);
+            let rendered = ansi_line(&render::render_line(&mode_config, &pieces, false));
+            let marker = if *mode == cfg.style.mode { "*" } else { " " };
+            let label = Span::styled(
+                format!("{}{:<10}", marker, style_mode_name(*mode)),
+                Style::default().fg(Color::DarkGray),
+            );
+            Line::from(
+                std::iter::once(label)
+                    .chain(rendered.spans)
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .collect()
+}
+
+fn render_style_editor(frame: &mut ratatui::Frame, area: Rect, editor: &StyleEditor) {
+    let separator = match &editor.input {
+        Some(input) => format!("\"{}_\"", input),
+        None => format!("\"{}\"", editor.style.separator),
+    };
+    let mut items = vec![
+        ListItem::new(format!("mode: {}", style_mode_name(editor.style.mode))),
+        ListItem::new(format!("separator: {}", separator)),
+    ];
+    for preset in SEPARATOR_PRESETS {
+        items.push(ListItem::new(format!("  preset \"{}\"", preset)));
+    }
+
+    let mut state = ListState::default();
+    state.select(Some(editor.selected));
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Style (Enter/Space change, Esc close)"),
+        )
+        .highlight_style(Style::default().bg(Color::Cyan).fg(Color::Black));
+    frame.render_widget(Clear, area);
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+fn handle_style_editor_keys(key: &KeyEvent, editor: &mut StyleEditor) -> PopupOutcome {
+    if let Some(input) = editor.input.as_mut() {
+        match key.code {
+            KeyCode::Esc => editor.input = None,
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(c) => input.push(c),
+            KeyCode::Enter => {
+                editor.style.separator = editor.input.take().unwrap_or_default();
+                return PopupOutcome::Message("separator updated".to_string());
+            }
+            _ => {}
+        }
+        return PopupOutcome::Continue;
+    }
+
+    let total = 2 + SEPARATOR_PRESETS.len();
+    match key.code {
+        KeyCode::Esc => return PopupOutcome::Close,
+        KeyCode::Up => editor.selected = editor.selected.checked_sub(1).unwrap_or(total - 1),
+        KeyCode::Down => editor.selected = (editor.selected + 1) % total,
+        KeyCode::Enter | KeyCode::Char(' ') | KeyCode::Left | KeyCode::Right => {
+            match editor.selected {
+                0 => {
+                    let index = STYLE_MODES
+                        .iter()
+                        .position(|mode| *mode == editor.style.mode)
+                        .unwrap_or(0);
+                    let next = if key.code == KeyCode::Left {
+                        (index + STYLE_MODES.len() - 1) % STYLE_MODES.len()
+                    } else {
+                        (index + 1) % STYLE_MODES.len()
+                    };
+                    editor.style.mode = STYLE_MODES[next];
+                    return PopupOutcome::Message(format!(
+                        "style mode: {}",
+                        style_mode_name(editor.style.mode)
+                    ));
+                }
+                1 => editor.input = Some(editor.style.separator.clone()),
+                row => {
+                    editor.style.separator = SEPARATOR_PRESETS[row - 2].to_string();
+                    return PopupOutcome::Message("separator preset applied".to_string());
+                }
+            }
+        }
+        _ => {}
+    }
+    PopupOutcome::Continue
+}
+
+fn tui_color(color: NamedColor) -> Color {
+    match color {
+        NamedColor::Black => Color::Black,
+        NamedColor::Red => Color::Red,
+        NamedColor::Green => Color::Green,
+        NamedColor::Yellow => Color::Yellow,
+        NamedColor::Blue => Color::Blue,
+        NamedColor::Magenta => Color::Magenta,
+        NamedColor::Cyan => Color::Cyan,
+        NamedColor::White => Color::Gray,
+        NamedColor::BrightBlack => Color::DarkGray,
+        NamedColor::BrightRed => Color::LightRed,
+        NamedColor::BrightGreen => Color::LightGreen,
+        NamedColor::BrightYellow => Color::LightYellow,
+        NamedColor::BrightBlue => Color::LightBlue,
+        NamedColor::BrightMagenta => Color::LightMagenta,
+        NamedColor::BrightCyan => Color::LightCyan,
+        NamedColor::BrightWhite => Color::White,
+    }
+}
+
+/// Below these sizes the configurator shows one panel at a time (switched with Tab) and drops
+/// the header, instead of squeezing three columns into an unreadable strip.
+const COMPACT_WIDTH: u16 = 70;
+const COMPACT_HEIGHT: u16 = 19;
+
+struct ConfiguratorLayout {
+    header: Option<Rect>,
+    tabs: Option<Rect>,
+    body: Rect,
+    themes: Option<Rect>,
+    segments: Option<Rect>,
+    inspector: Option<Rect>,
+    actions: Option<Rect>,
+    footer: Rect,
+}
+
+fn configurator_layout(area: Rect, focus: Focus) -> ConfiguratorLayout {
+    let header_height = if area.height < COMPACT_HEIGHT { 0 } else { 3 };
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(header_height),
+            Constraint::Min(3),
+            Constraint::Length(6),
+        ])
+        .split(area);
+    let header = (header_height > 0).then_some(rows[0]);
+
+    if area.width >= COMPACT_WIDTH {
+        let cols = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Percentage(28),
+                Constraint::Percentage(44),
+                Constraint::Percentage(28),
+            ])
+            .split(rows[1]);
+        let (segments, inspector) = split_inspector(cols[1]);
+        return ConfiguratorLayout {
+            header,
+            tabs: None,
+            body: rows[1],
+            themes: Some(cols[0]),
+            segments: Some(segments),
+            inspector,
+            actions: Some(cols[2]),
+            footer: rows[2],
+        };
+    }
+
+    let stacked = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(0)])
+        .split(rows[1]);
+    let panel = |target: Focus| (focus == target).then_some(stacked[1]);
+    let (segments, inspector) = match panel(Focus::Segments) {
+        Some(area) => {
+            let (segments, inspector) = split_inspector(area);
+            (Some(segments), inspector)
+        }
+        None => (None, None),
+    };
+    ConfiguratorLayout {
+        header,
+        tabs: Some(stacked[0]),
+        body: rows[1],
+        themes: panel(Focus::Themes),
+        segments,
+        inspector,
+        actions: panel(Focus::Actions),
+        footer: rows[2],
+    }
+}
+
+/// Gives the inspector the lower part of the segments panel when there is room for both.
+fn split_inspector(area: Rect) -> (Rect, Option<Rect>) {
+    if area.height < 14 {
+        return (area, None);
+    }
+    let parts = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(6), Constraint::Length(7)])
+        .split(area);
+    (parts[0], Some(parts[1]))
+}
+
+fn inspector_lines(
+    id: SegmentId,
+    insight: &segments::SegmentInsight,
+    collection: &collect::Collection,
+) -> Vec<Line<'static>> {
+    let source = match insight.source {
+        segments::SegmentSource::Rollout => match &collection.latest_rollout {
+            Some(path) => format!("rollout ({})", path.display()),
+            None => "rollout (none found)".to_string(),
+        },
+        segments::SegmentSource::WorkingDirectory => {
+            format!("working directory ({})", collection.context.cwd.display())
+        }
+        segments::SegmentSource::Git => "git".to_string(),
+        segments::SegmentSource::GitHub => "gh pr status (cached)".to_string(),
+        segments::SegmentSource::Plugins => "codexline-segment-* providers".to_string(),
+        segments::SegmentSource::Collection => "collector results".to_string(),
+    };
+    let status = match &insight.hidden_reason {
+        Some(reason) => Span::styled(
+            format!("hidden: {}", reason),
+            Style::default().fg(Color::Yellow),
+        ),
+        None => Span::styled("shown", Style::default().fg(Color::Green)),
+    };
+    vec![
+        Line::from(format!(
+            "{}: {}",
+            id.as_str(),
+            insight.value.as_deref().unwrap_or("-")
+        )),
+        Line::from(format!("source: {}", source)),
+        Line::from(status),
+    ]
+}
+
+fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
+    let vertical = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(area);
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(vertical[1])[1]
+}
+
+/// Compact segment toggler drawn inline below the prompt, without the alternate screen.
+pub fn run_tweak(base: &Config, options: CollectOptions) -> Result<Option<Config>> {
+    let mut cfg = base.clone();
+    let context = collect::collect_with(&cfg, options)?.context;
+    let height = cfg.segments.len() as u16 + 3;
+
+    enable_raw_mode().context("failed to enable raw mode")?;
+    let mut terminal = Terminal::with_options(
+        CrosstermBackend::new(io::stdout()),
+        TerminalOptions {
+            viewport: Viewport::Inline(height),
+        },
+    )
+    .context("failed to create terminal")?;
+    let result = tweak_loop(&mut terminal, &mut cfg, &context);
+    let _ = disable_raw_mode();
+    let _ = terminal.show_cursor();
+    println!();
+
+    if result? {
+        config::save(&cfg)?;
+        Ok(Some(cfg))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Returns `true` when the user asked to save.
+fn tweak_loop(
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    cfg: &mut Config,
+    context: &crate::context::StatusContext,
+) -> Result<bool> {
+    let mut selected = 0usize;
+    loop {
+        let pieces = segments::build_segments(cfg, context);
+        let preview = ansi_line(&render::render_line(cfg, &pieces, false));
+        terminal.draw(|frame| {
+            let area = frame.size();
+            let mut lines = vec![preview.clone()];
+            for (index, segment) in cfg.segments.iter().enumerate() {
+                let mark = if segment.enabled { "[x]" } else { "[ ]" };
+                let text = format!(" {} {}", mark, segment.id.as_str());
+                lines.push(if index == selected {
+                    Line::from(Span::styled(
+                        text,
+                        Style::default().bg(Color::Cyan).fg(Color::Black),
+                    ))
+                } else {
+                    Line::from(text)
+                });
+            }
+            lines.push(Line::from(Span::styled(
+                "↑/↓ move, Space toggle, J/K reorder, Enter save, q cancel",
+                Style::default().fg(Color::DarkGray),
+            )));
+            frame.render_widget(Clear, area);
+            frame.render_widget(Paragraph::new(lines), area);
+        })?;
+
+        if let Event::Key(key) = event::read()? {
+            if handle_segment_keys(&key, cfg, &mut selected) {
+                continue;
+            }
+            match key.code {
+                KeyCode::Enter => return Ok(true),
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('Q') => return Ok(false),
+                _ => {}
+            }
+        }
+    }
+}
+
+const SESSION_BROWSER_LIMIT: usize = 50;
+
+pub fn run_session_browser(cfg: &Config) -> Result<()> {
+    let collection = collect::collect(cfg)?;
+    let sessions = collect::list_sessions(cfg, &collection.sessions_dir, SESSION_BROWSER_LIMIT)?;
+    let mut guard = TerminalGuard::new()?;
+    let mut selected = 0usize;
+    let mut pinned = cfg.rollout.pinned.clone();
+    let mut footer_message =
+        String::from("↑/↓ select, P pin as statusline source, U unpin, q back");
+
+    loop {
+        let now = chrono::Utc::now();
+        guard.terminal.draw(|frame| {
+            let rows = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(8), Constraint::Length(3)])
+                .split(frame.size());
+            let cols = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(55), Constraint::Percentage(45)])
+                .split(rows[0]);
+
+            let items: Vec<ListItem> = sessions
+                .iter()
+                .map(|session| {
+                    let mark = if pinned.as_ref() == Some(&session.path) {
+                        "📌"
+                    } else {
+                        "  "
+                    };
+                    ListItem::new(format!(
+                        "{} {:<8} {:<14} {:>7} {:>4}",
+                        mark,
+                        session_short_id(session),
+                        session.model.as_deref().unwrap_or("-"),
+                        session
+                            .usage
+                            .as_ref()
+                            .map(|usage| segments::compact_tokens(usage.total_tokens))
+                            .unwrap_or_else(|| "-".to_string()),
+                        format_age(now - session.modified),
+                    ))
+                })
+                .collect();
+            let mut state = ListState::default();
+            if !sessions.is_empty() {
+                state.select(Some(selected));
+            }
+            let list = List::new(items)
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .title(format!("Sessions ({})", sessions.len())),
+                )
+                .highlight_style(Style::default().bg(Color::Cyan).fg(Color::Black));
+            frame.render_stateful_widget(list, cols[0], &mut state);
+
+            let detail = match sessions.get(selected) {
+                Some(session) => session_detail_lines(session),
+                None => vec![Line::from(format!(
+                    "No sessions found in {}",
+                    collection.sessions_dir.display()
+                ))],
+            };
+            let detail = Paragraph::new(detail)
+                .wrap(ratatui::widgets::Wrap { trim: false })
+                .block(Block::default().borders(Borders::ALL).title("Details"));
+            frame.render_widget(detail, cols[1]);
+
+            let footer = Paragraph::new(footer_message.as_str())
+                .block(Block::default().borders(Borders::ALL).title("Help"));
+            frame.render_widget(footer, rows[1]);
+        })?;
+
+        if let Event::Key(key) = event::read()? {
+            match key.code {
+                KeyCode::Up if !sessions.is_empty() => {
+                    selected = selected.checked_sub(1).unwrap_or(sessions.len() - 1);
+                }
+                KeyCode::Down if !sessions.is_empty() => {
+                    selected = (selected + 1) % sessions.len();
+                }
+                KeyCode::Char('p') | KeyCode::Char('P') => {
+                    if let Some(session) = sessions.get(selected) {
+                        save_pinned_rollout(Some(session.path.clone()))?;
+                        pinned = Some(session.path.clone());
+                        footer_message = format!("Pinned {}", session_short_id(session));
+                    }
+                }
+                KeyCode::Char('u') | KeyCode::Char('U') => {
+                    save_pinned_rollout(None)?;
+                    pinned = None;
+                    footer_message = "Unpinned, statusline follows the latest session".to_string();
+                }
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('Q') => return Ok(()),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Rows behind `--dashboard`, read from the `usage.sqlite` store.
+#[cfg(feature = "sqlite")]
+struct DashboardData {
+    days: Vec<store::DailyUsage>,
+    models: Vec<store::ModelUsage>,
+    limits: Vec<store::LimitPoint>,
+}
+
+#[cfg(feature = "sqlite")]
+impl DashboardData {
+    fn load(path: &Path, days: u32) -> Result<Self> {
+        Ok(Self {
+            days: store::daily_usage(path, days)?,
+            models: store::model_usage(path, days)?,
+            limits: store::limit_trajectory(path, days)?,
+        })
+    }
+}
+
+/// Charts of the last `days` days in the usage store: tokens per day, tokens per model and
+/// the hourly peaks of both rate limits. `R` reloads, `+`/`-` change the range.
+#[cfg(feature = "sqlite")]
+pub fn run_dashboard(path: &Path, days: u32) -> Result<()> {
+    let mut days = days.max(1);
+    let mut data = DashboardData::load(path, days)?;
+    let mut guard = TerminalGuard::new()?;
+    loop {
+        guard
+            .terminal
+            .draw(|frame| render_dashboard(frame, frame.size(), &data, days))?;
+        if let Event::Key(key) = event::read()? {
+            let range = match key.code {
+                KeyCode::Char('r') | KeyCode::Char('R') => days,
+                KeyCode::Char('+') | KeyCode::Right => (days * 2).min(365),
+                KeyCode::Char('-') | KeyCode::Left => (days / 2).max(1),
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('Q') => return Ok(()),
+                _ => continue,
+            };
+            days = range;
+            data = DashboardData::load(path, days)?;
+        }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+fn render_dashboard(frame: &mut ratatui::Frame, area: Rect, data: &DashboardData, days: u32) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage(45),
+            Constraint::Min(8),
+            Constraint::Length(3),
+        ])
+        .split(area);
+    let cols = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(rows[1]);
+
+    let total: i64 = data.days.iter().map(|day| day.total_tokens).sum();
+    let bars: Vec<Bar> = data
+        .days
+        .iter()
+        .map(|day| {
+            Bar::default()
+                .value(day.total_tokens.max(0) as u64)
+                .text_value(segments::compact_tokens(day.total_tokens))
+                .label(Line::from(day.day.get(5..).unwrap_or(&day.day).to_string()))
+        })
+        .collect();
+    let bar_width = (rows[0].width.saturating_sub(2) / bars.len().max(1) as u16)
+        .saturating_sub(1)
+        .clamp(1, 8);
+    let daily = BarChart::default()
+        .block(Block::default().borders(Borders::ALL).title(format!(
+            "Tokens per day (last {days} days, {} total)",
+            segments::compact_tokens(total)
+        )))
+        .data(BarGroup::default().bars(&bars))
+        .bar_width(bar_width)
+        .bar_gap(1)
+        .bar_style(Style::default().fg(Color::Cyan))
+        .value_style(Style::default().fg(Color::Black).bg(Color::Cyan));
+    frame.render_widget(daily, rows[0]);
+
+    let model_bars: Vec<Bar> = data
+        .models
+        .iter()
+        .map(|model| {
+            Bar::default()
+                .value(model.total_tokens.max(0) as u64)
+                .text_value(format!(
+                    "{} ({} sessions)",
+                    segments::compact_tokens(model.total_tokens),
+                    model.sessions
+                ))
+                .label(Line::from(model.model.clone()))
+        })
+        .collect();
+    let models = BarChart::default()
+        .block(Block::default().borders(Borders::ALL).title("Per model"))
+        .direction(Direction::Horizontal)
+        .data(BarGroup::default().bars(&model_bars))
+        .bar_width(1)
+        .bar_gap(0)
+        .bar_style(Style::default().fg(Color::Magenta))
+        .value_style(Style::default().fg(Color::White));
+    frame.render_widget(models, cols[0]);
+
+    let limit_rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(cols[1]);
+    for (area, name, color, pick) in [
+        (
+            limit_rows[0],
+            "5h limit",
+            Color::Yellow,
+            (|point: &store::LimitPoint| point.primary_used_percent) as fn(&_) -> _,
+        ),
+        (limit_rows[1], "weekly limit", Color::Red, |point| {
+            point.secondary_used_percent
+        }),
+    ] {
+        let series: Vec<u64> = data
+            .limits
+            .iter()
+            .map(|point| pick(point).map_or(0, |used| used.round().max(0.0) as u64))
+            .collect();
+        let latest = data
+            .limits
+            .iter()
+            .rev()
+            .find_map(pick)
+            .map_or_else(|| "-".to_string(), |used| format!("{used:.0}%"));
+        // The newest hours are kept when there are more than the chart is wide.
+        let width = usize::from(area.width.saturating_sub(2));
+        let visible = &series[series.len().saturating_sub(width)..];
+        let sparkline = Sparkline::default()
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!("{name}, hourly peak (latest {latest})")),
+            )
+            .data(visible)
+            .max(100)
+            .style(Style::default().fg(color));
+        frame.render_widget(sparkline, area);
+    }
+
+    let footer = Paragraph::new("R reload, +/- double or halve the range, q quit")
+        .block(Block::default().borders(Borders::ALL).title("Help"));
+    frame.render_widget(footer, rows[2]);
+}
+
+enum ProfilePrompt {
+    Create(String),
+    Rename(String),
+    Delete,
+}
+
+pub fn run_profile_manager() -> Result<()> {
+    let mut cfg = config::load()?;
+    let context = collect::collect(&cfg)?.context;
+    let mut guard = TerminalGuard::new()?;
+    let mut selected = 0usize;
+    let mut prompt: Option<ProfilePrompt> = None;
+    let mut footer_message = String::from(
+        "↑/↓ select, Enter apply, N new from current config, R rename, D delete, q back",
+    );
+
+    loop {
+        let names: Vec<String> = cfg.profiles.keys().cloned().collect();
+        selected = selected.min(names.len().saturating_sub(1));
+        let themes_dir = cfg.themes_dir();
+        let previews: Vec<Line<'static>> = names
+            .iter()
+            .map(|name| {
+                let mut preview = cfg.clone();
+                profiles::apply_profile(&mut preview, name)?;
+                let merged =
+                    merge_theme(&preview, &preview.theme, &themes_dir, None).unwrap_or(preview);
+                let pieces = segments::build_segments(&merged, &context);
+                Ok(ansi_line(&render::render_line(&merged, &pieces, false)))
+            })
+            .collect::<Result<_>>()?;
+
+        guard.terminal.draw(|frame| {
+            let rows = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(6), Constraint::Length(3)])
+                .split(frame.size());
+
+            let items: Vec<ListItem> = names
+                .iter()
+                .zip(&previews)
+                .map(|(name, preview)| {
+                    let profile = &cfg.profiles[name];
+                    ListItem::new(vec![
+                        Line::from(vec![
+                            Span::styled(
+                                name.clone(),
+                                Style::default().add_modifier(Modifier::BOLD),
+                            ),
+                            Span::styled(
+                                format!("  theme: {}", profile.theme),
+                                Style::default().fg(Color::DarkGray),
+                            ),
+                        ]),
+                        Line::from(
+                            std::iter::once(Span::raw("  "))
+                                .chain(preview.spans.iter().cloned())
+                                .collect::<Vec<_>>(),
+                        ),
+                    ])
+                })
+                .collect();
+            let mut state = ListState::default();
+            if !names.is_empty() {
+                state.select(Some(selected));
+            }
+            let title = if names.is_empty() {
+                "Profiles (none yet, press N to save the current config)".to_string()
+            } else {
+                format!("Profiles ({})", names.len())
+            };
+            let list = List::new(items)
+                .block(Block::default().borders(Borders::ALL).title(title))
+                .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+            frame.render_stateful_widget(list, rows[0], &mut state);
+
+            let footer_text = match &prompt {
+                Some(ProfilePrompt::Create(input)) => format!("New profile name: {}_", input),
+                Some(ProfilePrompt::Rename(input)) => format!("Rename to: {}_", input),
+                Some(ProfilePrompt::Delete) => format!(
+                    "Delete profile {}? y/n",
+                    names.get(selected).map(String::as_str).unwrap_or("")
+                ),
+                None => footer_message.clone(),
+            };
+            let footer = Paragraph::new(footer_text)
+                .block(Block::default().borders(Borders::ALL).title("Help"));
+            frame.render_widget(footer, rows[1]);
+        })?;
+
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        let current = names.get(selected).cloned();
+
+        if let Some(active) = prompt.as_mut() {
+            let result = match active {
+                ProfilePrompt::Create(input) | ProfilePrompt::Rename(input) => match key.code {
+                    KeyCode::Char(c) => {
+                        input.push(c);
+                        continue;
+                    }
+                    KeyCode::Backspace => {
+                        input.pop();
+                        continue;
+                    }
+                    KeyCode::Esc => None,
+                    KeyCode::Enter => {
+                        let name = input.trim().to_string();
+                        Some(match (&*active, &current) {
+                            (ProfilePrompt::Rename(_), Some(from)) => {
+                                profiles::rename_profile(&mut cfg, from, &name)
+                                    .map(|_| format!("Renamed {} to {}", from, name))
+                            }
+                            _ => profiles::create_profile(&mut cfg, &name)
+                                .map(|_| format!("Saved current config as {}", name)),
+                        })
+                    }
+                    _ => continue,
+                },
+                ProfilePrompt::Delete => match (key.code, &current) {
+                    (KeyCode::Char('y') | KeyCode::Char('Y'), Some(name)) => Some(
+                        profiles::delete_profile(&mut cfg, name)
+                            .map(|_| format!("Deleted {}", name)),
+                    ),
+                    _ => None,
+                },
+            };
+            prompt = None;
+            if let Some(result) = result {
+                footer_message = match result.and_then(|message| {
+                    config::save(&cfg)?;
+                    Ok(message)
+                }) {
+                    Ok(message) => message,
+                    Err(err) => format!("Error: {err:#}"),
+                };
+                if let Ok(reloaded) = config::load() {
+                    cfg = reloaded;
+                }
+            }
+            continue;
+        }
+
+        match key.code {
+            KeyCode::Up if !names.is_empty() => {
+                selected = selected.checked_sub(1).unwrap_or(names.len() - 1);
+            }
+            KeyCode::Down if !names.is_empty() => {
+                selected = (selected + 1) % names.len();
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') => {
+                prompt = Some(ProfilePrompt::Create(String::new()));
+            }
+            KeyCode::Char('r') | KeyCode::Char('R') if current.is_some() => {
+                prompt = Some(ProfilePrompt::Rename(current.clone().unwrap_or_default()));
+            }
+            KeyCode::Char('d') | KeyCode::Char('D') if current.is_some() => {
+                prompt = Some(ProfilePrompt::Delete);
+            }
+            KeyCode::Enter => {
+                if let Some(name) = &current {
+                    profiles::apply_profile(&mut cfg, name)?;
+                    config::save(&cfg)?;
+                    footer_message = format!("Applied profile {}", name);
+                }
+            }
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('Q') => return Ok(()),
+            _ => {}
+        }
+    }
+}
+
+pub struct DiagnosticsSection {
+    pub title: String,
+    pub checks: Vec<PatchCheck>,
+    pub notes: Vec<String>,
+}
+
+pub fn run_diagnostics_view(sections: &[DiagnosticsSection]) -> Result<()> {
+    let rows = diagnostics_rows(sections);
+    let mut guard = TerminalGuard::new()?;
+    let mut selected = 0usize;
+    let mut scroll = 0u16;
+
+    let (ok, warn, fail) = sections.iter().flat_map(|section| &section.checks).fold(
+        (0, 0, 0),
+        |(ok, warn, fail), check| match check.status {
+            CheckStatus::Ok => (ok + 1, warn, fail),
+            CheckStatus::Warn => (ok, warn + 1, fail),
+            CheckStatus::Fail => (ok, warn, fail + 1),
+        },
+    );
+    let summary = format!(
+        "{} ok · {} warn · {} fail   ↑/↓ select, PgUp/PgDn scroll details, q back",
+        ok, warn, fail
+    );
+
+    loop {
+        guard.terminal.draw(|frame| {
+            let layout = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(8), Constraint::Length(3)])
+                .split(frame.size());
+            let cols = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
+                .split(layout[0]);
+
+            let items: Vec<ListItem> = rows
+                .iter()
+                .map(|&(section, index)| {
+                    let check = &sections[section].checks[index];
+                    ListItem::new(Line::from(vec![
+                        status_badge(check.status),
+                        Span::raw(format!(" {}/{}", sections[section].title, check.name)),
+                    ]))
+                })
+                .collect();
+            let mut state = ListState::default();
+            if !rows.is_empty() {
+                state.select(Some(selected));
+            }
+            let list = List::new(items)
+                .block(Block::default().borders(Borders::ALL).title("Checks"))
+                .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+            frame.render_stateful_widget(list, cols[0], &mut state);
+
+            let mut detail = Vec::new();
+            if let Some(&(section, index)) = rows.get(selected) {
+                let check = &sections[section].checks[index];
+                detail.push(Line::from(vec![
+                    status_badge(check.status),
+                    Span::styled(
+                        format!(" {}", check.name),
+                        Style::default().add_modifier(Modifier::BOLD),
+                    ),
+                ]));
+                detail.push(Line::from(""));
+                detail.push(Line::from(check.detail.clone()));
+                if !sections[section].notes.is_empty() {
+                    detail.push(Line::from(""));
+                    detail.push(Line::from("Suggestions:"));
+                    for note in &sections[section].notes {
+                        detail.push(Line::from(format!("- {}", note)));
+                    }
+                }
+            }
+            let detail = Paragraph::new(detail)
+                .wrap(ratatui::widgets::Wrap { trim: false })
+                .scroll((scroll, 0))
+                .block(Block::default().borders(Borders::ALL).title("Details"));
+            frame.render_widget(detail, cols[1]);
+
+            let footer = Paragraph::new(summary.as_str())
+                .block(Block::default().borders(Borders::ALL).title("Diagnostics"));
+            frame.render_widget(footer, layout[1]);
+        })?;
+
+        if let Event::Key(key) = event::read()? {
+            match key.code {
+                KeyCode::Up if !rows.is_empty() => {
+                    selected = selected.checked_sub(1).unwrap_or(rows.len() - 1);
+                    scroll = 0;
+                }
+                KeyCode::Down if !rows.is_empty() => {
+                    selected = (selected + 1) % rows.len();
+                    scroll = 0;
+                }
+                KeyCode::PageDown => scroll = scroll.saturating_add(5),
+                KeyCode::PageUp => scroll = scroll.saturating_sub(5),
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('Q') => return Ok(()),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Flattens the sections into `(section, check)` indices, worst status first within each section.
+fn diagnostics_rows(sections: &[DiagnosticsSection]) -> Vec<(usize, usize)> {
+    let severity = |status: CheckStatus| match status {
+        CheckStatus::Fail => 0,
+        CheckStatus::Warn => 1,
+        CheckStatus::Ok => 2,
+    };
+    let mut rows = Vec::new();
+    for (section_index, section) in sections.iter().enumerate() {
+        let mut indices: Vec<usize> = (0..section.checks.len()).collect();
+        indices.sort_by_key(|&index| severity(section.checks[index].status));
+        rows.extend(indices.into_iter().map(|index| (section_index, index)));
+    }
+    rows
+}
+
+fn status_badge(status: CheckStatus) -> Span<'static> {
+    let (label, color) = match status {
+        CheckStatus::Ok => (" OK ", Color::Green),
+        CheckStatus::Warn => ("WARN", Color::Yellow),
+        CheckStatus::Fail => ("FAIL", Color::Red),
+    };
+    Span::styled(
+        format!("[{}]", label),
+        Style::default().fg(color).add_modifier(Modifier::BOLD),
+    )
+}
+
+/// Persists the pin on the on-disk config, not the theme-merged one used for rendering.
+fn save_pinned_rollout(pinned: Option<std::path::PathBuf>) -> Result<()> {
+    let mut cfg = config::load()?;
+    cfg.rollout.pinned = pinned;
+    config::save(&cfg)?;
+    Ok(())
+}
+
+fn session_short_id(session: &SessionSummary) -> String {
+    session
+        .session
+        .as_ref()
+        .and_then(|meta| meta.thread_id.as_deref())
+        .map(|id| id.chars().take(8).collect())
+        .unwrap_or_else(|| "-".to_string())
+}
+
+fn session_detail_lines(session: &SessionSummary) -> Vec<Line<'static>> {
+    let meta = session.session.as_ref();
+    let mut lines = vec![
+        Line::from(format!("path: {}", session.path.display())),
+        Line::from(format!(
+            "modified: {}",
+            session.modified.format("%Y-%m-%d %H:%M:%S UTC")
+        )),
+        Line::from(format!(
+            "thread: {}",
+            meta.and_then(|m| m.thread_id.as_deref()).unwrap_or("-")
+        )),
+        Line::from(format!(
+            "model: {}",
+            session.model.as_deref().unwrap_or("-")
+        )),
+        Line::from(format!(
+            "provider: {}",
+            meta.and_then(|m| m.model_provider.as_deref())
+                .unwrap_or("-")
+        )),
+        Line::from(format!(
+            "codex: {}",
+            meta.and_then(|m| m.cli_version.as_deref()).unwrap_or("-")
+        )),
+    ];
+    if let Some(usage) = &session.usage {
+        lines.push(Line::from(format!(
+            "tokens: {} in / {} out / {} total",
+            segments::compact_tokens(usage.input_tokens),
+            segments::compact_tokens(usage.output_tokens),
+            segments::compact_tokens(usage.total_tokens)
+        )));
+        if let Some(used) = usage.used_percent {
+            lines.push(Line::from(format!("context used: {}%", used)));
+        }
+    }
+    if let Some(limits) = &session.limits {
+        if let Some(primary) = limits.primary_used_percent {
+            lines.push(Line::from(format!("5h limit: {:.0}%", primary)));
+        }
+        if let Some(secondary) = limits.secondary_used_percent {
+            lines.push(Line::from(format!("weekly limit: {:.0}%", secondary)));
+        }
+    }
+    lines
+}
+
+fn format_age(age: chrono::Duration) -> String {
+    let secs = age.num_seconds().max(0);
+    if secs < 60 {
+        format!("{}s", secs)
+    } else if secs < 3600 {
+        format!("{}m", secs / 60)
+    } else if secs < 86_400 {
+        format!("{}h", secs / 3600)
+    } else {
+        format!("{}d", secs / 86_400)
+    }
+}
+
+fn handle_global_key(key: &KeyEvent, focus: &mut Focus) -> bool {
+    match key.code {
+        KeyCode::Tab => {
+            *focus = match focus {
+                Focus::Themes => Focus::Segments,
+                Focus::Segments => Focus::Actions,
+                Focus::Actions => Focus::Themes,
+            };
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Moves `selected` (an index into the full theme list) among the `visible` indexes.
+fn handle_theme_keys(key: &KeyEvent, selected: &mut usize, visible: &[usize]) -> bool {
+    if visible.is_empty() {
+        return false;
+    }
+
+    let position = visible.iter().position(|index| index == selected);
+    match key.code {
+        KeyCode::Up => {
+            let next = match position {
+                Some(0) | None => visible.len() - 1,
+                Some(position) => position - 1,
+            };
+            *selected = visible[next];
+            true
+        }
+        KeyCode::Down => {
+            let next = position.map(|position| (position + 1) % visible.len());
+            *selected = visible[next.unwrap_or(0)];
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Incremental `/` filter for the Themes panel.
+#[derive(Debug, Default)]
+struct ThemeFilter {
+    query: String,
+    typing: bool,
+}
+
+impl ThemeFilter {
+    fn visible(&self, names: &[String]) -> Vec<usize> {
+        let query = self.query.to_lowercase();
+        names
+            .iter()
+            .enumerate()
+            .filter(|(_, name)| name.to_lowercase().contains(&query))
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Handles a key while typing; keeps `selected` on a visible theme when possible.
+    fn handle_key(
+        &mut self,
+        key: &KeyEvent,
+        names: &[String],
+        selected: &mut usize,
+    ) -> Option<String> {
+        match key.code {
+            KeyCode::Esc => {
+                self.query.clear();
+                self.typing = false;
+                return Some("Theme filter cleared".to_string());
+            }
+            KeyCode::Enter => {
+                self.typing = false;
+                return None;
+            }
+            KeyCode::Backspace => {
+                self.query.pop();
+            }
+            KeyCode::Char(c) => self.query.push(c),
+            _ => return None,
+        }
+
+        let visible = self.visible(names);
+        if !visible.contains(selected) {
+            if let Some(first) = visible.first() {
+                *selected = *first;
+            }
+        }
+        if visible.is_empty() {
+            Some(format!("No theme matches \"{}\"", self.query))
+        } else {
+            None
+        }
+    }
+}
+
+fn handle_segment_keys(key: &KeyEvent, cfg: &mut Config, selected: &mut usize) -> bool {
+    if cfg.segments.is_empty() {
+        return false;
+    }
+
+    match key.code {
+        KeyCode::Up => {
+            if *selected == 0 {
+                *selected = cfg.segments.len() - 1;
+            } else {
+                *selected = selected.saturating_sub(1);
+            }
+            true
+        }
+        KeyCode::Down => {
+            *selected = (*selected + 1) % cfg.segments.len();
+            true
+        }
+        KeyCode::Char(c) => {
+            let text = c.to_string();
+            if text == " " {
+                let idx = (*selected).min(cfg.segments.len() - 1);
+                cfg.segments[idx].enabled = !cfg.segments[idx].enabled;
+                return true;
+            }
+            if text.eq_ignore_ascii_case("j") {
+                let idx = (*selected).min(cfg.segments.len() - 1);
+                if idx + 1 < cfg.segments.len() {
+                    cfg.segments.swap(idx, idx + 1);
+                    *selected = idx + 1;
+                }
+                return true;
+            }
+            if text.eq_ignore_ascii_case("k") {
+                let idx = (*selected).min(cfg.segments.len() - 1);
+                if idx > 0 {
+                    cfg.segments.swap(idx, idx - 1);
+                    *selected = idx - 1;
+                }
+                return true;
+            }
+            false
+        }
+        _ => false,
+    }
+}
+
+fn handle_action_nav(key: &KeyEvent, selected: &mut usize, total: usize) -> bool {
+    if total == 0 {
+        return false;
+    }
+
+    match key.code {
+        KeyCode::Up => {
+            if *selected == 0 {
+                *selected = total - 1;
+            } else {
+                *selected = selected.saturating_sub(1);
+            }
+            true
+        }
+        KeyCode::Down => {
+            *selected = (*selected + 1) % total;
+            true
+        }
+        _ => false,
+    }
+}
+
+struct TerminalGuard {
+    terminal: Terminal<CrosstermBackend<Stdout>>,
+}
+
+impl TerminalGuard {
+    fn new() -> Result<Self> {
+        enable_raw_mode().context("failed to enable raw mode")?;
+        let mut stdout = io::stdout();
+        execute!(stdout, EnterAlternateScreen, EnableBracketedPaste)
+            .context("failed to enter alternate screen")?;
         let backend = CrosstermBackend::new(stdout);
         let mut terminal = Terminal::new(backend).context("failed to create terminal")?;
         terminal.clear().context("failed to clear terminal")?;
@@ -475,7 +2566,318 @@ impl TerminalGuard {
 impl Drop for TerminalGuard {
     fn drop(&mut self) {
         let _ = disable_raw_mode();
-        let _ = execute!(self.terminal.backend_mut(), LeaveAlternateScreen);
+        let _ = execute!(
+            self.terminal.backend_mut(),
+            DisableBracketedPaste,
+            LeaveAlternateScreen
+        );
         let _ = self.terminal.show_cursor();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SegmentId;
+    use crossterm::event::KeyModifiers;
+
+    fn press(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn option_editor_validates_number_input() {
+        let mut cfg = Config::default();
+        let segment = cfg
+            .segments
+            .iter()
+            .position(|segment| segment.id == SegmentId::Session)
+            .expect("session segment");
+        let mut editor = OptionEditor {
+            segment,
+            selected: 0,
+            input: None,
+        };
+
+        handle_option_editor_keys(&press(KeyCode::Enter), &mut editor, &mut cfg);
+        assert_eq!(editor.input.as_deref(), Some("8"));
+        handle_option_editor_keys(&press(KeyCode::Char('0')), &mut editor, &mut cfg);
+        assert!(matches!(
+            handle_option_editor_keys(&press(KeyCode::Enter), &mut editor, &mut cfg),
+            PopupOutcome::Message(_)
+        ));
+        assert!(editor.input.is_some(), "invalid input keeps editing");
+        assert!(!cfg.segments[segment].options.contains_key("length"));
+
+        handle_option_editor_keys(&press(KeyCode::Backspace), &mut editor, &mut cfg);
+        handle_option_editor_keys(&press(KeyCode::Backspace), &mut editor, &mut cfg);
+        handle_option_editor_keys(&press(KeyCode::Char('6')), &mut editor, &mut cfg);
+        handle_option_editor_keys(&press(KeyCode::Enter), &mut editor, &mut cfg);
+        assert_eq!(
+            cfg.segments[segment].options.get("length"),
+            Some(&serde_json::Value::from(6))
+        );
+    }
+
+    #[test]
+    fn color_picker_sets_and_clears_target_color() {
+        let mut cfg = Config::default();
+        let mut picker = ColorPicker::new(&cfg, 0);
+        assert_eq!(picker.target, ColorTarget::Text);
+        assert_eq!(
+            picker.selected,
+            1 + 14,
+            "model text defaults to bright_cyan"
+        );
+
+        handle_color_picker_keys(&press(KeyCode::Tab), &mut picker, &mut cfg);
+        assert_eq!(picker.target, ColorTarget::Background);
+        assert_eq!(picker.selected, 0);
+        handle_color_picker_keys(&press(KeyCode::Down), &mut picker, &mut cfg);
+        handle_color_picker_keys(&press(KeyCode::Enter), &mut picker, &mut cfg);
+        assert_eq!(
+            cfg.segments[0].colors.background,
+            Some(NamedColor::Black.into())
+        );
+
+        handle_color_picker_keys(&press(KeyCode::Up), &mut picker, &mut cfg);
+        handle_color_picker_keys(&press(KeyCode::Enter), &mut picker, &mut cfg);
+        assert_eq!(cfg.segments[0].colors.background, None);
+    }
+
+    #[test]
+    fn icon_picker_filters_and_applies_glyph() {
+        let mut cfg = Config::default();
+        let mut picker = IconPicker {
+            segment: 0,
+            query: String::new(),
+            selected: 0,
+        };
+        for c in "robot".chars() {
+            handle_icon_picker_keys(&press(KeyCode::Char(c)), &mut picker, &mut cfg);
+        }
+        handle_icon_picker_keys(&press(KeyCode::Enter), &mut picker, &mut cfg);
+        assert_eq!(cfg.segments[0].icon.nerd_font, "\u{f06a9}");
+    }
+
+    #[test]
+    fn ansi_line_translates_sgr_codes() {
+        let line = ansi_line("\x1b[1;36mM\x1b[0m gpt · \x1b[38;2;1;2;3mdir\x1b[0m");
+        let rendered: Vec<(&str, Style)> = line
+            .spans
+            .iter()
+            .map(|span| (span.content.as_ref(), span.style))
+            .collect();
+        assert_eq!(
+            rendered,
+            vec![
+                (
+                    "M",
+                    Style::default()
+                        .add_modifier(Modifier::BOLD)
+                        .fg(Color::Indexed(6))
+                ),
+                (" gpt · ", Style::default()),
+                ("dir", Style::default().fg(Color::Rgb(1, 2, 3))),
+            ]
+        );
+    }
+
+    #[test]
+    fn style_editor_cycles_mode_and_applies_preset() {
+        let mut editor = StyleEditor {
+            style: StyleConfig::default(),
+            selected: 0,
+            input: None,
+        };
+        handle_style_editor_keys(&press(KeyCode::Enter), &mut editor);
+        assert_eq!(editor.style.mode, StyleMode::Powerline);
+        handle_style_editor_keys(&press(KeyCode::Left), &mut editor);
+        assert_eq!(editor.style.mode, StyleMode::NerdFont);
+
+        handle_style_editor_keys(&press(KeyCode::Down), &mut editor);
+        handle_style_editor_keys(&press(KeyCode::Down), &mut editor);
+        handle_style_editor_keys(&press(KeyCode::Down), &mut editor);
+        handle_style_editor_keys(&press(KeyCode::Enter), &mut editor);
+        assert_eq!(editor.style.separator, " | ");
+    }
+
+    #[test]
+    fn theme_filter_narrows_navigation() {
+        let names: Vec<String> = ["default", "gruvbox", "powerline-dark", "powerline-light"]
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+        let mut filter = ThemeFilter {
+            typing: true,
+            ..ThemeFilter::default()
+        };
+        let mut selected = 0;
+        for c in "light".chars() {
+            filter.handle_key(&press(KeyCode::Char(c)), &names, &mut selected);
+        }
+        assert_eq!(selected, 3);
+
+        filter.handle_key(&press(KeyCode::Backspace), &names, &mut selected);
+        filter.query = "power".to_string();
+        let visible = filter.visible(&names);
+        assert_eq!(visible, vec![2, 3]);
+        handle_theme_keys(&press(KeyCode::Down), &mut selected, &visible);
+        assert_eq!(selected, 2);
+
+        filter.handle_key(&press(KeyCode::Esc), &names, &mut selected);
+        assert!(!filter.typing);
+        assert_eq!(filter.visible(&names).len(), 4);
+    }
+
+    #[test]
+    fn diagnostics_rows_list_worst_checks_first() {
+        let check = |name: &str, status: CheckStatus| PatchCheck {
+            name: name.to_string(),
+            status,
+            detail: String::new(),
+        };
+        let sections = vec![
+            DiagnosticsSection {
+                title: "doctor".to_string(),
+                checks: vec![
+                    check("config", CheckStatus::Ok),
+                    check("git", CheckStatus::Warn),
+                ],
+                notes: Vec::new(),
+            },
+            DiagnosticsSection {
+                title: "patch".to_string(),
+                checks: vec![
+                    check("codex_home", CheckStatus::Ok),
+                    check("codex_binary", CheckStatus::Fail),
+                ],
+                notes: Vec::new(),
+            },
+        ];
+
+        assert_eq!(
+            diagnostics_rows(&sections),
+            vec![(0, 1), (0, 0), (1, 1), (1, 0)]
+        );
+    }
+
+    #[test]
+    fn unsaved_changes_prompt_resolves_choice() {
+        let original = Config::default();
+        let mut working = original.clone();
+        assert!(!has_unsaved_changes(
+            &original,
+            &working,
+            &original.theme,
+            None
+        ));
+
+        working.segments[0].enabled = !working.segments[0].enabled;
+        assert!(has_unsaved_changes(
+            &original,
+            &working,
+            &original.theme,
+            None
+        ));
+        assert!(has_unsaved_changes(&original, &original, "nord", None));
+        assert_eq!(
+            pending_changes(&original, &working, "nord", None),
+            vec!["1 segment changed".to_string(), "theme changed".to_string()]
+        );
+
+        let mut selected = 0;
+        assert_eq!(
+            handle_confirm_quit_keys(&press(KeyCode::Right), &mut selected),
+            None
+        );
+        assert_eq!(
+            handle_confirm_quit_keys(&press(KeyCode::Enter), &mut selected),
+            Some(QuitChoice::Discard)
+        );
+        assert_eq!(
+            handle_confirm_quit_keys(&press(KeyCode::Esc), &mut selected),
+            Some(QuitChoice::Cancel)
+        );
+    }
+
+    #[test]
+    fn configurator_layout_stacks_panels_on_narrow_terminals() {
+        let wide = configurator_layout(Rect::new(0, 0, 120, 40), Focus::Segments);
+        assert!(wide.header.is_some() && wide.tabs.is_none());
+        assert!(wide.themes.is_some() && wide.segments.is_some() && wide.actions.is_some());
+
+        let narrow = configurator_layout(Rect::new(0, 0, 50, 14), Focus::Actions);
+        assert!(narrow.header.is_none() && narrow.tabs.is_some());
+        assert!(narrow.themes.is_none() && narrow.segments.is_none());
+        assert_eq!(narrow.actions.map(|area| area.width), Some(50));
+    }
+
+    #[test]
+    fn mode_preview_renders_every_style_mode() {
+        let cfg = Config::default();
+        let ctx = crate::context::StatusContext {
+            model: Some("gpt-5".to_string()),
+            ..crate::context::StatusContext::new(
+                chrono::Utc::now(),
+                std::path::PathBuf::from("/work/codexline"),
+            )
+        };
+        let lines = mode_preview_lines(&cfg, &ctx);
+        let labels: Vec<String> = lines
+            .iter()
+            .map(|line| line.spans[0].content.trim_end().to_string())
+            .collect();
+
+        assert_eq!(labels.len(), STYLE_MODES.len());
+        assert!(labels.contains(&format!("*{}", style_mode_name(cfg.style.mode))));
+        assert_eq!(
+            labels.iter().filter(|label| label.starts_with('*')).count(),
+            1
+        );
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn dashboard_draws_every_chart() {
+        let data = DashboardData {
+            days: vec![store::DailyUsage {
+                day: "2025-10-09".to_string(),
+                sessions: 2,
+                total_tokens: 1_500_000,
+                peak_primary_used_percent: Some(40.0),
+            }],
+            models: vec![store::ModelUsage {
+                model: "gpt-5-codex".to_string(),
+                sessions: 2,
+                total_tokens: 1_500_000,
+            }],
+            limits: vec![store::LimitPoint {
+                hour: "2025-10-09T10".to_string(),
+                primary_used_percent: Some(40.0),
+                secondary_used_percent: None,
+            }],
+        };
+        let mut terminal =
+            Terminal::new(ratatui::backend::TestBackend::new(100, 30)).expect("terminal");
+        terminal
+            .draw(|frame| render_dashboard(frame, frame.size(), &data, 14))
+            .expect("draw");
+        let text: String = terminal
+            .backend()
+            .buffer()
+            .content
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        for expected in [
+            "Tokens per day (last 14 days, 1.5M total)",
+            "10-09",
+            "gpt-5-codex",
+            "5h limit, hourly peak (latest 40%)",
+            "weekly limit, hourly peak (latest -)",
+        ] {
+            assert!(text.contains(expected), "{expected}");
+        }
+    }
+}
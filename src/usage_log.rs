@@ -0,0 +1,120 @@
+use crate::context::StatusContext;
+use anyhow::{Context, Result};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+const HEADER: &str = "timestamp,session,model,input_tokens,output_tokens,total_tokens,\
+context_used_percent,primary_limit_used_percent,secondary_limit_used_percent";
+
+/// Appends one row for `usage.log_csv`, writing the header first when the file is new.
+/// Snapshots without token or rate-limit data are skipped.
+pub fn append_csv(path: &Path, ctx: &StatusContext) -> Result<()> {
+    let Some(row) = csv_row(ctx) else {
+        return Ok(());
+    };
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create dir: {}", parent.display()))?;
+    }
+    let needs_header = fs::metadata(path)
+        .map(|meta| meta.len() == 0)
+        .unwrap_or(true);
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("failed to open: {}", path.display()))?;
+    if needs_header {
+        writeln!(file, "{HEADER}")?;
+    }
+    writeln!(file, "{row}").with_context(|| format!("failed to write: {}", path.display()))
+}
+
+fn csv_row(ctx: &StatusContext) -> Option<String> {
+    if ctx.usage.is_none() && ctx.limits.is_none() {
+        return None;
+    }
+    let opt = |value: Option<String>| value.unwrap_or_default();
+    let usage = ctx.usage.as_ref();
+    let limits = ctx.limits.as_ref();
+    let fields = [
+        ctx.now.to_rfc3339(),
+        opt(ctx
+            .session
+            .as_ref()
+            .and_then(|session| session.thread_id.clone())),
+        opt(ctx.model.clone()),
+        opt(usage.map(|usage| usage.input_tokens.to_string())),
+        opt(usage.map(|usage| usage.output_tokens.to_string())),
+        opt(usage.map(|usage| usage.total_tokens.to_string())),
+        opt(usage
+            .and_then(|usage| usage.used_percent)
+            .map(|used| used.to_string())),
+        opt(limits
+            .and_then(|limits| limits.primary_used_percent)
+            .map(|used| used.to_string())),
+        opt(limits
+            .and_then(|limits| limits.secondary_used_percent)
+            .map(|used| used.to_string())),
+    ];
+    Some(
+        fields
+            .iter()
+            .map(|field| csv_field(field))
+            .collect::<Vec<_>>()
+            .join(","),
+    )
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::{RateLimitSnapshot, TokenUsageSnapshot};
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    #[test]
+    fn append_csv_writes_header_once() {
+        let dir = TempDir::new().expect("temp dir");
+        let path = dir.path().join("logs").join("usage.csv");
+        let ctx = StatusContext {
+            model: Some("gpt-5,mini".to_string()),
+            usage: Some(TokenUsageSnapshot {
+                input_tokens: 1200,
+                output_tokens: 300,
+                cached_input_tokens: 0,
+                reasoning_output_tokens: 0,
+                total_tokens: 1500,
+                model_context_window: Some(200_000),
+                used_percent: Some(1),
+                remaining_percent: Some(99),
+            }),
+            limits: Some(RateLimitSnapshot {
+                primary_used_percent: Some(12.5),
+                secondary_used_percent: None,
+            }),
+            ..StatusContext::new(chrono::Utc::now(), PathBuf::from("/work"))
+        };
+
+        append_csv(&path, &ctx).expect("append");
+        append_csv(&path, &ctx).expect("append");
+
+        let content = fs::read_to_string(&path).expect("read");
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], HEADER);
+        assert!(lines[1].ends_with(",\"gpt-5,mini\",1200,300,1500,1,12.5,"));
+    }
+}
@@ -0,0 +1,103 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::process::Command;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VersionInfo {
+    pub version: String,
+    pub git_commit: String,
+    pub build_date: Option<String>,
+    pub features: Vec<String>,
+    pub target_os: String,
+    pub target_arch: String,
+    pub codex_cli_version: Option<String>,
+}
+
+pub fn short() -> String {
+    format!("codexline {}", env!("CARGO_PKG_VERSION"))
+}
+
+pub fn collect_info() -> VersionInfo {
+    let build_date = env!("CODEXLINE_BUILD_TIMESTAMP")
+        .parse::<i64>()
+        .ok()
+        .and_then(|secs| DateTime::<Utc>::from_timestamp(secs, 0))
+        .map(|date| date.format("%Y-%m-%d").to_string());
+
+    VersionInfo {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        git_commit: env!("CODEXLINE_GIT_COMMIT").to_string(),
+        build_date,
+        features: env!("CODEXLINE_FEATURES")
+            .split(',')
+            .filter(|feature| !feature.is_empty())
+            .map(str::to_string)
+            .collect(),
+        target_os: std::env::consts::OS.to_string(),
+        target_arch: std::env::consts::ARCH.to_string(),
+        codex_cli_version: detect_codex_version(),
+    }
+}
+
+pub fn render_text(info: &VersionInfo) -> String {
+    let features = if info.features.is_empty() {
+        "<none>".to_string()
+    } else {
+        info.features.join(", ")
+    };
+
+    [
+        format!("codexline {}", info.version),
+        format!("commit: {}", info.git_commit),
+        format!(
+            "build_date: {}",
+            info.build_date.as_deref().unwrap_or("<unknown>")
+        ),
+        format!("features: {}", features),
+        format!("target: {}-{}", info.target_os, info.target_arch),
+        format!(
+            "codex_cli: {}",
+            info.codex_cli_version.as_deref().unwrap_or("<not found>")
+        ),
+    ]
+    .join("\n")
+}
+
+fn detect_codex_version() -> Option<String> {
+    // Resolved like the patch checks do, so `codex.cmd` from npm on Windows is found too.
+    let codex = crate::platform::find_executable("codex")?;
+    let output = Command::new(codex).arg("--version").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let text = String::from_utf8_lossy(&output.stdout);
+    let line = text.lines().next()?.trim();
+    if line.is_empty() {
+        return None;
+    }
+    // `codex --version` prints e.g. "codex-cli 0.42.0"; keep only the version token.
+    Some(line.rsplit(' ').next().unwrap_or(line).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_text_includes_build_metadata() {
+        let info = VersionInfo {
+            version: "1.2.3".to_string(),
+            git_commit: "abc123".to_string(),
+            build_date: Some("2026-01-01".to_string()),
+            features: vec![],
+            target_os: "linux".to_string(),
+            target_arch: "x86_64".to_string(),
+            codex_cli_version: None,
+        };
+        let text = render_text(&info);
+        assert!(text.starts_with("codexline 1.2.3\n"));
+        assert!(text.contains("commit: abc123"));
+        assert!(text.contains("features: <none>"));
+        assert!(text.contains("codex_cli: <not found>"));
+    }
+}
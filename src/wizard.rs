@@ -0,0 +1,176 @@
+use crate::config::{Config, SegmentId, StyleMode};
+use crate::profiles;
+use anyhow::Result;
+use std::io::{BufRead, Write};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WizardAnswers {
+    pub nerd_font: bool,
+    pub powerline: bool,
+    pub segments: Vec<SegmentId>,
+}
+
+const SEGMENT_CHOICES: [SegmentId; 8] = [
+    SegmentId::Model,
+    SegmentId::Cwd,
+    SegmentId::Git,
+    SegmentId::Context,
+    SegmentId::Tokens,
+    SegmentId::Limits,
+    SegmentId::Session,
+    SegmentId::CodexVersion,
+];
+
+const DEFAULT_SEGMENTS: [SegmentId; 5] = [
+    SegmentId::Model,
+    SegmentId::Cwd,
+    SegmentId::Git,
+    SegmentId::Context,
+    SegmentId::Tokens,
+];
+
+/// Asks the first-run questions. Returns `None` when the user declines the wizard.
+pub fn ask<R: BufRead, W: Write>(input: &mut R, output: &mut W) -> Result<Option<WizardAnswers>> {
+    writeln!(output, "No codexline config found.")?;
+    if !ask_yes_no(input, output, "Run the setup wizard?", true)? {
+        return Ok(None);
+    }
+
+    let nerd_font = ask_yes_no(
+        input,
+        output,
+        "Does your terminal font include Nerd Font glyphs?",
+        true,
+    )?;
+    let powerline = nerd_font && ask_yes_no(input, output, "Use powerline style?", false)?;
+
+    let names: Vec<&str> = SEGMENT_CHOICES.iter().map(|id| id.as_str()).collect();
+    let defaults: Vec<&str> = DEFAULT_SEGMENTS.iter().map(|id| id.as_str()).collect();
+    writeln!(output, "Available segments: {}", names.join(", "))?;
+    let segments = loop {
+        let answer = prompt(
+            input,
+            output,
+            &format!("Segments to show [{}]:", defaults.join(",")),
+        )?;
+        if answer.is_empty() {
+            break DEFAULT_SEGMENTS.to_vec();
+        }
+        match parse_segments(&answer) {
+            Ok(ids) => break ids,
+            Err(unknown) => writeln!(output, "unknown segment: {}", unknown)?,
+        }
+    };
+
+    Ok(Some(WizardAnswers {
+        nerd_font,
+        powerline,
+        segments,
+    }))
+}
+
+pub fn build_config(answers: &WizardAnswers) -> Config {
+    let mut cfg = Config::default();
+    profiles::apply_quick_config(&mut cfg);
+
+    let (theme, mode) = if !answers.nerd_font {
+        ("minimal", StyleMode::Plain)
+    } else if answers.powerline {
+        ("powerline-dark", StyleMode::Powerline)
+    } else {
+        ("default", StyleMode::NerdFont)
+    };
+    cfg.theme = theme.to_string();
+    cfg.style.mode = mode;
+
+    // Keep the chosen order first, followed by the remaining (disabled) segments.
+    let mut ordered = Vec::with_capacity(cfg.segments.len());
+    for id in &answers.segments {
+        if let Some(index) = cfg.segments.iter().position(|segment| segment.id == *id) {
+            let mut segment = cfg.segments.remove(index);
+            segment.enabled = true;
+            ordered.push(segment);
+        }
+    }
+    for mut segment in cfg.segments.drain(..) {
+        segment.enabled = false;
+        ordered.push(segment);
+    }
+    cfg.segments = ordered;
+    cfg
+}
+
+fn parse_segments(answer: &str) -> std::result::Result<Vec<SegmentId>, String> {
+    let mut ids = Vec::new();
+    for name in answer.split(',').map(str::trim).filter(|v| !v.is_empty()) {
+        let Some(id) = SEGMENT_CHOICES.iter().find(|id| id.as_str() == name) else {
+            return Err(name.to_string());
+        };
+        if !ids.contains(id) {
+            ids.push(*id);
+        }
+    }
+    if ids.is_empty() {
+        return Err(answer.to_string());
+    }
+    Ok(ids)
+}
+
+fn ask_yes_no<R: BufRead, W: Write>(
+    input: &mut R,
+    output: &mut W,
+    question: &str,
+    default: bool,
+) -> Result<bool> {
+    let hint = if default { "[Y/n]" } else { "[y/N]" };
+    let answer = prompt(input, output, &format!("{} {}", question, hint))?;
+    Ok(match answer.to_ascii_lowercase().as_str() {
+        "y" | "yes" => true,
+        "n" | "no" => false,
+        _ => default,
+    })
+}
+
+fn prompt<R: BufRead, W: Write>(input: &mut R, output: &mut W, question: &str) -> Result<String> {
+    write!(output, "{} ", question)?;
+    output.flush()?;
+    let mut line = String::new();
+    input.read_line(&mut line)?;
+    Ok(line.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn wizard_builds_plain_config_from_answers() {
+        let mut input = Cursor::new("y\nn\nbogus\ngit, cwd\n");
+        let mut output = Vec::new();
+
+        let answers = ask(&mut input, &mut output)
+            .expect("ask")
+            .expect("accepted");
+        assert_eq!(
+            answers,
+            WizardAnswers {
+                nerd_font: false,
+                powerline: false,
+                segments: vec![SegmentId::Git, SegmentId::Cwd],
+            }
+        );
+        assert!(String::from_utf8_lossy(&output).contains("unknown segment: bogus"));
+
+        let cfg = build_config(&answers);
+        assert_eq!(cfg.theme, "minimal");
+        let enabled: Vec<SegmentId> = cfg
+            .segments
+            .iter()
+            .filter(|segment| segment.enabled)
+            .map(|segment| segment.id)
+            .collect();
+        assert_eq!(enabled, vec![SegmentId::Git, SegmentId::Cwd]);
+        assert!(cfg.validate().is_ok());
+    }
+}
@@ -0,0 +1,41 @@
+use std::path::Path;
+
+/// True under Windows Subsystem for Linux; its kernel version string names Microsoft.
+pub fn is_wsl() -> bool {
+    std::fs::read_to_string("/proc/version")
+        .map(|version| version.to_lowercase().contains("microsoft"))
+        .unwrap_or(false)
+}
+
+/// `/mnt/c/Users/me` -> `C:\Users\me`; `None` for paths outside a mounted Windows drive.
+pub fn windows_path(path: &Path) -> Option<String> {
+    let text = path.to_str()?;
+    let rest = text.strip_prefix("/mnt/")?;
+    let mut chars = rest.chars();
+    let drive = chars.next().filter(|c| c.is_ascii_alphabetic())?;
+    let tail = chars.as_str();
+    if !(tail.is_empty() || tail.starts_with('/')) {
+        return None;
+    }
+    Some(format!(
+        "{}:\\{}",
+        drive.to_ascii_uppercase(),
+        tail.trim_start_matches('/').replace('/', "\\")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn windows_path_maps_mounted_drives_only() {
+        assert_eq!(
+            windows_path(Path::new("/mnt/c/Users/me/repo")).as_deref(),
+            Some("C:\\Users\\me\\repo")
+        );
+        assert_eq!(windows_path(Path::new("/mnt/d")).as_deref(), Some("D:\\"));
+        assert_eq!(windows_path(Path::new("/mnt/wsl/shared")), None);
+        assert_eq!(windows_path(Path::new("/home/me")), None);
+    }
+}